@@ -0,0 +1,304 @@
+// src/audio/mod.rs
+
+//! Measuring, joining and padding synthesized audio.
+//!
+//! Long inputs are synthesized in several requests; [concatenate] joins their audio into a
+//! single stream of the same encoding, and [duration] tells where each part starts in it.
+//! [silence] generates pauses in the format of existing audio, and
+//! [concatenate_with_silence] puts them between parts. `LINEAR16`, `MULAW`, `ALAW`, `PCM`,
+//! `MP3`, `MP3_64_KBPS` and `OGG_OPUS` audio is supported.
+
+pub mod mp3;
+pub mod ogg;
+pub mod wav;
+
+use std::{ fmt, time::Duration };
+
+use crate::types::{ audio::AudioContent, synthesize::AudioEncoding };
+
+/// Error returned when audio can't be measured or joined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioError {
+    /// The encoding isn't supported.
+    Unsupported(AudioEncoding),
+    /// The parts to join don't have the same encoding, sample rate or number of channels.
+    Mismatch,
+    /// The audio isn't valid for its encoding.
+    Malformed(&'static str),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::Unsupported(encoding) => {
+                write!(f, "{:?} audio can't be measured or joined", encoding)
+            }
+            AudioError::Mismatch => write!(f, "the audio parts have different formats"),
+            AudioError::Malformed(message) => write!(f, "malformed audio: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+/// Returns `true` if audio in `encoding` can be measured and joined.
+pub fn is_supported(encoding: AudioEncoding) -> bool {
+    matches!(
+        encoding,
+        AudioEncoding::Linear16 |
+            AudioEncoding::Mulaw |
+            AudioEncoding::Alaw |
+            AudioEncoding::Pcm |
+            AudioEncoding::Mp3 |
+            AudioEncoding::Mp3_64Kbps |
+            AudioEncoding::OggOpus
+    )
+}
+
+/// Returns the duration of `audio`.
+pub fn duration(audio: &AudioContent) -> Result<Duration, AudioError> {
+    match audio.encoding() {
+        AudioEncoding::Linear16 | AudioEncoding::Mulaw | AudioEncoding::Alaw => {
+            let (format, data) = wav::parse(audio.bytes())?;
+            Ok(format.duration(data.len()))
+        }
+        AudioEncoding::Pcm => Ok(pcm_format(audio)?.duration(audio.len())),
+        AudioEncoding::Mp3 | AudioEncoding::Mp3_64Kbps => Ok(mp3::duration(audio.bytes())),
+        AudioEncoding::OggOpus => Ok(ogg::OpusStream::parse(audio.bytes())?.duration()),
+        encoding => Err(AudioError::Unsupported(encoding)),
+    }
+}
+
+/// Joins `parts` into a single stream, played one after the other.
+///
+/// # Arguments
+///
+/// * `parts` - Audio of the same encoding and sample rate, as returned by requests sharing
+///   their audio configuration.
+///
+/// # Returns
+///
+/// Returns the joined audio, with the encoding and sample rate of the parts.
+pub fn concatenate(parts: &[AudioContent]) -> Result<AudioContent, AudioError> {
+    let Some(first) = parts.first() else {
+        return Ok(AudioContent::default());
+    };
+    let encoding = first.encoding();
+    let sample_rate_hertz = first.sample_rate_hertz();
+    let same_format = parts.iter().all(|part| {
+        part.encoding() == encoding && part.sample_rate_hertz() == sample_rate_hertz
+    });
+    if !same_format {
+        return Err(AudioError::Mismatch);
+    }
+
+    let bytes = match encoding {
+        AudioEncoding::Linear16 | AudioEncoding::Mulaw | AudioEncoding::Alaw => {
+            let (format, _) = wav::parse(first.bytes())?;
+            let mut data = Vec::new();
+            for part in parts {
+                let (part_format, part_data) = wav::parse(part.bytes())?;
+                if part_format != format {
+                    return Err(AudioError::Mismatch);
+                }
+                data.extend_from_slice(part_data);
+            }
+            wav::write(&format, &data)
+        }
+        AudioEncoding::Pcm => parts.iter().flat_map(|part| part.bytes()).copied().collect(),
+        AudioEncoding::Mp3 | AudioEncoding::Mp3_64Kbps => {
+            let parts: Vec<&[u8]> = parts.iter().map(AudioContent::bytes).collect();
+            mp3::concatenate(&parts)
+        }
+        AudioEncoding::OggOpus => {
+            let parts: Vec<&[u8]> = parts.iter().map(AudioContent::bytes).collect();
+            ogg::concatenate(&parts)?
+        }
+        encoding => {
+            return Err(AudioError::Unsupported(encoding));
+        }
+    };
+    Ok(AudioContent::new(bytes, encoding, sample_rate_hertz))
+}
+
+/// Returns silence lasting `duration`, in the format of `like`.
+///
+/// # Arguments
+///
+/// * `like` - Audio whose encoding, sample rate and number of channels are used.
+/// * `duration` - The length of the silence. `MP3` silence is rounded to whole frames, about
+///   26 ms at 44.1 kHz, and `OGG_OPUS` silence to 20 ms.
+///
+/// # Returns
+///
+/// Returns the silence, a complete stream that can be joined with `like` by [concatenate].
+pub fn silence(like: &AudioContent, duration: Duration) -> Result<AudioContent, AudioError> {
+    let encoding = like.encoding();
+    let bytes = match encoding {
+        AudioEncoding::Linear16 | AudioEncoding::Mulaw | AudioEncoding::Alaw => {
+            let (format, _) = wav::parse(like.bytes())?;
+            wav::write(&format, &silent_samples(&format, duration))
+        }
+        AudioEncoding::Pcm => silent_samples(&pcm_format(like)?, duration),
+        AudioEncoding::Mp3 | AudioEncoding::Mp3_64Kbps => {
+            let (_, frame) = mp3::frames(like.bytes())
+                .next()
+                .ok_or(AudioError::Malformed("no MP3 frame"))?;
+            mp3::silence([frame[0], frame[1], frame[2], frame[3]], duration)
+        }
+        AudioEncoding::OggOpus => {
+            let stream = ogg::OpusStream::parse(like.bytes())?;
+            ogg::silence(stream.channels(), duration)
+        }
+        encoding => {
+            return Err(AudioError::Unsupported(encoding));
+        }
+    };
+    Ok(AudioContent::new(bytes, encoding, like.sample_rate_hertz()))
+}
+
+/// Joins `parts` into a single stream like [concatenate], with `gap` of silence between
+/// consecutive parts.
+pub fn concatenate_with_silence(
+    parts: &[AudioContent],
+    gap: Duration
+) -> Result<AudioContent, AudioError> {
+    let Some(first) = parts.first() else {
+        return Ok(AudioContent::default());
+    };
+    if gap.is_zero() || parts.len() == 1 {
+        return concatenate(parts);
+    }
+    let pause = silence(first, gap)?;
+    let mut padded = Vec::with_capacity(parts.len() * 2 - 1);
+    for (index, part) in parts.iter().enumerate() {
+        if index > 0 {
+            padded.push(pause.clone());
+        }
+        padded.push(part.clone());
+    }
+    concatenate(&padded)
+}
+
+/// Returns samples of silence lasting `duration` in `format`, as stored in a `data` chunk.
+fn silent_samples(format: &wav::WavFormat, duration: Duration) -> Vec<u8> {
+    // Silence is the middle of the range: zero for signed samples, but not for 8-bit PCM,
+    // which is unsigned, nor for the companded encodings.
+    let value = match (format.format_tag, format.bits_per_sample) {
+        (wav::FORMAT_MULAW, _) => 0xff,
+        (wav::FORMAT_ALAW, _) => 0xd5,
+        (_, 8) => 0x80,
+        _ => 0,
+    };
+    let block_align = usize::from(format.block_align());
+    let blocks = (duration.as_secs_f64() * f64::from(format.sample_rate)).round() as usize;
+    vec![value; blocks * block_align]
+}
+
+/// Returns the format of headerless `PCM` audio: mono 16-bit samples.
+fn pcm_format(audio: &AudioContent) -> Result<wav::WavFormat, AudioError> {
+    let sample_rate = audio
+        .sample_rate_hertz()
+        .and_then(|rate| u32::try_from(rate).ok())
+        .ok_or(AudioError::Malformed("unknown sample rate"))?;
+    Ok(wav::WavFormat {
+        format_tag: wav::FORMAT_PCM,
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linear16(samples: &[i16]) -> AudioContent {
+        wave(24_000, samples)
+    }
+
+    fn wave(sample_rate: u32, samples: &[i16]) -> AudioContent {
+        let format = wav::WavFormat {
+            format_tag: wav::FORMAT_PCM,
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+        };
+        let data: Vec<u8> = samples.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+        let bytes = wav::write(&format, &data);
+        AudioContent::new(bytes, AudioEncoding::Linear16, Some(sample_rate as i32))
+    }
+
+    #[test]
+    fn joins_wave_files_under_a_single_header() {
+        let joined = concatenate(&[linear16(&[1, 2]), linear16(&[3]), linear16(&[])]).unwrap();
+        let (format, data) = wav::parse(joined.bytes()).unwrap();
+        assert_eq!(format.sample_rate, 24_000);
+        assert_eq!(data, [1, 0, 2, 0, 3, 0]);
+        assert_eq!(joined.len(), 44 + 6);
+        assert_eq!(joined.sample_rate_hertz(), Some(24_000));
+    }
+
+    #[test]
+    fn refuses_to_join_different_formats() {
+        let other_rate = wave(16_000, &[1]);
+        assert_eq!(concatenate(&[linear16(&[1]), other_rate]).err(), Some(AudioError::Mismatch));
+        // Labelled with the same rate, but not recorded at it.
+        let mislabelled = AudioContent::new(
+            wave(16_000, &[1]).into_bytes(),
+            AudioEncoding::Linear16,
+            Some(24_000)
+        );
+        assert_eq!(concatenate(&[linear16(&[1]), mislabelled]).err(), Some(AudioError::Mismatch));
+        let mulaw = AudioContent::new(vec![0; 4], AudioEncoding::Mulaw, Some(24_000));
+        assert_eq!(concatenate(&[linear16(&[1]), mulaw]).err(), Some(AudioError::Mismatch));
+        let m4a = AudioContent::new(vec![0; 4], AudioEncoding::M4a, None);
+        assert_eq!(duration(&m4a).err(), Some(AudioError::Unsupported(AudioEncoding::M4a)));
+    }
+
+    #[test]
+    fn generates_silence_in_the_format_of_the_audio() {
+        let pause = silence(&linear16(&[1]), Duration::from_millis(250)).unwrap();
+        let (_, data) = wav::parse(pause.bytes()).unwrap();
+        assert_eq!(data.len(), 6000 * 2);
+        assert!(data.iter().all(|byte| *byte == 0));
+        assert_eq!(duration(&pause), Ok(Duration::from_millis(250)));
+
+        let pcm = AudioContent::new(vec![0; 2], AudioEncoding::Pcm, Some(16_000));
+        let pause = silence(&pcm, Duration::from_millis(250)).unwrap();
+        assert_eq!(pause.len(), 4000 * 2);
+        assert_eq!(duration(&pause), Ok(Duration::from_millis(250)));
+
+        let ogg = AudioContent::new(ogg::silence(1, Duration::ZERO), AudioEncoding::OggOpus, None);
+        let pause = silence(&ogg, Duration::from_millis(800)).unwrap();
+        assert_eq!(duration(&pause), Ok(Duration::from_millis(800)));
+    }
+
+    #[test]
+    fn pads_companded_samples_with_their_silence() {
+        let format = wav::WavFormat {
+            format_tag: wav::FORMAT_MULAW,
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 8,
+        };
+        assert_eq!(silent_samples(&format, Duration::from_millis(1)), [0xff; 8]);
+        let format = wav::WavFormat { format_tag: wav::FORMAT_ALAW, ..format };
+        assert_eq!(silent_samples(&format, Duration::from_millis(1)), [0xd5; 8]);
+        let format = wav::WavFormat { format_tag: wav::FORMAT_PCM, ..format };
+        assert_eq!(silent_samples(&format, Duration::from_millis(1)), [0x80; 8]);
+    }
+
+    #[test]
+    fn puts_silence_between_parts() {
+        let parts = [linear16(&[1; 2400]), linear16(&[2; 2400])];
+        let joined = concatenate_with_silence(&parts, Duration::from_millis(250)).unwrap();
+        assert_eq!(duration(&joined), Ok(Duration::from_millis(100 + 250 + 100)));
+        let (_, data) = wav::parse(joined.bytes()).unwrap();
+        assert_eq!(data[4800..4800 + 12_000], [0; 12_000]);
+        assert_eq!(data[4800 + 12_000], 2);
+
+        let joined = concatenate_with_silence(&parts[..1], Duration::from_millis(250)).unwrap();
+        assert_eq!(duration(&joined), Ok(Duration::from_millis(100)));
+    }
+}
@@ -0,0 +1,307 @@
+// src/audio/mp3.rs
+
+//! MPEG audio layer III streams, as returned for `MP3` and `MP3_64_KBPS`.
+
+use std::time::Duration;
+
+/// Bitrates in kbit/s of MPEG-1 layer III, by bitrate index.
+const MPEG1_BITRATES: [u32; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+
+/// Bitrates in kbit/s of MPEG-2 and MPEG-2.5 layer III, by bitrate index.
+const MPEG2_BITRATES: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+/// The header starting every MPEG audio frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub sample_rate: u32,
+    /// Samples per channel in the frame.
+    pub samples: u32,
+    /// Size of the frame in bytes, header included.
+    pub length: usize,
+    pub channels: u16,
+}
+
+impl FrameHeader {
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(f64::from(self.samples) / f64::from(self.sample_rate))
+    }
+}
+
+/// Reads the header of the layer III frame starting `bytes`, if there is one.
+pub fn frame_header(bytes: &[u8]) -> Option<FrameHeader> {
+    let header = bytes.get(..4)?;
+    if header[0] != 0xff || header[1] & 0xe0 != 0xe0 || (header[1] >> 1) & 0b11 != 0b01 {
+        return None;
+    }
+    // 3: MPEG-1, 2: MPEG-2, 0: MPEG-2.5.
+    let version = (header[1] >> 3) & 0b11;
+    let bitrate_index = usize::from(header[2] >> 4);
+    let sample_rate_index = usize::from((header[2] >> 2) & 0b11);
+    if version == 1 || bitrate_index == 0 || bitrate_index == 15 || sample_rate_index == 3 {
+        return None;
+    }
+
+    let (bitrates, sample_rates, samples) = match version {
+        3 => (&MPEG1_BITRATES, [44_100, 48_000, 32_000], 1152),
+        2 => (&MPEG2_BITRATES, [22_050, 24_000, 16_000], 576),
+        _ => (&MPEG2_BITRATES, [11_025, 12_000, 8000], 576),
+    };
+    let bitrate = bitrates[bitrate_index] * 1000;
+    let sample_rate = sample_rates[sample_rate_index];
+    let padding = usize::from((header[2] >> 1) & 1);
+    let length = (samples / 8 * bitrate / sample_rate) as usize + padding;
+    let channels = if header[3] >> 6 == 0b11 { 1 } else { 2 };
+    Some(FrameHeader { sample_rate, samples, length, channels })
+}
+
+/// Returns the length of the ID3v2 tags at the start of `bytes`.
+pub fn id3v2_length(bytes: &[u8]) -> usize {
+    let mut length = 0;
+    while bytes.len() >= length + 10 && &bytes[length..length + 3] == b"ID3" {
+        let header = &bytes[length..length + 10];
+        // The size is stored on 4 bytes of 7 bits, and excludes the header and footer.
+        let size = header[6..10]
+            .iter()
+            .fold(0usize, |size, byte| (size << 7) | usize::from(byte & 0x7f));
+        let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+        length = (length + 10 + size + footer).min(bytes.len());
+    }
+    length
+}
+
+/// Returns `bytes` without the ID3v2 tags at its start and the ID3v1 tag at its end.
+pub fn strip_tags(bytes: &[u8]) -> &[u8] {
+    let bytes = &bytes[id3v2_length(bytes)..];
+    if bytes.len() >= 128 && &bytes[bytes.len() - 128..bytes.len() - 125] == b"TAG" {
+        return &bytes[..bytes.len() - 128];
+    }
+    bytes
+}
+
+/// Returns `true` if `frame` holds a Xing, Info or VBRI header instead of audio. Encoders
+/// write one at the start of a stream to describe it; it decodes to silence and is wrong
+/// once streams are joined.
+pub fn is_info_frame(frame: &[u8]) -> bool {
+    let Some(header) = frame_header(frame) else {
+        return false;
+    };
+    let mpeg1 = (frame[1] >> 3) & 0b11 == 3;
+    let side_info = match (mpeg1, header.channels) {
+        (true, 1) => 17,
+        (true, _) => 32,
+        (false, 1) => 9,
+        (false, _) => 17,
+    };
+    let crc = if frame[1] & 1 == 0 { 2 } else { 0 };
+    let xing = 4 + crc + side_info;
+    let tag_at = |offset: usize| frame.get(offset..offset + 4);
+    matches!(tag_at(xing), Some(b"Xing" | b"Info")) || tag_at(36) == Some(b"VBRI")
+}
+
+/// Iterates over the frames of a stream, skipping bytes that aren't part of a frame.
+pub fn frames(bytes: &[u8]) -> Frames<'_> {
+    Frames { bytes: strip_tags(bytes) }
+}
+
+/// Iterator returned by [frames], yielding the header and bytes of every frame.
+#[derive(Debug, Clone)]
+pub struct Frames<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = (FrameHeader, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.bytes.is_empty() {
+            match frame_header(self.bytes) {
+                Some(header) if header.length <= self.bytes.len() => {
+                    let (frame, rest) = self.bytes.split_at(header.length);
+                    self.bytes = rest;
+                    return Some((header, frame));
+                }
+                Some(_) => {
+                    self.bytes = &[];
+                }
+                None => {
+                    self.bytes = &self.bytes[1..];
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Returns the duration of a stream, the sum of the durations of its audio frames.
+pub fn duration(bytes: &[u8]) -> Duration {
+    frames(bytes)
+        .filter(|(_, frame)| !is_info_frame(frame))
+        .map(|(header, _)| header.duration())
+        .sum()
+}
+
+/// Joins streams at frame boundaries. The ID3v2 tags of the first stream are kept; other tags
+/// and the Xing, Info and VBRI frames are dropped.
+pub fn concatenate(parts: &[&[u8]]) -> Vec<u8> {
+    let mut bytes = match parts.first() {
+        Some(first) => first[..id3v2_length(first)].to_vec(),
+        None => Vec::new(),
+    };
+    for part in parts {
+        for (_, frame) in frames(part).filter(|(_, frame)| !is_info_frame(frame)) {
+            bytes.extend_from_slice(frame);
+        }
+    }
+    bytes
+}
+
+/// Returns silent frames lasting `duration`, rounded to whole frames, in the format of the
+/// frame starting with `header`.
+pub fn silence(header: [u8; 4], duration: Duration) -> Vec<u8> {
+    // Without padding nor CRC, frames have the same length and no checksum to compute.
+    let header = [header[0], header[1] | 1, header[2] & !0b10, header[3]];
+    let Some(format) = frame_header(&header) else {
+        return Vec::new();
+    };
+    // Zeroed side information describes frames without any sample: silence.
+    let mut frame = header.to_vec();
+    frame.resize(format.length, 0);
+    let count = (duration.as_secs_f64() / format.duration().as_secs_f64()).round() as usize;
+    frame.repeat(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MPEG-1 layer III, 128 kbit/s, 44.1 kHz, joint stereo, without CRC: 417 bytes.
+    const STEREO: [u8; 4] = [0xff, 0xfb, 0x90, 0x64];
+
+    /// MPEG-1 layer III, 128 kbit/s, 44.1 kHz, mono, without CRC.
+    const MONO: [u8; 4] = [0xff, 0xfb, 0x90, 0xc4];
+
+    /// MPEG-2 layer III, 64 kbit/s, 16 kHz, mono, with CRC and padding: 289 bytes.
+    const MPEG2: [u8; 4] = [0xff, 0xf2, 0x8a, 0xc4];
+
+    /// Returns a frame starting with `header`, its bytes after the header set to `fill`.
+    fn frame(header: [u8; 4], fill: u8) -> Vec<u8> {
+        let length = frame_header(&header).unwrap().length;
+        let mut frame = header.to_vec();
+        frame.resize(length, fill);
+        frame
+    }
+
+    /// Returns a frame holding `tag` at `offset`, as encoders write Xing and VBRI headers.
+    fn info_frame(header: [u8; 4], offset: usize, tag: &[u8; 4]) -> Vec<u8> {
+        let mut frame = frame(header, 0);
+        frame[offset..offset + 4].copy_from_slice(tag);
+        frame
+    }
+
+    /// Returns an ID3v2 tag of `size` bytes after its header.
+    fn id3v2(size: usize, footer: bool) -> Vec<u8> {
+        let flags = if footer { 0x10 } else { 0 };
+        let mut tag = vec![b'I', b'D', b'3', 4, 0, flags];
+        tag.extend((0..4).rev().map(|shift| ((size >> (7 * shift)) & 0x7f) as u8));
+        tag.resize(tag.len() + size + if footer { 10 } else { 0 }, 0xaa);
+        tag
+    }
+
+    fn id3v1() -> Vec<u8> {
+        let mut tag = b"TAG".to_vec();
+        tag.resize(128, b' ');
+        tag
+    }
+
+    #[test]
+    fn reads_frame_headers() {
+        assert_eq!(frame_header(&STEREO), Some(FrameHeader {
+            sample_rate: 44_100,
+            samples: 1152,
+            length: 417,
+            channels: 2,
+        }));
+        assert_eq!(frame_header(&MPEG2), Some(FrameHeader {
+            sample_rate: 16_000,
+            samples: 576,
+            length: 289,
+            channels: 1,
+        }));
+        // Layer II, a free bitrate and a reserved sample rate.
+        assert_eq!(frame_header(&[0xff, 0xfd, 0x90, 0x64]), None);
+        assert_eq!(frame_header(&[0xff, 0xfb, 0x00, 0x64]), None);
+        assert_eq!(frame_header(&[0xff, 0xfb, 0x9c, 0x64]), None);
+    }
+
+    #[test]
+    fn strips_id3_tags() {
+        let audio = frame(STEREO, 1);
+        let tagged = [id3v2(20, false), id3v2(300, true), audio.clone(), id3v1()].concat();
+        assert_eq!(id3v2_length(&tagged), 30 + 320);
+        assert_eq!(strip_tags(&tagged), audio);
+        assert_eq!(frames(&tagged).count(), 1);
+    }
+
+    #[test]
+    fn recognizes_info_frames() {
+        // The Xing header follows the side information, whose size depends on the channels.
+        assert!(is_info_frame(&info_frame(MONO, 4 + 17, b"Xing")));
+        assert!(is_info_frame(&info_frame(STEREO, 4 + 32, b"Info")));
+        assert!(is_info_frame(&info_frame(MPEG2, 4 + 2 + 9, b"Xing")));
+        assert!(is_info_frame(&info_frame(MONO, 36, b"VBRI")));
+        assert!(!is_info_frame(&info_frame(MONO, 4 + 32, b"Xing")));
+        assert!(!is_info_frame(&frame(STEREO, 0)));
+    }
+
+    #[test]
+    fn measures_audio_frames_only() {
+        let stream = [
+            id3v2(20, false),
+            info_frame(STEREO, 36, b"Xing"),
+            frame(STEREO, 1),
+            vec![0; 7],
+            frame(STEREO, 2),
+            frame(STEREO, 3),
+            id3v1(),
+        ].concat();
+        assert_eq!(frames(&stream).count(), 4);
+        assert_eq!(duration(&stream), Duration::from_secs_f64((3.0 * 1152.0) / 44_100.0));
+    }
+
+    #[test]
+    fn joins_streams_at_frame_boundaries() {
+        let first = [id3v2(20, false), info_frame(STEREO, 36, b"Info"), frame(STEREO, 1)].concat();
+        let second = [
+            id3v2(40, false),
+            info_frame(STEREO, 36, b"Xing"),
+            frame(STEREO, 2),
+            frame(STEREO, 3),
+            id3v1(),
+        ].concat();
+
+        let joined = concatenate(&[&first, &second]);
+        let expected = [id3v2(20, false), frame(STEREO, 1), frame(STEREO, 2), frame(STEREO, 3)];
+        assert_eq!(joined, expected.concat());
+        assert_eq!(concatenate(&[]), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn generates_whole_frames_of_silence() {
+        // 1152 samples at 44.1 kHz: 26.1 ms per frame.
+        let silent = silence(STEREO, Duration::from_secs(1));
+        assert_eq!(silent.len(), 38 * 417);
+        assert!(frames(&silent).all(|(_, frame)| frame[..4] == STEREO));
+
+        // 576 samples at 16 kHz: 36 ms per frame. Padding and CRC are turned off.
+        let silent = silence(MPEG2, Duration::from_secs(1));
+        let header = [0xff, 0xf3, 0x88, 0xc4];
+        assert_eq!(silent.len(), 28 * 288);
+        assert_eq!(frames(&silent).count(), 28);
+        assert!(frames(&silent).all(|(_, frame)| frame[..4] == header));
+        assert_eq!(duration(&silent), Duration::from_secs_f64(28.0 * 0.036));
+
+        assert_eq!(silence(STEREO, Duration::ZERO), Vec::<u8>::new());
+    }
+}
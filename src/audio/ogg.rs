@@ -0,0 +1,487 @@
+// src/audio/ogg.rs
+
+//! Ogg Opus streams, as returned for `OGG_OPUS`.
+
+use std::{ sync::OnceLock, time::Duration };
+
+use crate::audio::AudioError;
+
+/// Granule positions of Opus streams count samples at 48 kHz, whatever the input rate.
+const GRANULE_RATE: u64 = 48_000;
+
+/// Size above which a page is ended before the next packet is added.
+const PAGE_SIZE: usize = 4096;
+
+/// A 20 ms CELT frame decoding to silence, without its table of contents byte.
+const SILENT_FRAME: [u8; 2] = [0xff, 0xfe];
+
+/// Serial number of the streams written by [silence].
+const SILENCE_SERIAL: u32 = 0x5349_4c45;
+
+/// The packets of the first logical stream of an Ogg Opus file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpusStream {
+    pub serial: u32,
+    /// The identification header, starting with `OpusHead`.
+    pub head: Vec<u8>,
+    /// The comment header, starting with `OpusTags`.
+    pub tags: Vec<u8>,
+    /// The audio packets.
+    pub packets: Vec<Vec<u8>>,
+    /// Granule position of the last page: the number of samples at 48 kHz to play, counting
+    /// the pre-skip.
+    pub granule_position: u64,
+}
+
+impl OpusStream {
+    /// Reads the first logical stream of an Ogg Opus file.
+    pub fn parse(bytes: &[u8]) -> Result<Self, AudioError> {
+        let mut serial = None;
+        let mut packets: Vec<Vec<u8>> = Vec::new();
+        let mut packet = Vec::new();
+        let mut granule_position = 0;
+        let mut offset = 0;
+
+        while offset < bytes.len() {
+            let page = &bytes[offset..];
+            if page.len() < 27 || &page[0..4] != b"OggS" {
+                return Err(AudioError::Malformed("expected an Ogg page"));
+            }
+            let segments = usize::from(page[26]);
+            let lacing = page
+                .get(27..27 + segments)
+                .ok_or(AudioError::Malformed("truncated page"))?;
+            let data_length: usize = lacing.iter().map(|value| usize::from(*value)).sum();
+            let length = 27 + segments + data_length;
+            if page.len() < length {
+                return Err(AudioError::Malformed("truncated page"));
+            }
+            offset += length;
+
+            let page_serial = u32::from_le_bytes([page[14], page[15], page[16], page[17]]);
+            if *serial.get_or_insert(page_serial) != page_serial {
+                continue;
+            }
+            let granule = i64::from_le_bytes(page[6..14].try_into().expect("8 bytes"));
+            if granule >= 0 {
+                granule_position = granule as u64;
+            }
+
+            let mut data = &page[27 + segments..length];
+            for value in lacing {
+                let (segment, rest) = data.split_at(usize::from(*value));
+                packet.extend_from_slice(segment);
+                data = rest;
+                if *value < 255 {
+                    packets.push(std::mem::take(&mut packet));
+                }
+            }
+        }
+
+        if packets.len() < 2 || !packets[0].starts_with(b"OpusHead") || packets[0].len() < 19 {
+            return Err(AudioError::Malformed("missing OpusHead header"));
+        }
+        if !packets[1].starts_with(b"OpusTags") {
+            return Err(AudioError::Malformed("missing OpusTags header"));
+        }
+        let mut packets = packets.into_iter();
+        Ok(OpusStream {
+            serial: serial.unwrap_or_default(),
+            head: packets.next().unwrap_or_default(),
+            tags: packets.next().unwrap_or_default(),
+            packets: packets.collect(),
+            granule_position,
+        })
+    }
+
+    pub fn channels(&self) -> u8 {
+        self.head[9]
+    }
+
+    /// Returns the number of samples at 48 kHz decoded at the start but not played.
+    pub fn pre_skip(&self) -> u64 {
+        u64::from(u16::from_le_bytes([self.head[10], self.head[11]]))
+    }
+
+    /// Returns the duration of the stream.
+    pub fn duration(&self) -> Duration {
+        let samples = self.granule_position.saturating_sub(self.pre_skip());
+        Duration::from_secs_f64(samples as f64 / GRANULE_RATE as f64)
+    }
+}
+
+/// Returns the number of samples at 48 kHz an Opus packet decodes to, read from its table
+/// of contents.
+pub fn packet_samples(packet: &[u8]) -> u64 {
+    let Some(toc) = packet.first() else {
+        return 0;
+    };
+    let config = toc >> 3;
+    // Frame sizes in tenths of milliseconds: SILK, hybrid and CELT configurations.
+    let tenths: u64 = match config {
+        0..=11 => [100, 200, 400, 600][usize::from(config % 4)],
+        12..=15 => [100, 200][usize::from(config % 2)],
+        _ => [25, 50, 100, 200][usize::from(config % 4)],
+    };
+    let frames = match toc & 0b11 {
+        0 => 1,
+        1 | 2 => 2,
+        _ => packet.get(1).map_or(0, |count| u64::from(count & 0x3f)),
+    };
+    frames * tenths * GRANULE_RATE / 10_000
+}
+
+/// Remuxes streams into a single logical stream played one after the other, with the
+/// headers of the first stream and continuous granule positions. The pre-skip of the other
+/// streams is played, a few milliseconds of encoder warm-up.
+pub fn concatenate(parts: &[&[u8]]) -> Result<Vec<u8>, AudioError> {
+    let streams = parts
+        .iter()
+        .map(|part| OpusStream::parse(part))
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(first) = streams.first() else {
+        return Ok(Vec::new());
+    };
+    if streams.iter().any(|stream| stream.channels() != first.channels()) {
+        return Err(AudioError::Mismatch);
+    }
+
+    let mut writer = PageWriter::new(first.serial);
+    writer.push_header(&first.head);
+    writer.push_header(&first.tags);
+    let mut offset = 0;
+    for (index, stream) in streams.iter().enumerate() {
+        let mut samples = offset;
+        for packet in &stream.packets {
+            samples += packet_samples(packet);
+            writer.push_packet(packet, samples);
+        }
+        if index == streams.len() - 1 {
+            // Keep the samples trimmed from the end of the last stream trimmed.
+            writer.set_granule_position(samples.min(offset + stream.granule_position));
+        }
+        offset = samples;
+    }
+    Ok(writer.finish())
+}
+
+/// Returns a stream of silence lasting `duration`, rounded to 20 ms, with `channels`
+/// channels.
+pub fn silence(channels: u8, duration: Duration) -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.extend_from_slice(&[1, channels, 0, 0]);
+    head.extend_from_slice(&48_000u32.to_le_bytes());
+    head.extend_from_slice(&[0, 0, 0]);
+    let vendor = env!("CARGO_PKG_NAME");
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes());
+
+    // CELT, full band, 20 ms, one frame, stereo if needed.
+    let toc = if channels > 1 { 0xfc } else { 0xf8 };
+    let mut packet = vec![toc];
+    packet.extend_from_slice(&SILENT_FRAME);
+    let count = (duration.as_secs_f64() / 0.02).round() as u64;
+
+    let mut writer = PageWriter::new(SILENCE_SERIAL);
+    writer.push_header(&head);
+    writer.push_header(&tags);
+    for index in 1..=count {
+        writer.push_packet(&packet, index * packet_samples(&packet));
+    }
+    writer.finish()
+}
+
+/// Writes packets into the pages of a logical stream.
+struct PageWriter {
+    serial: u32,
+    sequence: u32,
+    bytes: Vec<u8>,
+    segments: Vec<u8>,
+    payload: Vec<u8>,
+    /// Granule position of the last packet completed on the current page, -1 if none.
+    granule_position: i64,
+    /// Whether the current page starts with the rest of a packet.
+    continued: bool,
+}
+
+impl PageWriter {
+    fn new(serial: u32) -> Self {
+        PageWriter {
+            serial,
+            sequence: 0,
+            bytes: Vec::new(),
+            segments: Vec::new(),
+            payload: Vec::new(),
+            granule_position: -1,
+            continued: false,
+        }
+    }
+
+    /// Writes a header packet on pages of its own.
+    fn push_header(&mut self, packet: &[u8]) {
+        self.push_packet(packet, 0);
+        self.write_page(false);
+    }
+
+    fn push_packet(&mut self, packet: &[u8], granule_position: u64) {
+        if self.payload.len() >= PAGE_SIZE {
+            self.write_page(false);
+        }
+        let mut rest = packet;
+        loop {
+            if self.segments.len() == 255 {
+                self.write_page(false);
+            }
+            let length = rest.len().min(255);
+            self.segments.push(length as u8);
+            self.payload.extend_from_slice(&rest[..length]);
+            rest = &rest[length..];
+            if length < 255 {
+                break;
+            }
+        }
+        self.granule_position = granule_position as i64;
+    }
+
+    /// Overrides the granule position of the last packet pushed.
+    fn set_granule_position(&mut self, granule_position: u64) {
+        self.granule_position = granule_position as i64;
+    }
+
+    fn write_page(&mut self, last: bool) {
+        let mut header_type = 0;
+        if self.continued {
+            header_type |= 0x01;
+        }
+        if self.sequence == 0 {
+            header_type |= 0x02;
+        }
+        if last {
+            header_type |= 0x04;
+        }
+
+        let start = self.bytes.len();
+        self.bytes.extend_from_slice(b"OggS");
+        self.bytes.extend_from_slice(&[0, header_type]);
+        self.bytes.extend_from_slice(&self.granule_position.to_le_bytes());
+        self.bytes.extend_from_slice(&self.serial.to_le_bytes());
+        self.bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        self.bytes.extend_from_slice(&[0; 4]);
+        self.bytes.push(self.segments.len() as u8);
+        self.bytes.extend_from_slice(&self.segments);
+        self.bytes.extend_from_slice(&self.payload);
+        let crc = crc(&self.bytes[start..]);
+        self.bytes[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+
+        self.sequence += 1;
+        self.continued = self.segments.last() == Some(&255);
+        self.segments.clear();
+        self.payload.clear();
+        self.granule_position = -1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.write_page(true);
+        self.bytes
+    }
+}
+
+/// Computes the checksum of an Ogg page: CRC-32 with the polynomial 0x04c11db7, without
+/// reflection nor final inversion.
+fn crc(bytes: &[u8]) -> u32 {
+    static TABLE: OnceLock<[u32; 256]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [0u32; 256];
+        for (index, entry) in table.iter_mut().enumerate() {
+            let mut value = (index as u32) << 24;
+            for _ in 0..8 {
+                value = match value & 0x8000_0000 {
+                    0 => value << 1,
+                    _ => (value << 1) ^ 0x04c1_1db7,
+                };
+            }
+            *entry = value;
+        }
+        table
+    });
+    bytes
+        .iter()
+        .fold(0, |crc, byte| (crc << 8) ^ table[(((crc >> 24) as u8) ^ byte) as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The header fields of an Ogg page.
+    #[derive(Debug)]
+    struct Page {
+        header_type: u8,
+        granule_position: i64,
+        sequence: u32,
+        lacing: Vec<u8>,
+        /// Whether the checksum stored in the page matches its content.
+        valid_crc: bool,
+    }
+
+    fn pages(bytes: &[u8]) -> Vec<Page> {
+        let mut pages = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let segments = usize::from(rest[26]);
+            let lacing = rest[27..27 + segments].to_vec();
+            let data_length: usize = lacing.iter().map(|value| usize::from(*value)).sum();
+            let length = 27 + segments + data_length;
+            let mut page = rest[..length].to_vec();
+            let stored = u32::from_le_bytes(page[22..26].try_into().unwrap());
+            page[22..26].fill(0);
+            pages.push(Page {
+                header_type: rest[5],
+                granule_position: i64::from_le_bytes(rest[6..14].try_into().unwrap()),
+                sequence: u32::from_le_bytes(rest[18..22].try_into().unwrap()),
+                lacing,
+                valid_crc: crc(&page) == stored,
+            });
+            rest = &rest[length..];
+        }
+        pages
+    }
+
+    /// Returns an `OpusHead` header for `channels` channels skipping `pre_skip` samples.
+    fn head(channels: u8, pre_skip: u16) -> Vec<u8> {
+        let mut head = b"OpusHead".to_vec();
+        head.extend_from_slice(&[1, channels]);
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&48_000u32.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]);
+        head
+    }
+
+    /// Writes a stream of `packets`, the granule position of the last page set to `end`.
+    fn stream(head: &[u8], packets: &[Vec<u8>], end: u64) -> Vec<u8> {
+        let mut writer = PageWriter::new(7);
+        writer.push_header(head);
+        writer.push_header(b"OpusTags\0\0\0\0\0\0\0\0");
+        let mut samples = 0;
+        for packet in packets {
+            samples += packet_samples(packet);
+            writer.push_packet(packet, samples);
+        }
+        writer.set_granule_position(end);
+        writer.finish()
+    }
+
+    #[test]
+    fn computes_the_ogg_checksum() {
+        assert_eq!(crc(b"123456789"), 0x89a1_897f);
+        assert_eq!(crc(b"OggS"), 0x5fb0_a94f);
+    }
+
+    #[test]
+    fn counts_packet_samples() {
+        // CELT 20 ms, SILK 60 ms, hybrid 10 ms, then CELT 2.5 ms frames.
+        assert_eq!(packet_samples(&[0xf8]), 960);
+        assert_eq!(packet_samples(&[0x18]), 2880);
+        assert_eq!(packet_samples(&[0x60]), 480);
+        assert_eq!(packet_samples(&[0x81]), 240);
+        assert_eq!(packet_samples(&[0x83, 0x05]), 600);
+        assert_eq!(packet_samples(&[]), 0);
+    }
+
+    #[test]
+    fn laces_packets_across_pages() {
+        // 254 one-byte packets, then packets of 255 and 600 bytes spanning the next pages.
+        let mut packets: Vec<Vec<u8>> = (0..254).map(|_| vec![0xf8]).collect();
+        packets.push(vec![0xf8; 255]);
+        packets.push(vec![0xf8; 600]);
+        packets.push(vec![0xf8]);
+        let bytes = stream(&head(1, 0), &packets, 257 * 960);
+
+        let pages = pages(&bytes);
+        assert!(pages.iter().all(|page| page.valid_crc));
+        assert_eq!(pages.iter().map(|page| page.sequence).collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert_eq!(pages[0].header_type, 0x02);
+        assert_eq!(pages[2].lacing.len(), 255);
+        assert_eq!(pages[2].lacing[254], 255);
+        // The 255-byte packet ends with an empty segment on the next page.
+        assert_eq!(pages[3].header_type, 0x01 | 0x04);
+        assert_eq!(pages[3].lacing, [0, 255, 255, 90, 1]);
+        assert_eq!(pages[3].granule_position, 257 * 960);
+
+        let parsed = OpusStream::parse(&bytes).unwrap();
+        assert_eq!(parsed.packets, packets);
+        assert_eq!(parsed.serial, 7);
+        assert_eq!(parsed.duration(), Duration::from_secs_f64(257.0 * 0.02));
+    }
+
+    #[test]
+    fn rejects_streams_that_are_not_opus() {
+        let bytes = stream(b"Vorbis", &[], 0);
+        assert_eq!(
+            OpusStream::parse(&bytes),
+            Err(AudioError::Malformed("missing OpusHead header"))
+        );
+        // Without the empty last page and the last byte of the comment header.
+        let bytes = stream(&head(1, 0), &[], 0);
+        assert_eq!(
+            OpusStream::parse(&bytes[..bytes.len() - 27 - 1]),
+            Err(AudioError::Malformed("truncated page"))
+        );
+        assert_eq!(
+            OpusStream::parse(b"RIFF"),
+            Err(AudioError::Malformed("expected an Ogg page"))
+        );
+    }
+
+    #[test]
+    fn joins_streams_with_continuous_granule_positions() {
+        // The end of the first stream is trimmed, 312 samples of pre-skip and 2000 played.
+        let first = stream(&head(1, 312), &vec![vec![0xf8]; 3], 312 + 2000);
+        let second = silence(1, Duration::from_millis(100));
+
+        let joined = concatenate(&[&first, &second, &first]).unwrap();
+        let pages = pages(&joined);
+        assert!(pages.iter().all(|page| page.valid_crc));
+        assert!(pages.windows(2).all(|pair| pair[1].sequence == pair[0].sequence + 1));
+        assert_eq!(pages.last().unwrap().header_type & 0x04, 0x04);
+
+        let parsed = OpusStream::parse(&joined).unwrap();
+        assert_eq!(parsed.head, head(1, 312));
+        assert_eq!(parsed.packets.len(), 3 + 5 + 3);
+        // Only the end of the last stream is trimmed.
+        assert_eq!(parsed.granule_position, 3 * 960 + 5 * 960 + 312 + 2000);
+        assert_eq!(parsed.duration(), Duration::from_secs_f64((8.0 * 960.0 + 2000.0) / 48_000.0));
+    }
+
+    #[test]
+    fn refuses_to_join_streams_of_different_channels() {
+        let mono = silence(1, Duration::from_millis(20));
+        let stereo = silence(2, Duration::from_millis(20));
+        assert_eq!(concatenate(&[&mono, &stereo]), Err(AudioError::Mismatch));
+        assert_eq!(concatenate(&[]), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn generates_silence() {
+        let parsed = OpusStream::parse(&silence(2, Duration::from_millis(800))).unwrap();
+        assert_eq!(parsed.channels(), 2);
+        assert_eq!(parsed.pre_skip(), 0);
+        assert_eq!(parsed.packets.len(), 40);
+        assert!(parsed.packets.iter().all(|packet| packet == &[0xfc, 0xff, 0xfe]));
+        assert_eq!(parsed.duration(), Duration::from_millis(800));
+
+        // Long enough to span several pages.
+        let bytes = silence(1, Duration::from_secs_f64(120.5));
+        let pages = pages(&bytes);
+        assert!(pages.len() > 20);
+        assert!(pages.iter().all(|page| page.valid_crc && page.lacing.len() <= 255));
+        let granules: Vec<i64> = pages[2..].iter().map(|page| page.granule_position).collect();
+        assert!(granules.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let parsed = OpusStream::parse(&bytes).unwrap();
+        assert_eq!(parsed.packets.len(), 6025);
+        assert_eq!(parsed.duration(), Duration::from_secs_f64(120.5));
+    }
+}
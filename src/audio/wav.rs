@@ -0,0 +1,240 @@
+// src/audio/wav.rs
+
+//! RIFF/WAVE files, as returned for `LINEAR16`, `MULAW` and `ALAW`.
+
+use std::time::Duration;
+
+use crate::audio::AudioError;
+
+/// Format tag of linear PCM samples.
+pub const FORMAT_PCM: u16 = 1;
+
+/// Format tag of A-law samples.
+pub const FORMAT_ALAW: u16 = 6;
+
+/// Format tag of mu-law samples.
+pub const FORMAT_MULAW: u16 = 7;
+
+/// The `fmt ` chunk of a WAVE file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavFormat {
+    /// [FORMAT_PCM], [FORMAT_ALAW] or [FORMAT_MULAW].
+    pub format_tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+}
+
+impl WavFormat {
+    /// Returns the size in bytes of one sample of every channel.
+    ///
+    /// Formats returned by [parse] never overflow; see [WavFormat::checked_byte_rate].
+    pub fn block_align(&self) -> u16 {
+        self.channels * self.bits_per_sample.div_ceil(8)
+    }
+
+    pub fn byte_rate(&self) -> u32 {
+        self.sample_rate * u32::from(self.block_align())
+    }
+
+    /// Returns the byte rate, or `None` if the block align or the byte rate overflows.
+    pub fn checked_byte_rate(&self) -> Option<u32> {
+        let block_align = self.channels.checked_mul(self.bits_per_sample.div_ceil(8))?;
+        self.sample_rate.checked_mul(u32::from(block_align))
+    }
+
+    /// Returns the duration of `length` bytes of samples.
+    pub fn duration(&self, length: usize) -> Duration {
+        match self.byte_rate() {
+            0 => Duration::ZERO,
+            rate => Duration::from_secs_f64(length as f64 / f64::from(rate)),
+        }
+    }
+}
+
+/// Reads a WAVE file.
+///
+/// # Returns
+///
+/// Returns the format of the samples and the content of the `data` chunk. A `data` chunk
+/// whose size runs past the end of the file, as written by streaming encoders, is read up to
+/// the end.
+pub fn parse(bytes: &[u8]) -> Result<(WavFormat, &[u8]), AudioError> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(AudioError::Malformed("missing RIFF/WAVE header"));
+    }
+
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = u32_at(bytes, offset + 4) as usize;
+        let start = offset + 8;
+        let end = start.saturating_add(size).min(bytes.len());
+        match id {
+            b"fmt " if end - start >= 16 => {
+                let parsed = WavFormat {
+                    format_tag: u16_at(bytes, start),
+                    channels: u16_at(bytes, start + 2),
+                    sample_rate: u32_at(bytes, start + 4),
+                    bits_per_sample: u16_at(bytes, start + 14),
+                };
+                if parsed.checked_byte_rate().is_none() {
+                    return Err(AudioError::Malformed("byte rate out of range"));
+                }
+                format = Some(parsed);
+            }
+            b"data" => {
+                let format = format.ok_or(AudioError::Malformed("'data' before 'fmt '"))?;
+                return Ok((format, &bytes[start..end]));
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even size.
+        offset = end + (size % 2);
+    }
+    Err(AudioError::Malformed("missing 'data' chunk"))
+}
+
+/// Writes a WAVE file holding `data`, samples in `format`.
+pub fn write(format: &WavFormat, data: &[u8]) -> Vec<u8> {
+    // Formats other than linear PCM carry the size of their (empty) extension.
+    let fmt_size: u32 = if format.format_tag == FORMAT_PCM { 16 } else { 18 };
+    let data_size = data.len() as u32;
+    let padding = data.len() % 2;
+
+    let mut bytes = Vec::with_capacity(28 + fmt_size as usize + data.len() + padding);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(20 + fmt_size + data_size + padding as u32).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&fmt_size.to_le_bytes());
+    bytes.extend_from_slice(&format.format_tag.to_le_bytes());
+    bytes.extend_from_slice(&format.channels.to_le_bytes());
+    bytes.extend_from_slice(&format.sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&format.byte_rate().to_le_bytes());
+    bytes.extend_from_slice(&format.block_align().to_le_bytes());
+    bytes.extend_from_slice(&format.bits_per_sample.to_le_bytes());
+    if fmt_size == 18 {
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+    }
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    bytes.extend_from_slice(data);
+    if padding == 1 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINEAR16: WavFormat = WavFormat {
+        format_tag: FORMAT_PCM,
+        channels: 1,
+        sample_rate: 24_000,
+        bits_per_sample: 16,
+    };
+
+    const MULAW: WavFormat = WavFormat {
+        format_tag: FORMAT_MULAW,
+        channels: 2,
+        sample_rate: 8000,
+        bits_per_sample: 8,
+    };
+
+    #[test]
+    fn writes_the_header_of_linear_pcm() {
+        let bytes = write(&LINEAR16, &[1, 2, 3, 4]);
+        assert_eq!(bytes.len(), 44 + 4);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(&bytes, 4), 36 + 4);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u32_at(&bytes, 16), 16);
+        assert_eq!(u32_at(&bytes, 28), 48_000);
+        assert_eq!(u16_at(&bytes, 32), 2);
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32_at(&bytes, 40), 4);
+
+        assert_eq!(parse(&bytes), Ok((LINEAR16, &[1, 2, 3, 4][..])));
+    }
+
+    #[test]
+    fn pads_data_of_odd_length() {
+        let bytes = write(&MULAW, &[0xff; 3]);
+        // The `fmt ` chunk holds the size of its extension, and `data` a padding byte.
+        assert_eq!(bytes.len(), 12 + 8 + 18 + 8 + 3 + 1);
+        assert_eq!(u32_at(&bytes, 4), (bytes.len() - 8) as u32);
+        assert_eq!(u32_at(&bytes, 16), 18);
+        assert_eq!(&bytes[38..42], b"data");
+        assert_eq!(u32_at(&bytes, 42), 3);
+        assert_eq!(bytes.last(), Some(&0));
+
+        let (format, data) = parse(&bytes).unwrap();
+        assert_eq!(format, MULAW);
+        assert_eq!(data, [0xff; 3]);
+    }
+
+    #[test]
+    fn skips_padded_chunks_before_the_data() {
+        let mut bytes = write(&LINEAR16, &[]);
+        // A `LIST` chunk of odd size, inserted between `fmt ` and `data`.
+        let list = [b"LIST".as_slice(), &3u32.to_le_bytes(), &[b'a', b'b', b'c', 0]].concat();
+        bytes.splice(36..36, list);
+        bytes.extend_from_slice(&[5, 6]);
+        bytes[40 + 12..44 + 12].copy_from_slice(&2u32.to_le_bytes());
+
+        assert_eq!(parse(&bytes), Ok((LINEAR16, &[5, 6][..])));
+    }
+
+    #[test]
+    fn reads_streamed_data_up_to_the_end() {
+        let mut bytes = write(&LINEAR16, &[1, 2, 3, 4]);
+        bytes[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(parse(&bytes), Ok((LINEAR16, &[1, 2, 3, 4][..])));
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_wave() {
+        assert_eq!(parse(b"RIFF"), Err(AudioError::Malformed("missing RIFF/WAVE header")));
+        let bytes = write(&LINEAR16, &[]);
+        assert_eq!(parse(&bytes[..36]), Err(AudioError::Malformed("missing 'data' chunk")));
+        let mut bytes = bytes;
+        bytes[12..16].copy_from_slice(b"junk");
+        assert_eq!(parse(&bytes), Err(AudioError::Malformed("'data' before 'fmt '")));
+    }
+
+    #[test]
+    fn rejects_formats_whose_byte_rate_overflows() {
+        let wide = WavFormat { channels: u16::MAX, ..LINEAR16 };
+        assert_eq!(wide.checked_byte_rate(), None);
+        let fast = WavFormat { sample_rate: u32::MAX, ..LINEAR16 };
+        assert_eq!(fast.checked_byte_rate(), None);
+        assert_eq!(LINEAR16.checked_byte_rate(), Some(LINEAR16.byte_rate()));
+
+        let mut bytes = write(&LINEAR16, &[1, 2]);
+        bytes[22..24].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert_eq!(parse(&bytes), Err(AudioError::Malformed("byte rate out of range")));
+        let mut bytes = write(&LINEAR16, &[1, 2]);
+        bytes[24..28].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(parse(&bytes), Err(AudioError::Malformed("byte rate out of range")));
+    }
+
+    #[test]
+    fn measures_samples() {
+        assert_eq!(LINEAR16.duration(48_000), Duration::from_secs(1));
+        assert_eq!(MULAW.duration(4000), Duration::from_millis(250));
+        let empty = WavFormat { sample_rate: 0, ..LINEAR16 };
+        assert_eq!(empty.duration(100), Duration::ZERO);
+    }
+}
@@ -1,10 +1,32 @@
 use crate::{
-    types::{ credentials::{ GoogleCredentials, read_credentials }, synthesize::SynthesisInput },
+    normalize::Normalizer,
+    ssml::lexicon::Lexicon,
+    types::{
+        credentials::{ GoogleCredentials, read_credentials },
+        pronunciation::PronunciationSet,
+        synthesize::{ SynthesisInput, VoiceSelectionParams },
+    },
     auth::get_access_token,
-    services::synthesize::SynthesizeSpeechBuilder,
+    services::{
+        cache::AudioCache,
+        endpoint::{ DEFAULT_ENDPOINT, Transport },
+        long_audio::SynthesizeLongAudioBuilder,
+        operations::TokenProvider,
+        synthesize::{ SynthesizeSpeechBuilder, SynthesisError },
+        voices::{ self, VoiceCatalog },
+        voice_resolver::{ VoicePreferences, VoiceResolver },
+    },
 };
 
-use std::{ time::Instant, fs::File };
+use std::{ collections::HashMap, time::{ Duration, Instant }, fs::File };
+
+#[cfg(feature = "grpc")]
+use crate::grpc::{ self, streaming::StreamingSynthesizeBuilder, DEFAULT_GRPC_ENDPOINT };
+#[cfg(feature = "grpc")]
+use tonic::transport::Channel;
+
+/// Default time a fetched voice catalog is reused before `voices.list` is called again.
+pub const DEFAULT_VOICE_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
 
 /// Represents an authentication token.
 ///
@@ -21,6 +43,20 @@ pub struct AuthToken {
 pub struct TextToSpeechClient {
     credentials: GoogleCredentials,
     auth_token: AuthToken,
+    endpoint: String,
+    voice_cache: HashMap<String, VoiceCatalog>,
+    voice_cache_ttl: Duration,
+    voice_resolver: VoiceResolver,
+    transport: Transport,
+    pronunciations: PronunciationSet,
+    lint_ssml: bool,
+    normalizer: Option<Normalizer>,
+    lexicon: Option<Lexicon>,
+    audio_cache: Option<AudioCache>,
+    #[cfg(feature = "grpc")]
+    grpc_endpoint: String,
+    #[cfg(feature = "grpc")]
+    grpc_channel: Option<Channel>,
 }
 
 impl TextToSpeechClient {
@@ -46,6 +82,20 @@ impl TextToSpeechClient {
         Ok(TextToSpeechClient {
             credentials: credentials.clone(),
             auth_token: AuthToken { access_token, expiration },
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            voice_cache: HashMap::new(),
+            voice_cache_ttl: DEFAULT_VOICE_CACHE_TTL,
+            voice_resolver: VoiceResolver::default(),
+            transport: Transport::Rest,
+            pronunciations: PronunciationSet::new(),
+            lint_ssml: false,
+            normalizer: None,
+            lexicon: None,
+            audio_cache: None,
+            #[cfg(feature = "grpc")]
+            grpc_endpoint: DEFAULT_GRPC_ENDPOINT.to_string(),
+            #[cfg(feature = "grpc")]
+            grpc_channel: None,
         })
     }
 
@@ -71,6 +121,20 @@ impl TextToSpeechClient {
         Ok(TextToSpeechClient {
             credentials,
             auth_token: AuthToken { access_token, expiration },
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            voice_cache: HashMap::new(),
+            voice_cache_ttl: DEFAULT_VOICE_CACHE_TTL,
+            voice_resolver: VoiceResolver::default(),
+            transport: Transport::Rest,
+            pronunciations: PronunciationSet::new(),
+            lint_ssml: false,
+            normalizer: None,
+            lexicon: None,
+            audio_cache: None,
+            #[cfg(feature = "grpc")]
+            grpc_endpoint: DEFAULT_GRPC_ENDPOINT.to_string(),
+            #[cfg(feature = "grpc")]
+            grpc_channel: None,
         })
     }
 
@@ -117,10 +181,628 @@ impl TextToSpeechClient {
     ///
     /// Returns a [SynthesizeSpeechBuilder] instance for further configuration and synthesis.
     pub async fn synthesize_text(&mut self, input_text: String) -> SynthesizeSpeechBuilder {
+        self.synthesize_input(SynthesisInput::Text(input_text)).await
+    }
+
+    /// Synthesizes an SSML document into speech.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_ssml` - The SSML document to be synthesized into speech.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [SynthesizeSpeechBuilder] instance for further configuration and synthesis.
+    pub async fn synthesize_ssml(&mut self, input_ssml: String) -> SynthesizeSpeechBuilder {
+        self.synthesize_input(SynthesisInput::Ssml(input_ssml)).await
+    }
+
+    /// Synthesizes any kind of input into speech: text, SSML, markup or a multi-speaker dialogue.
+    ///
+    /// The pronunciations set with [TextToSpeechClient::set_pronunciations], the normalizer
+    /// and the lexicon are applied, and the request is sent through the transport set with
+    /// [TextToSpeechClient::set_transport].
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The input to be synthesized into speech.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [SynthesizeSpeechBuilder] instance for further configuration and synthesis.
+    pub async fn synthesize_input(&mut self, input: SynthesisInput) -> SynthesizeSpeechBuilder {
         let _ = self.check_token().await;
-        SynthesizeSpeechBuilder::new(self.auth_token.access_token.clone(), SynthesisInput {
-            text: Some(input_text),
-            ssml: None,
-        })
+        let mut builder = SynthesizeSpeechBuilder::new(self.auth_token.access_token.clone(), input)
+            .endpoint(&self.endpoint)
+            .pronunciations(&self.pronunciations)
+            .lint_ssml(self.lint_ssml);
+        if let Some(normalizer) = &self.normalizer {
+            builder = builder.normalize(normalizer);
+        }
+        if let Some(lexicon) = &self.lexicon {
+            builder = builder.lexicon(lexicon);
+        }
+        if let Some(cache) = &self.audio_cache {
+            builder = builder.cache(cache);
+        }
+        self.with_transport(builder)
+    }
+
+    /// Routes `builder` through the transport selected with [TextToSpeechClient::set_transport].
+    fn with_transport(&self, builder: SynthesizeSpeechBuilder) -> SynthesizeSpeechBuilder {
+        #[cfg(feature = "grpc")]
+        if let (Transport::Grpc, Some(channel)) = (self.transport, &self.grpc_channel) {
+            return builder.grpc_channel(channel.clone());
+        }
+        builder
+    }
+
+    /// Synthesizes long text into speech asynchronously.
+    ///
+    /// This method returns a builder for a `synthesizeLongAudio` request, which isn't bound by
+    /// the per-request input limit. The audio is written to `output_gcs_uri` and the request
+    /// resolves to an [Operation](crate::services::operations::Operation) handle that can be
+    /// polled or waited on. The project id is taken from the credentials, if present.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The text or SSML to be synthesized into speech.
+    /// * `output_gcs_uri` - The Cloud Storage object the audio is written to, e.g. `gs://bucket/book.wav`.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [SynthesizeLongAudioBuilder] instance for further configuration and synthesis.
+    pub async fn synthesize_long_audio(
+        &mut self,
+        input: SynthesisInput,
+        output_gcs_uri: &str
+    ) -> SynthesizeLongAudioBuilder {
+        let _ = self.check_token().await;
+        let tokens = TokenProvider::refreshing(
+            self.credentials.clone(),
+            self.auth_token.access_token.clone(),
+            self.auth_token.expiration
+        );
+        let mut builder = SynthesizeLongAudioBuilder::new(
+            self.auth_token.access_token.clone(),
+            input,
+            output_gcs_uri
+        )
+            .token_provider(tokens)
+            .endpoint(&self.endpoint)
+            .pronunciations(&self.pronunciations);
+        if let Some(normalizer) = &self.normalizer {
+            builder = builder.normalize(normalizer);
+        }
+        if let Some(lexicon) = &self.lexicon {
+            builder = builder.lexicon(lexicon);
+        }
+        match &self.credentials.project_id {
+            Some(project_id) => builder.project_id(project_id),
+            None => builder,
+        }
+    }
+
+    /// Sends every request to `endpoint` instead of [DEFAULT_ENDPOINT], e.g. a local stand-in
+    /// used in tests. The token endpoint is taken from the credentials' `token_uri`.
+    pub fn set_endpoint(&mut self, endpoint: &str) {
+        self.endpoint = endpoint.to_string();
+        self.voice_cache.clear();
+    }
+
+    /// Sets the pronunciations applied to every request made through this client.
+    ///
+    /// Pronunciations set on a single request take precedence for the same phrase.
+    pub fn set_pronunciations(&mut self, pronunciations: PronunciationSet) {
+        self.pronunciations = pronunciations;
+    }
+
+    /// Sets the normalizer applied to the text input of every request made through this
+    /// client, e.g. `Some(Normalizer::for_language(&language))`. `None` turns normalization
+    /// off, which is the default.
+    pub fn set_normalizer(&mut self, normalizer: Option<Normalizer>) {
+        self.normalizer = normalizer;
+    }
+
+    /// Sets the lexicon applied to the text and SSML input of every request made through this
+    /// client, after the normalizer, e.g. `Some(Lexicon::load("lexicon.pls")?)`. `None` turns
+    /// it off, which is the default.
+    pub fn set_lexicon(&mut self, lexicon: Option<Lexicon>) {
+        self.lexicon = lexicon;
+    }
+
+    /// Sets the cache answering synthesis requests made through this client that are
+    /// identical to a recent one, e.g. `Some(AudioCache::default())`. `None` turns caching
+    /// off, which is the default. Single requests can skip it with
+    /// [SynthesizeSpeechBuilder::no_cache].
+    pub fn set_audio_cache(&mut self, cache: Option<AudioCache>) {
+        self.audio_cache = cache;
+    }
+
+    /// Returns the cache set with [TextToSpeechClient::set_audio_cache], e.g. to read its
+    /// [stats](AudioCache::stats).
+    pub fn audio_cache(&self) -> Option<&AudioCache> {
+        self.audio_cache.as_ref()
+    }
+
+    /// Checks SSML input offline before every synthesis request made through this client,
+    /// so that documents the API would reject fail with
+    /// [SynthesisError::InvalidSsml](crate::services::synthesize::SynthesisError::InvalidSsml)
+    /// and positioned diagnostics instead of a bare 400. Off by default.
+    pub fn set_lint_ssml(&mut self, enabled: bool) {
+        self.lint_ssml = enabled;
+    }
+
+    /// Selects the protocol used to reach the API; [Transport::Rest] by default.
+    ///
+    /// Switching to [Transport::Grpc] opens the gRPC channel, which is then kept and shared by
+    /// every request.
+    pub fn set_transport(&mut self, transport: Transport) -> Result<(), SynthesisError> {
+        #[cfg(feature = "grpc")]
+        if transport == Transport::Grpc {
+            self.grpc_channel()?;
+        }
+        self.transport = transport;
+        Ok(())
+    }
+
+    /// Lists the voices supported by the TTS service.
+    ///
+    /// The result is cached per language code for [DEFAULT_VOICE_CACHE_TTL], or the duration
+    /// set with [TextToSpeechClient::set_voice_cache_ttl].
+    ///
+    /// # Arguments
+    ///
+    /// * `language_code` - Optional BCP-47 language tag. If set, only voices that can be used
+    ///   to synthesize this language are returned.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [Result] which is either a [VoiceCatalog] or a [SynthesisError].
+    pub async fn list_voices(
+        &mut self,
+        language_code: Option<&str>
+    ) -> Result<VoiceCatalog, SynthesisError> {
+        let key = language_code.unwrap_or_default().to_string();
+        if let Some(catalog) = self.voice_cache.get(&key) {
+            if !catalog.is_expired(self.voice_cache_ttl) {
+                return Ok(catalog.clone());
+            }
+        }
+
+        let _ = self.check_token().await;
+        let voices = match self.transport {
+            Transport::Rest => {
+                let access_token = &self.auth_token.access_token;
+                voices::list_voices(access_token, &self.endpoint, language_code).await?
+            }
+            #[cfg(feature = "grpc")]
+            Transport::Grpc => {
+                let channel = self.grpc_channel()?;
+                let access_token = &self.auth_token.access_token;
+                grpc::unary::list_voices(channel, access_token, language_code).await?
+            }
+        };
+        let catalog = VoiceCatalog::new(voices);
+        self.voice_cache.insert(key, catalog.clone());
+        Ok(catalog)
+    }
+
+    /// Sets how long a fetched voice catalog is reused before `voices.list` is called again.
+    pub fn set_voice_cache_ttl(&mut self, ttl: Duration) {
+        self.voice_cache_ttl = ttl;
+    }
+
+    /// Drops every cached voice catalog.
+    pub fn clear_voice_cache(&mut self) {
+        self.voice_cache.clear();
+    }
+
+    /// Replaces the [VoiceResolver] used by [TextToSpeechClient::resolve_voices].
+    pub fn set_voice_resolver(&mut self, resolver: VoiceResolver) {
+        self.voice_resolver = resolver;
+    }
+
+    /// Resolves the voices matching `preferences` from the voice catalog, best first.
+    ///
+    /// Pass the result to [SynthesizeSpeechBuilder::voice_chain] to retry with the next voice
+    /// if the API rejects one.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [Result] which is either a non-empty list of voices or
+    /// [SynthesisError::VoiceNotFound] if no voice is available for the language or its fallbacks.
+    pub async fn resolve_voices(
+        &mut self,
+        preferences: &VoicePreferences
+    ) -> Result<Vec<VoiceSelectionParams>, SynthesisError> {
+        let catalog = self.list_voices(None).await?;
+        let voices = self.voice_resolver.resolve(&catalog, preferences);
+        if voices.is_empty() {
+            return Err(
+                SynthesisError::VoiceNotFound(
+                    format!("no voice available for language '{}'", preferences.language_code)
+                )
+            );
+        }
+        Ok(voices)
+    }
+
+    /// Sends gRPC requests to `endpoint` instead of [DEFAULT_GRPC_ENDPOINT], e.g. a local
+    /// stand-in used in tests. Plaintext is used unless the endpoint is `https`.
+    #[cfg(feature = "grpc")]
+    pub fn set_grpc_endpoint(&mut self, endpoint: &str) -> Result<(), SynthesisError> {
+        self.grpc_channel = Some(grpc::connect(endpoint)?);
+        self.grpc_endpoint = endpoint.to_string();
+        self.voice_cache.clear();
+        Ok(())
+    }
+
+    /// Returns the gRPC channel, opening it on first use. The channel is shared by every
+    /// gRPC request made through this client.
+    #[cfg(feature = "grpc")]
+    fn grpc_channel(&mut self) -> Result<Channel, SynthesisError> {
+        if let Some(channel) = &self.grpc_channel {
+            return Ok(channel.clone());
+        }
+        let channel = grpc::connect(&self.grpc_endpoint)?;
+        self.grpc_channel = Some(channel.clone());
+        Ok(channel)
+    }
+
+    /// Starts a streaming synthesis session over gRPC.
+    ///
+    /// Text is sent in chunks while audio chunks are received as they are produced, which
+    /// keeps the latency low for conversational use. Only Chirp 3 HD voices support streaming.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [StreamingSynthesizeBuilder] instance for further configuration; call
+    /// [StreamingSynthesizeBuilder::start] to open the stream.
+    #[cfg(feature = "grpc")]
+    pub async fn streaming_synthesize(
+        &mut self
+    ) -> Result<StreamingSynthesizeBuilder, SynthesisError> {
+        let _ = self.check_token().await;
+        let channel = self.grpc_channel()?;
+        Ok(StreamingSynthesizeBuilder::new(channel, self.auth_token.access_token.clone()))
+    }
+}
+
+#[cfg(all(test, feature = "grpc"))]
+mod tests {
+    use async_trait::async_trait;
+    use base64::{ engine::general_purpose::STANDARD, Engine };
+    use tonic::{ Request, Response, Status };
+
+    use super::*;
+    use crate::{
+        grpc::{ proto, test_server::{ serve, TextToSpeech } },
+        test_server::TestServer,
+        types::{
+            pronunciation::PhoneticEncoding,
+            synthesize::{ AudioEncoding, SsmlVoiceGender, SynthesizeResponse },
+        },
+    };
+
+    const AUDIO: [u8; 6] = [0xff, 0xf3, 0x44, 0xc4, 0x00, 0x01];
+
+    /// Creates a client holding `access_token`, without fetching one.
+    fn client(access_token: &str) -> TextToSpeechClient {
+        TextToSpeechClient {
+            credentials: GoogleCredentials {
+                private_key: String::new(),
+                client_email: "tts@example.iam.gserviceaccount.com".to_string(),
+                token_uri: "http://127.0.0.1:1/token".to_string(),
+                project_id: None,
+            },
+            auth_token: AuthToken {
+                access_token: access_token.to_string(),
+                expiration: Instant::now() + Duration::from_secs(3600),
+            },
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            voice_cache: HashMap::new(),
+            voice_cache_ttl: DEFAULT_VOICE_CACHE_TTL,
+            voice_resolver: VoiceResolver::default(),
+            transport: Transport::Rest,
+            pronunciations: PronunciationSet::new(),
+            lint_ssml: false,
+            normalizer: None,
+            lexicon: None,
+            audio_cache: None,
+            grpc_endpoint: DEFAULT_GRPC_ENDPOINT.to_string(),
+            grpc_channel: None,
+        }
+    }
+
+    /// Answers like the REST stand-in of [rest_server], recording the requests.
+    #[derive(Default)]
+    struct Stub {
+        requests: std::sync::Arc<std::sync::Mutex<Vec<(String, String)>>>,
+    }
+
+    impl Stub {
+        fn record<T>(&self, request: &Request<T>, text: &str) {
+            let authorization = request
+                .metadata()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            self.requests.lock().unwrap().push((authorization, text.to_string()));
+        }
+    }
+
+    #[async_trait]
+    impl TextToSpeech for Stub {
+        async fn synthesize_speech(
+            &self,
+            request: Request<proto::SynthesizeSpeechRequest>
+        ) -> Result<Response<proto::SynthesizeSpeechResponse>, Status> {
+            let message = request.get_ref();
+            let text = match message.input.as_ref().and_then(|input| input.input_source.clone()) {
+                Some(proto::synthesis_input::InputSource::Text(text)) => text,
+                _ => String::new(),
+            };
+            self.record(&request, &text);
+            let voice = message.voice.clone().unwrap_or_default();
+            if voice.name != "en-US-Neural2-F" || voice.language_code != "en-US" {
+                return Err(Status::not_found("voice not found"));
+            }
+            Ok(
+                Response::new(proto::SynthesizeSpeechResponse {
+                    audio_content: AUDIO.to_vec(),
+                    ..Default::default()
+                })
+            )
+        }
+
+        async fn list_voices(
+            &self,
+            request: Request<proto::ListVoicesRequest>
+        ) -> Result<Response<proto::ListVoicesResponse>, Status> {
+            let language_code = request.get_ref().language_code.clone();
+            self.record(&request, &language_code);
+            let voice = |name: &str, gender: proto::SsmlVoiceGender| proto::Voice {
+                language_codes: vec!["en-US".to_string()],
+                name: name.to_string(),
+                ssml_gender: gender as i32,
+                natural_sample_rate_hertz: 24000,
+            };
+            Ok(
+                Response::new(proto::ListVoicesResponse {
+                    voices: vec![
+                        voice("en-US-Neural2-F", proto::SsmlVoiceGender::Female),
+                        voice("en-US-Neural2-D", proto::SsmlVoiceGender::Male)
+                    ],
+                })
+            )
+        }
+    }
+
+    async fn rest_server() -> TestServer {
+        TestServer::start(|request| {
+            match (request.method.as_str(), request.path.as_str()) {
+                ("POST", "/v1/text:synthesize") => {
+                    let body = request.json();
+                    if body["voice"]["name"] != "en-US-Neural2-F" {
+                        let error = r#"{"error": {"code": 404, "status": "NOT_FOUND"}}"#;
+                        return (404, error.to_string());
+                    }
+                    (200, serde_json::json!({ "audioContent": STANDARD.encode(AUDIO) }).to_string())
+                }
+                ("GET", "/v1/voices?languageCode=en-US") => {
+                    let voices = serde_json::json!({ "voices": [
+                        {
+                            "languageCodes": ["en-US"],
+                            "name": "en-US-Neural2-F",
+                            "ssmlGender": "FEMALE",
+                            "naturalSampleRateHertz": 24000,
+                        },
+                        {
+                            "languageCodes": ["en-US"],
+                            "name": "en-US-Neural2-D",
+                            "ssmlGender": "MALE",
+                            "naturalSampleRateHertz": 24000,
+                        },
+                    ] });
+                    (200, voices.to_string())
+                }
+                _ => (404, "{}".to_string()),
+            }
+        }).await
+    }
+
+    async fn synthesize(client: &mut TextToSpeechClient) -> SynthesizeResponse {
+        client
+            .synthesize_text("Hello".to_string()).await
+            .voice_name("en-US-Neural2-F".parse().unwrap())
+            .audio_encoding(AudioEncoding::Mp3)
+            .sample_rate_hertz(24000)
+            .exec().await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn synthesizes_the_same_over_rest_and_grpc() {
+        let rest = rest_server().await;
+        let stub = Stub::default();
+        let grpc_requests = stub.requests.clone();
+        let (channel, grpc) = serve(stub).await;
+
+        let mut client = client("token");
+        client.set_endpoint(&rest.endpoint);
+        let over_rest = synthesize(&mut client).await;
+
+        client.grpc_channel = Some(channel);
+        client.set_transport(Transport::Grpc).unwrap();
+        let over_grpc = synthesize(&mut client).await;
+
+        for response in [&over_rest, &over_grpc] {
+            assert_eq!(response.audioContent.bytes(), AUDIO);
+            assert_eq!(response.audioContent.encoding(), AudioEncoding::Mp3);
+            assert_eq!(response.audioContent.sample_rate_hertz(), Some(24000));
+            assert!(response.timepoints.is_empty());
+        }
+
+        let rest_requests = rest.requests();
+        assert_eq!(rest_requests.len(), 1);
+        assert_eq!(rest_requests[0].header("authorization"), Some("Bearer token"));
+        assert_eq!(rest_requests[0].json()["input"]["text"], "Hello");
+        assert_eq!(
+            *grpc_requests.lock().unwrap(),
+            [("Bearer token".to_string(), "Hello".to_string())]
+        );
+        grpc.abort();
+    }
+
+    #[tokio::test]
+    async fn lists_the_same_voices_over_rest_and_grpc() {
+        let rest = rest_server().await;
+        let stub = Stub::default();
+        let grpc_requests = stub.requests.clone();
+        let (channel, grpc) = serve(stub).await;
+
+        let mut client = client("token");
+        client.set_endpoint(&rest.endpoint);
+        let over_rest = client.list_voices(Some("en-US")).await.unwrap();
+
+        client.grpc_channel = Some(channel);
+        client.set_transport(Transport::Grpc).unwrap();
+        client.clear_voice_cache();
+        let over_grpc = client.list_voices(Some("en-US")).await.unwrap();
+
+        assert_eq!(over_rest.voices(), over_grpc.voices());
+        let names: Vec<String> = over_grpc
+            .iter()
+            .map(|voice| voice.name.to_string())
+            .collect();
+        assert_eq!(names, ["en-US-Neural2-D", "en-US-Neural2-F"]);
+        assert_eq!(over_grpc.voices()[1].ssmlGender, SsmlVoiceGender::Female);
+        assert_eq!(over_grpc.voices()[1].naturalSampleRateHertz, 24000);
+
+        assert_eq!(rest.requests().len(), 1);
+        assert_eq!(
+            *grpc_requests.lock().unwrap(),
+            [("Bearer token".to_string(), "en-US".to_string())]
+        );
+        grpc.abort();
+    }
+
+    #[tokio::test]
+    async fn caches_voice_catalogs_for_their_ttl() {
+        let rest = rest_server().await;
+        let mut client = client("token");
+        client.set_endpoint(&rest.endpoint);
+
+        let first = client.list_voices(Some("en-US")).await.unwrap();
+        let second = client.list_voices(Some("en-US")).await.unwrap();
+        assert_eq!(second.fetched_at(), first.fetched_at());
+        assert_eq!(rest.requests().len(), 1);
+
+        // Catalogs are cached per language.
+        assert!(client.list_voices(None).await.is_err());
+        assert_eq!(rest.requests().len(), 2);
+
+        client.set_voice_cache_ttl(Duration::ZERO);
+        let refreshed = client.list_voices(Some("en-US")).await.unwrap();
+        assert!(refreshed.fetched_at() > first.fetched_at());
+        assert_eq!(rest.requests().len(), 3);
+
+        client.set_voice_cache_ttl(DEFAULT_VOICE_CACHE_TTL);
+        client.list_voices(Some("en-US")).await.unwrap();
+        assert_eq!(rest.requests().len(), 3);
+
+        client.clear_voice_cache();
+        client.list_voices(Some("en-US")).await.unwrap();
+        assert_eq!(rest.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_next_voice_over_grpc() {
+        let stub = Stub::default();
+        let grpc_requests = stub.requests.clone();
+        let (channel, grpc) = serve(stub).await;
+
+        let mut client = client("token");
+        client.grpc_channel = Some(channel);
+        client.set_transport(Transport::Grpc).unwrap();
+        let voices = ["en-US-Neural2-Z", "en-US-Neural2-F"]
+            .map(|name| VoiceSelectionParams::from_name(name.parse().unwrap()));
+        let response = client
+            .synthesize_text("Hello".to_string()).await
+            .voice_chain(voices.to_vec())
+            .exec().await
+            .unwrap();
+
+        assert_eq!(response.audioContent.bytes(), AUDIO);
+        assert_eq!(grpc_requests.lock().unwrap().len(), 2);
+        grpc.abort();
+    }
+
+    #[tokio::test]
+    async fn rewrites_inputs_set_after_the_builder_is_created() {
+        let rest = rest_server().await;
+        let mut client = client("token");
+        client.set_endpoint(&rest.endpoint);
+        client.set_normalizer(Some(Normalizer::for_language(&"en-US".parse().unwrap())));
+        client.set_lexicon(Some(Lexicon::new().alias("TTS", "text to speech")));
+
+        let builder = client
+            .synthesize_text("Hello".to_string()).await
+            .input_text("TTS for $5")
+            .voice_name("en-US-Neural2-F".parse().unwrap());
+        builder.exec().await.unwrap();
+
+        let ssml = r#"<speak><sub alias="text to speech">TTS</sub> for 5 dollars</speak>"#;
+        assert_eq!(rest.requests()[0].json()["input"], serde_json::json!({ "ssml": ssml }));
+    }
+
+    #[tokio::test]
+    async fn merges_client_pronunciations_with_the_request_ones() {
+        let rest = rest_server().await;
+        let mut client = client("token");
+        client.set_endpoint(&rest.endpoint);
+        client.set_pronunciations(
+            PronunciationSet::new().ipa("tomato", "təˈmɑːtoʊ").ipa("Nguyen", "wɪn")
+        );
+
+        client
+            .synthesize_text("tomato, Nguyen and Siobhan".to_string()).await
+            .custom_pronunciation("Nguyen", PhoneticEncoding::XSampa, "N_win")
+            .pronunciations(&PronunciationSet::new().ipa("Siobhan", "ʃɪˈvɔːn"))
+            .voice_name("en-US-Neural2-F".parse().unwrap())
+            .exec().await
+            .unwrap();
+        client
+            .synthesize_text("tomato".to_string()).await
+            .voice_name("en-US-Neural2-F".parse().unwrap())
+            .exec().await
+            .unwrap();
+
+        let requests = rest.requests();
+        let pronunciations = |index: usize| {
+            requests[index].json()["input"]["customPronunciations"]["pronunciations"].clone()
+        };
+        let ipa = "PHONETIC_ENCODING_IPA";
+        assert_eq!(
+            pronunciations(0),
+            serde_json::json!([
+                { "phrase": "tomato", "phoneticEncoding": ipa, "pronunciation": "təˈmɑːtoʊ" },
+                {
+                    "phrase": "Nguyen",
+                    "phoneticEncoding": "PHONETIC_ENCODING_X_SAMPA",
+                    "pronunciation": "N_win",
+                },
+                { "phrase": "Siobhan", "phoneticEncoding": ipa, "pronunciation": "ʃɪˈvɔːn" },
+            ])
+        );
+        assert_eq!(
+            pronunciations(1),
+            serde_json::json!([
+                { "phrase": "tomato", "phoneticEncoding": ipa, "pronunciation": "təˈmɑːtoʊ" },
+                { "phrase": "Nguyen", "phoneticEncoding": ipa, "pronunciation": "wɪn" },
+            ])
+        );
     }
 }
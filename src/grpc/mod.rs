@@ -0,0 +1,242 @@
+// src/grpc/mod.rs
+
+//! gRPC transport for the Text-to-Speech API, enabled with the `grpc` feature.
+
+use tonic::{
+    metadata::{ Ascii, MetadataValue },
+    service::Interceptor,
+    transport::{ Channel, ClientTlsConfig, Endpoint },
+    Request,
+    Status,
+};
+
+use crate::{
+    services::synthesize::SynthesisError,
+    types::{
+        pronunciation::{ CustomPronunciations, PhoneticEncoding },
+        synthesize::{
+            AudioConfig,
+            AudioEncoding,
+            CustomVoiceParams,
+            ReportedUsage,
+            RequestInput,
+            SsmlVoiceGender,
+            SynthesisInput,
+            TimepointType,
+            VoiceSelectionParams,
+        },
+        voice_name::ParseVoiceNameError,
+        voices::Voice,
+    },
+};
+
+pub mod proto;
+pub mod streaming;
+pub mod unary;
+#[cfg(test)]
+pub(crate) mod test_server;
+
+/// Endpoint of the Google Text-to-Speech gRPC API.
+pub const DEFAULT_GRPC_ENDPOINT: &str = "https://texttospeech.googleapis.com";
+
+/// Opens a channel to `endpoint`.
+///
+/// The channel connects lazily on the first call, and is cheap to clone. TLS is used for
+/// `https` endpoints only, so a plaintext local stand-in can be used in tests.
+pub fn connect(endpoint: &str) -> Result<Channel, SynthesisError> {
+    let mut endpoint = Endpoint::from_shared(endpoint.to_string())?;
+    if endpoint.uri().scheme_str() == Some("https") {
+        endpoint = endpoint.tls_config(ClientTlsConfig::new().with_webpki_roots())?;
+    }
+    Ok(endpoint.connect_lazy())
+}
+
+/// Adds the OAuth access token to every gRPC request.
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    authorization: MetadataValue<Ascii>,
+}
+
+impl AuthInterceptor {
+    pub fn new(access_token: &str) -> Result<Self, SynthesisError> {
+        let authorization = format!("Bearer {}", access_token)
+            .parse()
+            .map_err(|_| Status::unauthenticated("access token is not valid ASCII"))?;
+        Ok(AuthInterceptor { authorization })
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request.metadata_mut().insert("authorization", self.authorization.clone());
+        Ok(request)
+    }
+}
+
+impl From<SsmlVoiceGender> for proto::SsmlVoiceGender {
+    fn from(value: SsmlVoiceGender) -> Self {
+        match value {
+            SsmlVoiceGender::SsmlVoiceGenderUnspecified => proto::SsmlVoiceGender::Unspecified,
+            SsmlVoiceGender::Male => proto::SsmlVoiceGender::Male,
+            SsmlVoiceGender::Female => proto::SsmlVoiceGender::Female,
+            SsmlVoiceGender::Neutral => proto::SsmlVoiceGender::Neutral,
+        }
+    }
+}
+
+impl From<proto::SsmlVoiceGender> for SsmlVoiceGender {
+    fn from(value: proto::SsmlVoiceGender) -> Self {
+        match value {
+            proto::SsmlVoiceGender::Unspecified => SsmlVoiceGender::SsmlVoiceGenderUnspecified,
+            proto::SsmlVoiceGender::Male => SsmlVoiceGender::Male,
+            proto::SsmlVoiceGender::Female => SsmlVoiceGender::Female,
+            proto::SsmlVoiceGender::Neutral => SsmlVoiceGender::Neutral,
+        }
+    }
+}
+
+impl From<ReportedUsage> for proto::ReportedUsage {
+    fn from(value: ReportedUsage) -> Self {
+        match value {
+            ReportedUsage::ReportedUsageUnspecified => proto::ReportedUsage::Unspecified,
+            ReportedUsage::Realtime => proto::ReportedUsage::Realtime,
+            ReportedUsage::Offline => proto::ReportedUsage::Offline,
+        }
+    }
+}
+
+impl From<TimepointType> for proto::TimepointType {
+    fn from(value: TimepointType) -> Self {
+        match value {
+            TimepointType::TimepointTypeUnspecified => proto::TimepointType::Unspecified,
+            TimepointType::SsmlMark => proto::TimepointType::SsmlMark,
+        }
+    }
+}
+
+impl From<AudioEncoding> for proto::AudioEncoding {
+    fn from(value: AudioEncoding) -> Self {
+        match value {
+            AudioEncoding::AudioEncodingUnspecified => proto::AudioEncoding::Unspecified,
+            AudioEncoding::Linear16 => proto::AudioEncoding::Linear16,
+            AudioEncoding::Mp3 => proto::AudioEncoding::Mp3,
+            AudioEncoding::Mp3_64Kbps => proto::AudioEncoding::Mp364Kbps,
+            AudioEncoding::OggOpus => proto::AudioEncoding::OggOpus,
+            AudioEncoding::Mulaw => proto::AudioEncoding::Mulaw,
+            AudioEncoding::Alaw => proto::AudioEncoding::Alaw,
+            AudioEncoding::Pcm => proto::AudioEncoding::Pcm,
+            AudioEncoding::M4a => proto::AudioEncoding::M4a,
+        }
+    }
+}
+
+impl From<&VoiceSelectionParams> for proto::VoiceSelectionParams {
+    fn from(value: &VoiceSelectionParams) -> Self {
+        proto::VoiceSelectionParams {
+            language_code: value.languageCode.to_string(),
+            name: value.name
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            ssml_gender: proto::SsmlVoiceGender::from(value.ssmlGender) as i32,
+            custom_voice: value.customVoice.as_ref().map(Into::into),
+            voice_clone: value.voiceClone.as_ref().map(|clone| proto::VoiceCloneParams {
+                voice_cloning_key: clone.voiceCloningKey.clone(),
+            }),
+        }
+    }
+}
+
+impl From<&CustomVoiceParams> for proto::CustomVoiceParams {
+    fn from(value: &CustomVoiceParams) -> Self {
+        proto::CustomVoiceParams {
+            model: value.model.clone(),
+            reported_usage: value.reportedUsage
+                .map(|usage| proto::ReportedUsage::from(usage) as i32)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&RequestInput> for proto::SynthesisInput {
+    fn from(value: &RequestInput) -> Self {
+        use proto::synthesis_input::InputSource;
+
+        let input_source = match &value.source {
+            SynthesisInput::Text(text) => InputSource::Text(text.clone()),
+            SynthesisInput::Ssml(ssml) => InputSource::Ssml(ssml.clone()),
+            SynthesisInput::Markup(markup) => InputSource::Markup(markup.clone()),
+            SynthesisInput::MultiSpeaker(markup) => {
+                let turns = markup.turns
+                    .iter()
+                    .map(|turn| proto::multi_speaker_markup::Turn {
+                        speaker: turn.speaker.clone(),
+                        text: turn.text.clone(),
+                    })
+                    .collect();
+                InputSource::MultiSpeakerMarkup(proto::MultiSpeakerMarkup { turns })
+            }
+        };
+        proto::SynthesisInput {
+            input_source: Some(input_source),
+            custom_pronunciations: value.customPronunciations.as_ref().map(Into::into),
+        }
+    }
+}
+
+impl From<&CustomPronunciations> for proto::CustomPronunciations {
+    fn from(value: &CustomPronunciations) -> Self {
+        let pronunciations = value.pronunciations
+            .iter()
+            .map(|params| {
+                let encoding = match params.phoneticEncoding {
+                    PhoneticEncoding::PhoneticEncodingUnspecified => {
+                        proto::PhoneticEncoding::Unspecified
+                    }
+                    PhoneticEncoding::Ipa => proto::PhoneticEncoding::Ipa,
+                    PhoneticEncoding::XSampa => proto::PhoneticEncoding::XSampa,
+                };
+                proto::CustomPronunciationParams {
+                    phrase: Some(params.phrase.clone()),
+                    phonetic_encoding: Some(encoding as i32),
+                    pronunciation: Some(params.pronunciation.clone()),
+                }
+            })
+            .collect();
+        proto::CustomPronunciations { pronunciations }
+    }
+}
+
+impl From<&AudioConfig> for proto::AudioConfig {
+    fn from(value: &AudioConfig) -> Self {
+        proto::AudioConfig {
+            audio_encoding: proto::AudioEncoding::from(value.audioEncoding) as i32,
+            speaking_rate: value.speakingRate.unwrap_or_default().into(),
+            pitch: value.pitch.unwrap_or_default().into(),
+            volume_gain_db: value.volumeGainDb.unwrap_or_default().into(),
+            sample_rate_hertz: value.sampleRateHertz.unwrap_or_default(),
+            effects_profile_id: value.effectsProfileId.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl TryFrom<proto::Voice> for Voice {
+    type Error = ParseVoiceNameError;
+
+    /// Fails if the name of the voice can't be parsed. Language codes that can't be parsed
+    /// are skipped.
+    fn try_from(value: proto::Voice) -> Result<Self, Self::Error> {
+        let gender = proto::SsmlVoiceGender::try_from(value.ssml_gender).unwrap_or_default();
+        Ok(Voice {
+            languageCodes: value.language_codes
+                .iter()
+                .filter_map(|code| {
+                    code.parse().map_err(|e| log::warn!("skipping language code: {}", e)).ok()
+                })
+                .collect(),
+            name: value.name.parse()?,
+            ssmlGender: gender.into(),
+            naturalSampleRateHertz: value.natural_sample_rate_hertz,
+        })
+    }
+}
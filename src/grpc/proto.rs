@@ -0,0 +1,358 @@
+// src/grpc/proto.rs
+
+//! Protobuf messages of `google.cloud.texttospeech.v1`, as defined in `cloud_tts.proto`.
+//!
+//! These are written by hand rather than generated so that building the crate doesn't
+//! require `protoc`. Only the messages used by this crate are declared.
+
+use tonic::{
+    client::Grpc,
+    codec::{ ProstCodec, Streaming },
+    codegen::http::uri::PathAndQuery,
+    service::interceptor::InterceptedService,
+    transport::Channel,
+    IntoRequest,
+    IntoStreamingRequest,
+    Response,
+    Status,
+};
+
+use crate::services::endpoint::ApiVersion;
+
+use super::AuthInterceptor;
+
+/// Gender of the voice as described in the SSML voice element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SsmlVoiceGender {
+    Unspecified = 0,
+    Male = 1,
+    Female = 2,
+    Neutral = 3,
+}
+
+/// Configuration to set up audio encoder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AudioEncoding {
+    Unspecified = 0,
+    Linear16 = 1,
+    Mp3 = 2,
+    OggOpus = 3,
+    Mp364Kbps = 4,
+    Mulaw = 5,
+    Alaw = 6,
+    Pcm = 7,
+    M4a = 8,
+}
+
+/// The usage of the synthesized audio, reported for custom voices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReportedUsage {
+    Unspecified = 0,
+    Realtime = 1,
+    Offline = 2,
+}
+
+/// The type of timepoint to return in the response (`v1beta1` only).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TimepointType {
+    Unspecified = 0,
+    SsmlMark = 1,
+}
+
+/// Contains text input to be synthesized. Exactly one of the sources must be set.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SynthesisInput {
+    #[prost(oneof = "synthesis_input::InputSource", tags = "1, 2, 4, 5")]
+    pub input_source: Option<synthesis_input::InputSource>,
+    #[prost(message, optional, tag = "3")]
+    pub custom_pronunciations: Option<CustomPronunciations>,
+}
+
+pub mod synthesis_input {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum InputSource {
+        #[prost(string, tag = "1")] Text(String),
+        #[prost(string, tag = "2")] Ssml(String),
+        #[prost(message, tag = "4")] MultiSpeakerMarkup(super::MultiSpeakerMarkup),
+        #[prost(string, tag = "5")] Markup(String),
+    }
+}
+
+/// The phonetic encoding of a custom pronunciation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum PhoneticEncoding {
+    Unspecified = 0,
+    Ipa = 1,
+    XSampa = 2,
+}
+
+/// Pronunciation customization for a phrase.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CustomPronunciationParams {
+    #[prost(string, optional, tag = "1")]
+    pub phrase: Option<String>,
+    #[prost(enumeration = "PhoneticEncoding", optional, tag = "2")]
+    pub phonetic_encoding: Option<i32>,
+    #[prost(string, optional, tag = "3")]
+    pub pronunciation: Option<String>,
+}
+
+/// A collection of pronunciation customizations.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CustomPronunciations {
+    #[prost(message, repeated, tag = "1")]
+    pub pronunciations: Vec<CustomPronunciationParams>,
+}
+
+/// A collection of turns for multi-speaker synthesis.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MultiSpeakerMarkup {
+    #[prost(message, repeated, tag = "1")]
+    pub turns: Vec<multi_speaker_markup::Turn>,
+}
+
+pub mod multi_speaker_markup {
+    /// A multi-speaker turn.
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Turn {
+        #[prost(string, tag = "1")]
+        pub speaker: String,
+        #[prost(string, tag = "2")]
+        pub text: String,
+    }
+}
+
+/// Description of which voice to use for a synthesis request.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VoiceSelectionParams {
+    #[prost(string, tag = "1")]
+    pub language_code: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(enumeration = "SsmlVoiceGender", tag = "3")]
+    pub ssml_gender: i32,
+    #[prost(message, optional, tag = "4")]
+    pub custom_voice: Option<CustomVoiceParams>,
+    #[prost(message, optional, tag = "5")]
+    pub voice_clone: Option<VoiceCloneParams>,
+}
+
+/// The configuration of a voice clone (`v1beta1` only).
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VoiceCloneParams {
+    #[prost(string, tag = "1")]
+    pub voice_cloning_key: String,
+}
+
+/// Description of the custom voice to be synthesized.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CustomVoiceParams {
+    #[prost(string, tag = "1")]
+    pub model: String,
+    #[prost(enumeration = "ReportedUsage", tag = "3")]
+    pub reported_usage: i32,
+}
+
+/// Description of audio data to be synthesized.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AudioConfig {
+    #[prost(enumeration = "AudioEncoding", tag = "1")]
+    pub audio_encoding: i32,
+    #[prost(double, tag = "2")]
+    pub speaking_rate: f64,
+    #[prost(double, tag = "3")]
+    pub pitch: f64,
+    #[prost(double, tag = "4")]
+    pub volume_gain_db: f64,
+    #[prost(int32, tag = "5")]
+    pub sample_rate_hertz: i32,
+    #[prost(string, repeated, tag = "6")]
+    pub effects_profile_id: Vec<String>,
+}
+
+/// The top-level message sent by the client for the `SynthesizeSpeech` method.
+///
+/// `enable_time_pointing` is only known to `v1beta1`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SynthesizeSpeechRequest {
+    #[prost(message, optional, tag = "1")]
+    pub input: Option<SynthesisInput>,
+    #[prost(message, optional, tag = "2")]
+    pub voice: Option<VoiceSelectionParams>,
+    #[prost(message, optional, tag = "3")]
+    pub audio_config: Option<AudioConfig>,
+    #[prost(enumeration = "TimepointType", repeated, tag = "4")]
+    pub enable_time_pointing: Vec<i32>,
+}
+
+/// The message returned to the client by the `SynthesizeSpeech` method.
+///
+/// `timepoints` is only returned by `v1beta1`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SynthesizeSpeechResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub audio_content: Vec<u8>,
+    #[prost(message, repeated, tag = "2")]
+    pub timepoints: Vec<Timepoint>,
+}
+
+/// This contains a mapping between a certain point in the input text and a corresponding time
+/// in the output audio.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Timepoint {
+    #[prost(double, tag = "3")]
+    pub time_seconds: f64,
+    #[prost(string, tag = "4")]
+    pub mark_name: String,
+}
+
+/// The top-level message sent by the client for the `ListVoices` method.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListVoicesRequest {
+    #[prost(string, tag = "1")]
+    pub language_code: String,
+}
+
+/// The message returned to the client by the `ListVoices` method.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListVoicesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub voices: Vec<Voice>,
+}
+
+/// Description of a voice supported by the TTS service.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Voice {
+    #[prost(string, repeated, tag = "1")]
+    pub language_codes: Vec<String>,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(enumeration = "SsmlVoiceGender", tag = "3")]
+    pub ssml_gender: i32,
+    #[prost(int32, tag = "4")]
+    pub natural_sample_rate_hertz: i32,
+}
+
+/// Description of the desired output audio data for streaming synthesis.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamingAudioConfig {
+    #[prost(enumeration = "AudioEncoding", tag = "1")]
+    pub audio_encoding: i32,
+    #[prost(int32, tag = "2")]
+    pub sample_rate_hertz: i32,
+    #[prost(double, tag = "3")]
+    pub speaking_rate: f64,
+}
+
+/// Provides configuration information for the `StreamingSynthesize` request.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamingSynthesizeConfig {
+    #[prost(message, optional, tag = "1")]
+    pub voice: Option<VoiceSelectionParams>,
+    #[prost(message, optional, tag = "4")]
+    pub streaming_audio_config: Option<StreamingAudioConfig>,
+}
+
+/// Input to be synthesized in a `StreamingSynthesize` request.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamingSynthesisInput {
+    #[prost(oneof = "streaming_synthesis_input::InputSource", tags = "1")]
+    pub input_source: Option<streaming_synthesis_input::InputSource>,
+}
+
+pub mod streaming_synthesis_input {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum InputSource {
+        #[prost(string, tag = "1")] Text(String),
+    }
+}
+
+/// Request message for the `StreamingSynthesize` method. The first message must carry the
+/// configuration, every following message carries input text.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamingSynthesizeRequest {
+    #[prost(oneof = "streaming_synthesize_request::StreamingRequest", tags = "1, 2")]
+    pub streaming_request: Option<streaming_synthesize_request::StreamingRequest>,
+}
+
+pub mod streaming_synthesize_request {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum StreamingRequest {
+        #[prost(message, tag = "1")] StreamingConfig(super::StreamingSynthesizeConfig),
+        #[prost(message, tag = "2")] Input(super::StreamingSynthesisInput),
+    }
+}
+
+/// `StreamingSynthesizeResponse` is the only message returned to the client by the
+/// `StreamingSynthesize` method.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamingSynthesizeResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub audio_content: Vec<u8>,
+}
+
+/// Client for the `google.cloud.texttospeech.v1.TextToSpeech` service, and its `v1beta1`
+/// counterpart for the methods that take an [ApiVersion].
+#[derive(Debug, Clone)]
+pub struct TextToSpeechGrpcClient {
+    inner: Grpc<InterceptedService<Channel, AuthInterceptor>>,
+}
+
+impl TextToSpeechGrpcClient {
+    pub fn new(channel: Channel, interceptor: AuthInterceptor) -> Self {
+        TextToSpeechGrpcClient {
+            inner: Grpc::new(InterceptedService::new(channel, interceptor)),
+        }
+    }
+
+    /// Synthesizes speech synchronously. `v1beta1` is needed for timepoints.
+    pub async fn synthesize_speech(
+        &mut self,
+        request: impl IntoRequest<SynthesizeSpeechRequest>,
+        api_version: ApiVersion
+    ) -> Result<Response<SynthesizeSpeechResponse>, Status> {
+        self.ready().await?;
+        let path = format!(
+            "/google.cloud.texttospeech.{}.TextToSpeech/SynthesizeSpeech",
+            api_version.as_str()
+        );
+        let path = PathAndQuery::try_from(path).map_err(|e| Status::internal(e.to_string()))?;
+        self.inner.unary(request.into_request(), path, ProstCodec::default()).await
+    }
+
+    /// Returns a list of voices supported for synthesis.
+    pub async fn list_voices(
+        &mut self,
+        request: impl IntoRequest<ListVoicesRequest>
+    ) -> Result<Response<ListVoicesResponse>, Status> {
+        self.ready().await?;
+        let path = PathAndQuery::from_static(
+            "/google.cloud.texttospeech.v1.TextToSpeech/ListVoices"
+        );
+        self.inner.unary(request.into_request(), path, ProstCodec::default()).await
+    }
+
+    /// Performs bidirectional streaming speech synthesis: receives audio while sending text.
+    pub async fn streaming_synthesize(
+        &mut self,
+        request: impl IntoStreamingRequest<Message = StreamingSynthesizeRequest>
+    ) -> Result<Response<Streaming<StreamingSynthesizeResponse>>, Status> {
+        self.ready().await?;
+        let codec = ProstCodec::default();
+        let path = PathAndQuery::from_static(
+            "/google.cloud.texttospeech.v1.TextToSpeech/StreamingSynthesize"
+        );
+        self.inner.streaming(request.into_streaming_request(), path, codec).await
+    }
+
+    async fn ready(&mut self) -> Result<(), Status> {
+        self.inner
+            .ready().await
+            .map_err(|e| Status::unknown(format!("Service was not ready: {}", e)))
+    }
+}
@@ -0,0 +1,393 @@
+// src/grpc/streaming.rs
+
+use std::{ future::poll_fn, pin::Pin, task::{ Context, Poll } };
+
+use futures_core::Stream;
+use futures_sink::Sink;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::PollSender;
+use tonic::{ codec::Streaming, transport::Channel, Status };
+
+use crate::{
+    grpc::{
+        proto::{
+            streaming_synthesis_input::InputSource,
+            streaming_synthesize_request::StreamingRequest,
+            StreamingAudioConfig,
+            StreamingSynthesisInput,
+            StreamingSynthesizeConfig,
+            StreamingSynthesizeRequest,
+            StreamingSynthesizeResponse,
+            TextToSpeechGrpcClient,
+        },
+        AuthInterceptor,
+    },
+    types::{
+        audio::AudioContent,
+        language::LanguageTag,
+        synthesize::{ AudioEncoding, VoiceSelectionParams },
+        voice_name::VoiceName,
+    },
+    services::synthesize::SynthesisError,
+};
+
+/// Number of text chunks that can be queued before [TextInputSink::send_text] waits.
+pub const DEFAULT_INPUT_BUFFER: usize = 16;
+
+/// Builder for a `StreamingSynthesize` session.
+///
+/// Streaming synthesis is only supported by Chirp 3 HD voices. Text is sent in chunks through
+/// a [TextInputSink] while the audio is received, as it is produced, from an [AudioChunkStream].
+pub struct StreamingSynthesizeBuilder {
+    channel: Channel,
+    access_token: String,
+    voice: VoiceSelectionParams,
+    audio_encoding: AudioEncoding,
+    sample_rate_hertz: Option<i32>,
+    speaking_rate: Option<f64>,
+    input_buffer: usize,
+}
+
+impl StreamingSynthesizeBuilder {
+    pub fn new(channel: Channel, access_token: String) -> Self {
+        StreamingSynthesizeBuilder {
+            channel,
+            access_token,
+            voice: VoiceSelectionParams::default(),
+            audio_encoding: AudioEncoding::Pcm,
+            sample_rate_hertz: None,
+            speaking_rate: None,
+            input_buffer: DEFAULT_INPUT_BUFFER,
+        }
+    }
+
+    pub fn voice_params(mut self, voice: VoiceSelectionParams) -> Self {
+        self.voice = voice;
+        self
+    }
+
+    pub fn language_code(mut self, language_code: LanguageTag) -> Self {
+        self.voice.languageCode = language_code;
+        self
+    }
+
+    /// Selects the voice `name`, and sets the language code to the language of the voice.
+    pub fn voice_name(mut self, name: VoiceName) -> Self {
+        self.voice = VoiceSelectionParams::from_name(name);
+        self
+    }
+
+    /// Sets the encoding of the audio chunks. Streaming supports [AudioEncoding::Pcm] (the
+    /// default), [AudioEncoding::OggOpus], [AudioEncoding::Mulaw] and [AudioEncoding::Alaw].
+    pub fn audio_encoding(mut self, encoding: AudioEncoding) -> Self {
+        self.audio_encoding = encoding;
+        self
+    }
+
+    pub fn sample_rate_hertz(mut self, sample_rate_hertz: i32) -> Self {
+        self.sample_rate_hertz = Some(sample_rate_hertz);
+        self
+    }
+
+    pub fn speaking_rate(mut self, speaking_rate: f64) -> Self {
+        self.speaking_rate = Some(speaking_rate);
+        self
+    }
+
+    /// Sets how many text chunks can be queued before [TextInputSink::send_text] waits.
+    pub fn input_buffer(mut self, input_buffer: usize) -> Self {
+        self.input_buffer = input_buffer.max(1);
+        self
+    }
+
+    /// Builds the configuration message that opens the stream.
+    pub fn config(&self) -> StreamingSynthesizeConfig {
+        StreamingSynthesizeConfig {
+            voice: Some((&self.voice).into()),
+            streaming_audio_config: Some(StreamingAudioConfig {
+                audio_encoding: crate::grpc::proto::AudioEncoding::from(self.audio_encoding) as i32,
+                sample_rate_hertz: self.sample_rate_hertz.unwrap_or_default(),
+                speaking_rate: self.speaking_rate.unwrap_or_default(),
+            }),
+        }
+    }
+
+    /// Opens the stream.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [TextInputSink] to send the text with, and an [AudioChunkStream] yielding the
+    /// audio. Close the sink once all the text is sent so that the stream ends.
+    pub async fn start(self) -> Result<(TextInputSink, AudioChunkStream), SynthesisError> {
+        let (sender, receiver) = mpsc::channel(self.input_buffer);
+        let config = StreamingSynthesizeRequest {
+            streaming_request: Some(StreamingRequest::StreamingConfig(self.config())),
+        };
+        sender.send(config).await.map_err(|_| input_closed())?;
+
+        let interceptor = AuthInterceptor::new(&self.access_token)?;
+        let mut client = TextToSpeechGrpcClient::new(self.channel, interceptor);
+        let response = client.streaming_synthesize(ReceiverStream::new(receiver)).await?;
+
+        let sink = TextInputSink { sender: PollSender::new(sender) };
+        let stream = AudioChunkStream {
+            inner: response.into_inner(),
+            encoding: self.audio_encoding,
+            sample_rate_hertz: self.sample_rate_hertz,
+        };
+        Ok((sink, stream))
+    }
+}
+
+fn input_closed() -> SynthesisError {
+    Status::cancelled("the synthesis stream is closed").into()
+}
+
+fn input_request(text: String) -> StreamingSynthesizeRequest {
+    StreamingSynthesizeRequest {
+        streaming_request: Some(
+            StreamingRequest::Input(StreamingSynthesisInput {
+                input_source: Some(InputSource::Text(text)),
+            })
+        ),
+    }
+}
+
+/// Sending half of a streaming synthesis session.
+///
+/// Also implements [Sink], so a stream of text can be forwarded into it.
+pub struct TextInputSink {
+    sender: PollSender<StreamingSynthesizeRequest>,
+}
+
+impl TextInputSink {
+    /// Sends a chunk of text to be synthesized, waiting if the input buffer is full.
+    pub async fn send_text(&mut self, text: &str) -> Result<(), SynthesisError> {
+        poll_fn(|cx| self.sender.poll_reserve(cx)).await.map_err(|_| input_closed())?;
+        self.sender.send_item(input_request(text.to_string())).map_err(|_| input_closed())
+    }
+
+    /// Ends the input. The server finishes synthesizing the text already sent, then ends
+    /// the [AudioChunkStream].
+    pub fn close(&mut self) {
+        self.sender.close();
+    }
+}
+
+impl Sink<String> for TextInputSink {
+    type Error = SynthesisError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().sender.poll_reserve(cx).map_err(|_| input_closed())
+    }
+
+    fn start_send(self: Pin<&mut Self>, text: String) -> Result<(), Self::Error> {
+        self.get_mut().sender.send_item(input_request(text)).map_err(|_| input_closed())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Receiving half of a streaming synthesis session.
+///
+/// Yields the audio in chunks, in the encoding set on the builder. Chunks can be played or
+/// written out as they arrive; [AudioEncoding::Pcm] chunks can simply be concatenated.
+pub struct AudioChunkStream {
+    inner: Streaming<StreamingSynthesizeResponse>,
+    encoding: AudioEncoding,
+    sample_rate_hertz: Option<i32>,
+}
+
+impl AudioChunkStream {
+    /// Receives the next audio chunk, or `None` once the stream has ended.
+    pub async fn next_chunk(&mut self) -> Result<Option<AudioContent>, SynthesisError> {
+        let response = self.inner.message().await?;
+        Ok(response.map(|response| self.audio_content(response)))
+    }
+
+    fn audio_content(&self, response: StreamingSynthesizeResponse) -> AudioContent {
+        AudioContent::new(response.audio_content, self.encoding, self.sample_rate_hertz)
+    }
+}
+
+impl Stream for AudioChunkStream {
+    type Item = Result<AudioContent, SynthesisError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        Pin::new(&mut this.inner)
+            .poll_next(cx)
+            .map(|item| item.map(|result| {
+                result.map(|response| this.audio_content(response)).map_err(SynthesisError::from)
+            }))
+    }
+}
+
+#[cfg(all(test, feature = "grpc"))]
+mod tests {
+    use std::{ sync::{ Arc, Mutex }, time::Duration };
+
+    use tokio_stream::StreamExt;
+    use tonic::{ Request, Response };
+
+    use super::*;
+    use crate::grpc::test_server::{ serve, AudioStream, TextToSpeech };
+
+    /// Answers every text chunk with its bytes as audio, and fails on the text `fail`.
+    #[derive(Default)]
+    struct Echo {
+        authorizations: Arc<Mutex<Vec<String>>>,
+        configs: Arc<Mutex<Vec<StreamingSynthesizeConfig>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TextToSpeech for Echo {
+        async fn streaming_synthesize(
+            &self,
+            request: Request<Streaming<StreamingSynthesizeRequest>>
+        ) -> Result<Response<AudioStream>, Status> {
+            let authorization = request
+                .metadata()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .map(String::from);
+            self.authorizations.lock().unwrap().push(authorization.clone().unwrap_or_default());
+            if authorization.as_deref() != Some("Bearer token") {
+                return Err(Status::unauthenticated("invalid access token"));
+            }
+
+            let mut input = request.into_inner();
+            match input.message().await?.and_then(|request| request.streaming_request) {
+                Some(StreamingRequest::StreamingConfig(config)) => {
+                    self.configs.lock().unwrap().push(config);
+                }
+                _ => {
+                    return Err(Status::invalid_argument("the first request must be a config"));
+                }
+            }
+
+            let (sender, receiver) = mpsc::channel(4);
+            tokio::spawn(async move {
+                while let Ok(Some(request)) = input.message().await {
+                    let text = match request.streaming_request {
+                        Some(StreamingRequest::Input(StreamingSynthesisInput {
+                            input_source: Some(InputSource::Text(text)),
+                        })) => text,
+                        _ => {
+                            let status = Status::invalid_argument("expected text");
+                            let _ = sender.send(Err(status)).await;
+                            return;
+                        }
+                    };
+                    if text == "fail" {
+                        let status = Status::invalid_argument("cannot synthesize 'fail'");
+                        let _ = sender.send(Err(status)).await;
+                        return;
+                    }
+                    let audio = StreamingSynthesizeResponse { audio_content: text.into_bytes() };
+                    if sender.send(Ok(audio)).await.is_err() {
+                        return;
+                    }
+                }
+            });
+            Ok(Response::new(Box::pin(ReceiverStream::new(receiver)) as AudioStream))
+        }
+    }
+
+    fn builder(channel: Channel, access_token: &str) -> StreamingSynthesizeBuilder {
+        StreamingSynthesizeBuilder::new(channel, access_token.to_string())
+            .voice_name("en-US-Chirp3-HD-Achernar".parse().unwrap())
+            .sample_rate_hertz(24000)
+    }
+
+    #[tokio::test]
+    async fn streams_text_in_and_audio_out() {
+        let echo = Echo::default();
+        let (authorizations, configs) = (echo.authorizations.clone(), echo.configs.clone());
+        let (channel, server) = serve(echo).await;
+
+        let (mut sink, mut stream) = builder(channel, "token").start().await.unwrap();
+        sink.send_text("Hello, ").await.unwrap();
+        let chunk = stream.next_chunk().await.unwrap().unwrap();
+        assert_eq!(chunk.bytes(), b"Hello, ");
+        assert_eq!(chunk.encoding(), AudioEncoding::Pcm);
+
+        // Through the Sink and Stream implementations.
+        poll_fn(|cx| Pin::new(&mut sink).poll_ready(cx)).await.unwrap();
+        Pin::new(&mut sink).start_send("world!".to_string()).unwrap();
+        poll_fn(|cx| Pin::new(&mut sink).poll_close(cx)).await.unwrap();
+
+        let chunk = stream.next().await.unwrap().unwrap();
+        assert_eq!(chunk.bytes(), b"world!");
+        // Closing the input ends the audio stream.
+        assert!(stream.next().await.is_none());
+
+        assert_eq!(*authorizations.lock().unwrap(), ["Bearer token"]);
+        let config = configs.lock().unwrap()[0].clone();
+        assert_eq!(config.voice.unwrap().name, "en-US-Chirp3-HD-Achernar");
+        assert_eq!(config.streaming_audio_config.unwrap().sample_rate_hertz, 24000);
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn sends_the_access_token() {
+        let echo = Echo::default();
+        let authorizations = echo.authorizations.clone();
+        let (channel, server) = serve(echo).await;
+
+        let error = builder(channel, "expired").start().await.err().unwrap();
+        match error {
+            SynthesisError::GrpcStatus(status) => {
+                assert_eq!(status.code(), tonic::Code::Unauthenticated);
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert_eq!(*authorizations.lock().unwrap(), ["Bearer expired"]);
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn rejects_an_access_token_that_cannot_be_sent() {
+        let channel = crate::grpc::connect("http://127.0.0.1:1").unwrap();
+        let error = builder(channel, "tok\nen").start().await.err().unwrap();
+        match error {
+            SynthesisError::GrpcStatus(status) => {
+                assert_eq!(status.code(), tonic::Code::Unauthenticated);
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+    }
+
+    #[tokio::test]
+    async fn propagates_server_errors_and_closes_the_input() {
+        let (channel, server) = serve(Echo::default()).await;
+
+        let (mut sink, mut stream) = builder(channel, "token").start().await.unwrap();
+        sink.send_text("fail").await.unwrap();
+        match stream.next_chunk().await {
+            Err(SynthesisError::GrpcStatus(status)) => {
+                assert_eq!(status.code(), tonic::Code::InvalidArgument);
+                assert_eq!(status.message(), "cannot synthesize 'fail'");
+            }
+            result => panic!("unexpected result {:?}", result.map(|chunk| chunk.is_some())),
+        }
+
+        // Once the call is over, the server stops reading the input.
+        let closed = tokio::time::timeout(Duration::from_secs(5), async {
+            while sink.send_text("more").await.is_ok() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await;
+        assert!(closed.is_ok(), "the input was never closed");
+        server.abort();
+    }
+}
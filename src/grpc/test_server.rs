@@ -0,0 +1,178 @@
+// src/grpc/test_server.rs
+
+//! A local stand-in for the gRPC API: serves an implementation of [TextToSpeech] on
+//! 127.0.0.1, the server half of [TextToSpeechGrpcClient](super::proto::TextToSpeechGrpcClient).
+
+use std::{ convert::Infallible, pin::Pin, sync::Arc, task::{ Context, Poll } };
+
+use async_trait::async_trait;
+use futures_core::Stream;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+    body::BoxBody,
+    codec::{ ProstCodec, Streaming },
+    codegen::{ empty_body, http, Body, BoxFuture, Service, StdError },
+    server::{ Grpc, NamedService, StreamingService, UnaryService },
+    transport::{ Channel, Server },
+    Request,
+    Response,
+    Status,
+};
+
+use super::proto::{
+    ListVoicesRequest,
+    ListVoicesResponse,
+    StreamingSynthesizeRequest,
+    StreamingSynthesizeResponse,
+    SynthesizeSpeechRequest,
+    SynthesizeSpeechResponse,
+};
+
+/// Stream of the responses of a `StreamingSynthesize` call.
+pub(crate) type AudioStream =
+    Pin<Box<dyn Stream<Item = Result<StreamingSynthesizeResponse, Status>> + Send>>;
+
+/// The `google.cloud.texttospeech.v1.TextToSpeech` service. Methods that aren't overridden
+/// answer `UNIMPLEMENTED`.
+#[async_trait]
+pub(crate) trait TextToSpeech: Send + Sync + 'static {
+    async fn synthesize_speech(
+        &self,
+        _request: Request<SynthesizeSpeechRequest>
+    ) -> Result<Response<SynthesizeSpeechResponse>, Status> {
+        Err(Status::unimplemented("SynthesizeSpeech"))
+    }
+
+    async fn list_voices(
+        &self,
+        _request: Request<ListVoicesRequest>
+    ) -> Result<Response<ListVoicesResponse>, Status> {
+        Err(Status::unimplemented("ListVoices"))
+    }
+
+    async fn streaming_synthesize(
+        &self,
+        _request: Request<Streaming<StreamingSynthesizeRequest>>
+    ) -> Result<Response<AudioStream>, Status> {
+        Err(Status::unimplemented("StreamingSynthesize"))
+    }
+}
+
+/// Serves `service` on a free port of 127.0.0.1 until the returned task is aborted.
+///
+/// # Returns
+///
+/// Returns a plaintext channel to the server, and the task serving it.
+pub(crate) async fn serve<T: TextToSpeech>(service: T) -> (Channel, tokio::task::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let server = TextToSpeechServer { inner: Arc::new(service) };
+    let task = tokio::spawn(async move {
+        Server::builder()
+            .add_service(server)
+            .serve_with_incoming(TcpListenerStream::new(listener)).await
+            .unwrap();
+    });
+    (super::connect(&endpoint).unwrap(), task)
+}
+
+struct TextToSpeechServer<T> {
+    inner: Arc<T>,
+}
+
+impl<T> Clone for TextToSpeechServer<T> {
+    fn clone(&self) -> Self {
+        TextToSpeechServer { inner: self.inner.clone() }
+    }
+}
+
+impl<T> NamedService for TextToSpeechServer<T> {
+    const NAME: &'static str = "google.cloud.texttospeech.v1.TextToSpeech";
+}
+
+struct SynthesizeSpeechSvc<T>(Arc<T>);
+
+impl<T: TextToSpeech> UnaryService<SynthesizeSpeechRequest> for SynthesizeSpeechSvc<T> {
+    type Response = SynthesizeSpeechResponse;
+    type Future = BoxFuture<Response<Self::Response>, Status>;
+
+    fn call(&mut self, request: Request<SynthesizeSpeechRequest>) -> Self::Future {
+        let inner = self.0.clone();
+        Box::pin(async move { inner.synthesize_speech(request).await })
+    }
+}
+
+struct ListVoicesSvc<T>(Arc<T>);
+
+impl<T: TextToSpeech> UnaryService<ListVoicesRequest> for ListVoicesSvc<T> {
+    type Response = ListVoicesResponse;
+    type Future = BoxFuture<Response<Self::Response>, Status>;
+
+    fn call(&mut self, request: Request<ListVoicesRequest>) -> Self::Future {
+        let inner = self.0.clone();
+        Box::pin(async move { inner.list_voices(request).await })
+    }
+}
+
+struct StreamingSynthesizeSvc<T>(Arc<T>);
+
+impl<T: TextToSpeech> StreamingService<StreamingSynthesizeRequest> for StreamingSynthesizeSvc<T> {
+    type Response = StreamingSynthesizeResponse;
+    type ResponseStream = AudioStream;
+    type Future = BoxFuture<Response<Self::ResponseStream>, Status>;
+
+    fn call(&mut self, request: Request<Streaming<StreamingSynthesizeRequest>>) -> Self::Future {
+        let inner = self.0.clone();
+        Box::pin(async move { inner.streaming_synthesize(request).await })
+    }
+}
+
+impl<T, B> Service<http::Request<B>> for TextToSpeechServer<T>
+    where
+        T: TextToSpeech,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let inner = self.inner.clone();
+        match request.uri().path().rsplit('/').next() {
+            Some("SynthesizeSpeech") => {
+                Box::pin(async move {
+                    let mut grpc = Grpc::new(ProstCodec::default());
+                    Ok(grpc.unary(SynthesizeSpeechSvc(inner), request).await)
+                })
+            }
+            Some("ListVoices") => {
+                Box::pin(async move {
+                    let mut grpc = Grpc::new(ProstCodec::default());
+                    Ok(grpc.unary(ListVoicesSvc(inner), request).await)
+                })
+            }
+            Some("StreamingSynthesize") => {
+                Box::pin(async move {
+                    let mut grpc = Grpc::new(ProstCodec::default());
+                    Ok(grpc.streaming(StreamingSynthesizeSvc(inner), request).await)
+                })
+            }
+            _ => {
+                Box::pin(async move {
+                    let response = http::Response::builder()
+                        .header("grpc-status", (tonic::Code::Unimplemented as i32).to_string())
+                        .header("content-type", "application/grpc")
+                        .body(empty_body())
+                        .unwrap();
+                    Ok(response)
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+// src/grpc/unary.rs
+
+use tonic::transport::Channel;
+
+use crate::{
+    grpc::{ proto, AuthInterceptor, proto::TextToSpeechGrpcClient },
+    types::{
+        audio::AudioContent,
+        synthesize::{ SynthesizeRequest, SynthesizeResponse, Timepoint },
+        voices::Voice,
+    },
+    services::{ endpoint::ApiVersion, synthesize::SynthesisError },
+};
+
+/// Sends a `SynthesizeSpeech` request over gRPC.
+///
+/// The audio is returned as raw bytes, so unlike the REST API there is no base64 body to
+/// decode. The format of the returned [AudioContent] is taken from the request.
+///
+/// # Arguments
+///
+/// * `channel` - The channel to send the request on, see [connect](crate::grpc::connect).
+/// * `access_token` - The OAuth access token used to authenticate the request.
+/// * `api_version` - The API version, `v1beta1` being needed for timepoints.
+/// * `request` - The request, already validated.
+pub async fn synthesize(
+    channel: Channel,
+    access_token: &str,
+    api_version: ApiVersion,
+    request: &SynthesizeRequest
+) -> Result<SynthesizeResponse, SynthesisError> {
+    let message = proto::SynthesizeSpeechRequest {
+        input: Some((&request.input).into()),
+        voice: Some((&request.voice).into()),
+        audio_config: Some((&request.audioConfig).into()),
+        enable_time_pointing: request.enableTimePointing
+            .iter()
+            .flatten()
+            .map(|timepoint_type| proto::TimepointType::from(*timepoint_type) as i32)
+            .collect(),
+    };
+
+    let mut client = TextToSpeechGrpcClient::new(channel, AuthInterceptor::new(access_token)?);
+    let response = client.synthesize_speech(message, api_version).await?.into_inner();
+
+    Ok(SynthesizeResponse {
+        audioContent: AudioContent::new(
+            response.audio_content,
+            request.audioConfig.audioEncoding,
+            request.audioConfig.sampleRateHertz
+        ),
+        timepoints: response.timepoints
+            .into_iter()
+            .map(|timepoint| Timepoint {
+                markName: timepoint.mark_name,
+                timeSeconds: timepoint.time_seconds,
+            })
+            .collect(),
+        audioConfig: None,
+    })
+}
+
+/// Fetches the voices supported by the TTS service over gRPC.
+///
+/// # Arguments
+///
+/// * `channel` - The channel to send the request on, see [connect](crate::grpc::connect).
+/// * `access_token` - The OAuth access token used to authenticate the request.
+/// * `language_code` - Optional BCP-47 language tag. If set, only voices that can be used to
+///   synthesize this language are returned.
+pub async fn list_voices(
+    channel: Channel,
+    access_token: &str,
+    language_code: Option<&str>
+) -> Result<Vec<Voice>, SynthesisError> {
+    let message = proto::ListVoicesRequest {
+        language_code: language_code.unwrap_or_default().to_string(),
+    };
+
+    let mut client = TextToSpeechGrpcClient::new(channel, AuthInterceptor::new(access_token)?);
+    let response = client.list_voices(message).await?.into_inner();
+
+    // Voices whose name can't be parsed are skipped rather than failing the whole list.
+    Ok(
+        response.voices
+            .into_iter()
+            .filter_map(|voice| {
+                Voice::try_from(voice).map_err(|e| log::warn!("skipping voice: {}", e)).ok()
+            })
+            .collect()
+    )
+}
+
+#[cfg(all(test, feature = "grpc"))]
+mod tests {
+    use std::sync::{ Arc, Mutex };
+
+    use tonic::{ Request, Response, Status };
+
+    use super::*;
+    use crate::{
+        grpc::test_server::{ serve, TextToSpeech },
+        services::synthesize::SynthesizeSpeechBuilder,
+        types::synthesize::{ AudioEncoding, SynthesisInput },
+    };
+
+    /// Records the requests it receives, and answers with fixed audio and voices.
+    #[derive(Default)]
+    struct Recorder {
+        requests: Arc<Mutex<Vec<proto::SynthesizeSpeechRequest>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TextToSpeech for Recorder {
+        async fn synthesize_speech(
+            &self,
+            request: Request<proto::SynthesizeSpeechRequest>
+        ) -> Result<Response<proto::SynthesizeSpeechResponse>, Status> {
+            self.requests.lock().unwrap().push(request.into_inner());
+            Ok(
+                Response::new(proto::SynthesizeSpeechResponse {
+                    audio_content: vec![1, 2, 3],
+                    ..Default::default()
+                })
+            )
+        }
+
+        async fn list_voices(
+            &self,
+            _request: Request<proto::ListVoicesRequest>
+        ) -> Result<Response<proto::ListVoicesResponse>, Status> {
+            let voice = |name: &str, language_codes: &[&str]| proto::Voice {
+                language_codes: language_codes.iter().map(|code| code.to_string()).collect(),
+                name: name.to_string(),
+                ..Default::default()
+            };
+            Ok(
+                Response::new(proto::ListVoicesResponse {
+                    voices: vec![
+                        voice("Achernar", &["en-US"]),
+                        voice("en-US-Chirp3-HD-Achernar", &["en-US", "not a tag"])
+                    ],
+                })
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_the_request_as_protobuf() {
+        let recorder = Recorder::default();
+        let requests = recorder.requests.clone();
+        let (channel, server) = serve(recorder).await;
+
+        let request = SynthesizeSpeechBuilder::new(
+            "token".to_string(),
+            SynthesisInput::Ssml("<speak>Hi</speak>".to_string())
+        )
+            .voice_name("en-GB-Neural2-A".parse().unwrap())
+            .audio_encoding(AudioEncoding::OggOpus)
+            .speaking_rate(1.25)
+            .pitch(-2.0)
+            .request();
+        let response = synthesize(channel, "token", ApiVersion::V1, &request).await.unwrap();
+        assert_eq!(response.audioContent.bytes(), [1, 2, 3]);
+        assert_eq!(response.audioContent.encoding(), AudioEncoding::OggOpus);
+
+        let sent = requests.lock().unwrap()[0].clone();
+        assert_eq!(
+            sent.input.unwrap().input_source,
+            Some(proto::synthesis_input::InputSource::Ssml("<speak>Hi</speak>".to_string()))
+        );
+        let voice = sent.voice.unwrap();
+        assert_eq!(voice.language_code, "en-GB");
+        assert_eq!(voice.name, "en-GB-Neural2-A");
+        let audio_config = sent.audio_config.unwrap();
+        assert_eq!(audio_config.audio_encoding, proto::AudioEncoding::OggOpus as i32);
+        assert_eq!(audio_config.speaking_rate, 1.25);
+        assert_eq!(audio_config.pitch, -2.0);
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn skips_voices_that_cannot_be_parsed() {
+        let (channel, server) = serve(Recorder::default()).await;
+
+        let voices = list_voices(channel, "token", None).await.unwrap();
+        assert_eq!(voices.len(), 1);
+        assert_eq!(voices[0].name.to_string(), "en-US-Chirp3-HD-Achernar");
+        assert_eq!(voices[0].languageCodes, ["en-US".parse().unwrap()]);
+        server.abort();
+    }
+}
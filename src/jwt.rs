@@ -35,8 +35,15 @@ struct Claims {
 ///
 /// ## Example
 ///
-/// ```
-/// let credentials = GoogleCredentials::new(client_email, private_key, token_uri);
+/// ```no_run
+/// use google_tts_api::{ jwt::create_jwt, types::credentials::GoogleCredentials };
+///
+/// let credentials = GoogleCredentials {
+///     private_key: std::fs::read_to_string("private_key.pem").unwrap(),
+///     client_email: "tts@my-project.iam.gserviceaccount.com".to_string(),
+///     token_uri: "https://oauth2.googleapis.com/token".to_string(),
+///     project_id: None,
+/// };
 /// let jwt = create_jwt(&credentials).expect("Failed to create JWT");
 /// ```
 pub fn create_jwt(
@@ -1,5 +1,12 @@
+pub mod audio;
 pub mod client;
 pub mod types;
 pub mod services;
 pub mod auth;
 pub mod jwt;
+pub mod ssml;
+pub mod normalize;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(test)]
+mod test_server;
@@ -0,0 +1,72 @@
+// src/normalize/de.rs
+
+use crate::normalize::{ discord::DiscordWords, locale::Locale };
+
+pub const LOCALE: Locale = Locale {
+    language: "de",
+    decimal_separator: ',',
+    date_order: "dmy",
+    time: Some("{h} Uhr {m}"),
+    time_on_the_hour: Some("{h} Uhr"),
+    fraction: None,
+    // "2." is both an ordinal and the end of a sentence, so ordinals are left to the voice.
+    ordinal_suffixes: &[],
+    scales: &[(1e9, " Milliarde", " Milliarden"), (1e6, " Million", " Millionen")],
+    currencies: &[
+        ("$", "{amount} Dollar"),
+        ("€", "{amount} Euro"),
+        ("£", "{amount} Pfund"),
+        ("¥", "{amount} Yen"),
+        ("₫", "{amount} Dong"),
+    ],
+    units: &[
+        ("km/h", "{n} Kilometer pro Stunde", "{n} Kilometer pro Stunde"),
+        ("m/s", "{n} Meter pro Sekunde", "{n} Meter pro Sekunde"),
+        ("mph", "{n} Meile pro Stunde", "{n} Meilen pro Stunde"),
+        ("kWh", "{n} Kilowattstunde", "{n} Kilowattstunden"),
+        ("km", "{n} Kilometer", "{n} Kilometer"),
+        ("kg", "{n} Kilogramm", "{n} Kilogramm"),
+        ("cm", "{n} Zentimeter", "{n} Zentimeter"),
+        ("mm", "{n} Millimeter", "{n} Millimeter"),
+        ("GB", "{n} Gigabyte", "{n} Gigabyte"),
+        ("MB", "{n} Megabyte", "{n} Megabyte"),
+        ("°C", "{n} Grad Celsius", "{n} Grad Celsius"),
+        ("°F", "{n} Grad Fahrenheit", "{n} Grad Fahrenheit"),
+    ],
+    version: "Version",
+    version_separator: "Punkt",
+};
+
+pub const DISCORD: DiscordWords = DiscordWords {
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    date: "{day}. {month} {year}",
+    date_time: "{date} um {time}",
+    future: "in {n} {unit}",
+    past: "vor {n} {unit}",
+    now: "jetzt",
+    // Dative plurals, as read after "in" and "vor".
+    units: [
+        ("Sekunde", "Sekunden"),
+        ("Minute", "Minuten"),
+        ("Stunde", "Stunden"),
+        ("Tag", "Tagen"),
+        ("Monat", "Monaten"),
+        ("Jahr", "Jahren"),
+    ],
+    unknown_user: "unbekannter Benutzer",
+    unknown_channel: "unbekannter Kanal",
+    unknown_role: "unbekannte Rolle",
+};
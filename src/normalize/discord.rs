@@ -0,0 +1,563 @@
+// src/normalize/discord.rs
+
+//! Verbalization of Discord message content.
+//!
+//! Discord encodes mentions, custom emoji and timestamps as tokens such as `<@123>`, which
+//! voices read digit by digit. [DiscordNormalizer] replaces them with the names given by
+//! caller-supplied resolvers, typically backed by the bot's cache, so that the result can be
+//! passed to [synthesize_text](crate::client::TextToSpeechClient::synthesize_text) and played
+//! with the songbird integration.
+
+use std::{ fmt, time::{ SystemTime, UNIX_EPOCH } };
+
+use regex::{ Captures, Regex };
+
+use crate::{
+    normalize::{ de, en, fr, ja, vi },
+    ssml::markdown::{ bare_url, host },
+    types::language::LanguageTag,
+};
+
+/// Words used to read Discord content in a language.
+#[derive(Debug)]
+pub struct DiscordWords {
+    pub months: [&'static str; 12],
+    /// Template of a date, with `{day}`, `{month}` and `{year}`.
+    pub date: &'static str,
+    /// Template of a date and time, with `{date}` and `{time}`.
+    pub date_time: &'static str,
+    /// Template of a time in the future, with `{n}` and `{unit}`.
+    pub future: &'static str,
+    /// Template of a time in the past, with `{n}` and `{unit}`.
+    pub past: &'static str,
+    pub now: &'static str,
+    /// Singular and plural of seconds, minutes, hours, days, months and years.
+    pub units: [(&'static str, &'static str); 6],
+    /// Read in place of a user, channel or role the resolver doesn't know.
+    pub unknown_user: &'static str,
+    pub unknown_channel: &'static str,
+    pub unknown_role: &'static str,
+}
+
+/// Returns the words of `language`, falling back to English.
+pub fn words_for(language: &LanguageTag) -> &'static DiscordWords {
+    match language.language() {
+        "vi" => &vi::DISCORD,
+        "de" => &de::DISCORD,
+        "fr" => &fr::DISCORD,
+        "ja" => &ja::DISCORD,
+        _ => &en::DISCORD,
+    }
+}
+
+/// English CLDR short names of emoji common in chat, read when no emoji resolver is set and
+/// the language is English.
+const COMMON_EMOJI: &[(&str, &str)] = &[
+    ("👍", "thumbs up"),
+    ("👎", "thumbs down"),
+    ("❤", "red heart"),
+    ("😂", "face with tears of joy"),
+    ("🤣", "rolling on the floor laughing"),
+    ("😭", "loudly crying face"),
+    ("😢", "crying face"),
+    ("🙏", "folded hands"),
+    ("😊", "smiling face with smiling eyes"),
+    ("🙂", "slightly smiling face"),
+    ("😀", "grinning face"),
+    ("😁", "beaming face with smiling eyes"),
+    ("😆", "grinning squinting face"),
+    ("😅", "grinning face with sweat"),
+    ("😉", "winking face"),
+    ("😍", "smiling face with heart-eyes"),
+    ("😘", "face blowing a kiss"),
+    ("😎", "smiling face with sunglasses"),
+    ("🤔", "thinking face"),
+    ("😡", "enraged face"),
+    ("😱", "face screaming in fear"),
+    ("😴", "sleeping face"),
+    ("🥺", "pleading face"),
+    ("🥳", "partying face"),
+    ("🤯", "exploding head"),
+    ("💀", "skull"),
+    ("🔥", "fire"),
+    ("✨", "sparkles"),
+    ("⭐", "star"),
+    ("🎉", "party popper"),
+    ("✅", "check mark button"),
+    ("❌", "cross mark"),
+    ("👀", "eyes"),
+    ("👋", "waving hand"),
+    ("👏", "clapping hands"),
+    ("👌", "OK hand"),
+    ("💪", "flexed biceps"),
+    ("💯", "hundred points"),
+    ("🚀", "rocket"),
+    ("🤷", "person shrugging"),
+    ("🤦", "person facepalming"),
+];
+
+type IdResolver = Box<dyn Fn(u64) -> Option<String> + Send + Sync>;
+type CustomEmojiResolver = Box<dyn Fn(&str, u64) -> Option<String> + Send + Sync>;
+type EmojiResolver = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Rewrites Discord message content into plain text to be read.
+///
+/// * `<@123>` and `<@!123>` are read as the user's display name, `<#456>` as the channel
+///   name and `<@&789>` as the role name, or as an unknown user, channel or role.
+/// * `<:name:id>` and `<a:name:id>` are read as the emoji name, with underscores as spaces.
+/// * `<t:1700000000:R>` is read as a time relative to now, other styles as a date and time
+///   in the listener's time zone.
+/// * Unicode emoji are read by name, and repeated emoji once. Unnamed emoji are dropped.
+/// * URLs are read by their host.
+pub struct DiscordNormalizer {
+    words: &'static DiscordWords,
+    english: bool,
+    users: Option<IdResolver>,
+    channels: Option<IdResolver>,
+    roles: Option<IdResolver>,
+    custom_emoji: Option<CustomEmojiResolver>,
+    emoji: Option<EmojiResolver>,
+    utc_offset: i64,
+    now: Option<SystemTime>,
+    tokens: Regex,
+}
+
+impl fmt::Debug for DiscordNormalizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiscordNormalizer")
+            .field("words", &self.words)
+            .field("utc_offset", &self.utc_offset)
+            .field("now", &self.now)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DiscordNormalizer {
+    /// Creates a normalizer reading content in `language`, the language of the listener.
+    pub fn new(language: &LanguageTag) -> Self {
+        let tokens = Regex::new(
+            concat!(
+                r"<(@!?|@&|#)(\d+)>|<a?:(\w+):(\d+)>|<t:(-?\d+)(?::([tTdDfFR]))?>",
+                r"|</([\w -]+):\d+>|https?://[^\s<>]+"
+            )
+        ).expect("valid pattern");
+        DiscordNormalizer {
+            words: words_for(language),
+            english: language.language() == "en",
+            users: None,
+            channels: None,
+            roles: None,
+            custom_emoji: None,
+            emoji: None,
+            utc_offset: 0,
+            now: None,
+            tokens,
+        }
+    }
+
+    /// Resolves user ids into display names.
+    pub fn users(
+        mut self,
+        resolve: impl Fn(u64) -> Option<String> + Send + Sync + 'static
+    ) -> Self {
+        self.users = Some(Box::new(resolve));
+        self
+    }
+
+    /// Resolves channel ids into channel names.
+    pub fn channels(
+        mut self,
+        resolve: impl Fn(u64) -> Option<String> + Send + Sync + 'static
+    ) -> Self {
+        self.channels = Some(Box::new(resolve));
+        self
+    }
+
+    /// Resolves role ids into role names.
+    pub fn roles(
+        mut self,
+        resolve: impl Fn(u64) -> Option<String> + Send + Sync + 'static
+    ) -> Self {
+        self.roles = Some(Box::new(resolve));
+        self
+    }
+
+    /// Resolves custom emoji, given their name and id, into the words to read. By default,
+    /// the name is read with underscores as spaces.
+    pub fn custom_emoji(
+        mut self,
+        resolve: impl Fn(&str, u64) -> Option<String> + Send + Sync + 'static
+    ) -> Self {
+        self.custom_emoji = Some(Box::new(resolve));
+        self
+    }
+
+    /// Resolves Unicode emoji into their CLDR short name in the listener's language. The emoji
+    /// is given as written, with its variation selectors and skin tone modifiers.
+    pub fn emoji(
+        mut self,
+        resolve: impl Fn(&str) -> Option<String> + Send + Sync + 'static
+    ) -> Self {
+        self.emoji = Some(Box::new(resolve));
+        self
+    }
+
+    /// Sets the offset of the listener's time zone from UTC, in seconds; 0 by default.
+    pub fn utc_offset(mut self, seconds: i32) -> Self {
+        self.utc_offset = seconds.into();
+        self
+    }
+
+    /// Sets the time relative timestamps are read from, instead of the current time.
+    pub fn now(mut self, now: SystemTime) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Rewrites `content` into text to be read.
+    pub fn verbalize(&self, content: &str) -> String {
+        let text = self.tokens
+            .replace_all(content, |captures: &Captures| self.token(content, captures));
+        let text = self.verbalize_emoji(&text);
+        collapse_spaces(&text)
+    }
+
+    fn token(&self, content: &str, captures: &Captures) -> String {
+        if let (Some(kind), Some(id)) = (captures.get(1), captures.get(2)) {
+            let id = id.as_str().parse().unwrap_or_default();
+            let (resolver, unknown) = match kind.as_str() {
+                "#" => (&self.channels, self.words.unknown_channel),
+                "@&" => (&self.roles, self.words.unknown_role),
+                _ => (&self.users, self.words.unknown_user),
+            };
+            let name = resolver.as_ref().and_then(|resolve| resolve(id));
+            return name.unwrap_or_else(|| unknown.to_string());
+        }
+        if let (Some(name), Some(id)) = (captures.get(3), captures.get(4)) {
+            let id = id.as_str().parse().unwrap_or_default();
+            let resolved = self.custom_emoji
+                .as_ref()
+                .and_then(|resolve| resolve(name.as_str(), id));
+            let name = resolved.unwrap_or_else(|| name.as_str().replace('_', " "));
+            let before = &content[..captures.get(0).map_or(0, |token| token.start())];
+            return pad(&name, before.is_empty() || before.ends_with('\n'));
+        }
+        if let Some(timestamp) = captures.get(5) {
+            let style = captures.get(6).map_or("f", |style| style.as_str());
+            // Timestamps out of range are left out.
+            let timestamp = timestamp.as_str().parse().ok();
+            return timestamp
+                .and_then(|timestamp| self.timestamp(timestamp, style))
+                .unwrap_or_default();
+        }
+        if let Some(command) = captures.get(7) {
+            return format!("/{}", command.as_str());
+        }
+
+        let text = &captures[0];
+        match bare_url(text).and_then(|url| Some((url.len(), host(url)?))) {
+            Some((length, host)) => format!("{}{}", host, &text[length..]),
+            None => text.to_string(),
+        }
+    }
+
+    /// Reads a timestamp in the given Discord style, or returns `None` if it is out of range.
+    fn timestamp(&self, timestamp: i64, style: &str) -> Option<String> {
+        if style == "R" {
+            return self.relative(timestamp);
+        }
+        let local = timestamp.checked_add(self.utc_offset)?;
+        let (year, month, day) = civil_from_days(local.div_euclid(86_400));
+        let seconds = local.rem_euclid(86_400);
+        let time = match style {
+            "T" => format!("{}:{:02}:{:02}", seconds / 3600, (seconds / 60) % 60, seconds % 60),
+            _ => format!("{}:{:02}", seconds / 3600, (seconds / 60) % 60),
+        };
+        let date = self.words.date
+            .replace("{day}", &day.to_string())
+            .replace("{month}", self.words.months[(month - 1) as usize])
+            .replace("{year}", &year.to_string());
+        let text = match style {
+            "t" | "T" => time,
+            "d" | "D" => date,
+            _ => self.words.date_time.replace("{date}", &date).replace("{time}", &time),
+        };
+        Some(text)
+    }
+
+    fn relative(&self, timestamp: i64) -> Option<String> {
+        let now = self.now.unwrap_or_else(SystemTime::now);
+        let now = match now.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs() as i64,
+            Err(error) => -(error.duration().as_secs() as i64),
+        };
+        let difference = timestamp.checked_sub(now)?;
+        let seconds = difference.unsigned_abs();
+        const LENGTHS: [u64; 6] = [1, 60, 3600, 86_400, 30 * 86_400, 365 * 86_400];
+        if seconds < 10 {
+            return Some(self.words.now.to_string());
+        }
+        let unit = LENGTHS.iter().rposition(|length| seconds >= *length).unwrap_or(0);
+        let count = seconds / LENGTHS[unit];
+        let (singular, plural) = self.words.units[unit];
+        let template = if difference > 0 { self.words.future } else { self.words.past };
+        let text = template
+            .replace("{n}", &count.to_string())
+            .replace("{unit}", if count == 1 { singular } else { plural });
+        Some(text)
+    }
+
+    /// Replaces Unicode emoji with their name.
+    fn verbalize_emoji(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut output = String::with_capacity(text.len());
+        let mut previous: Option<String> = None;
+        let mut i = 0;
+
+        while i < chars.len() {
+            if !is_emoji(chars[i]) {
+                if !chars[i].is_whitespace() {
+                    previous = None;
+                }
+                output.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            let end = emoji_end(&chars, i);
+            let emoji: String = chars[i..end].iter().collect();
+            i = end;
+            let Some(name) = self.emoji_name(&emoji) else {
+                continue;
+            };
+            if previous.as_deref() != Some(name.as_str()) {
+                output.push_str(&pad(&name, output.is_empty() || output.ends_with('\n')));
+            }
+            previous = Some(name);
+        }
+        output
+    }
+
+    fn emoji_name(&self, emoji: &str) -> Option<String> {
+        if let Some(resolve) = &self.emoji {
+            return resolve(emoji);
+        }
+        if !self.english {
+            return None;
+        }
+        let base: String = emoji
+            .chars()
+            .filter(|c| !is_emoji_modifier(*c))
+            .collect();
+        COMMON_EMOJI.iter()
+            .find(|(known, _)| *known == base)
+            .map(|(_, name)| name.to_string())
+    }
+}
+
+/// Returns `true` for characters starting an emoji.
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1f000..=0x1faff | 0x2600..=0x27bf | 0x2300..=0x23ff | 0x2b00..=0x2bff | 0x3030 | 0x303d
+    )
+}
+
+/// Returns `true` for characters modifying the emoji before them: variation selectors, skin
+/// tones, the keycap and tags.
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c as u32, 0xfe0e | 0xfe0f | 0x1f3fb..=0x1f3ff | 0x20e3 | 0xe0020..=0xe007f)
+}
+
+/// Returns the index following the emoji starting at `start`, including the emoji joined to
+/// it and the second regional indicator of a flag.
+fn emoji_end(chars: &[char], start: usize) -> usize {
+    let is_regional_indicator = |c: char| (0x1f1e6..=0x1f1ff).contains(&(c as u32));
+    let mut i = start + 1;
+    let flag = chars.get(i).is_some_and(|c| is_regional_indicator(*c));
+    if is_regional_indicator(chars[start]) && flag {
+        i += 1;
+    }
+    loop {
+        match chars.get(i) {
+            Some(c) if is_emoji_modifier(*c) => i += 1,
+            Some('\u{200d}') if chars.get(i + 1).is_some_and(|c| is_emoji(*c)) => i += 2,
+            _ => return i,
+        }
+    }
+}
+
+/// Surrounds `name` with spaces to keep it apart from the surrounding words, without a leading
+/// space at the start of a line, which would be kept as indentation.
+fn pad(name: &str, line_start: bool) -> String {
+    if line_start { format!("{} ", name) } else { format!(" {} ", name) }
+}
+
+/// Collapses runs of spaces left by replacements, keeping line breaks and indentation, so
+/// that the result can still be rendered as Markdown.
+fn collapse_spaces(text: &str) -> String {
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| {
+            let content = line.trim_start();
+            let words: Vec<&str> = content.split(' ').filter(|word| !word.is_empty()).collect();
+            format!("{}{}", &line[..line.len() - content.len()], words.join(" "))
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// Converts days since 1970-01-01 into a year, month and day of the proleptic Gregorian
+/// calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// 2023-11-14 22:13:20 UTC.
+    const NOW: u64 = 1_700_000_000;
+
+    fn normalizer(language: &str) -> DiscordNormalizer {
+        DiscordNormalizer::new(&language.parse().unwrap()).now(
+            UNIX_EPOCH + Duration::from_secs(NOW)
+        )
+    }
+
+    #[test]
+    fn test_mentions() {
+        let normalizer = normalizer("en-US")
+            .users(|id| (id == 1).then(|| "Alice".to_string()))
+            .channels(|id| (id == 2).then(|| "general".to_string()))
+            .roles(|id| (id == 3).then(|| "moderators".to_string()));
+
+        let cases = [
+            ("hi <@1> and <@!1>", "hi Alice and Alice"),
+            ("see <#2>", "see general"),
+            ("ping <@&3>", "ping moderators"),
+            ("<@9> in <#9> with <@&9>", "unknown user in unknown channel with unknown role"),
+            ("run </ban user:123>", "run /ban user"),
+            ("<:party_parrot:42> <a:wave:43>", "party parrot wave"),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(normalizer.verbalize(content), expected, "{}", content);
+        }
+    }
+
+    #[test]
+    fn test_mentions_without_resolvers() {
+        let normalizer = normalizer("vi-VN");
+        println!("{}", normalizer.verbalize("<@1> <#2> <@&3>"));
+        assert_eq!(normalizer.verbalize("<@1>"), vi::DISCORD.unknown_user);
+        assert_eq!(normalizer.verbalize("<#2>"), vi::DISCORD.unknown_channel);
+        assert_eq!(normalizer.verbalize("<@&3>"), vi::DISCORD.unknown_role);
+    }
+
+    #[test]
+    fn test_timestamp_styles() {
+        let normalizer = normalizer("en-US").utc_offset(2 * 3600);
+
+        let cases = [
+            ("<t:1700000000:t>", "0:13"),
+            ("<t:1700000000:T>", "0:13:20"),
+            ("<t:1700000000:d>", "November 15, 2023"),
+            ("<t:1700000000:D>", "November 15, 2023"),
+            ("<t:1700000000:f>", "November 15, 2023 at 0:13"),
+            ("<t:1700000000:F>", "November 15, 2023 at 0:13"),
+            ("<t:1700000000>", "November 15, 2023 at 0:13"),
+            ("<t:1700000005:R>", "now"),
+            ("<t:1700000060:R>", "in 1 minute"),
+            ("<t:1699992800:R>", "2 hours ago"),
+            ("<t:1702592000:R>", "in 1 month"),
+            ("<t:1636928000:R>", "2 years ago"),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(normalizer.verbalize(content), expected, "{}", content);
+        }
+
+        let normalizer = self::normalizer("vi-VN").utc_offset(7 * 3600);
+        println!("{}", normalizer.verbalize("<t:1700000000:f> <t:1700086400:R>"));
+        assert_eq!(normalizer.verbalize("<t:1700000000:d>"), "ngày 15 tháng 11 năm 2023");
+        assert_eq!(normalizer.verbalize("<t:1700086400:R>"), "1 ngày nữa");
+    }
+
+    #[test]
+    fn test_timestamp_overflow() {
+        let normalizer = normalizer("en-US").utc_offset(3600);
+
+        let cases = [
+            ("at <t:-9223372036854775808:R>.", "at ."),
+            ("at <t:9223372036854775807:f>.", "at ."),
+            ("at <t:99999999999999999999>.", "at ."),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(normalizer.verbalize(content), expected, "{}", content);
+        }
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        let cases = [
+            (0, (1970, 1, 1)),
+            (-1, (1969, 12, 31)),
+            (11_016, (2000, 2, 29)),
+            (11_017, (2000, 3, 1)),
+            (-25_509, (1900, 2, 28)),
+            (-25_508, (1900, 3, 1)),
+            (19_782, (2024, 2, 29)),
+            (19_783, (2024, 3, 1)),
+            (19_722, (2023, 12, 31)),
+        ];
+        for (days, expected) in cases {
+            assert_eq!(civil_from_days(days), expected, "{}", days);
+        }
+    }
+
+    #[test]
+    fn test_emoji() {
+        let normalizer = normalizer("en-US");
+
+        let cases = [
+            ("nice 👍👍👍", "nice thumbs up"),
+            ("nice 👍 👍🏽 👍", "nice thumbs up"),
+            ("🔥 hot 🔥", "fire hot fire"),
+            ("ok 🫠", "ok"),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(normalizer.verbalize(content), expected, "{}", content);
+        }
+
+        // Without a resolver, emoji aren't read in other languages.
+        assert_eq!(self::normalizer("fr-FR").verbalize("super 👍"), "super");
+
+        let normalizer = self::normalizer("fr-FR")
+            .emoji(|emoji| (emoji == "👍").then(|| "pouce levé".to_string()));
+        assert_eq!(normalizer.verbalize("super 👍👍"), "super pouce levé");
+    }
+
+    #[test]
+    fn test_urls() {
+        let normalizer = normalizer("en-US");
+
+        let cases = [
+            ("see https://www.example.com/a/b?c=d", "see example.com"),
+            ("at http://docs.rs/regex.", "at docs.rs."),
+            ("(https://github.com/x)", "(github.com)"),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(normalizer.verbalize(content), expected, "{}", content);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// src/normalize/en.rs
+
+use crate::normalize::{ discord::DiscordWords, locale::Locale };
+
+pub const LOCALE: Locale = Locale {
+    language: "en",
+    decimal_separator: '.',
+    date_order: "mdy",
+    time: None,
+    time_on_the_hour: None,
+    fraction: None,
+    ordinal_suffixes: &["st", "nd", "rd", "th"],
+    scales: &[(1e9, " billion", " billion"), (1e6, " million", " million")],
+    currencies: &[
+        ("$", "{amount} dollars"),
+        ("€", "{amount} euros"),
+        ("£", "{amount} pounds"),
+        ("¥", "{amount} yen"),
+        ("₫", "{amount} dong"),
+    ],
+    units: &[
+        ("km/h", "{n} kilometer per hour", "{n} kilometers per hour"),
+        ("m/s", "{n} meter per second", "{n} meters per second"),
+        ("mph", "{n} mile per hour", "{n} miles per hour"),
+        ("kWh", "{n} kilowatt hour", "{n} kilowatt hours"),
+        ("km", "{n} kilometer", "{n} kilometers"),
+        ("kg", "{n} kilogram", "{n} kilograms"),
+        ("cm", "{n} centimeter", "{n} centimeters"),
+        ("mm", "{n} millimeter", "{n} millimeters"),
+        ("GB", "{n} gigabyte", "{n} gigabytes"),
+        ("MB", "{n} megabyte", "{n} megabytes"),
+        ("°C", "{n} degree Celsius", "{n} degrees Celsius"),
+        ("°F", "{n} degree Fahrenheit", "{n} degrees Fahrenheit"),
+    ],
+    version: "version",
+    version_separator: "point",
+};
+
+pub const DISCORD: DiscordWords = DiscordWords {
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    date: "{month} {day}, {year}",
+    date_time: "{date} at {time}",
+    future: "in {n} {unit}",
+    past: "{n} {unit} ago",
+    now: "now",
+    units: [
+        ("second", "seconds"),
+        ("minute", "minutes"),
+        ("hour", "hours"),
+        ("day", "days"),
+        ("month", "months"),
+        ("year", "years"),
+    ],
+    unknown_user: "unknown user",
+    unknown_channel: "unknown channel",
+    unknown_role: "unknown role",
+};
@@ -0,0 +1,72 @@
+// src/normalize/fr.rs
+
+use crate::normalize::{ discord::DiscordWords, locale::Locale };
+
+pub const LOCALE: Locale = Locale {
+    language: "fr",
+    decimal_separator: ',',
+    date_order: "dmy",
+    time: Some("{h} heures {m}"),
+    time_on_the_hour: Some("{h} heures"),
+    fraction: None,
+    ordinal_suffixes: &["er", "re", "ère", "ème", "eme", "e"],
+    scales: &[(1e9, " milliard", " milliards"), (1e6, " million", " millions")],
+    currencies: &[
+        ("$", "{amount} dollars"),
+        ("€", "{amount} euros"),
+        ("£", "{amount} livres"),
+        ("¥", "{amount} yens"),
+        ("₫", "{amount} dongs"),
+    ],
+    units: &[
+        ("km/h", "{n} kilomètre par heure", "{n} kilomètres par heure"),
+        ("m/s", "{n} mètre par seconde", "{n} mètres par seconde"),
+        ("mph", "{n} mile par heure", "{n} miles par heure"),
+        ("kWh", "{n} kilowattheure", "{n} kilowattheures"),
+        ("km", "{n} kilomètre", "{n} kilomètres"),
+        ("kg", "{n} kilogramme", "{n} kilogrammes"),
+        ("cm", "{n} centimètre", "{n} centimètres"),
+        ("mm", "{n} millimètre", "{n} millimètres"),
+        ("Go", "{n} gigaoctet", "{n} gigaoctets"),
+        ("Mo", "{n} mégaoctet", "{n} mégaoctets"),
+        ("GB", "{n} gigaoctet", "{n} gigaoctets"),
+        ("MB", "{n} mégaoctet", "{n} mégaoctets"),
+        ("°C", "{n} degré Celsius", "{n} degrés Celsius"),
+        ("°F", "{n} degré Fahrenheit", "{n} degrés Fahrenheit"),
+    ],
+    version: "version",
+    version_separator: "point",
+};
+
+pub const DISCORD: DiscordWords = DiscordWords {
+    months: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    date: "{day} {month} {year}",
+    date_time: "{date} à {time}",
+    future: "dans {n} {unit}",
+    past: "il y a {n} {unit}",
+    now: "maintenant",
+    units: [
+        ("seconde", "secondes"),
+        ("minute", "minutes"),
+        ("heure", "heures"),
+        ("jour", "jours"),
+        ("mois", "mois"),
+        ("an", "ans"),
+    ],
+    unknown_user: "utilisateur inconnu",
+    unknown_channel: "salon inconnu",
+    unknown_role: "rôle inconnu",
+};
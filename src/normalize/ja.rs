@@ -0,0 +1,71 @@
+// src/normalize/ja.rs
+
+use crate::normalize::{ discord::DiscordWords, locale::Locale };
+
+pub const LOCALE: Locale = Locale {
+    language: "ja",
+    decimal_separator: '.',
+    date_order: "ymd",
+    time: Some("{h}時{m}分"),
+    time_on_the_hour: Some("{h}時"),
+    fraction: Some("{d}分の{n}"),
+    // Ordinals are written with counters, e.g. "2番目".
+    ordinal_suffixes: &[],
+    scales: &[(1e8, "億", "億"), (1e4, "万", "万")],
+    currencies: &[
+        ("$", "{amount}ドル"),
+        ("€", "{amount}ユーロ"),
+        ("£", "{amount}ポンド"),
+        ("¥", "{amount}円"),
+        ("₫", "{amount}ドン"),
+    ],
+    units: &[
+        ("km/h", "時速{n}キロメートル", "時速{n}キロメートル"),
+        ("m/s", "秒速{n}メートル", "秒速{n}メートル"),
+        ("mph", "時速{n}マイル", "時速{n}マイル"),
+        ("kWh", "{n}キロワット時", "{n}キロワット時"),
+        ("km", "{n}キロメートル", "{n}キロメートル"),
+        ("kg", "{n}キログラム", "{n}キログラム"),
+        ("cm", "{n}センチメートル", "{n}センチメートル"),
+        ("mm", "{n}ミリメートル", "{n}ミリメートル"),
+        ("GB", "{n}ギガバイト", "{n}ギガバイト"),
+        ("MB", "{n}メガバイト", "{n}メガバイト"),
+        ("°C", "{n}度", "{n}度"),
+        ("°F", "華氏{n}度", "華氏{n}度"),
+    ],
+    version: "バージョン",
+    version_separator: "てん",
+};
+
+pub const DISCORD: DiscordWords = DiscordWords {
+    months: [
+        "1月",
+        "2月",
+        "3月",
+        "4月",
+        "5月",
+        "6月",
+        "7月",
+        "8月",
+        "9月",
+        "10月",
+        "11月",
+        "12月",
+    ],
+    date: "{year}年{month}{day}日",
+    date_time: "{date} {time}",
+    future: "{n}{unit}後",
+    past: "{n}{unit}前",
+    now: "今",
+    units: [
+        ("秒", "秒"),
+        ("分", "分"),
+        ("時間", "時間"),
+        ("日", "日"),
+        ("か月", "か月"),
+        ("年", "年"),
+    ],
+    unknown_user: "不明なユーザー",
+    unknown_channel: "不明なチャンネル",
+    unknown_role: "不明なロール",
+};
@@ -0,0 +1,51 @@
+// src/normalize/locale.rs
+
+use crate::{ normalize::{ de, en, fr, ja, vi }, types::language::LanguageTag };
+
+/// How numbers, dates, amounts and units are read in a language.
+///
+/// Templates hold placeholders in braces, e.g. `{n}`, which the rules replace with digits.
+/// Digits are left to the voice, which reads them well on their own.
+#[derive(Debug)]
+pub struct Locale {
+    /// Primary language subtag, e.g. `en`.
+    pub language: &'static str,
+    pub decimal_separator: char,
+    /// Order of the day, month and year in dates written with slashes or dots: `mdy`, `dmy`
+    /// or `ymd`.
+    pub date_order: &'static str,
+    /// Template of a time, with `{h}` and `{m}`. `None` reads times with
+    /// `<say-as interpret-as="time">`.
+    pub time: Option<&'static str>,
+    /// Template of a time on the hour, with `{h}`.
+    pub time_on_the_hour: Option<&'static str>,
+    /// Template of a fraction, with `{n}` and `{d}`. `None` reads fractions with
+    /// `<say-as interpret-as="fraction">`.
+    pub fraction: Option<&'static str>,
+    /// Suffixes written after digits to make an ordinal, e.g. `nd` in `2nd`.
+    pub ordinal_suffixes: &'static [&'static str],
+    /// Large number words, largest first: the value, the singular and the plural form, with
+    /// the separator expected after the number.
+    pub scales: &'static [(f64, &'static str, &'static str)],
+    /// Templates of amounts by currency symbol, with `{amount}`.
+    pub currencies: &'static [(&'static str, &'static str)],
+    /// Templates of measures by unit symbol, with `{n}`: the singular form, read after
+    /// `1`, and the plural form.
+    pub units: &'static [(&'static str, &'static str, &'static str)],
+    /// Word read before a version written with a `v`, e.g. `v1.2`.
+    pub version: &'static str,
+    /// Word read between the parts of a version.
+    pub version_separator: &'static str,
+}
+
+/// Returns the locale of `language`, if its rules are built in.
+pub fn for_language(language: &LanguageTag) -> Option<&'static Locale> {
+    match language.language() {
+        "en" => Some(&en::LOCALE),
+        "vi" => Some(&vi::LOCALE),
+        "de" => Some(&de::LOCALE),
+        "fr" => Some(&fr::LOCALE),
+        "ja" => Some(&ja::LOCALE),
+        _ => None,
+    }
+}
@@ -0,0 +1,189 @@
+// src/normalize/mod.rs
+
+//! Locale-aware normalization of text before synthesis.
+//!
+//! Voices read numbers well, but guess at fractions, times, shorthand amounts, units and
+//! versions, and often guess wrong outside of English. A [Normalizer] applies a set of
+//! [Rule]s that rewrite these into explicit words, or into `<say-as>` elements telling the
+//! voice how to read them. Built-in rules exist for `en`, `vi`, `de`, `fr` and `ja`; each of
+//! them can be turned off by its [Rule::id].
+//!
+//! [discord::DiscordNormalizer] reads the mentions, emoji and timestamps of Discord messages.
+
+pub mod de;
+pub mod discord;
+pub mod en;
+pub mod fr;
+pub mod ja;
+pub mod locale;
+pub mod rules;
+pub mod vi;
+
+use std::{ collections::HashSet, fmt, ops::Range, sync::Arc };
+
+use crate::{
+    ssml::node::{ Node, Speak },
+    types::{ language::LanguageTag, synthesize::SynthesisInput },
+};
+
+/// A span of text to replace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rewrite {
+    /// Byte range of the span in the text given to [Rule::rewrite].
+    pub range: Range<usize>,
+    /// What the span is replaced with, typically [Node::Text] or [Node::SayAs].
+    pub node: Node,
+}
+
+/// A normalization rule.
+pub trait Rule: Send + Sync {
+    /// Identifier used to turn the rule off, e.g. `fraction`.
+    fn id(&self) -> &'static str;
+
+    /// Returns the spans of `text` to replace. Spans overlapping an earlier one are ignored.
+    fn rewrite(&self, text: &str) -> Vec<Rewrite>;
+}
+
+/// Applies [Rule]s to text, in order. Text rewritten by a rule isn't seen by the next ones.
+#[derive(Clone, Default)]
+pub struct Normalizer {
+    rules: Vec<Arc<dyn Rule>>,
+    disabled: HashSet<String>,
+}
+
+impl fmt::Debug for Normalizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Normalizer")
+            .field("rules", &self.rule_ids())
+            .field("disabled", &self.disabled)
+            .finish()
+    }
+}
+
+/// Part of the text being normalized.
+enum Segment {
+    /// Text not rewritten yet.
+    Pending(String),
+    Rewritten(Node),
+}
+
+impl Normalizer {
+    /// Creates a normalizer without rules.
+    pub fn new() -> Self {
+        Normalizer::default()
+    }
+
+    /// Creates a normalizer with the built-in rules of `language`, or without rules if there
+    /// are none for it.
+    pub fn for_language(language: &LanguageTag) -> Self {
+        let rules = locale::for_language(language).map(rules::for_locale).unwrap_or_default();
+        Normalizer { rules, disabled: HashSet::new() }
+    }
+
+    /// Appends `rule`, applied after the rules already added.
+    pub fn rule(mut self, rule: impl Rule + 'static) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Turns off the rules identified by `id`.
+    pub fn disable(mut self, id: &str) -> Self {
+        self.disabled.insert(id.to_string());
+        self
+    }
+
+    /// Turns back on the rules identified by `id`.
+    pub fn enable(mut self, id: &str) -> Self {
+        self.disabled.remove(id);
+        self
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        !self.disabled.contains(id) && self.rules.iter().any(|rule| rule.id() == id)
+    }
+
+    /// Returns the identifiers of the rules, in the order they are applied.
+    pub fn rule_ids(&self) -> Vec<&'static str> {
+        self.rules
+            .iter()
+            .map(|rule| rule.id())
+            .collect()
+    }
+
+    /// Normalizes `text` into SSML nodes.
+    pub fn normalize(&self, text: &str) -> Vec<Node> {
+        let mut segments = vec![Segment::Pending(text.to_string())];
+        for rule in self.rules.iter().filter(|rule| !self.disabled.contains(rule.id())) {
+            segments = segments
+                .into_iter()
+                .flat_map(|segment| {
+                    match segment {
+                        Segment::Pending(text) => apply(rule.as_ref(), text),
+                        rewritten => vec![rewritten],
+                    }
+                })
+                .collect();
+        }
+
+        let mut nodes: Vec<Node> = Vec::new();
+        for segment in segments {
+            let node = match segment {
+                Segment::Pending(text) => Node::Text(text),
+                Segment::Rewritten(node) => node,
+            };
+            match (nodes.last_mut(), node) {
+                (Some(Node::Text(last)), Node::Text(text)) => last.push_str(&text),
+                (_, Node::Text(text)) if text.is_empty() => {}
+                (_, node) => nodes.push(node),
+            }
+        }
+        nodes
+    }
+
+    /// Normalizes `text` into a `<speak>` document.
+    pub fn to_speak(&self, text: &str) -> Speak {
+        Speak::new(self.normalize(text))
+    }
+
+    /// Normalizes a text input. The input stays plain text when every rewrite is made of
+    /// words, and becomes SSML otherwise. Other inputs are returned unchanged.
+    pub fn normalize_input(&self, input: SynthesisInput) -> SynthesisInput {
+        let SynthesisInput::Text(text) = input else {
+            return input;
+        };
+        let nodes = self.normalize(&text);
+        match nodes.as_slice() {
+            [] => SynthesisInput::Text(String::new()),
+            [Node::Text(text)] => SynthesisInput::Text(text.clone()),
+            _ => Speak::new(nodes).into(),
+        }
+    }
+}
+
+/// Splits `text` around the rewrites of `rule`.
+fn apply(rule: &dyn Rule, text: String) -> Vec<Segment> {
+    let mut rewrites = rule.rewrite(&text);
+    if rewrites.is_empty() {
+        return vec![Segment::Pending(text)];
+    }
+    rewrites.sort_by_key(|rewrite| rewrite.range.start);
+
+    let mut segments = Vec::new();
+    let mut end = 0;
+    for rewrite in rewrites {
+        let valid = text.is_char_boundary(rewrite.range.start) &&
+            text.is_char_boundary(rewrite.range.end);
+        if rewrite.range.start < end || !valid {
+            continue;
+        }
+        if rewrite.range.start > end {
+            segments.push(Segment::Pending(text[end..rewrite.range.start].to_string()));
+        }
+        end = rewrite.range.end;
+        segments.push(Segment::Rewritten(rewrite.node));
+    }
+    if end < text.len() {
+        segments.push(Segment::Pending(text[end..].to_string()));
+    }
+    segments
+}
@@ -0,0 +1,562 @@
+// src/normalize/rules.rs
+
+//! The built-in rules, each parameterized by a [Locale].
+
+use std::sync::Arc;
+
+use regex::{ Captures, Regex };
+
+use crate::{
+    normalize::{ locale::Locale, Rewrite, Rule },
+    ssml::{ attributes::InterpretAs, node::{ Node, SayAs } },
+};
+
+/// Returns the built-in rules of `locale`, in the order they are applied.
+pub fn for_locale(locale: &'static Locale) -> Vec<Arc<dyn Rule>> {
+    let mut rules: Vec<Arc<dyn Rule>> = vec![
+        Arc::new(DateRule::new(locale)),
+        Arc::new(TimeRule::new(locale)),
+        Arc::new(CurrencyRule::new(locale)),
+        Arc::new(VersionRule::new(locale)),
+        Arc::new(UnitRule::new(locale)),
+        Arc::new(FractionRule::new(locale)),
+    ];
+    if !locale.ordinal_suffixes.is_empty() {
+        rules.push(Arc::new(OrdinalRule::new(locale)));
+    }
+    rules
+}
+
+/// Compiles a built-in pattern. `\b` only matches next to ASCII word characters, so that
+/// `10:30に` has a boundary after the digits, as it does in `10:30 in`.
+fn pattern(source: &str) -> Regex {
+    Regex::new(&source.replace(r"\b", r"(?-u:\b)")).expect("valid pattern")
+}
+
+/// Rewrites every match of `pattern` for which `rewrite` returns a node.
+fn rewrite_matches(
+    pattern: &Regex,
+    text: &str,
+    mut rewrite: impl FnMut(&Captures) -> Option<Node>
+) -> Vec<Rewrite> {
+    pattern
+        .captures_iter(text)
+        .filter_map(|captures| {
+            let range = captures.get(0)?.range();
+            rewrite(&captures).map(|node| Rewrite { range, node })
+        })
+        .collect()
+}
+
+/// Returns `true` if the match ending at `end` is directly followed by `separator` and a
+/// digit, i.e. is part of a longer sequence such as `10:30:15` or `1/2/3`.
+fn continues_with(text: &str, end: usize, separator: char) -> bool {
+    let mut rest = text[end..].chars();
+    rest.next() == Some(separator) && rest.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+/// Returns `true` if the match starting at `start` directly follows `separator`.
+fn follows(text: &str, start: usize, separator: char) -> bool {
+    text[..start].ends_with(separator)
+}
+
+/// Returns `true` if `parts` are the four bytes of an IPv4 address, e.g. `192.168.1.1`.
+fn is_ipv4_address(parts: &[&str]) -> bool {
+    parts.len() == 4 && parts.iter().all(|part| part.len() <= 3 && part.parse::<u8>().is_ok())
+}
+
+/// Parses a number written with the decimal separator of `locale`. The other separator is
+/// taken for a thousands separator when followed by exactly three digits.
+fn parse_number(number: &str, locale: &Locale) -> Option<f64> {
+    let thousands = if locale.decimal_separator == ',' { '.' } else { ',' };
+    let groups: Vec<&str> = number.split(thousands).collect();
+    let number = if groups.len() > 1 && groups[1..].iter().all(|group| group.len() == 3) {
+        groups.concat()
+    } else {
+        number.replace(thousands, ".")
+    };
+    number.replace(locale.decimal_separator, ".").parse().ok()
+}
+
+/// Formats `value` with at most two decimals, written with the decimal separator of `locale`.
+fn format_number(value: f64, locale: &Locale) -> String {
+    let formatted = format!("{:.2}", value);
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    formatted.replace('.', &locale.decimal_separator.to_string())
+}
+
+/// Formats an amount with the large number words of `locale`, e.g. `1.5 million`.
+fn format_amount(value: f64, locale: &Locale) -> String {
+    for (scale, singular, plural) in locale.scales {
+        if value >= *scale {
+            let scaled = value / scale;
+            let word = if scaled == 1.0 { singular } else { plural };
+            return format!("{}{}", format_number(scaled, locale), word);
+        }
+    }
+    format_number(value, locale)
+}
+
+/// Reads dates as `<say-as interpret-as="date">`, e.g. `2024-12-25` or `25.12.2024`.
+///
+/// Dates written with dots need a four-digit year, so that versions such as `2.10.20` are
+/// not taken for dates.
+pub struct DateRule {
+    locale: &'static Locale,
+    iso: Regex,
+    numeric: Regex,
+}
+
+impl DateRule {
+    pub fn new(locale: &'static Locale) -> Self {
+        DateRule {
+            locale,
+            iso: pattern(r"\b(\d{4})-(\d{2})-(\d{2})\b"),
+            numeric: pattern(r"\b(\d{1,4})([/.])(\d{1,2})([/.])(\d{1,4})\b"),
+        }
+    }
+}
+
+impl Rule for DateRule {
+    fn id(&self) -> &'static str {
+        "date"
+    }
+
+    fn rewrite(&self, text: &str) -> Vec<Rewrite> {
+        let mut rewrites = rewrite_matches(&self.iso, text, |captures| {
+            let month: u32 = captures[2].parse().ok()?;
+            let day: u32 = captures[3].parse().ok()?;
+            if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+                return None;
+            }
+            let date = SayAs::new(InterpretAs::Date, &captures[0]).format("yyyymmdd");
+            Some(Node::SayAs(date))
+        });
+        rewrites.extend(
+            rewrite_matches(&self.numeric, text, |captures| {
+                let range = captures.get(0)?.range();
+                let separator = captures[2].chars().next()?;
+                if
+                    captures[2] != captures[4] ||
+                    continues_with(text, range.end, separator) ||
+                    follows(text, range.start, separator)
+                {
+                    return None;
+                }
+                let parts = [&captures[1], &captures[3], &captures[5]];
+                let order = self.locale.date_order;
+                let part = |field: char| -> Option<(usize, u32)> {
+                    let index = order.find(field)?;
+                    Some((parts[index].len(), parts[index].parse().ok()?))
+                };
+                let (_, day) = part('d')?;
+                let (_, month) = part('m')?;
+                let (year_digits, _) = part('y')?;
+                let valid =
+                    (1..=31).contains(&day) &&
+                    (1..=12).contains(&month) &&
+                    (year_digits == 4 || (year_digits == 2 && separator == '/'));
+                let date = SayAs::new(InterpretAs::Date, &captures[0]).format(order);
+                valid.then_some(Node::SayAs(date))
+            })
+        );
+        rewrites
+    }
+}
+
+/// Reads times such as `10:30`.
+pub struct TimeRule {
+    locale: &'static Locale,
+    pattern: Regex,
+}
+
+impl TimeRule {
+    pub fn new(locale: &'static Locale) -> Self {
+        TimeRule {
+            locale,
+            pattern: pattern(r"\b([01]?\d|2[0-3]):([0-5]\d)\b"),
+        }
+    }
+}
+
+impl Rule for TimeRule {
+    fn id(&self) -> &'static str {
+        "time"
+    }
+
+    fn rewrite(&self, text: &str) -> Vec<Rewrite> {
+        rewrite_matches(&self.pattern, text, |captures| {
+            let range = captures.get(0)?.range();
+            if continues_with(text, range.end, ':') || follows(text, range.start, ':') {
+                return None;
+            }
+            let hours: u32 = captures[1].parse().ok()?;
+            let minutes: u32 = captures[2].parse().ok()?;
+            let template = match (self.locale.time, self.locale.time_on_the_hour) {
+                (_, Some(on_the_hour)) if minutes == 0 => on_the_hour,
+                (Some(time), _) => time,
+                (None, _) => {
+                    let time = SayAs::new(InterpretAs::Time, &captures[0]).format("hms24");
+                    return Some(Node::SayAs(time));
+                }
+            };
+            let words = template
+                .replace("{h}", &hours.to_string())
+                .replace("{m}", &minutes.to_string());
+            Some(Node::Text(words))
+        })
+    }
+}
+
+/// Reads fractions such as `3/4`.
+pub struct FractionRule {
+    locale: &'static Locale,
+    pattern: Regex,
+}
+
+impl FractionRule {
+    pub fn new(locale: &'static Locale) -> Self {
+        FractionRule {
+            locale,
+            pattern: pattern(r"\b(\d{1,3})/(\d{1,3})\b"),
+        }
+    }
+}
+
+impl Rule for FractionRule {
+    fn id(&self) -> &'static str {
+        "fraction"
+    }
+
+    fn rewrite(&self, text: &str) -> Vec<Rewrite> {
+        rewrite_matches(&self.pattern, text, |captures| {
+            let range = captures.get(0)?.range();
+            if continues_with(text, range.end, '/') || follows(text, range.start, '/') {
+                return None;
+            }
+            if captures[2].trim_start_matches('0').is_empty() {
+                return None;
+            }
+            match self.locale.fraction {
+                Some(template) => {
+                    let words = template.replace("{n}", &captures[1]).replace("{d}", &captures[2]);
+                    Some(Node::Text(words))
+                }
+                None => Some(Node::SayAs(SayAs::new(InterpretAs::Fraction, &captures[0]))),
+            }
+        })
+    }
+}
+
+/// Reads amounts such as `$1.5k` or `20€`, expanding `k`, `M` and `B` suffixes.
+pub struct CurrencyRule {
+    locale: &'static Locale,
+    prefixed: Regex,
+    suffixed: Regex,
+}
+
+impl CurrencyRule {
+    pub fn new(locale: &'static Locale) -> Self {
+        let symbols: String = locale.currencies
+            .iter()
+            .map(|(symbol, _)| regex::escape(symbol))
+            .collect::<Vec<_>>()
+            .join("|");
+        let amount = r"(\d+(?:[.,]\d+)*)(bn|[kKmMbB])?";
+        CurrencyRule {
+            locale,
+            prefixed: pattern(&format!(r"({})\s?{}\b", symbols, amount)),
+            suffixed: pattern(&format!(r"\b{}\s?({})", amount, symbols)),
+        }
+    }
+
+    fn amount(&self, symbol: &str, number: &str, suffix: Option<&str>) -> Option<Node> {
+        let (_, template) = self.locale.currencies.iter().find(|(known, _)| *known == symbol)?;
+        let multiplier = match suffix {
+            None => 1.0,
+            Some("k" | "K") => 1e3,
+            Some("m" | "M") => 1e6,
+            Some(_) => 1e9,
+        };
+        let value = parse_number(number, self.locale)? * multiplier;
+        Some(Node::Text(template.replace("{amount}", &format_amount(value, self.locale))))
+    }
+}
+
+impl Rule for CurrencyRule {
+    fn id(&self) -> &'static str {
+        "currency"
+    }
+
+    fn rewrite(&self, text: &str) -> Vec<Rewrite> {
+        let mut rewrites = rewrite_matches(&self.prefixed, text, |captures| {
+            let suffix = captures.get(3).map(|suffix| suffix.as_str());
+            self.amount(&captures[1], &captures[2], suffix)
+        });
+        rewrites.extend(
+            rewrite_matches(&self.suffixed, text, |captures| {
+                let suffix = captures.get(2).map(|suffix| suffix.as_str());
+                self.amount(&captures[3], &captures[1], suffix)
+            })
+        );
+        rewrites
+    }
+}
+
+/// Reads ordinals written with a suffix, e.g. `2nd`, as `<say-as interpret-as="ordinal">`.
+pub struct OrdinalRule {
+    pattern: Regex,
+}
+
+impl OrdinalRule {
+    pub fn new(locale: &'static Locale) -> Self {
+        let suffixes: Vec<String> = locale.ordinal_suffixes
+            .iter()
+            .map(|suffix| regex::escape(suffix))
+            .collect();
+        OrdinalRule {
+            pattern: pattern(&format!(r"\b(\d+)(?:{})\b", suffixes.join("|"))),
+        }
+    }
+}
+
+impl Rule for OrdinalRule {
+    fn id(&self) -> &'static str {
+        "ordinal"
+    }
+
+    fn rewrite(&self, text: &str) -> Vec<Rewrite> {
+        rewrite_matches(&self.pattern, text, |captures| {
+            Some(Node::SayAs(SayAs::new(InterpretAs::Ordinal, &captures[1])))
+        })
+    }
+}
+
+/// Reads measures such as `60 km/h`.
+pub struct UnitRule {
+    locale: &'static Locale,
+    pattern: Regex,
+}
+
+impl UnitRule {
+    pub fn new(locale: &'static Locale) -> Self {
+        let units: Vec<String> = locale.units
+            .iter()
+            .map(|(unit, _, _)| regex::escape(unit))
+            .collect();
+        UnitRule {
+            locale,
+            pattern: pattern(&format!(r"\b(\d+(?:[.,]\d+)?)\s?({})\b", units.join("|"))),
+        }
+    }
+}
+
+impl Rule for UnitRule {
+    fn id(&self) -> &'static str {
+        "unit"
+    }
+
+    fn rewrite(&self, text: &str) -> Vec<Rewrite> {
+        rewrite_matches(&self.pattern, text, |captures| {
+            let (_, singular, plural) = self.locale.units
+                .iter()
+                .find(|(unit, _, _)| *unit == &captures[2])?;
+            let template = if &captures[1] == "1" { singular } else { plural };
+            Some(Node::Text(template.replace("{n}", &captures[1])))
+        })
+    }
+}
+
+/// Reads versions such as `v2.1` or `1.4.2` part by part. Addresses such as `192.168.1.1`
+/// are left to the voice.
+pub struct VersionRule {
+    locale: &'static Locale,
+    pattern: Regex,
+}
+
+impl VersionRule {
+    pub fn new(locale: &'static Locale) -> Self {
+        VersionRule {
+            locale,
+            pattern: pattern(r"\b(v)?(\d+(?:\.\d+)+)\b"),
+        }
+    }
+}
+
+impl Rule for VersionRule {
+    fn id(&self) -> &'static str {
+        "version"
+    }
+
+    fn rewrite(&self, text: &str) -> Vec<Rewrite> {
+        rewrite_matches(&self.pattern, text, |captures| {
+            let prefixed = captures.get(1).is_some();
+            let parts: Vec<&str> = captures[2].split('.').collect();
+            // Without a `v`, two parts are a decimal number.
+            if !prefixed && (parts.len() < 3 || is_ipv4_address(&parts)) {
+                return None;
+            }
+            let separator = format!(" {} ", self.locale.version_separator);
+            let mut words = parts.join(&separator);
+            if prefixed {
+                words = format!("{} {}", self.locale.version, words);
+            }
+            Some(Node::Text(words))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ normalize::{ en, Normalizer }, types::synthesize::SynthesisInput };
+
+    /// Returns `text` normalized for `language`, as the content of a `<speak>` document.
+    fn normalize(language: &str, text: &str) -> String {
+        let normalizer = Normalizer::for_language(&language.parse().unwrap());
+        let speak = normalizer.to_speak(text).to_string();
+        speak.trim_start_matches("<speak>").trim_end_matches("</speak>").to_string()
+    }
+
+    fn assert_normalizes(language: &str, cases: &[(&str, &str)]) {
+        for (text, expected) in cases {
+            let normalized = normalize(language, text);
+            assert_eq!(normalized, *expected, "normalizing {:?} in {}", text, language);
+        }
+    }
+
+    #[test]
+    fn normalizes_english() {
+        assert_normalizes("en-US", &[
+            ("3/4 of it", r#"<say-as interpret-as="fraction">3/4</say-as> of it"#),
+            ("at 10:30", r#"at <say-as interpret-as="time" format="hms24">10:30</say-as>"#),
+            ("$1.5k", "1500 dollars"),
+            ("the 2nd try", r#"the <say-as interpret-as="ordinal">2</say-as> try"#),
+            ("90 km/h", "90 kilometers per hour"),
+            ("v1.2.3", "version 1 point 2 point 3"),
+        ]);
+    }
+
+    #[test]
+    fn normalizes_vietnamese() {
+        assert_normalizes("vi-VN", &[
+            ("3/4 cái bánh", "3 phần 4 cái bánh"),
+            ("lúc 10:30", "lúc 10 giờ 30 phút"),
+            ("$1.5k", "1500 đô la"),
+            ("2nd", "2nd"),
+            ("90 km/h", "90 ki lô mét trên giờ"),
+            ("v1.2.3", "phiên bản 1 chấm 2 chấm 3"),
+        ]);
+    }
+
+    #[test]
+    fn normalizes_german() {
+        assert_normalizes("de-DE", &[
+            ("3/4 Liter", r#"<say-as interpret-as="fraction">3/4</say-as> Liter"#),
+            ("um 10:30", "um 10 Uhr 30"),
+            ("$1.5k", "1500 Dollar"),
+            ("2nd", "2nd"),
+            ("90 km/h", "90 Kilometer pro Stunde"),
+            ("v1.2.3", "Version 1 Punkt 2 Punkt 3"),
+        ]);
+    }
+
+    #[test]
+    fn normalizes_french() {
+        assert_normalizes("fr-FR", &[
+            ("3/4 du gâteau", r#"<say-as interpret-as="fraction">3/4</say-as> du gâteau"#),
+            ("à 10:30", "à 10 heures 30"),
+            ("$1.5k", "1500 dollars"),
+            ("le 2e essai", r#"le <say-as interpret-as="ordinal">2</say-as> essai"#),
+            ("2nd", "2nd"),
+            ("90 km/h", "90 kilomètres par heure"),
+            ("v1.2.3", "version 1 point 2 point 3"),
+        ]);
+    }
+
+    #[test]
+    fn normalizes_japanese() {
+        assert_normalizes("ja-JP", &[
+            ("3/4の人", "4分の3の人"),
+            ("10:30に", "10時30分に"),
+            ("$1.5k", "1500ドル"),
+            ("2nd", "2nd"),
+            ("90 km/h", "時速90キロメートル"),
+            ("v1.2.3", "バージョン 1 てん 2 てん 3"),
+        ]);
+    }
+
+    #[test]
+    fn reads_a_single_unit_in_the_singular() {
+        assert_normalizes("en-US", &[
+            ("1 km", "1 kilometer"),
+            ("1 km/h", "1 kilometer per hour"),
+            ("1°C", "1 degree Celsius"),
+            ("2 km", "2 kilometers"),
+            ("1.5 km", "1.5 kilometers"),
+            ("10 km", "10 kilometers"),
+        ]);
+        assert_normalizes("de-DE", &[("1 kWh", "1 Kilowattstunde"), ("2 kWh", "2 Kilowattstunden")]);
+        assert_normalizes("fr-FR", &[("1 km", "1 kilomètre"), ("3 Go", "3 gigaoctets")]);
+        assert_normalizes("vi-VN", &[("1 km", "1 ki lô mét")]);
+        assert_normalizes("ja-JP", &[("1 kg", "1キログラム")]);
+    }
+
+    #[test]
+    fn leaves_addresses_and_versions_alone() {
+        assert_normalizes("en-US", &[
+            ("ping 192.168.1.1", "ping 192.168.1.1"),
+            ("at 10.0.0.1:8080", "at 10.0.0.1:8080"),
+            ("v10.0.0.1", "version 10 point 0 point 0 point 1"),
+            ("1.2.3.456", "1 point 2 point 3 point 456"),
+            ("2.10.20", "2 point 10 point 20"),
+            ("on 12/25/24", r#"on <say-as interpret-as="date" format="mdy">12/25/24</say-as>"#),
+        ]);
+        assert_normalizes("de-DE", &[
+            ("Version 2.10.20", "Version 2 Punkt 10 Punkt 20"),
+            ("am 1.2.2024.5", "am 1 Punkt 2 Punkt 2024 Punkt 5"),
+            (
+                "am 25.12.2024",
+                r#"am <say-as interpret-as="date" format="dmy">25.12.2024</say-as>"#,
+            ),
+        ]);
+    }
+
+    #[test]
+    fn returns_the_byte_ranges_of_the_rewrites() {
+        let text = "Über 3/4 and 1/2";
+        let rewrites = FractionRule::new(&en::LOCALE).rewrite(text);
+        let ranges: Vec<&str> = rewrites
+            .iter()
+            .map(|rewrite| &text[rewrite.range.clone()])
+            .collect();
+        assert_eq!(ranges, ["3/4", "1/2"]);
+    }
+
+    #[test]
+    fn disables_rules_by_id() {
+        let normalizer = Normalizer::for_language(&"en-US".parse().unwrap());
+        assert!(normalizer.is_enabled("fraction"));
+
+        let disabled = normalizer.clone().disable("fraction").disable("unknown");
+        assert!(!disabled.is_enabled("fraction"));
+        assert!(!disabled.is_enabled("unknown"));
+        assert!(disabled.is_enabled("unit"));
+        assert_eq!(disabled.rule_ids(), normalizer.rule_ids());
+        assert_eq!(
+            disabled.to_speak("3/4 at 90 km/h").to_string(),
+            "<speak>3/4 at 90 kilometers per hour</speak>"
+        );
+        match disabled.normalize_input(SynthesisInput::Text("3/4 at 90 km/h".to_string())) {
+            SynthesisInput::Text(text) => assert_eq!(text, "3/4 at 90 kilometers per hour"),
+            input => panic!("unexpected input {:?}", input),
+        }
+
+        let enabled = disabled.enable("fraction");
+        assert!(enabled.is_enabled("fraction"));
+        assert_eq!(
+            enabled.to_speak("3/4").to_string(),
+            r#"<speak><say-as interpret-as="fraction">3/4</say-as></speak>"#
+        );
+    }
+}
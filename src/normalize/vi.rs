@@ -0,0 +1,71 @@
+// src/normalize/vi.rs
+
+use crate::normalize::{ discord::DiscordWords, locale::Locale };
+
+pub const LOCALE: Locale = Locale {
+    language: "vi",
+    decimal_separator: ',',
+    date_order: "dmy",
+    time: Some("{h} giờ {m} phút"),
+    time_on_the_hour: Some("{h} giờ"),
+    fraction: Some("{n} phần {d}"),
+    // Ordinals are written with words, e.g. "thứ 2".
+    ordinal_suffixes: &[],
+    scales: &[(1e9, " tỷ", " tỷ"), (1e6, " triệu", " triệu")],
+    currencies: &[
+        ("$", "{amount} đô la"),
+        ("€", "{amount} euro"),
+        ("£", "{amount} bảng Anh"),
+        ("¥", "{amount} yên"),
+        ("₫", "{amount} đồng"),
+    ],
+    units: &[
+        ("km/h", "{n} ki lô mét trên giờ", "{n} ki lô mét trên giờ"),
+        ("m/s", "{n} mét trên giây", "{n} mét trên giây"),
+        ("mph", "{n} dặm trên giờ", "{n} dặm trên giờ"),
+        ("kWh", "{n} ki lô oát giờ", "{n} ki lô oát giờ"),
+        ("km", "{n} ki lô mét", "{n} ki lô mét"),
+        ("kg", "{n} ki lô gam", "{n} ki lô gam"),
+        ("cm", "{n} xen ti mét", "{n} xen ti mét"),
+        ("mm", "{n} mi li mét", "{n} mi li mét"),
+        ("GB", "{n} gi ga bai", "{n} gi ga bai"),
+        ("MB", "{n} mê ga bai", "{n} mê ga bai"),
+        ("°C", "{n} độ C", "{n} độ C"),
+        ("°F", "{n} độ F", "{n} độ F"),
+    ],
+    version: "phiên bản",
+    version_separator: "chấm",
+};
+
+pub const DISCORD: DiscordWords = DiscordWords {
+    months: [
+        "tháng 1",
+        "tháng 2",
+        "tháng 3",
+        "tháng 4",
+        "tháng 5",
+        "tháng 6",
+        "tháng 7",
+        "tháng 8",
+        "tháng 9",
+        "tháng 10",
+        "tháng 11",
+        "tháng 12",
+    ],
+    date: "ngày {day} {month} năm {year}",
+    date_time: "{time} {date}",
+    future: "{n} {unit} nữa",
+    past: "{n} {unit} trước",
+    now: "bây giờ",
+    units: [
+        ("giây", "giây"),
+        ("phút", "phút"),
+        ("giờ", "giờ"),
+        ("ngày", "ngày"),
+        ("tháng", "tháng"),
+        ("năm", "năm"),
+    ],
+    unknown_user: "người dùng không xác định",
+    unknown_channel: "kênh không xác định",
+    unknown_role: "vai trò không xác định",
+};
@@ -0,0 +1,433 @@
+// src/services/cache.rs
+
+use std::{
+    collections::{ hash_map::DefaultHasher, BTreeMap, HashMap },
+    hash::{ Hash, Hasher },
+    sync::{ Arc, Mutex, MutexGuard },
+    time::{ Duration, Instant },
+};
+
+use serde_json::Value;
+
+use crate::types::synthesize::{ SynthesizeRequest, SynthesizeResponse };
+
+/// Default total size of the audio kept by an [AudioCache]: 64 MiB.
+pub const DEFAULT_AUDIO_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Default time a synthesized response is reused for.
+pub const DEFAULT_AUDIO_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Counters of an [AudioCache], since it was created or its stats were reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    /// Requests answered from the cache.
+    pub hits: u64,
+    /// Requests sent to the API, the response not being cached or having expired.
+    pub misses: u64,
+    /// Responses dropped to make room for newer ones.
+    pub evictions: u64,
+    /// Responses currently cached.
+    pub entries: usize,
+    /// Size of the audio currently cached, in bytes.
+    pub bytes: usize,
+}
+
+impl CacheStats {
+    /// Returns the share of requests answered from the cache, between 0 and 1.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => (self.hits as f64) / (total as f64),
+        }
+    }
+}
+
+/// An in-memory cache of synthesized speech, bounded by the size of its audio.
+///
+/// Responses are keyed by a hash of the canonical JSON of the whole request: input, voice,
+/// audio configuration and timepoints. They are reused for a time to live, and the least
+/// recently used ones are dropped once the audio kept exceeds the size limit.
+///
+/// The cache is a handle: clones share the same entries. Set it on a client with
+/// [TextToSpeechClient::set_audio_cache](crate::client::TextToSpeechClient::set_audio_cache),
+/// or on a single request with
+/// [SynthesizeSpeechBuilder::cache](crate::services::synthesize::SynthesizeSpeechBuilder::cache).
+#[derive(Debug, Clone)]
+pub struct AudioCache {
+    state: Arc<Mutex<CacheState>>,
+}
+
+#[derive(Debug)]
+struct CacheState {
+    max_bytes: usize,
+    ttl: Duration,
+    entries: HashMap<u64, CacheEntry>,
+    /// Keys of the entries by their last use, least recent first.
+    recency: BTreeMap<u64, u64>,
+    /// Incremented on every use, ordering [CacheState::recency].
+    clock: u64,
+    stats: CacheStats,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    /// The canonical request, compared on lookup so that hash collisions are misses.
+    request: String,
+    response: SynthesizeResponse,
+    inserted_at: Instant,
+    last_used: u64,
+}
+
+impl Default for AudioCache {
+    fn default() -> Self {
+        AudioCache::new(DEFAULT_AUDIO_CACHE_BYTES, DEFAULT_AUDIO_CACHE_TTL)
+    }
+}
+
+impl AudioCache {
+    /// Creates a cache keeping at most `max_bytes` of audio, each response for `ttl`.
+    pub fn new(max_bytes: usize, ttl: Duration) -> Self {
+        AudioCache {
+            state: Arc::new(
+                Mutex::new(CacheState {
+                    max_bytes,
+                    ttl,
+                    entries: HashMap::new(),
+                    recency: BTreeMap::new(),
+                    clock: 0,
+                    stats: CacheStats::default(),
+                })
+            ),
+        }
+    }
+
+    /// Returns the cached response to `request`, if any and not expired. Counts a hit or
+    /// a miss.
+    pub fn get(&self, request: &SynthesizeRequest) -> Option<SynthesizeResponse> {
+        let (key, canonical) = cache_key(request);
+        let mut state = self.lock();
+        let ttl = state.ttl;
+        let fresh = state.entries
+            .get(&key)
+            .filter(|entry| entry.request == canonical)
+            .map(|entry| entry.inserted_at.elapsed() < ttl);
+        if fresh != Some(true) {
+            if fresh == Some(false) {
+                state.remove(key);
+            }
+            state.stats.misses += 1;
+            return None;
+        }
+
+        state.stats.hits += 1;
+        state.clock += 1;
+        let clock = state.clock;
+        let entry = state.entries.get_mut(&key)?;
+        let previous = std::mem::replace(&mut entry.last_used, clock);
+        let response = entry.response.clone();
+        state.recency.remove(&previous);
+        state.recency.insert(clock, key);
+        Some(response)
+    }
+
+    /// Caches `response` as the response to `request`, dropping the least recently used
+    /// responses if needed. Responses larger than the whole cache aren't kept.
+    pub fn insert(&self, request: &SynthesizeRequest, response: &SynthesizeResponse) {
+        let (key, canonical) = cache_key(request);
+        let size = response.audioContent.len();
+        let mut state = self.lock();
+        state.remove(key);
+        if size > state.max_bytes {
+            return;
+        }
+        while state.stats.bytes + size > state.max_bytes && state.evict_oldest() {}
+
+        state.clock += 1;
+        let clock = state.clock;
+        state.entries.insert(key, CacheEntry {
+            request: canonical,
+            response: response.clone(),
+            inserted_at: Instant::now(),
+            last_used: clock,
+        });
+        state.recency.insert(clock, key);
+        state.stats.entries += 1;
+        state.stats.bytes += size;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats
+    }
+
+    /// Resets the hit, miss and eviction counters.
+    pub fn reset_stats(&self) {
+        let mut state = self.lock();
+        state.stats = CacheStats {
+            entries: state.stats.entries,
+            bytes: state.stats.bytes,
+            ..Default::default()
+        };
+    }
+
+    /// Drops every cached response.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.recency.clear();
+        state.stats.entries = 0;
+        state.stats.bytes = 0;
+    }
+
+    /// Sets the total size of the audio kept, dropping the least recently used responses if
+    /// the cache is now over it.
+    pub fn set_max_bytes(&self, max_bytes: usize) {
+        let mut state = self.lock();
+        state.max_bytes = max_bytes;
+        while state.stats.bytes > max_bytes && state.evict_oldest() {}
+    }
+
+    /// Sets how long responses are reused for, including those already cached.
+    pub fn set_ttl(&self, ttl: Duration) {
+        self.lock().ttl = ttl;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        // The state is consistent between statements: a panic elsewhere doesn't corrupt it.
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl CacheState {
+    /// Removes the entry of `key`, if any.
+    fn remove(&mut self, key: u64) {
+        if let Some(entry) = self.entries.remove(&key) {
+            self.recency.remove(&entry.last_used);
+            self.stats.entries -= 1;
+            self.stats.bytes -= entry.response.audioContent.len();
+        }
+    }
+
+    /// Removes the least recently used entry. Returns `false` if the cache is empty.
+    fn evict_oldest(&mut self) -> bool {
+        let Some(&oldest) = self.recency.values().next() else {
+            return false;
+        };
+        self.remove(oldest);
+        self.stats.evictions += 1;
+        true
+    }
+}
+
+/// Returns the hash of the canonical JSON of `request`, and the canonical JSON itself: the
+/// request serialized with the keys of every object sorted.
+fn cache_key(request: &SynthesizeRequest) -> (u64, String) {
+    let mut canonical = String::new();
+    if let Ok(value) = serde_json::to_value(request) {
+        write_canonical(&value, &mut canonical);
+    }
+    let mut hasher = DefaultHasher::new();
+    canonical.hash(&mut hasher);
+    (hasher.finish(), canonical)
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(value, out);
+            }
+            out.push(']');
+        }
+        Value::Object(fields) => {
+            let mut fields: Vec<_> = fields.iter().collect();
+            fields.sort_by_key(|(name, _)| *name);
+            out.push('{');
+            for (i, (name, value)) in fields.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(name.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        value => out.push_str(&value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        services::synthesize::SynthesizeSpeechBuilder,
+        test_server::TestServer,
+        types::{ audio::AudioContent, synthesize::{ AudioEncoding, SynthesisInput } },
+    };
+
+    fn builder(text: &str) -> SynthesizeSpeechBuilder {
+        SynthesizeSpeechBuilder::new("token".to_string(), SynthesisInput::Text(text.to_string()))
+    }
+
+    fn request(text: &str) -> SynthesizeRequest {
+        builder(text).request()
+    }
+
+    /// Returns a response holding `size` bytes of audio.
+    fn response(size: usize) -> SynthesizeResponse {
+        SynthesizeResponse {
+            audioContent: AudioContent::new(vec![1; size], AudioEncoding::Mp3, None),
+            timepoints: Vec::new(),
+            audioConfig: None,
+        }
+    }
+
+    fn cached(cache: &AudioCache, text: &str) -> bool {
+        cache.get(&request(text)).is_some()
+    }
+
+    #[test]
+    fn answers_identical_requests_only() {
+        let cache = AudioCache::default();
+        cache.insert(&request("Hello"), &response(3));
+
+        let hit = cache.get(&request("Hello")).unwrap();
+        assert_eq!(hit.audioContent.bytes(), [1, 1, 1]);
+        assert!(!cached(&cache, "Hello!"));
+        assert!(cache.get(&builder("Hello").speaking_rate(1.5).request()).is_none());
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_responses() {
+        let cache = AudioCache::new(10, DEFAULT_AUDIO_CACHE_TTL);
+        cache.insert(&request("a"), &response(4));
+        cache.insert(&request("b"), &response(4));
+        // Using "a" makes "b" the least recently used.
+        assert!(cached(&cache, "a"));
+        cache.insert(&request("c"), &response(4));
+
+        assert!(!cached(&cache, "b"));
+        assert!(cached(&cache, "a"));
+        assert!(cached(&cache, "c"));
+        let stats = cache.stats();
+        assert_eq!((stats.evictions, stats.entries, stats.bytes), (1, 2, 8));
+
+        // Making room for a large response can evict several.
+        cache.insert(&request("d"), &response(10));
+        assert!(cached(&cache, "d"));
+        let stats = cache.stats();
+        assert_eq!((stats.evictions, stats.entries, stats.bytes), (3, 1, 10));
+    }
+
+    #[test]
+    fn does_not_keep_responses_larger_than_the_cache() {
+        let cache = AudioCache::new(10, DEFAULT_AUDIO_CACHE_TTL);
+        cache.insert(&request("a"), &response(6));
+        cache.insert(&request("b"), &response(11));
+
+        assert!(!cached(&cache, "b"));
+        assert!(cached(&cache, "a"));
+        assert_eq!(cache.stats().evictions, 0);
+
+        // Replacing a response with one too large drops the old one.
+        cache.insert(&request("a"), &response(11));
+        assert!(!cached(&cache, "a"));
+        assert_eq!((cache.stats().entries, cache.stats().bytes), (0, 0));
+    }
+
+    #[test]
+    fn replaces_the_response_to_the_same_request() {
+        let cache = AudioCache::new(10, DEFAULT_AUDIO_CACHE_TTL);
+        cache.insert(&request("a"), &response(4));
+        cache.insert(&request("a"), &response(6));
+        assert_eq!(cache.get(&request("a")).unwrap().audioContent.len(), 6);
+        assert_eq!((cache.stats().entries, cache.stats().bytes), (1, 6));
+    }
+
+    #[test]
+    fn shrinks_to_a_lower_size_limit() {
+        let cache = AudioCache::new(10, DEFAULT_AUDIO_CACHE_TTL);
+        cache.insert(&request("a"), &response(4));
+        cache.insert(&request("b"), &response(4));
+        cache.set_max_bytes(5);
+        assert!(!cached(&cache, "a"));
+        assert!(cached(&cache, "b"));
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn expires_responses_after_their_time_to_live() {
+        let cache = AudioCache::new(10, Duration::from_millis(50));
+        cache.insert(&request("a"), &response(4));
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!cached(&cache, "a"));
+        // The expired response is dropped.
+        assert_eq!((cache.stats().entries, cache.stats().bytes), (0, 0));
+
+        // A new time to live applies to the responses already cached.
+        let cache = AudioCache::new(10, Duration::from_secs(3600));
+        cache.insert(&request("a"), &response(4));
+        assert!(cached(&cache, "a"));
+        cache.set_ttl(Duration::ZERO);
+        assert!(!cached(&cache, "a"));
+    }
+
+    #[test]
+    fn counts_hits_misses_and_evictions() {
+        let cache = AudioCache::new(4, DEFAULT_AUDIO_CACHE_TTL);
+        assert_eq!(cache.stats().hit_rate(), 0.0);
+        cache.insert(&request("a"), &response(4));
+        assert!(cached(&cache, "a"));
+        assert!(cached(&cache, "a"));
+        assert!(cached(&cache, "a"));
+        assert!(!cached(&cache, "b"));
+        cache.insert(&request("b"), &response(4));
+
+        assert_eq!(cache.stats(), CacheStats {
+            hits: 3,
+            misses: 1,
+            evictions: 1,
+            entries: 1,
+            bytes: 4,
+        });
+        assert_eq!(cache.stats().hit_rate(), 0.75);
+
+        // Resetting keeps what is cached.
+        cache.reset_stats();
+        assert_eq!(cache.stats(), CacheStats { entries: 1, bytes: 4, ..Default::default() });
+
+        cache.clear();
+        assert_eq!(cache.stats(), CacheStats::default());
+        assert!(!cached(&cache, "b"));
+    }
+
+    #[test]
+    fn clones_share_their_entries() {
+        let cache = AudioCache::default();
+        cache.clone().insert(&request("a"), &response(1));
+        assert!(cached(&cache, "a"));
+    }
+
+    #[tokio::test]
+    async fn answers_requests_from_the_cache_unless_bypassed() {
+        let server = TestServer::start(|_| (200, r#"{"audioContent": "AAAA"}"#.to_string())).await;
+        let cache = AudioCache::default();
+        let send = |builder: SynthesizeSpeechBuilder| builder.endpoint(&server.endpoint).exec();
+
+        send(builder("Hello").cache(&cache)).await.unwrap();
+        let response = send(builder("Hello").cache(&cache)).await.unwrap();
+        assert_eq!(response.audioContent.bytes(), [0, 0, 0]);
+        assert_eq!(server.requests().len(), 1);
+
+        // Sent again, and not cached: the cached response is left as it was.
+        send(builder("Hello").cache(&cache).no_cache()).await.unwrap();
+        send(builder("Bye").cache(&cache).no_cache()).await.unwrap();
+        assert_eq!(server.requests().len(), 3);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    }
+}
@@ -0,0 +1,295 @@
+// src/services/chunked.rs
+
+use std::{ future::{ IntoFuture, Future }, pin::Pin, time::Duration };
+
+use tokio::task::JoinSet;
+
+use crate::{
+    audio::{ self, AudioError },
+    services::{
+        synthesize::{ SynthesisError, SynthesizeSpeechBuilder },
+        validation::MAX_INPUT_BYTES,
+    },
+    ssml::chunk::split_input,
+    types::{
+        audio::AudioContent,
+        synthesize::{ SynthesisInput, SynthesizeResponse, Timepoint },
+    },
+};
+
+/// Number of requests in flight when none is set with [ChunkedSynthesisBuilder::concurrency].
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Builder synthesizing an input of any length, returned by
+/// [SynthesizeSpeechBuilder::chunked].
+///
+/// The input is split into chunks under the per-request limit, at paragraph, sentence and
+/// clause boundaries, see [split_input]. The chunks are synthesized with the settings of the
+/// builder it was created from, a few at a time, and their audio is joined into one stream.
+/// Only encodings supported by [audio::concatenate] can be used.
+pub struct ChunkedSynthesisBuilder {
+    builder: SynthesizeSpeechBuilder,
+    max_chunk_bytes: usize,
+    concurrency: usize,
+}
+
+/// A chunk of a [ChunkedSynthesis].
+#[derive(Debug, Clone)]
+pub struct SynthesizedChunk {
+    /// The input synthesized for the chunk.
+    pub input: SynthesisInput,
+    /// Time at which the chunk starts in the joined audio.
+    pub start: Duration,
+    /// Time at which the chunk ends in the joined audio.
+    pub end: Duration,
+}
+
+/// The result of a chunked synthesis.
+#[derive(Debug, Clone)]
+pub struct ChunkedSynthesis {
+    /// The audio of every chunk, joined.
+    pub audio: AudioContent,
+    /// The chunks, in order.
+    pub chunks: Vec<SynthesizedChunk>,
+    /// The timepoints of every chunk, relative to the start of the joined audio. Only
+    /// returned if requested, e.g. with [SynthesizeSpeechBuilder::enable_ssml_marks].
+    pub timepoints: Vec<Timepoint>,
+}
+
+impl ChunkedSynthesisBuilder {
+    pub fn new(builder: SynthesizeSpeechBuilder) -> Self {
+        ChunkedSynthesisBuilder {
+            builder,
+            max_chunk_bytes: MAX_INPUT_BYTES,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Sets the maximum size of a chunk, in bytes; [MAX_INPUT_BYTES] by default.
+    pub fn max_chunk_bytes(mut self, bytes: usize) -> Self {
+        self.max_chunk_bytes = bytes.clamp(1, MAX_INPUT_BYTES);
+        self
+    }
+
+    /// Sets how many chunks are synthesized at the same time; [DEFAULT_CONCURRENCY] by
+    /// default.
+    pub fn concurrency(mut self, requests: usize) -> Self {
+        self.concurrency = requests.max(1);
+        self
+    }
+
+    /// Returns the inputs the chunks will be synthesized from.
+    pub fn chunks(&self) -> Vec<SynthesisInput> {
+        split_input(&self.builder.request().input.source, self.max_chunk_bytes)
+    }
+
+    pub async fn exec(self) -> Result<ChunkedSynthesis, SynthesisError> {
+        self.into_future().await
+    }
+}
+
+impl IntoFuture for ChunkedSynthesisBuilder {
+    type Output = Result<ChunkedSynthesis, SynthesisError>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move {
+            let encoding = self.builder.request().audioConfig.audioEncoding;
+            if !audio::is_supported(encoding) {
+                return Err(AudioError::Unsupported(encoding).into());
+            }
+            let inputs = self.chunks();
+            let builder = self.builder.clone().rewritten();
+            let requests = inputs
+                .iter()
+                .map(|input| builder.clone().input(input.clone()))
+                .collect();
+            let responses = synthesize_all(requests, self.concurrency).await?;
+
+            let mut chunks = Vec::with_capacity(inputs.len());
+            let mut timepoints = Vec::new();
+            let mut parts = Vec::with_capacity(inputs.len());
+            let mut start = Duration::ZERO;
+            for (input, response) in inputs.into_iter().zip(responses) {
+                let end = start + audio::duration(&response.audioContent)?;
+                timepoints.extend(
+                    response.timepoints.into_iter().map(|timepoint| Timepoint {
+                        markName: timepoint.markName,
+                        timeSeconds: timepoint.timeSeconds + start.as_secs_f64(),
+                    })
+                );
+                chunks.push(SynthesizedChunk { input, start, end });
+                parts.push(response.audioContent);
+                start = end;
+            }
+
+            let audio = audio::concatenate(&parts)?;
+            Ok(ChunkedSynthesis { audio, chunks, timepoints })
+        })
+    }
+}
+
+/// Sends every request, with at most `concurrency` in flight, and returns the responses in
+/// the order of the requests.
+pub(crate) async fn synthesize_all(
+    requests: Vec<SynthesizeSpeechBuilder>,
+    concurrency: usize
+) -> Result<Vec<SynthesizeResponse>, SynthesisError> {
+    let mut responses: Vec<Option<SynthesizeResponse>> = vec![None; requests.len()];
+    let mut pending = requests.into_iter().enumerate();
+    let mut tasks = JoinSet::new();
+
+    loop {
+        while tasks.len() < concurrency {
+            let Some((index, request)) = pending.next() else {
+                break;
+            };
+            tasks.spawn(async move { (index, request.exec().await) });
+        }
+        // Dropping the set on error aborts the requests still in flight.
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        let (index, response) = joined.map_err(|error| {
+            SynthesisError::ApiError(format!("synthesis task failed: {}", error))
+        })?;
+        responses[index] = Some(response?);
+    }
+    Ok(responses.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ sync::{ atomic::{ AtomicUsize, Ordering }, Arc }, thread };
+
+    use base64::{ engine::general_purpose::STANDARD, Engine };
+
+    use super::*;
+    use crate::{ audio::wav, test_server::TestServer, types::synthesize::AudioEncoding };
+
+    /// Returns `duration` of silence as a WAVE file at 24 kHz, base64-encoded.
+    fn silence(duration: Duration) -> String {
+        let format = wav::WavFormat {
+            format_tag: wav::FORMAT_PCM,
+            channels: 1,
+            sample_rate: 24_000,
+            bits_per_sample: 16,
+        };
+        let samples = (duration.as_secs_f64() * 24_000.0) as usize;
+        STANDARD.encode(wav::write(&format, &vec![0; samples * 2]))
+    }
+
+    fn builder(endpoint: &str, input: SynthesisInput) -> SynthesizeSpeechBuilder {
+        SynthesizeSpeechBuilder::new("token".to_string(), input)
+            .endpoint(endpoint)
+            .audio_encoding(AudioEncoding::Linear16)
+            .sample_rate_hertz(24_000)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn synthesizes_a_few_requests_at_a_time_in_order() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let most_in_flight = Arc::new(AtomicUsize::new(0));
+        let (current, most) = (in_flight.clone(), most_in_flight.clone());
+        let server = TestServer::start(move |request| {
+            let count = current.fetch_add(1, Ordering::SeqCst) + 1;
+            most.fetch_max(count, Ordering::SeqCst);
+            // Later requests are answered first.
+            let index: u64 = request.json()["input"]["text"].as_str().unwrap().parse().unwrap();
+            thread::sleep(Duration::from_millis(100 - index * 10));
+            current.fetch_sub(1, Ordering::SeqCst);
+            let audio = STANDARD.encode([index as u8]);
+            (200, serde_json::json!({ "audioContent": audio }).to_string())
+        }).await;
+
+        let requests = (0..6)
+            .map(|index| builder(&server.endpoint, SynthesisInput::Text(index.to_string())))
+            .collect();
+        let responses = synthesize_all(requests, 2).await.unwrap();
+
+        let audio: Vec<&[u8]> = responses
+            .iter()
+            .map(|response| response.audioContent.bytes())
+            .collect();
+        assert_eq!(audio, [[0], [1], [2], [3], [4], [5]]);
+        assert_eq!(server.requests().len(), 6);
+        assert_eq!(most_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_first_error() {
+        let server = TestServer::start(|request| {
+            match request.json()["input"]["text"].as_str() {
+                Some("fail") => (500, r#"{"error": {"code": 500, "status": "INTERNAL"}}"#.into()),
+                _ => (200, r#"{"audioContent": "AAAA"}"#.to_string()),
+            }
+        }).await;
+        let requests = ["ok", "fail", "ok"]
+            .map(|text| builder(&server.endpoint, SynthesisInput::Text(text.to_string())))
+            .to_vec();
+        let error = synthesize_all(requests, 1).await.unwrap_err();
+        assert!(matches!(error, SynthesisError::HttpStatus { .. }), "{:?}", error);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn offsets_chunks_and_timepoints_by_the_audio_before_them() {
+        // Each chunk lasts 100 ms per word, and its mark is 50 ms in.
+        let server = TestServer::start(|request| {
+            let ssml = request.json()["input"]["ssml"].as_str().unwrap().to_string();
+            let mark = ssml.split('"').nth(1).unwrap().to_string();
+            let text = ssml.split("/>").nth(1).unwrap().split('<').next().unwrap();
+            let words = text.split_whitespace().count() as u32;
+            let body = serde_json::json!({
+                "audioContent": silence(Duration::from_millis(100) * words),
+                "timepoints": [{ "markName": mark, "timeSeconds": 0.05 }],
+            });
+            (200, body.to_string())
+        }).await;
+
+        let ssml = concat!(
+            r#"<speak><p><mark name="a"/>one two three</p><p><mark name="b"/>four</p>"#,
+            r#"<p><mark name="c"/>five six</p></speak>"#
+        );
+        let synthesis = builder(&server.endpoint, SynthesisInput::Ssml(ssml.to_string()))
+            .enable_ssml_marks()
+            .chunked()
+            .max_chunk_bytes(60)
+            .exec().await
+            .unwrap();
+
+        assert_eq!(server.requests().len(), 3);
+        let times: Vec<(u128, u128)> = synthesis.chunks
+            .iter()
+            .map(|chunk| (chunk.start.as_millis(), chunk.end.as_millis()))
+            .collect();
+        assert_eq!(times, [(0, 300), (300, 400), (400, 600)]);
+        let timepoints: Vec<(&str, i64)> = synthesis.timepoints
+            .iter()
+            .map(|timepoint| {
+                (timepoint.markName.as_str(), (timepoint.timeSeconds * 1000.0).round() as i64)
+            })
+            .collect();
+        assert_eq!(timepoints, [("a", 50), ("b", 350), ("c", 450)]);
+        assert_eq!(audio::duration(&synthesis.audio).unwrap(), Duration::from_millis(600));
+    }
+
+    #[tokio::test]
+    async fn rejects_encodings_that_cannot_be_joined() {
+        let server = TestServer::start(|_| (200, r#"{"audioContent": "AAAA"}"#.to_string())).await;
+        let builder = builder(&server.endpoint, SynthesisInput::Text("Hi".to_string()))
+            .audio_encoding(AudioEncoding::AudioEncodingUnspecified);
+        let error = builder.chunked().exec().await.unwrap_err();
+        assert!(
+            matches!(
+                error,
+                SynthesisError::AudioError(
+                    AudioError::Unsupported(AudioEncoding::AudioEncodingUnspecified)
+                )
+            ),
+            "{:?}",
+            error
+        );
+        assert!(server.requests().is_empty());
+    }
+}
@@ -5,12 +5,12 @@ use async_trait::async_trait;
 use super::media_source::GoogleTtsMediaSource;
 
 pub struct GoogleTtsComposer {
-    text: String, // Add the access token
+    audio_data: Vec<u8>,
 }
 
 impl GoogleTtsComposer {
-    pub fn new(text: String) -> Self {
-        GoogleTtsComposer { text }
+    pub fn new(audio_data: Vec<u8>) -> Self {
+        GoogleTtsComposer { audio_data }
     }
 }
 
@@ -24,9 +24,7 @@ impl Compose for GoogleTtsComposer {
     async fn create_async(
         &mut self
     ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
-        let source = GoogleTtsMediaSource::new(self.text.clone()).expect(
-            "Failed to create media source"
-        );
+        let source = GoogleTtsMediaSource::new(self.audio_data.clone());
         Ok(AudioStream {
             input: Box::new(source),
             hint: None,
@@ -0,0 +1,41 @@
+// src/services/endpoint.rs
+
+/// Base URL of the Google Text-to-Speech REST API.
+pub const DEFAULT_ENDPOINT: &str = "https://texttospeech.googleapis.com";
+
+/// Version of the Text-to-Speech REST API a request is sent to.
+///
+/// Some features (timepoints, `MP3_64_KBPS`, ...) are only available through `v1beta1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ApiVersion {
+    #[default]
+    V1,
+    V1Beta1,
+}
+
+impl ApiVersion {
+    /// Returns the path segment of the version, e.g. `v1beta1`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+            ApiVersion::V1Beta1 => "v1beta1",
+        }
+    }
+
+    /// Builds the URL of `method` (e.g. `text:synthesize`) for this version under `endpoint`.
+    pub fn url(&self, endpoint: &str, method: &str) -> String {
+        format!("{}/{}/{}", endpoint.trim_end_matches('/'), self.as_str(), method)
+    }
+}
+
+/// Protocol a [TextToSpeechClient](crate::client::TextToSpeechClient) reaches the API with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Transport {
+    /// JSON over HTTP/1.1. The audio is returned base64-encoded.
+    #[default]
+    Rest,
+    /// Protobuf over a persistent HTTP/2 channel. The audio is returned as raw bytes, which
+    /// saves decoding large bodies. Requires the `grpc` feature.
+    #[cfg(feature = "grpc")]
+    Grpc,
+}
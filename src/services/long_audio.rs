@@ -0,0 +1,377 @@
+// src/services/long_audio.rs
+
+use std::{ future::{ IntoFuture, Future }, pin::Pin };
+
+use reqwest::Client;
+
+use crate::{
+    normalize::Normalizer,
+    ssml::lexicon::Lexicon,
+    types::{
+        language::LanguageTag,
+        long_audio::{
+            SynthesizeLongAudioMetadata,
+            SynthesizeLongAudioRequest,
+            SynthesizeLongAudioResponse,
+        },
+        operation::LongRunningOperation,
+        pronunciation::{ CustomPronunciationParams, PhoneticEncoding, PronunciationSet },
+        synthesize::{
+            AudioConfig,
+            AudioEncoding,
+            RequestInput,
+            SynthesisInput,
+            VoiceSelectionParams,
+        },
+        voice_name::VoiceName,
+    },
+    services::{
+        endpoint::{ ApiVersion, DEFAULT_ENDPOINT },
+        operations::{ Operation, OperationsClient, TokenProvider },
+        synthesize::SynthesisError,
+        validation::{ validate_long_audio_request, ValidationError, Violation },
+    },
+};
+
+/// Operation returned by `synthesizeLongAudio`.
+pub type LongAudioOperation = Operation<SynthesizeLongAudioMetadata, SynthesizeLongAudioResponse>;
+
+/// Location used when none is set with [SynthesizeLongAudioBuilder::location].
+pub const DEFAULT_LOCATION: &str = "global";
+
+/// Builder for a `synthesizeLongAudio` request.
+///
+/// Long audio synthesis is asynchronous: the audio is written to a Cloud Storage object and
+/// the request returns a [LongAudioOperation] that can be polled until the file is ready.
+pub struct SynthesizeLongAudioBuilder {
+    tokens: TokenProvider,
+    endpoint: String,
+    project_id: Option<String>,
+    location: String,
+    input: RequestInput,
+    voice: VoiceSelectionParams,
+    audio_config: AudioConfig,
+    output_gcs_uri: String,
+    normalizer: Option<Normalizer>,
+    lexicon: Option<Lexicon>,
+}
+
+impl SynthesizeLongAudioBuilder {
+    pub fn new(access_token: String, input: SynthesisInput, output_gcs_uri: &str) -> Self {
+        SynthesizeLongAudioBuilder {
+            tokens: TokenProvider::fixed(access_token),
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            project_id: None,
+            location: DEFAULT_LOCATION.to_string(),
+            input: input.into(),
+            voice: VoiceSelectionParams::default(),
+            audio_config: AudioConfig {
+                audioEncoding: AudioEncoding::Linear16,
+                ..Default::default()
+            },
+            output_gcs_uri: output_gcs_uri.to_string(),
+            normalizer: None,
+            lexicon: None,
+        }
+    }
+
+    /// Sends the request, and polls the operation, at `endpoint` instead of [DEFAULT_ENDPOINT].
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
+    /// Authenticates the request, and the polls and cancellation of its operation, with the
+    /// tokens of `tokens` instead of the access token the builder was created with.
+    pub fn token_provider(mut self, tokens: TokenProvider) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    /// Sets the project the request is billed to. Required.
+    pub fn project_id(mut self, project_id: &str) -> Self {
+        self.project_id = Some(project_id.to_string());
+        self
+    }
+
+    pub fn location(mut self, location: &str) -> Self {
+        self.location = location.to_string();
+        self
+    }
+
+    /// Sets how `phrase` is pronounced, replacing a pronunciation set earlier for it.
+    pub fn custom_pronunciation(
+        mut self,
+        phrase: &str,
+        encoding: PhoneticEncoding,
+        pronunciation: &str
+    ) -> Self {
+        self.input.customPronunciations
+            .get_or_insert_with(Default::default)
+            .insert(CustomPronunciationParams::new(phrase, encoding, pronunciation));
+        self
+    }
+
+    /// Adds every pronunciation of `set`, replacing the ones set earlier for the same phrases.
+    pub fn pronunciations(mut self, set: &PronunciationSet) -> Self {
+        if set.is_empty() {
+            return self;
+        }
+        let pronunciations = self.input.customPronunciations.get_or_insert_with(Default::default);
+        for params in set {
+            pronunciations.insert(params.clone());
+        }
+        self
+    }
+
+    /// Rewrites a text input with `normalizer` when the request is built, see
+    /// [Normalizer::normalize_input]; other inputs are left as they are.
+    pub fn normalize(mut self, normalizer: &Normalizer) -> Self {
+        self.normalizer = Some(normalizer.clone());
+        self
+    }
+
+    /// Applies `lexicon` to a text or SSML input when the request is built, after the
+    /// normalizer, see [Lexicon::apply_input].
+    pub fn lexicon(mut self, lexicon: &Lexicon) -> Self {
+        self.lexicon = Some(lexicon.clone());
+        self
+    }
+
+    pub fn voice_params(mut self, voice: VoiceSelectionParams) -> Self {
+        self.voice = voice;
+        self
+    }
+
+    pub fn audio_config(mut self, config: AudioConfig) -> Self {
+        self.audio_config = config;
+        self
+    }
+
+    pub fn language_code(mut self, language_code: LanguageTag) -> Self {
+        self.voice.languageCode = language_code;
+        self
+    }
+
+    /// Selects the voice `name`, and sets the language code to the language of the voice.
+    pub fn voice_name(mut self, name: VoiceName) -> Self {
+        self.voice = VoiceSelectionParams::from_name(name);
+        self
+    }
+
+    pub fn audio_encoding(mut self, encoding: AudioEncoding) -> Self {
+        self.audio_config.audioEncoding = encoding;
+        self
+    }
+
+    /// Builds the request that will be sent to the API.
+    pub fn request(&self) -> SynthesizeLongAudioRequest {
+        let mut input = self.input.clone();
+        if let Some(normalizer) = &self.normalizer {
+            input.source = normalizer.normalize_input(input.source);
+        }
+        if let Some(lexicon) = &self.lexicon {
+            input.source = lexicon.apply_input(input.source);
+        }
+        SynthesizeLongAudioRequest {
+            input,
+            audioConfig: self.audio_config.clone(),
+            outputGcsUri: self.output_gcs_uri.clone(),
+            voice: self.voice.clone(),
+        }
+    }
+
+    /// Validates the request against the documented API limits without sending it.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_long_audio_request(&self.request())
+    }
+
+    pub async fn exec(self) -> Result<LongAudioOperation, SynthesisError> {
+        self.into_future().await
+    }
+}
+
+impl IntoFuture for SynthesizeLongAudioBuilder {
+    type Output = Result<LongAudioOperation, SynthesisError>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move {
+            let request = self.request();
+            validate_long_audio_request(&request)?;
+
+            let project_id = self.project_id.ok_or_else(|| ValidationError {
+                violations: vec![
+                    Violation::new("parent", "a project id is required for long audio synthesis")
+                ],
+            })?;
+            let parent = format!("projects/{}/locations/{}", project_id, self.location);
+            let method = format!("{}:synthesizeLongAudio", parent);
+            let url = ApiVersion::V1.url(&self.endpoint, &method);
+
+            let response = Client::new()
+                .post(&url)
+                .bearer_auth(self.tokens.token().await?)
+                .json(&request)
+                .send().await?;
+
+            if response.status() != reqwest::StatusCode::OK {
+                return Err(SynthesisError::from_response(response).await);
+            }
+
+            let state: LongRunningOperation<_, _> = response.json().await?;
+            let client = OperationsClient::new(self.tokens, &self.endpoint, ApiVersion::V1);
+            Ok(Operation::new(client, state))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ sync::{ atomic::{ AtomicBool, AtomicUsize, Ordering }, Arc }, time::Duration };
+
+    use super::*;
+    use crate::{ services::operations::Backoff, test_server::TestServer };
+
+    const OPERATION: &str = "projects/book/locations/global/operations/42";
+
+    /// A stand-in for the API whose operation is done on its third poll, or after being
+    /// cancelled.
+    async fn server() -> TestServer {
+        let polls = AtomicUsize::new(0);
+        let cancelled = AtomicBool::new(false);
+        TestServer::start(move |request| {
+            let operation = match (request.method.as_str(), request.path.as_str()) {
+                ("POST", "/v1/projects/book/locations/global:synthesizeLongAudio") => {
+                    serde_json::json!({ "name": OPERATION, "metadata": {} })
+                }
+                ("POST", path) if path == format!("/v1/{}:cancel", OPERATION) => {
+                    cancelled.store(true, Ordering::SeqCst);
+                    serde_json::json!({})
+                }
+                ("GET", path) if path == format!("/v1/{}", OPERATION) => {
+                    let poll = polls.fetch_add(1, Ordering::SeqCst) + 1;
+                    if cancelled.load(Ordering::SeqCst) {
+                        serde_json::json!({
+                            "name": OPERATION,
+                            "done": true,
+                            "error": { "code": 1, "message": "Operation cancelled" },
+                        })
+                    } else if poll < 3 {
+                        serde_json::json!({
+                            "name": OPERATION,
+                            "metadata": { "progressPercentage": 50.0 * (poll as f64) },
+                        })
+                    } else {
+                        serde_json::json!({
+                            "name": OPERATION,
+                            "metadata": { "progressPercentage": 100.0 },
+                            "done": true,
+                            "response": {},
+                        })
+                    }
+                }
+                _ => {
+                    return (404, r#"{"error": {"code": 404, "status": "NOT_FOUND"}}"#.to_string());
+                }
+            };
+            (200, operation.to_string())
+        }).await
+    }
+
+    /// Returns a provider handing out `token-1`, `token-2`, ... one per request.
+    fn counting_tokens() -> TokenProvider {
+        let count = Arc::new(AtomicUsize::new(0));
+        TokenProvider::new(move || {
+            let count = count.fetch_add(1, Ordering::SeqCst) + 1;
+            async move { Ok(format!("token-{}", count)) }
+        })
+    }
+
+    fn builder(server: &TestServer) -> SynthesizeLongAudioBuilder {
+        SynthesizeLongAudioBuilder::new(
+            "unused".to_string(),
+            SynthesisInput::Text("Chapter one.".to_string()),
+            "gs://bucket/book.wav"
+        )
+            .endpoint(&server.endpoint)
+            .project_id("book")
+            .token_provider(counting_tokens())
+    }
+
+    fn authorizations(server: &TestServer) -> Vec<String> {
+        server
+            .requests()
+            .iter()
+            .map(|request| request.header("authorization").unwrap_or_default().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn submits_and_polls_until_done() {
+        let server = server().await;
+
+        let mut operation = builder(&server).exec().await.unwrap();
+        assert_eq!(operation.name(), OPERATION);
+        assert!(!operation.is_done());
+
+        assert!(!operation.poll().await.unwrap());
+        assert_eq!(operation.metadata().unwrap().progressPercentage, 50.0);
+
+        let backoff = Backoff::default().initial_delay(Duration::from_millis(1));
+        operation.wait(backoff).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0].json()["outputGcsUri"], "gs://bucket/book.wav");
+        assert_eq!(authorizations(&server), [
+            "Bearer token-1",
+            "Bearer token-2",
+            "Bearer token-3",
+            "Bearer token-4",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn cancels_the_operation() {
+        let server = server().await;
+
+        let operation = builder(&server).exec().await.unwrap();
+        operation.cancel().await.unwrap();
+
+        let backoff = Backoff::default().initial_delay(Duration::from_millis(1));
+        match operation.wait(backoff).await.unwrap_err() {
+            SynthesisError::OperationFailed(status) => {
+                assert_eq!(status.code, 1);
+                assert_eq!(status.message, "Operation cancelled");
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+
+        let paths: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|request| request.path)
+            .collect();
+        assert_eq!(paths, [
+            "/v1/projects/book/locations/global:synthesizeLongAudio".to_string(),
+            format!("/v1/{}:cancel", OPERATION),
+            format!("/v1/{}", OPERATION),
+        ]);
+        assert_eq!(authorizations(&server), ["Bearer token-1", "Bearer token-2", "Bearer token-3"]);
+    }
+
+    #[tokio::test]
+    async fn times_out_waiting() {
+        let server = TestServer::start(|_| {
+            (200, serde_json::json!({ "name": OPERATION }).to_string())
+        }).await;
+
+        let operation = builder(&server).exec().await.unwrap();
+        let backoff = Backoff::default()
+            .initial_delay(Duration::from_millis(5))
+            .timeout(Duration::from_millis(20));
+        let error = operation.wait(backoff).await.unwrap_err();
+        assert!(matches!(error, SynthesisError::OperationTimeout(name) if name == OPERATION));
+    }
+}
@@ -15,7 +15,14 @@ pub struct GoogleTtsMediaSource {
 }
 
 impl GoogleTtsMediaSource {
-    pub fn new(base64_audio_content: String) -> io::Result<Self> {
+    pub fn new(audio_data: Vec<u8>) -> Self {
+        GoogleTtsMediaSource {
+            audio_data,
+            position: 0,
+        }
+    }
+
+    pub fn from_base64(base64_audio_content: String) -> io::Result<Self> {
         // Convert the base64 string to bytes
         let base64_bytes = base64_audio_content.into_bytes();
         let mut cursor = Cursor::new(base64_bytes);
@@ -31,10 +38,7 @@ impl GoogleTtsMediaSource {
             .read_to_end(&mut audio_data)
             .map_err(|_| io::Error::new(ErrorKind::InvalidData, "Base64 decode error"))?;
 
-        Ok(GoogleTtsMediaSource {
-            audio_data,
-            position: 0,
-        })
+        Ok(GoogleTtsMediaSource::new(audio_data))
     }
 }
 
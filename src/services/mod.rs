@@ -5,7 +5,16 @@ pub mod composer;
 #[cfg(feature = "enable_songbird")]
 pub mod songbird;
 
+pub mod cache;
+pub mod chunked;
+pub mod endpoint;
+pub mod long_audio;
+pub mod multilingual;
+pub mod operations;
 pub mod synthesize;
+pub mod validation;
+pub mod voice_resolver;
+pub mod voices;
 
 // pub async fn synthesize_speech(
 //     client: &Client, // Use the configured client
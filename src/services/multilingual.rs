@@ -0,0 +1,587 @@
+// src/services/multilingual.rs
+
+use std::{ future::{ IntoFuture, Future }, ops::Range, pin::Pin, time::Duration };
+
+use crate::{
+    audio::{ self, AudioError },
+    services::{
+        chunked::{ synthesize_all, DEFAULT_CONCURRENCY },
+        synthesize::{ SynthesisError, SynthesizeSpeechBuilder },
+        validation::MAX_INPUT_BYTES,
+        voice_resolver::{ VoicePreferences, VoiceResolver },
+        voices::VoiceCatalog,
+    },
+    ssml::{
+        chunk::split_text,
+        node::{ Node, Speak, Voice },
+        segment::{ LanguageHint, LanguageSegment, Segmenter },
+    },
+    types::{
+        audio::AudioContent,
+        language::LanguageTag,
+        synthesize::{ SynthesisInput, VoiceSelectionParams },
+    },
+};
+
+/// Prefix of the marks placed before every segment of an SSML request.
+const SEGMENT_MARK: &str = "segment-";
+
+/// The voices reading each language of a [MultilingualSynthesisBuilder].
+#[derive(Debug, Clone, Default)]
+pub struct VoiceMap {
+    voices: Vec<(LanguageTag, Vec<VoiceSelectionParams>)>,
+}
+
+impl VoiceMap {
+    pub fn new() -> Self {
+        VoiceMap::default()
+    }
+
+    /// Reads text in `language`, or any of its more specific tags, with `voice`.
+    pub fn voice(self, language: LanguageTag, voice: VoiceSelectionParams) -> Self {
+        self.voice_chain(language, vec![voice])
+    }
+
+    /// Reads text in `language` with the first voice of `chain`, trying the next one whenever
+    /// the API rejects a voice as not found. An empty chain removes the language.
+    pub fn voice_chain(mut self, language: LanguageTag, chain: Vec<VoiceSelectionParams>) -> Self {
+        self.voices.retain(|(mapped, _)| *mapped != language);
+        if !chain.is_empty() {
+            self.voices.push((language, chain));
+        }
+        self
+    }
+
+    /// Creates a map with the voices `resolver` finds in `catalog` for every preferences.
+    /// Languages without any voice are left out.
+    pub fn resolve(
+        resolver: &VoiceResolver,
+        catalog: &VoiceCatalog,
+        preferences: &[VoicePreferences]
+    ) -> Self {
+        preferences.iter().fold(VoiceMap::new(), |map, preferences| {
+            let chain = resolver.resolve(catalog, preferences);
+            map.voice_chain(preferences.language_code.clone(), chain)
+        })
+    }
+
+    /// Returns the voices reading `language`: those of the most specific language it matches,
+    /// or else those of a language with the same primary subtag, e.g. `en-GB` for `en-US`.
+    pub fn get(&self, language: &LanguageTag) -> Option<&[VoiceSelectionParams]> {
+        self.voices
+            .iter()
+            .filter(|(mapped, _)| language.matches(mapped))
+            .max_by_key(|(mapped, _)| mapped.as_str().len())
+            .or_else(|| {
+                self.voices.iter().find(|(mapped, _)| mapped.language() == language.language())
+            })
+            .map(|(_, chain)| chain.as_slice())
+    }
+}
+
+/// How the segments of a [MultilingualSynthesisBuilder] are synthesized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MixedLanguageMode {
+    /// [Ssml](MixedLanguageMode::Ssml) when every voice switched to is a named voice accepting
+    /// SSML, [Separate](MixedLanguageMode::Separate) otherwise.
+    #[default]
+    Auto,
+    /// A single SSML document, switching voices with `<voice>`, or with `<lang>` for
+    /// languages without a named voice. The times of the segments are read from SSML marks,
+    /// which switches the request to the `v1beta1` API.
+    Ssml,
+    /// A request per segment, whose audio is joined. Used for markup input whatever the mode.
+    Separate,
+}
+
+/// A segment of a [MultilingualSynthesis].
+#[derive(Debug, Clone)]
+pub struct SynthesizedSegment {
+    pub segment: LanguageSegment,
+    /// The voice the segment was read with, the first of its chain.
+    pub voice: VoiceSelectionParams,
+    /// Time at which the segment starts in the audio.
+    pub start: Duration,
+    /// Time at which the segment ends in the audio.
+    pub end: Duration,
+}
+
+/// The result of a multilingual synthesis.
+#[derive(Debug, Clone)]
+pub struct MultilingualSynthesis {
+    pub audio: AudioContent,
+    /// The segments, in order. Empty for inputs that aren't segmented.
+    pub segments: Vec<SynthesizedSegment>,
+    /// The mode the segments were synthesized in, never [MixedLanguageMode::Auto].
+    pub mode: MixedLanguageMode,
+}
+
+/// Builder synthesizing text mixing languages, each read by its own voice, returned by
+/// [SynthesizeSpeechBuilder::multilingual].
+///
+/// The text is split into segments of a single language by a [Segmenter], and every segment
+/// is read with the voice the [VoiceMap] has for its language. Languages missing from the
+/// map are read by the voice of the builder if it speaks them, or by a voice chosen by the
+/// API otherwise. Text and markup inputs are segmented; other inputs are synthesized as
+/// they are, in chunks. Only encodings supported by [audio::concatenate] can be used.
+pub struct MultilingualSynthesisBuilder {
+    builder: SynthesizeSpeechBuilder,
+    voices: VoiceMap,
+    segmenter: Segmenter,
+    hints: Vec<LanguageHint>,
+    mode: MixedLanguageMode,
+    concurrency: usize,
+}
+
+impl MultilingualSynthesisBuilder {
+    /// Creates a builder segmenting the input of `builder` with the default [Segmenter],
+    /// reading text without letters in the language of its voice.
+    pub fn new(builder: SynthesizeSpeechBuilder, voices: VoiceMap) -> Self {
+        let language = builder.voice().languageCode.clone();
+        MultilingualSynthesisBuilder {
+            builder,
+            voices,
+            segmenter: Segmenter::new().default_language(language),
+            hints: Vec::new(),
+            mode: MixedLanguageMode::default(),
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    pub fn segmenter(mut self, segmenter: Segmenter) -> Self {
+        self.segmenter = segmenter;
+        self
+    }
+
+    /// Reads the text in `range` of the input, in bytes, in `language`.
+    pub fn hint(mut self, range: Range<usize>, language: LanguageTag) -> Self {
+        self.hints.push(LanguageHint::new(range, language));
+        self
+    }
+
+    pub fn mode(mut self, mode: MixedLanguageMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets how many requests are sent at the same time; [DEFAULT_CONCURRENCY] by default.
+    pub fn concurrency(mut self, requests: usize) -> Self {
+        self.concurrency = requests.max(1);
+        self
+    }
+
+    /// Returns the segments the input will be synthesized as. The input is segmented as it
+    /// was set: the normalizer and the lexicon of the builder are applied to every segment.
+    pub fn segments(&self) -> Vec<LanguageSegment> {
+        match self.builder.source() {
+            SynthesisInput::Text(text) | SynthesisInput::Markup(text) => {
+                self.segmenter
+                    .segment_with_hints(text, &self.hints)
+                    .into_iter()
+                    .filter(|segment| !segment.text.trim().is_empty())
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the voices reading `language`, or `None` for the voice of the builder.
+    fn voices_for(&self, language: &LanguageTag) -> Option<Vec<VoiceSelectionParams>> {
+        if let Some(chain) = self.voices.get(language) {
+            return Some(chain.to_vec());
+        }
+        let voice = self.builder.voice();
+        if voice.languageCode.language() == language.language() {
+            return None;
+        }
+        Some(
+            vec![VoiceSelectionParams {
+                languageCode: language.clone(),
+                ssmlGender: voice.ssmlGender,
+                ..Default::default()
+            }]
+        )
+    }
+
+    /// Returns the mode the segments will be synthesized in.
+    pub fn resolved_mode(&self) -> MixedLanguageMode {
+        if !matches!(self.builder.source(), SynthesisInput::Text(_)) {
+            return MixedLanguageMode::Separate;
+        }
+        match self.mode {
+            MixedLanguageMode::Auto => {
+                let accepts_ssml = |voice: &VoiceSelectionParams| {
+                    voice.customVoice.is_none() &&
+                        voice.voiceClone.is_none() &&
+                        voice.name.as_ref().is_some_and(|name| name.family().supports_ssml())
+                };
+                let segments = self.segments();
+                let voices_accept_ssml =
+                    accepts_ssml(self.builder.voice()) &&
+                    segments
+                        .iter()
+                        .filter_map(|segment| self.voices_for(&segment.language))
+                        .all(|chain| accepts_ssml(&chain[0]));
+                if segments.len() > 1 && voices_accept_ssml {
+                    MixedLanguageMode::Ssml
+                } else {
+                    MixedLanguageMode::Separate
+                }
+            }
+            mode => mode,
+        }
+    }
+
+    pub async fn exec(self) -> Result<MultilingualSynthesis, SynthesisError> {
+        self.into_future().await
+    }
+
+    /// Synthesizes the segments as a single SSML document.
+    async fn synthesize_ssml(
+        self,
+        segments: Vec<(LanguageSegment, VoiceSelectionParams, bool)>
+    ) -> Result<MultilingualSynthesis, SynthesisError> {
+        let mut children = Vec::with_capacity(segments.len() * 2);
+        for (index, (segment, voice, switched)) in segments.iter().enumerate() {
+            children.push(Node::Mark(format!("{}{}", SEGMENT_MARK, index)));
+            // The lexicon is applied to the whole document when it is synthesized.
+            let text = match self.builder.normalizer() {
+                Some(normalizer) => normalizer.normalize(&segment.text),
+                None => vec![Node::Text(segment.text.clone())],
+            };
+            match (&voice.name, switched) {
+                (_, false) => children.extend(text),
+                (Some(name), true) => {
+                    children.push(Node::Voice(Voice::named(name.clone()), text));
+                }
+                (None, true) => children.push(Node::Lang(segment.language.clone(), text)),
+            }
+        }
+        let ssml = Speak::new(children).to_string();
+
+        let synthesis = self.builder
+            .input(SynthesisInput::Ssml(ssml))
+            .enable_ssml_marks()
+            .chunked()
+            .concurrency(self.concurrency)
+            .exec().await?;
+        let duration = synthesis.chunks.last().map_or(Duration::ZERO, |chunk| chunk.end);
+
+        let mut starts = vec![None; segments.len()];
+        for timepoint in &synthesis.timepoints {
+            let index = timepoint.markName
+                .strip_prefix(SEGMENT_MARK)
+                .and_then(|index| index.parse::<usize>().ok());
+            if let Some(start) = index.and_then(|index| starts.get_mut(index)) {
+                *start = Some(Duration::from_secs_f64(timepoint.timeSeconds.max(0.0)));
+            }
+        }
+        // A segment whose mark is missing starts where the one before it does.
+        let mut previous = Duration::ZERO;
+        let starts: Vec<Duration> = starts
+            .into_iter()
+            .map(|start| {
+                previous = start.unwrap_or(previous);
+                previous
+            })
+            .collect();
+
+        let segments = segments
+            .into_iter()
+            .enumerate()
+            .map(|(index, (segment, voice, _))| SynthesizedSegment {
+                segment,
+                voice,
+                start: starts[index],
+                end: starts.get(index + 1).copied().unwrap_or(duration),
+            })
+            .collect();
+        Ok(MultilingualSynthesis {
+            audio: synthesis.audio,
+            segments,
+            mode: MixedLanguageMode::Ssml,
+        })
+    }
+
+    /// Synthesizes every segment with its own requests, and joins their audio.
+    async fn synthesize_separately(
+        self,
+        segments: Vec<(LanguageSegment, VoiceSelectionParams, bool)>
+    ) -> Result<MultilingualSynthesis, SynthesisError> {
+        let request = self.builder.request();
+        let markup = matches!(self.builder.source(), SynthesisInput::Markup(_));
+        let mut builder = self.builder.clone();
+        // Voices have different natural sample rates: ask for the same one to join them.
+        if request.audioConfig.sampleRateHertz.is_none() {
+            if let Some(rate) = request.audioConfig.audioEncoding.default_sample_rate() {
+                builder = builder.sample_rate_hertz(rate);
+            }
+        }
+
+        let mut requests = Vec::new();
+        let mut owners = Vec::new();
+        for (index, (segment, _, _)) in segments.iter().enumerate() {
+            let segment_builder = match self.voices_for(&segment.language) {
+                Some(chain) => builder.clone().voice_chain(chain),
+                None => builder.clone(),
+            };
+            for chunk in split_text(segment.text.trim(), MAX_INPUT_BYTES) {
+                let input = match markup {
+                    true => SynthesisInput::Markup(chunk),
+                    false => SynthesisInput::Text(chunk),
+                };
+                requests.push(segment_builder.clone().input(input));
+                owners.push(index);
+            }
+        }
+        let responses = synthesize_all(requests, self.concurrency).await?;
+
+        let mut ends = vec![Duration::ZERO; segments.len()];
+        let mut parts = Vec::with_capacity(responses.len());
+        let mut time = Duration::ZERO;
+        for (owner, response) in owners.into_iter().zip(responses) {
+            time += audio::duration(&response.audioContent)?;
+            ends[owner] = time;
+            parts.push(response.audioContent);
+        }
+
+        let segments = segments
+            .into_iter()
+            .enumerate()
+            .map(|(index, (segment, voice, _))| SynthesizedSegment {
+                segment,
+                voice,
+                start: index.checked_sub(1).map_or(Duration::ZERO, |previous| ends[previous]),
+                end: ends[index],
+            })
+            .collect();
+        Ok(MultilingualSynthesis {
+            audio: audio::concatenate(&parts)?,
+            segments,
+            mode: MixedLanguageMode::Separate,
+        })
+    }
+}
+
+impl IntoFuture for MultilingualSynthesisBuilder {
+    type Output = Result<MultilingualSynthesis, SynthesisError>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move {
+            let request = self.builder.request();
+            let encoding = request.audioConfig.audioEncoding;
+            if !audio::is_supported(encoding) {
+                return Err(AudioError::Unsupported(encoding).into());
+            }
+
+            let segments: Vec<_> = self
+                .segments()
+                .into_iter()
+                .map(|segment| {
+                    match self.voices_for(&segment.language) {
+                        Some(mut chain) => (segment, chain.swap_remove(0), true),
+                        None => (segment, request.voice.clone(), false),
+                    }
+                })
+                .collect();
+            if segments.is_empty() {
+                let synthesis = self.builder.chunked().concurrency(self.concurrency).exec().await?;
+                return Ok(MultilingualSynthesis {
+                    audio: synthesis.audio,
+                    segments: Vec::new(),
+                    mode: MixedLanguageMode::Separate,
+                });
+            }
+
+            match self.resolved_mode() {
+                MixedLanguageMode::Ssml => self.synthesize_ssml(segments).await,
+                _ => self.synthesize_separately(segments).await,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{ engine::general_purpose::STANDARD, Engine };
+
+    use super::*;
+    use crate::{
+        audio::wav,
+        normalize::Normalizer,
+        ssml::lexicon::Lexicon,
+        test_server::TestServer,
+        types::synthesize::AudioEncoding,
+    };
+
+    fn tag(language: &str) -> LanguageTag {
+        language.parse().unwrap()
+    }
+
+    fn named(name: &str) -> VoiceSelectionParams {
+        VoiceSelectionParams::from_name(name.parse().unwrap())
+    }
+
+    fn names(voices: Option<&[VoiceSelectionParams]>) -> Option<Vec<String>> {
+        voices.map(|voices| {
+            voices
+                .iter()
+                .map(|voice| voice.name.as_ref().map(ToString::to_string).unwrap_or_default())
+                .collect()
+        })
+    }
+
+    fn multilingual(text: &str, voices: VoiceMap) -> MultilingualSynthesisBuilder {
+        let builder = SynthesizeSpeechBuilder::new(
+            "token".to_string(),
+            SynthesisInput::Text(text.to_string())
+        ).voice_name("vi-VN-Neural2-A".parse().unwrap());
+        MultilingualSynthesisBuilder::new(builder, voices)
+    }
+
+    #[test]
+    fn finds_the_voices_of_the_most_specific_language() {
+        let voices = VoiceMap::new()
+            .voice(tag("en"), named("en-US-Standard-A"))
+            .voice(tag("en-US"), named("en-US-Neural2-C"))
+            .voice_chain(tag("ja-JP"), vec![named("ja-JP-Neural2-B"), named("ja-JP-Standard-A")]);
+
+        assert_eq!(names(voices.get(&tag("en-US"))), Some(vec!["en-US-Neural2-C".to_string()]));
+        assert_eq!(names(voices.get(&tag("en"))), Some(vec!["en-US-Standard-A".to_string()]));
+        assert_eq!(
+            names(voices.get(&tag("ja-JP"))),
+            Some(vec!["ja-JP-Neural2-B".to_string(), "ja-JP-Standard-A".to_string()])
+        );
+        assert_eq!(names(voices.get(&tag("ko-KR"))), None);
+
+        // An empty chain removes the language.
+        let voices = voices.voice_chain(tag("ja-JP"), Vec::new());
+        assert_eq!(names(voices.get(&tag("ja-JP"))), None);
+    }
+
+    #[test]
+    fn falls_back_to_a_language_with_the_same_primary_subtag() {
+        let voices = VoiceMap::new().voice(tag("en-GB"), named("en-GB-Neural2-A"));
+        assert_eq!(names(voices.get(&tag("en-US"))), Some(vec!["en-GB-Neural2-A".to_string()]));
+        assert_eq!(names(voices.get(&tag("fr-FR"))), None);
+    }
+
+    #[test]
+    fn segments_with_hints() {
+        let builder = multilingual("Discord rất vui", VoiceMap::new()).hint(0..7, tag("en-US"));
+        let segments: Vec<(String, String)> = builder
+            .segments()
+            .into_iter()
+            .map(|segment| (segment.language.to_string(), segment.text))
+            .collect();
+        assert_eq!(segments, [
+            ("en-US".to_string(), "Discord ".to_string()),
+            ("vi-VN".to_string(), "rất vui".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn resolves_to_ssml_when_every_voice_accepts_it() {
+        let text = "Hôm nay mình đi xem Avengers Endgame nhé, vui lắm!";
+        let voices = VoiceMap::new().voice(tag("en-US"), named("en-US-Neural2-C"));
+        assert_eq!(multilingual(text, voices).resolved_mode(), MixedLanguageMode::Ssml);
+
+        // Chirp 3: HD voices don't accept SSML.
+        let voices = VoiceMap::new().voice(tag("en-US"), named("en-US-Chirp3-HD-Achernar"));
+        assert_eq!(multilingual(text, voices).resolved_mode(), MixedLanguageMode::Separate);
+
+        // Nor do languages left to the API to choose a voice for.
+        let builder = multilingual(text, VoiceMap::new());
+        assert_eq!(builder.resolved_mode(), MixedLanguageMode::Separate);
+    }
+
+    #[test]
+    fn resolves_to_separate_requests_otherwise() {
+        let voices = VoiceMap::new().voice(tag("en-US"), named("en-US-Neural2-C"));
+        // A single segment.
+        let builder = multilingual("Xin chào các bạn", voices.clone());
+        assert_eq!(builder.resolved_mode(), MixedLanguageMode::Separate);
+
+        // Markup, whatever the mode.
+        let text = "Hôm nay mình đi xem Avengers Endgame nhé, vui lắm!";
+        let builder = MultilingualSynthesisBuilder::new(
+            SynthesizeSpeechBuilder::new(
+                "token".to_string(),
+                SynthesisInput::Markup(text.to_string())
+            ),
+            voices.clone()
+        ).mode(MixedLanguageMode::Ssml);
+        assert_eq!(builder.resolved_mode(), MixedLanguageMode::Separate);
+        assert_eq!(builder.segments().len(), 3);
+
+        // A mode set explicitly.
+        let builder = multilingual(text, voices).mode(MixedLanguageMode::Separate);
+        assert_eq!(builder.resolved_mode(), MixedLanguageMode::Separate);
+        let builder = multilingual(text, VoiceMap::new()).mode(MixedLanguageMode::Ssml);
+        assert_eq!(builder.resolved_mode(), MixedLanguageMode::Ssml);
+    }
+
+    /// Answers every synthesis request with 100 ms of silence.
+    async fn server() -> TestServer {
+        let format = wav::WavFormat {
+            format_tag: wav::FORMAT_PCM,
+            channels: 1,
+            sample_rate: 24_000,
+            bits_per_sample: 16,
+        };
+        let audio = STANDARD.encode(wav::write(&format, &[0; 4800]));
+        TestServer::start(move |_| {
+            (200, serde_json::json!({ "audioContent": audio }).to_string())
+        }).await
+    }
+
+    #[tokio::test]
+    async fn rewrites_every_segment_of_the_original_text() {
+        let text = "Hôm nay mình xem TTS Avengers lúc 20:30 nhé, vui lắm!";
+        let voices = VoiceMap::new().voice(tag("en-US"), named("en-US-Neural2-C"));
+        let lexicon = Lexicon::new().alias("TTS", "text to speech");
+        let normalizer = Normalizer::for_language(&tag("vi-VN"));
+        let multilingual = |endpoint: &str| {
+            let builder = multilingual(text, voices.clone()).builder
+                .normalize(&normalizer)
+                .lexicon(&lexicon)
+                .audio_encoding(AudioEncoding::Linear16)
+                .endpoint(endpoint);
+            MultilingualSynthesisBuilder::new(builder, voices.clone())
+        };
+
+        let server = server().await;
+        let builder = multilingual(&server.endpoint);
+        assert_eq!(builder.segments().len(), 3);
+        let synthesis = builder.exec().await.unwrap();
+        assert_eq!(synthesis.mode, MixedLanguageMode::Ssml);
+        let ssml = concat!(
+            r#"<speak><mark name="segment-0"/>Hôm nay mình <mark name="segment-1"/>"#,
+            r#"<voice name="en-US-Neural2-C">xem <sub alias="text to speech">TTS</sub> "#,
+            r#"Avengers </voice><mark name="segment-2"/>lúc 20 giờ 30 phút nhé, vui lắm!</speak>"#
+        );
+        let inputs: Vec<_> = server
+            .requests()
+            .iter()
+            .map(|request| request.json()["input"].clone())
+            .collect();
+        assert_eq!(inputs, [serde_json::json!({ "ssml": ssml })]);
+
+        let server = self::server().await;
+        let builder = multilingual(&server.endpoint).mode(MixedLanguageMode::Separate);
+        builder.exec().await.unwrap();
+        let inputs: Vec<_> = server
+            .requests()
+            .iter()
+            .map(|request| request.json()["input"].clone())
+            .collect();
+        assert_eq!(inputs, [
+            serde_json::json!({ "text": "Hôm nay mình" }),
+            serde_json::json!({
+                "ssml": r#"<speak>xem <sub alias="text to speech">TTS</sub> Avengers</speak>"#,
+            }),
+            serde_json::json!({ "text": "lúc 20 giờ 30 phút nhé, vui lắm!" }),
+        ]);
+    }
+}
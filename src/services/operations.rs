@@ -0,0 +1,279 @@
+// src/services/operations.rs
+
+use std::{ future::Future, pin::Pin, sync::Arc, time::{ Duration, Instant } };
+
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use tokio::sync::Mutex;
+
+use crate::{
+    auth::get_access_token,
+    types::{ credentials::GoogleCredentials, operation::LongRunningOperation },
+    services::{ endpoint::ApiVersion, synthesize::SynthesisError },
+};
+
+type TokenFuture = Pin<Box<dyn Future<Output = Result<String, SynthesisError>> + Send>>;
+
+/// Supplies the access token of every request sent by an [OperationsClient].
+///
+/// Operations can outlive an access token, so the token is asked for on each poll or
+/// cancellation rather than fixed when the operation starts.
+#[derive(Clone)]
+pub struct TokenProvider {
+    provide: Arc<dyn Fn() -> TokenFuture + Send + Sync>,
+}
+
+impl TokenProvider {
+    /// Creates a provider calling `provide` for every request.
+    pub fn new<F, Fut>(provide: F) -> Self
+        where
+            F: Fn() -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Result<String, SynthesisError>> + Send + 'static
+    {
+        TokenProvider {
+            provide: Arc::new(move || Box::pin(provide()) as TokenFuture),
+        }
+    }
+
+    /// Creates a provider always returning `access_token`.
+    pub fn fixed(access_token: String) -> Self {
+        TokenProvider::new(move || {
+            let access_token = access_token.clone();
+            async move { Ok(access_token) }
+        })
+    }
+
+    /// Creates a provider returning `access_token` until `expiration`, then a token obtained
+    /// with `credentials`. Clones of the provider share the refreshed token.
+    pub fn refreshing(
+        credentials: GoogleCredentials,
+        access_token: String,
+        expiration: Instant
+    ) -> Self {
+        let credentials = Arc::new(credentials);
+        let token = Arc::new(Mutex::new((access_token, expiration)));
+        TokenProvider::new(move || {
+            let credentials = credentials.clone();
+            let token = token.clone();
+            async move {
+                let mut token = token.lock().await;
+                if Instant::now() >= token.1 {
+                    *token = get_access_token(&credentials).await.map_err(|e| {
+                        let message = format!("failed to refresh the access token: {}", e);
+                        SynthesisError::ApiError(message)
+                    })?;
+                }
+                Ok(token.0.clone())
+            }
+        })
+    }
+
+    /// Returns the access token to send the next request with.
+    pub async fn token(&self) -> Result<String, SynthesisError> {
+        (self.provide)().await
+    }
+}
+
+impl From<String> for TokenProvider {
+    fn from(access_token: String) -> Self {
+        TokenProvider::fixed(access_token)
+    }
+}
+
+impl std::fmt::Debug for TokenProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenProvider").finish_non_exhaustive()
+    }
+}
+
+/// Client for the `operations` API, shared by every long-running method.
+///
+/// Operation names are resolved against `endpoint`, so a local stand-in can serve them.
+#[derive(Debug, Clone)]
+pub struct OperationsClient {
+    tokens: TokenProvider,
+    endpoint: String,
+    api_version: ApiVersion,
+    client: Client,
+}
+
+impl OperationsClient {
+    /// Creates a client sending its requests to `endpoint`, authenticated with the tokens of
+    /// `tokens`: a [TokenProvider], or a fixed access token.
+    pub fn new(tokens: impl Into<TokenProvider>, endpoint: &str, api_version: ApiVersion) -> Self {
+        OperationsClient {
+            tokens: tokens.into(),
+            endpoint: endpoint.to_string(),
+            api_version,
+            client: Client::new(),
+        }
+    }
+
+    /// Gets the latest state of the operation `name`.
+    pub async fn get<M: DeserializeOwned, R: DeserializeOwned>(
+        &self,
+        name: &str
+    ) -> Result<LongRunningOperation<M, R>, SynthesisError> {
+        let response = self.client
+            .get(self.api_version.url(&self.endpoint, name))
+            .bearer_auth(self.tokens.token().await?)
+            .send().await?;
+
+        if response.status() != reqwest::StatusCode::OK {
+            return Err(SynthesisError::from_response(response).await);
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Starts asynchronous cancellation of the operation `name`.
+    ///
+    /// The server makes a best effort to cancel the operation; use [OperationsClient::get]
+    /// to check whether it succeeded.
+    pub async fn cancel(&self, name: &str) -> Result<(), SynthesisError> {
+        let response = self.client
+            .post(self.api_version.url(&self.endpoint, &format!("{}:cancel", name)))
+            .bearer_auth(self.tokens.token().await?)
+            .json(&serde_json::json!({}))
+            .send().await?;
+
+        if response.status() != reqwest::StatusCode::OK {
+            return Err(SynthesisError::from_response(response).await);
+        }
+
+        Ok(())
+    }
+}
+
+/// Polling schedule used by [Operation::wait].
+///
+/// The delay between two polls starts at `initial_delay` and is multiplied by `multiplier`
+/// after each poll, up to `max_delay`. Waiting gives up after `timeout`, if set.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            multiplier: 1.5,
+            timeout: None,
+        }
+    }
+}
+
+impl Backoff {
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn next_delay(&self, delay: Duration) -> Duration {
+        delay.mul_f64(self.multiplier.max(1.0)).min(self.max_delay)
+    }
+}
+
+/// Handle on a long-running operation.
+///
+/// `M` is the type of the progress metadata and `R` the type of the response once done.
+#[derive(Debug, Clone)]
+pub struct Operation<M, R> {
+    client: OperationsClient,
+    state: LongRunningOperation<M, R>,
+}
+
+impl<M: DeserializeOwned, R: DeserializeOwned> Operation<M, R> {
+    pub fn new(client: OperationsClient, state: LongRunningOperation<M, R>) -> Self {
+        Operation { client, state }
+    }
+
+    /// Returns the server-assigned name of the operation.
+    pub fn name(&self) -> &str {
+        &self.state.name
+    }
+
+    /// Returns `true` once the operation has completed, successfully or not.
+    pub fn is_done(&self) -> bool {
+        self.state.done
+    }
+
+    /// Returns the metadata of the last known state, typically progress information.
+    pub fn metadata(&self) -> Option<&M> {
+        self.state.metadata.as_ref()
+    }
+
+    /// Returns the last known state of the operation.
+    pub fn state(&self) -> &LongRunningOperation<M, R> {
+        &self.state
+    }
+
+    /// Refreshes the state of the operation.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the operation is done.
+    pub async fn poll(&mut self) -> Result<bool, SynthesisError> {
+        if !self.state.done {
+            self.state = self.client.get(&self.state.name).await?;
+        }
+        Ok(self.state.done)
+    }
+
+    /// Requests the cancellation of the operation.
+    pub async fn cancel(&self) -> Result<(), SynthesisError> {
+        self.client.cancel(&self.state.name).await
+    }
+
+    /// Polls the operation following `backoff` until it is done.
+    ///
+    /// # Returns
+    ///
+    /// Returns the response of the operation, [SynthesisError::OperationFailed] if it failed
+    /// or was cancelled, or [SynthesisError::OperationTimeout] if `backoff.timeout` elapsed.
+    pub async fn wait(mut self, backoff: Backoff) -> Result<R, SynthesisError> {
+        let started = Instant::now();
+        let mut delay = backoff.initial_delay;
+
+        while !self.poll().await? {
+            if let Some(timeout) = backoff.timeout {
+                let elapsed = started.elapsed();
+                if elapsed >= timeout {
+                    return Err(SynthesisError::OperationTimeout(self.state.name));
+                }
+                delay = delay.min(timeout - elapsed);
+            }
+            tokio::time::sleep(delay).await;
+            delay = backoff.next_delay(delay);
+        }
+
+        if let Some(status) = self.state.error {
+            return Err(SynthesisError::OperationFailed(status));
+        }
+
+        let name = self.state.name;
+        self.state.response.ok_or_else(|| {
+            SynthesisError::ApiError(format!("operation {} finished without a response", name))
+        })
+    }
+}
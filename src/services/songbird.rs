@@ -1,11 +1,12 @@
 use songbird::input::Input;
-use crate::services::composer::GoogleTtsComposer;
+use crate::{ services::composer::GoogleTtsComposer, types::synthesize::SynthesizeResponse };
+
 impl From<SynthesizeResponse> for Input {
     /// Converts a [SynthesizeResponse] into [songbird::input::Input].
     ///
     /// This implementation is used to create a [songbird::input::Input] instance from
-    /// a [SynthesizeResponse]. It utilizes [GoogleTtsComposer] to handle the conversion
-    /// of the audio content from [SynthesizeResponse].
+    /// a [SynthesizeResponse]. It utilizes [GoogleTtsComposer] to play back the already
+    /// decoded audio content from [SynthesizeResponse].
     ///
     /// ## Arguments
     ///
@@ -18,13 +19,20 @@ impl From<SynthesizeResponse> for Input {
     ///
     /// ## Example
     ///
-    /// ```
-    /// let response = SynthesizeResponse { audioContent: "base64-encoded-audio" };
+    /// ```no_run
+    /// # use google_tts_api::client::TextToSpeechClient;
+    /// # use songbird::input::Input;
+    /// # async fn example(
+    /// #     tts: &mut TextToSpeechClient
+    /// # ) -> Result<(), google_tts_api::services::synthesize::SynthesisError> {
+    /// let response = tts.synthesize_text("Hello".to_string()).await.exec().await?;
     /// let input = Input::from(response);
     /// // Now `input` can be used with songbird's audio handling functions.
+    /// # Ok(())
+    /// # }
     /// ```
     fn from(value: SynthesizeResponse) -> Self {
-        let composer = GoogleTtsComposer::new(value.audioContent.clone());
+        let composer = GoogleTtsComposer::new(value.audioContent.into_bytes());
         Input::Lazy(Box::new(composer))
     }
 }
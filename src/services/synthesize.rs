@@ -3,46 +3,163 @@
 use std::{ future::{ IntoFuture, Future }, pin::Pin };
 
 use async_trait::async_trait;
+use serde::Deserialize;
 use reqwest::Client;
 
-use crate::types::synthesize::{
-    AudioConfig,
-    VoiceSelectionParams,
-    SynthesizeResponse,
-    SynthesizeRequest,
-    SynthesisInput,
-    default_language_code,
-    SsmlVoiceGender,
+use crate::{
+    normalize::Normalizer,
+    types::{
+        language::LanguageTag,
+        operation::Status,
+        pronunciation::{ CustomPronunciationParams, PhoneticEncoding, PronunciationSet },
+        voice_name::VoiceName,
+        synthesize::{
+            AudioConfig,
+            AudioEncoding,
+            CustomVoiceParams,
+            ReportedUsage,
+            VoiceCloneParams,
+            VoiceSelectionParams,
+            SynthesizeResponse,
+            SynthesizeRequest,
+            SynthesisInput,
+            RequestInput,
+            default_language_code,
+            SsmlVoiceGender,
+            TimepointType,
+            MultiSpeakerMarkup,
+            Turn,
+            MULTI_SPEAKER_VOICE,
+        },
+    },
+    audio::AudioError,
+    services::{
+        cache::AudioCache,
+        chunked::ChunkedSynthesisBuilder,
+        endpoint::{ ApiVersion, DEFAULT_ENDPOINT },
+        multilingual::{ MultilingualSynthesisBuilder, VoiceMap },
+        validation::{ validate_request, ValidationError },
+    },
+    ssml::{ lexicon::Lexicon, lint::{ errors as ssml_errors, Diagnostic } },
 };
 
+#[derive(Clone)]
 pub struct SynthesizeSpeechBuilder {
     access_token: String,
-    input: SynthesisInput,
+    endpoint: String,
+    input: RequestInput,
     voice: VoiceSelectionParams,
     audio_config: AudioConfig,
+    enable_time_pointing: Option<Vec<TimepointType>>,
+    voice_fallbacks: Vec<VoiceSelectionParams>,
+    lint_ssml: bool,
+    normalizer: Option<Normalizer>,
+    lexicon: Option<Lexicon>,
+    cache: Option<AudioCache>,
+    #[cfg(feature = "grpc")]
+    grpc_channel: Option<tonic::transport::Channel>,
 }
 
 impl SynthesizeSpeechBuilder {
     pub fn new(access_token: String, input: SynthesisInput) -> Self {
         SynthesizeSpeechBuilder {
             access_token,
-            input,
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            input: input.into(),
             voice: VoiceSelectionParams {
                 languageCode: default_language_code(),
                 ssmlGender: SsmlVoiceGender::Neutral,
                 ..Default::default()
             },
             audio_config: AudioConfig::default(),
+            enable_time_pointing: None,
+            voice_fallbacks: Vec::new(),
+            lint_ssml: false,
+            normalizer: None,
+            lexicon: None,
+            cache: None,
+            #[cfg(feature = "grpc")]
+            grpc_channel: None,
         }
     }
 
-    pub fn input_text(mut self, text: &str) -> Self {
-        self.input.text = Some(text.to_string());
+    /// Sends the request to `endpoint` instead of [DEFAULT_ENDPOINT], e.g. a local stand-in.
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
         self
     }
 
-    pub fn input_ssml(mut self, ssml: &str) -> Self {
-        self.input.ssml = Some(ssml.to_string());
+    /// Sends the request over gRPC on `channel` instead of the REST API.
+    #[cfg(feature = "grpc")]
+    pub fn grpc_channel(mut self, channel: tonic::transport::Channel) -> Self {
+        self.grpc_channel = Some(channel);
+        self
+    }
+
+    /// Replaces the input. Pronunciations set on the request are kept.
+    pub fn input(mut self, input: SynthesisInput) -> Self {
+        self.input.source = input;
+        self
+    }
+
+    /// Replaces the input with plain text.
+    pub fn input_text(self, text: &str) -> Self {
+        self.input(SynthesisInput::Text(text.to_string()))
+    }
+
+    /// Replaces the input with an SSML document.
+    pub fn input_ssml(self, ssml: &str) -> Self {
+        self.input(SynthesisInput::Ssml(ssml.to_string()))
+    }
+
+    /// Replaces the input with Chirp 3 HD markup.
+    pub fn input_markup(self, markup: &str) -> Self {
+        self.input(SynthesisInput::Markup(markup.to_string()))
+    }
+
+    /// Replaces the input with a dialogue read by the [MULTI_SPEAKER_VOICE] voice, which is
+    /// selected as well. Each [Turn] is spoken by one of the speakers `R`, `S`, `T` or `U`.
+    pub fn dialogue(self, turns: Vec<Turn>) -> Self {
+        let name = MULTI_SPEAKER_VOICE.parse().expect("MULTI_SPEAKER_VOICE is a valid voice name");
+        self.input(SynthesisInput::MultiSpeaker(MultiSpeakerMarkup { turns })).voice_name(name)
+    }
+
+    /// Sets how `phrase` is pronounced, replacing a pronunciation set earlier for it.
+    pub fn custom_pronunciation(
+        mut self,
+        phrase: &str,
+        encoding: PhoneticEncoding,
+        pronunciation: &str
+    ) -> Self {
+        self.input.customPronunciations
+            .get_or_insert_with(Default::default)
+            .insert(CustomPronunciationParams::new(phrase, encoding, pronunciation));
+        self
+    }
+
+    /// Adds every pronunciation of `set`, replacing the ones set earlier for the same phrases.
+    pub fn pronunciations(mut self, set: &PronunciationSet) -> Self {
+        if set.is_empty() {
+            return self;
+        }
+        let pronunciations = self.input.customPronunciations.get_or_insert_with(Default::default);
+        for params in set {
+            pronunciations.insert(params.clone());
+        }
+        self
+    }
+
+    /// Rewrites a text input with `normalizer` when the request is built, see
+    /// [Normalizer::normalize_input]; other inputs are left as they are.
+    pub fn normalize(mut self, normalizer: &Normalizer) -> Self {
+        self.normalizer = Some(normalizer.clone());
+        self
+    }
+
+    /// Applies `lexicon` to a text or SSML input when the request is built, after the
+    /// normalizer, see [Lexicon::apply_input].
+    pub fn lexicon(mut self, lexicon: &Lexicon) -> Self {
+        self.lexicon = Some(lexicon.clone());
         self
     }
 
@@ -56,6 +173,203 @@ impl SynthesizeSpeechBuilder {
         self
     }
 
+    /// Uses the first voice of `chain`, and retries with the next one whenever the API
+    /// rejects a voice as not found. Typically fed with the result of
+    /// [VoiceResolver::resolve](crate::services::voice_resolver::VoiceResolver::resolve).
+    ///
+    /// An empty chain leaves the voice unchanged.
+    pub fn voice_chain(mut self, chain: Vec<VoiceSelectionParams>) -> Self {
+        let mut chain = chain.into_iter();
+        if let Some(voice) = chain.next() {
+            self.voice = voice;
+            self.voice_fallbacks = chain.collect();
+        }
+        self
+    }
+
+    pub fn language_code(mut self, language_code: LanguageTag) -> Self {
+        self.voice.languageCode = language_code;
+        self
+    }
+
+    /// Selects the voice `name`, and sets the language code to the language of the voice.
+    pub fn voice_name(mut self, name: VoiceName) -> Self {
+        self.voice.languageCode = name.language().clone();
+        self.voice.name = Some(name);
+        self
+    }
+
+    /// Selects the custom voice trained as `model`, e.g.
+    /// `projects/{project}/locations/{location}/models/{model}`.
+    pub fn custom_voice(mut self, model: &str, reported_usage: ReportedUsage) -> Self {
+        self.voice.customVoice = Some(CustomVoiceParams::new(model, reported_usage));
+        self
+    }
+
+    /// Selects the voice clone identified by `voice_cloning_key`. Switches the request to the
+    /// `v1beta1` API.
+    pub fn voice_clone(mut self, voice_cloning_key: &str) -> Self {
+        self.voice.voiceClone = Some(VoiceCloneParams::new(voice_cloning_key));
+        self
+    }
+
+    pub fn ssml_gender(mut self, gender: SsmlVoiceGender) -> Self {
+        self.voice.ssmlGender = gender;
+        self
+    }
+
+    pub fn audio_encoding(mut self, encoding: AudioEncoding) -> Self {
+        self.audio_config.audioEncoding = encoding;
+        self
+    }
+
+    pub fn speaking_rate(mut self, rate: f32) -> Self {
+        self.audio_config.speakingRate = Some(rate);
+        self
+    }
+
+    pub fn pitch(mut self, pitch: f32) -> Self {
+        self.audio_config.pitch = Some(pitch);
+        self
+    }
+
+    pub fn volume_gain_db(mut self, gain: f32) -> Self {
+        self.audio_config.volumeGainDb = Some(gain);
+        self
+    }
+
+    pub fn sample_rate_hertz(mut self, sample_rate: i32) -> Self {
+        self.audio_config.sampleRateHertz = Some(sample_rate);
+        self
+    }
+
+    /// Appends an audio effects profile. Profiles are applied in the order they are added.
+    pub fn effects_profile_id(mut self, profile: &str) -> Self {
+        self.audio_config.effectsProfileId
+            .get_or_insert_with(Vec::new)
+            .push(profile.to_string());
+        self
+    }
+
+    /// Requests timepoint information in the response. Switches the request to the `v1beta1` API.
+    pub fn enable_time_pointing(mut self, types: Vec<TimepointType>) -> Self {
+        self.enable_time_pointing = Some(types);
+        self
+    }
+
+    /// Requests the time at which each SSML `<mark>` is spoken, returned in
+    /// [SynthesizeResponse::timepoints]. Switches the request to the `v1beta1` API.
+    pub fn enable_ssml_marks(self) -> Self {
+        self.enable_time_pointing(vec![TimepointType::SsmlMark])
+    }
+
+    /// Checks SSML input with [lint](crate::ssml::lint::lint) before sending it, failing with
+    /// [SynthesisError::InvalidSsml] if errors are found. Off by default.
+    pub fn lint_ssml(mut self, enabled: bool) -> Self {
+        self.lint_ssml = enabled;
+        self
+    }
+
+    /// Answers the request from `cache` when an identical one was synthesized recently, and
+    /// caches the response otherwise.
+    pub fn cache(mut self, cache: &AudioCache) -> Self {
+        self.cache = Some(cache.clone());
+        self
+    }
+
+    /// Sends the request to the API even if the client has an [AudioCache], without caching
+    /// the response.
+    pub fn no_cache(mut self) -> Self {
+        self.cache = None;
+        self
+    }
+
+    /// Returns the API version the request needs to be sent to.
+    pub fn api_version(&self) -> ApiVersion {
+        if
+            self.enable_time_pointing.is_some() ||
+            self.voice.voiceClone.is_some() ||
+            self.audio_config.audioEncoding.requires_v1beta1()
+        {
+            ApiVersion::V1Beta1
+        } else {
+            ApiVersion::V1
+        }
+    }
+
+    fn connection(&self) -> Connection {
+        let api_version = self.api_version();
+        #[cfg(feature = "grpc")]
+        if let Some(channel) = &self.grpc_channel {
+            return Connection::Grpc { channel: channel.clone(), api_version };
+        }
+        Connection::Rest {
+            client: Client::new(),
+            url: api_version.url(&self.endpoint, "text:synthesize"),
+        }
+    }
+
+    /// Builds the request that will be sent to the API.
+    pub fn request(&self) -> SynthesizeRequest {
+        SynthesizeRequest {
+            input: self.rewritten_input(),
+            voice: self.voice.clone(),
+            audioConfig: self.audio_config.clone(),
+            enableTimePointing: self.enable_time_pointing.clone(),
+        }
+    }
+
+    /// Returns the input as set, before the normalizer and the lexicon are applied.
+    pub(crate) fn source(&self) -> &SynthesisInput {
+        &self.input.source
+    }
+
+    pub(crate) fn voice(&self) -> &VoiceSelectionParams {
+        &self.voice
+    }
+
+    pub(crate) fn normalizer(&self) -> Option<&Normalizer> {
+        self.normalizer.as_ref()
+    }
+
+    /// Returns the input with the normalizer and the lexicon applied.
+    fn rewritten_input(&self) -> RequestInput {
+        let mut input = self.input.clone();
+        if let Some(normalizer) = &self.normalizer {
+            input.source = normalizer.normalize_input(input.source);
+        }
+        if let Some(lexicon) = &self.lexicon {
+            input.source = lexicon.apply_input(input.source);
+        }
+        input
+    }
+
+    /// Applies the normalizer and the lexicon to the input once and for all, for builders
+    /// whose input is replaced with parts of the rewritten one.
+    pub(crate) fn rewritten(mut self) -> Self {
+        self.input = self.rewritten_input();
+        self.normalizer = None;
+        self.lexicon = None;
+        self
+    }
+
+    /// Validates the request against the documented API limits without sending it.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_request(&self.request())
+    }
+
+    /// Synthesizes the input in chunks under the per-request limit, for inputs of any
+    /// length. See [ChunkedSynthesisBuilder].
+    pub fn chunked(self) -> ChunkedSynthesisBuilder {
+        ChunkedSynthesisBuilder::new(self)
+    }
+
+    /// Synthesizes text mixing languages, reading each language with its voice in `voices`.
+    /// See [MultilingualSynthesisBuilder].
+    pub fn multilingual(self, voices: VoiceMap) -> MultilingualSynthesisBuilder {
+        MultilingualSynthesisBuilder::new(self, voices)
+    }
+
     pub async fn exec(self) -> Result<SynthesizeResponse, SynthesisError> {
         self.into_future().await
     }
@@ -68,55 +382,274 @@ impl IntoFuture for SynthesizeSpeechBuilder {
 
     fn into_future(self) -> Self::IntoFuture {
         Box::pin(async move {
-            let client = Client::new();
-            let request = SynthesizeRequest {
-                input: self.input,
-                voice: self.voice,
-                audioConfig: AudioConfig::default(),
-            };
+            let encoding = self.audio_config.audioEncoding;
+            let sample_rate_hertz = self.audio_config.sampleRateHertz;
+            let connection = self.connection();
+            let mut request = self.request();
+            validate_request(&request)?;
+            if let (true, SynthesisInput::Ssml(ssml)) = (self.lint_ssml, &request.input.source) {
+                let errors = ssml_errors(ssml);
+                if !errors.is_empty() {
+                    return Err(SynthesisError::InvalidSsml(errors));
+                }
+            }
+
+            // Cached under the request as built, whatever voice of the chain answers it.
+            let cache = self.cache.map(|cache| (cache, request.clone()));
+            if let Some(response) = cache.as_ref().and_then(|(cache, key)| cache.get(key)) {
+                return Ok(response);
+            }
 
-            // Make the request to the Google TTS API
-            let response = client
-                .post("https://texttospeech.googleapis.com/v1/text:synthesize")
-                .bearer_auth(&self.access_token)
-                .json(&request)
-                .send().await?;
+            let mut fallbacks = self.voice_fallbacks.into_iter();
 
-            // Assuming `response` is a reqwest::Response object
-            if response.status() != reqwest::StatusCode::OK {
-                let error_body = response.text().await.unwrap_or_default();
-                println!("Error response body: {}", error_body);
+            loop {
+                match connection.send(&self.access_token, &request).await {
+                    Err(error) if error.is_voice_not_found() => {
+                        match fallbacks.next() {
+                            Some(voice) => {
+                                request.voice = voice;
+                            }
+                            None => {
+                                return Err(error);
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        return Err(error);
+                    }
+                    Ok(mut response) => {
+                        response.audioContent.set_format(encoding, sample_rate_hertz);
+                        if let Some((cache, key)) = &cache {
+                            cache.insert(key, &response);
+                        }
+                        return Ok(response);
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Where a synthesis request is sent.
+enum Connection {
+    Rest {
+        client: Client,
+        url: String,
+    },
+    #[cfg(feature = "grpc")]
+    Grpc {
+        channel: tonic::transport::Channel,
+        api_version: ApiVersion,
+    },
+}
 
-                return Err(
-                    SynthesisError::ApiError(format!("error from google api {}", error_body))
-                );
+impl Connection {
+    async fn send(
+        &self,
+        access_token: &str,
+        request: &SynthesizeRequest
+    ) -> Result<SynthesizeResponse, SynthesisError> {
+        match self {
+            Connection::Rest { client, url } => {
+                send_request(client, url, access_token, request).await
+            }
+            #[cfg(feature = "grpc")]
+            Connection::Grpc { channel, api_version } => {
+                crate::grpc::unary::synthesize(
+                    channel.clone(),
+                    access_token,
+                    *api_version,
+                    request
+                ).await
             }
+        }
+    }
+}
 
-            // println!("request {:?}", request);
+async fn send_request(
+    client: &Client,
+    url: &str,
+    access_token: &str,
+    request: &SynthesizeRequest
+) -> Result<SynthesizeResponse, SynthesisError> {
+    // Make the request to the Google TTS API
+    let response = client.post(url).bearer_auth(access_token).json(request).send().await?;
 
-            Ok(response.json::<SynthesizeResponse>().await?)
-        })
+    if response.status() != reqwest::StatusCode::OK {
+        return Err(SynthesisError::from_response(response).await);
     }
+
+    Ok(response.json::<SynthesizeResponse>().await?)
 }
 
 #[derive(Debug)]
 pub enum SynthesisError {
     RequestError(reqwest::Error),
     ApiError(String), // Assuming API errors are returned as a string
+    /// The REST API answered with an error status.
+    HttpStatus {
+        /// The HTTP status code of the response.
+        status: reqwest::StatusCode,
+        /// The canonical code of the JSON error body, e.g. `NOT_FOUND`, if the body has one.
+        code: Option<String>,
+        /// The message of the JSON error body, or the whole body if it isn't a JSON error.
+        message: String,
+    },
+    ValidationError(ValidationError),
+    /// No voice is available for the requested language or any of its fallbacks.
+    VoiceNotFound(String),
+    /// A long-running operation finished with an error, or was cancelled.
+    OperationFailed(Status),
+    /// Waiting for a long-running operation timed out. Holds the name of the operation.
+    OperationTimeout(String),
+    /// The SSML input failed the checks enabled with [SynthesizeSpeechBuilder::lint_ssml].
+    InvalidSsml(Vec<Diagnostic>),
+    /// The audio of a [chunked](SynthesizeSpeechBuilder::chunked) synthesis couldn't be
+    /// measured or joined.
+    AudioError(AudioError),
+    /// The gRPC API returned an error status.
+    #[cfg(feature = "grpc")]
+    GrpcStatus(Box<tonic::Status>),
+    /// The gRPC channel could not be set up.
+    #[cfg(feature = "grpc")]
+    GrpcTransport(tonic::transport::Error),
     // You can add more error variants as needed
 }
 
+impl SynthesisError {
+    /// Returns `true` if the API rejected the request as naming something that doesn't exist,
+    /// or as an invalid argument whose message names the voice, which is how it reports a
+    /// voice that does not exist.
+    ///
+    /// Such a rejection is the cue to try the next voice of a
+    /// [chain](SynthesizeSpeechBuilder::voice_chain). Other invalid arguments, such as
+    /// malformed SSML, would fail with every voice.
+    pub fn is_voice_not_found(&self) -> bool {
+        let names_voice = |message: &str| message.to_lowercase().contains("voice");
+        match self {
+            SynthesisError::VoiceNotFound(_) => true,
+            SynthesisError::HttpStatus { status, code, message } => {
+                match code.as_deref() {
+                    Some("NOT_FOUND") => true,
+                    Some("INVALID_ARGUMENT") => names_voice(message),
+                    Some(_) => false,
+                    None => {
+                        *status == reqwest::StatusCode::NOT_FOUND ||
+                            (*status == reqwest::StatusCode::BAD_REQUEST && names_voice(message))
+                    }
+                }
+            }
+            #[cfg(feature = "grpc")]
+            SynthesisError::GrpcStatus(status) => {
+                match status.code() {
+                    tonic::Code::NotFound => true,
+                    tonic::Code::InvalidArgument => names_voice(status.message()),
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Reads the error of a REST response with an error status.
+    pub(crate) async fn from_response(response: reqwest::Response) -> SynthesisError {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        SynthesisError::from_error_body(status, &body)
+    }
+
+    /// Parses a JSON error body of the form `{"error": {"code", "message", "status"}}`.
+    fn from_error_body(status: reqwest::StatusCode, body: &str) -> SynthesisError {
+        #[derive(Deserialize)]
+        struct ErrorBody {
+            error: ErrorDetails,
+        }
+
+        #[derive(Deserialize)]
+        struct ErrorDetails {
+            #[serde(default)]
+            message: String,
+            status: Option<String>,
+        }
+
+        match serde_json::from_str::<ErrorBody>(body) {
+            Ok(ErrorBody { error }) => {
+                SynthesisError::HttpStatus {
+                    status,
+                    code: error.status,
+                    message: error.message,
+                }
+            }
+            Err(_) => {
+                SynthesisError::HttpStatus {
+                    status,
+                    code: None,
+                    message: body.to_string(),
+                }
+            }
+        }
+    }
+}
+
 impl From<reqwest::Error> for SynthesisError {
     fn from(error: reqwest::Error) -> Self {
         SynthesisError::RequestError(error)
     }
 }
 
+impl From<ValidationError> for SynthesisError {
+    fn from(error: ValidationError) -> Self {
+        SynthesisError::ValidationError(error)
+    }
+}
+
+impl From<AudioError> for SynthesisError {
+    fn from(error: AudioError) -> Self {
+        SynthesisError::AudioError(error)
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl From<tonic::Status> for SynthesisError {
+    fn from(status: tonic::Status) -> Self {
+        SynthesisError::GrpcStatus(Box::new(status))
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl From<tonic::transport::Error> for SynthesisError {
+    fn from(error: tonic::transport::Error) -> Self {
+        SynthesisError::GrpcTransport(error)
+    }
+}
+
 impl std::fmt::Display for SynthesisError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SynthesisError::RequestError(e) => write!(f, "Request error: {}", e),
             SynthesisError::ApiError(e) => write!(f, "API error: {}", e),
+            SynthesisError::HttpStatus { status, code: Some(code), message } => {
+                write!(f, "API error: {} ({}, {})", message, code, status)
+            }
+            SynthesisError::HttpStatus { status, code: None, message } => {
+                write!(f, "API error: {} ({})", message, status)
+            }
+            SynthesisError::ValidationError(e) => write!(f, "Validation error: {}", e),
+            SynthesisError::VoiceNotFound(e) => write!(f, "Voice not found: {}", e),
+            SynthesisError::OperationFailed(e) => write!(f, "Operation failed: {}", e),
+            SynthesisError::OperationTimeout(e) => write!(f, "Operation timed out: {}", e),
+            SynthesisError::InvalidSsml(diagnostics) => {
+                write!(f, "Invalid SSML:")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n  {}", diagnostic)?;
+                }
+                Ok(())
+            }
+            SynthesisError::AudioError(e) => write!(f, "Audio error: {}", e),
+            #[cfg(feature = "grpc")]
+            SynthesisError::GrpcStatus(e) => write!(f, "gRPC error: {}", e),
+            #[cfg(feature = "grpc")]
+            SynthesisError::GrpcTransport(e) => write!(f, "gRPC transport error: {}", e),
         }
     }
 }
@@ -124,3 +657,169 @@ impl std::fmt::Display for SynthesisError {
 impl std::error::Error for SynthesisError {}
 
 unsafe impl Send for SynthesisError {}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use super::*;
+    use crate::test_server::TestServer;
+
+    const NOT_FOUND_BODY: &str = r#"{"error": {
+        "code": 404,
+        "message": "Voice 'en-US-Neural2-Z' does not exist.",
+        "status": "NOT_FOUND"
+    }}"#;
+
+    fn voice(name: &str) -> VoiceSelectionParams {
+        let name: VoiceName = name.parse().unwrap();
+        VoiceSelectionParams {
+            languageCode: name.language().clone(),
+            name: Some(name),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_the_json_error_body() {
+        let error = SynthesisError::from_error_body(StatusCode::NOT_FOUND, NOT_FOUND_BODY);
+        match &error {
+            SynthesisError::HttpStatus { status, code, message } => {
+                assert_eq!(*status, StatusCode::NOT_FOUND);
+                assert_eq!(code.as_deref(), Some("NOT_FOUND"));
+                assert_eq!(message, "Voice 'en-US-Neural2-Z' does not exist.");
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert!(error.is_voice_not_found());
+
+        let error = SynthesisError::from_error_body(StatusCode::BAD_GATEWAY, "<html>Bad</html>");
+        match &error {
+            SynthesisError::HttpStatus { status, code, message } => {
+                assert_eq!(*status, StatusCode::BAD_GATEWAY);
+                assert_eq!(*code, None);
+                assert_eq!(message, "<html>Bad</html>");
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert!(!error.is_voice_not_found());
+    }
+
+    #[test]
+    fn falls_back_on_not_found_and_invalid_voices_only() {
+        let error = |status: StatusCode, code: Option<&str>, message: &str| {
+            SynthesisError::HttpStatus {
+                status,
+                code: code.map(str::to_string),
+                message: message.to_string(),
+            }
+        };
+        let voice = "Voice 'en-US-Neural2-Z' does not exist.";
+        let ssml = "Invalid SSML: unclosed tag <prosody>.";
+
+        assert!(error(StatusCode::NOT_FOUND, Some("NOT_FOUND"), ssml).is_voice_not_found());
+        assert!(error(StatusCode::NOT_FOUND, None, "").is_voice_not_found());
+        let invalid = |message| error(StatusCode::BAD_REQUEST, Some("INVALID_ARGUMENT"), message);
+        assert!(invalid(voice).is_voice_not_found());
+        assert!(!invalid(ssml).is_voice_not_found());
+        assert!(error(StatusCode::BAD_REQUEST, None, voice).is_voice_not_found());
+        assert!(!error(StatusCode::BAD_REQUEST, None, "Bad Request").is_voice_not_found());
+        let forbidden = error(StatusCode::FORBIDDEN, Some("PERMISSION_DENIED"), voice);
+        assert!(!forbidden.is_voice_not_found());
+        let precondition = error(StatusCode::BAD_REQUEST, Some("FAILED_PRECONDITION"), voice);
+        assert!(!precondition.is_voice_not_found());
+        assert!(!SynthesisError::ApiError("voice not found".to_string()).is_voice_not_found());
+    }
+
+    #[cfg(feature = "grpc")]
+    #[test]
+    fn falls_back_on_grpc_not_found_and_invalid_voices_only() {
+        let error = |status| SynthesisError::from(status);
+        assert!(error(tonic::Status::not_found("")).is_voice_not_found());
+        let invalid = tonic::Status::invalid_argument("Requested voice does not exist.");
+        assert!(error(invalid).is_voice_not_found());
+        assert!(!error(tonic::Status::invalid_argument("Invalid SSML.")).is_voice_not_found());
+        assert!(!error(tonic::Status::unauthenticated("voice not found")).is_voice_not_found());
+    }
+
+    #[tokio::test]
+    async fn tries_the_next_voice_of_the_chain_when_a_voice_is_not_found() {
+        let server = TestServer::start(|request| {
+            match request.json()["voice"]["name"].as_str() {
+                Some("en-US-Neural2-Z") => (404, NOT_FOUND_BODY.to_string()),
+                _ => (200, r#"{"audioContent": "AAAA"}"#.to_string()),
+            }
+        }).await;
+
+        let response = SynthesizeSpeechBuilder::new(
+            "token".to_string(),
+            SynthesisInput::Text("Hello".to_string())
+        )
+            .endpoint(&server.endpoint)
+            .voice_chain(vec![voice("en-US-Neural2-Z"), voice("en-US-Neural2-F")])
+            .exec().await
+            .unwrap();
+
+        assert_eq!(response.audioContent.bytes(), [0, 0, 0]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/v1/text:synthesize");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer token"));
+        assert_eq!(requests[1].json()["voice"]["name"], "en-US-Neural2-F");
+    }
+
+    #[tokio::test]
+    async fn returns_other_api_errors_without_falling_back() {
+        let server = TestServer::start(|_| {
+            let body =
+                r#"{"error": {"code": 403, "message": "Denied", "status": "PERMISSION_DENIED"}}"#;
+            (403, body.to_string())
+        }).await;
+
+        let error = SynthesizeSpeechBuilder::new(
+            "token".to_string(),
+            SynthesisInput::Text("Hello".to_string())
+        )
+            .endpoint(&server.endpoint)
+            .voice_chain(vec![voice("en-US-Neural2-Z"), voice("en-US-Neural2-F")])
+            .exec().await
+            .unwrap_err();
+
+        match error {
+            SynthesisError::HttpStatus { status, code, .. } => {
+                assert_eq!(status, StatusCode::FORBIDDEN);
+                assert_eq!(code.as_deref(), Some("PERMISSION_DENIED"));
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_ssml_before_sending_it_when_linting() {
+        let server = TestServer::start(|_| (200, r#"{"audioContent": "AAAA"}"#.to_string())).await;
+        let ssml = "<speak>\n  <prosody rate=\"fast-ish\">Hi</prosody><mark name=\"\"/>\n</speak>";
+        let builder = || {
+            let input = SynthesisInput::Ssml(ssml.to_string());
+            SynthesizeSpeechBuilder::new("token".to_string(), input).endpoint(&server.endpoint)
+        };
+
+        let error = builder().lint_ssml(true).exec().await.unwrap_err();
+        match error {
+            SynthesisError::InvalidSsml(diagnostics) => {
+                let positions: Vec<String> = diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.position.to_string())
+                    .collect();
+                assert_eq!(positions, ["2:12", "2:46"]);
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert!(server.requests().is_empty());
+
+        // Off by default: the API is left to judge the document.
+        builder().exec().await.unwrap();
+        assert_eq!(server.requests().len(), 1);
+    }
+}
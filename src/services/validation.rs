@@ -0,0 +1,543 @@
+// src/services/validation.rs
+
+use std::{ fmt, ops::RangeInclusive };
+
+use crate::types::{
+    long_audio::SynthesizeLongAudioRequest,
+    pronunciation::{ CustomPronunciations, PhoneticEncoding },
+    voice_name::VoiceFamily,
+    synthesize::{
+        AudioConfig,
+        AudioEncoding,
+        MultiSpeakerMarkup,
+        ReportedUsage,
+        SynthesisInput,
+        SynthesizeRequest,
+        VoiceSelectionParams,
+        MULTI_SPEAKER_VOICE,
+    },
+};
+
+/// Maximum size, in bytes, of the `text` or `ssml` input accepted by a single synthesis request.
+pub const MAX_INPUT_BYTES: usize = 5000;
+
+/// Accepted range for `AudioConfig::speakingRate`. A value of `0.0` means unset.
+pub const SPEAKING_RATE_RANGE: RangeInclusive<f32> = 0.25..=4.0;
+
+/// Accepted range for `AudioConfig::pitch`, in semitones.
+pub const PITCH_RANGE: RangeInclusive<f32> = -20.0..=20.0;
+
+/// Accepted range for `AudioConfig::volumeGainDb`, in decibels.
+pub const VOLUME_GAIN_DB_RANGE: RangeInclusive<f32> = -96.0..=16.0;
+
+/// Accepted range for `AudioConfig::sampleRateHertz`.
+pub const SAMPLE_RATE_HERTZ_RANGE: RangeInclusive<i32> = 8000..=48000;
+
+/// Audio effects profiles documented at <https://cloud.google.com/text-to-speech/docs/audio-profiles>.
+pub const KNOWN_EFFECTS_PROFILES: &[&str] = &[
+    "wearable-class-device",
+    "handset-class-device",
+    "headphone-class-device",
+    "small-bluetooth-speaker-class-device",
+    "medium-bluetooth-speaker-class-device",
+    "large-home-entertainment-class-device",
+    "large-automotive-class-device",
+    "telephony-class-application",
+];
+
+/// Speaker labels accepted in a [MultiSpeakerMarkup] turn.
+pub const MULTI_SPEAKER_LABELS: &[&str] = &["R", "S", "T", "U"];
+
+/// A single problem found while validating a [SynthesizeRequest].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Path of the offending field in the JSON request, e.g. `audioConfig.pitch`.
+    pub field: &'static str,
+    /// Human readable description of the problem.
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Violation { field, message: message.into() }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Error returned when a [SynthesizeRequest] fails client-side validation.
+///
+/// It lists every violation found, not just the first one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationError {
+    /// Returns `Ok(())` if `violations` is empty, or a [ValidationError] listing them.
+    pub fn from_violations(violations: Vec<Violation>) -> Result<(), ValidationError> {
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { violations })
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid synthesis request: ")?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Validates a [SynthesizeRequest] against the ranges documented by the Google TTS API.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the request is valid, or a [ValidationError] listing every violation.
+pub fn validate_request(request: &SynthesizeRequest) -> Result<(), ValidationError> {
+    let mut violations = Vec::new();
+
+    validate_input(&request.input.source, &request.voice, &mut violations);
+    check_input_size(&request.input.source, &mut violations);
+    validate_voice(&request.voice, &mut violations);
+    if let Some(pronunciations) = &request.input.customPronunciations {
+        validate_pronunciations(pronunciations, &request.voice, &mut violations);
+    }
+    validate_audio_config(&request.audioConfig, &mut violations);
+
+    ValidationError::from_violations(violations)
+}
+
+/// Validates a [SynthesizeLongAudioRequest]. Unlike [validate_request], the input size is not
+/// limited to [MAX_INPUT_BYTES].
+pub fn validate_long_audio_request(
+    request: &SynthesizeLongAudioRequest
+) -> Result<(), ValidationError> {
+    let mut violations = Vec::new();
+
+    validate_input(&request.input.source, &request.voice, &mut violations);
+    validate_voice(&request.voice, &mut violations);
+    if let Some(pronunciations) = &request.input.customPronunciations {
+        validate_pronunciations(pronunciations, &request.voice, &mut violations);
+    }
+    validate_audio_config(&request.audioConfig, &mut violations);
+    if !request.outputGcsUri.starts_with("gs://") {
+        violations.push(
+            Violation::new(
+                "outputGcsUri",
+                "expected a URI of the form gs://bucket_name/object_name"
+            )
+        );
+    }
+
+    ValidationError::from_violations(violations)
+}
+
+fn validate_input(
+    input: &SynthesisInput,
+    voice: &VoiceSelectionParams,
+    violations: &mut Vec<Violation>
+) {
+    if input.is_empty() {
+        violations.push(Violation::new(input_field(input), "input must not be empty"));
+    }
+    let family = voice.name.as_ref().map(|name| name.family());
+    match input {
+        SynthesisInput::Text(_) => {}
+        SynthesisInput::Ssml(_) => {
+            if let Some(family) = family.filter(|family| !family.supports_ssml()) {
+                violations.push(
+                    Violation::new("input.ssml", format!("{} voices don't accept SSML", family))
+                );
+            }
+        }
+        SynthesisInput::Markup(_) => {
+            if let Some(family) = family.filter(|family| **family != VoiceFamily::Chirp3Hd) {
+                violations.push(
+                    Violation::new(
+                        "input.markup",
+                        format!("markup is only accepted by Chirp3-HD voices, not {}", family)
+                    )
+                );
+            }
+        }
+        SynthesisInput::MultiSpeaker(markup) => {
+            validate_multi_speaker_markup(markup, violations);
+            validate_multi_speaker_voice(voice, violations);
+        }
+    }
+}
+
+fn input_field(input: &SynthesisInput) -> &'static str {
+    match input {
+        SynthesisInput::Text(_) => "input.text",
+        SynthesisInput::Ssml(_) => "input.ssml",
+        SynthesisInput::Markup(_) => "input.markup",
+        SynthesisInput::MultiSpeaker(_) => "input.multiSpeakerMarkup",
+    }
+}
+
+fn validate_multi_speaker_markup(markup: &MultiSpeakerMarkup, violations: &mut Vec<Violation>) {
+    if markup.turns.is_empty() {
+        violations.push(
+            Violation::new("input.multiSpeakerMarkup.turns", "at least one turn is required")
+        );
+    }
+    for (index, turn) in markup.turns.iter().enumerate() {
+        if !MULTI_SPEAKER_LABELS.contains(&turn.speaker.as_str()) {
+            violations.push(
+                Violation::new(
+                    "input.multiSpeakerMarkup.turns.speaker",
+                    format!(
+                        "turn {} has speaker '{}', expected one of {}",
+                        index,
+                        turn.speaker,
+                        MULTI_SPEAKER_LABELS.join(", ")
+                    )
+                )
+            );
+        }
+        if turn.text.trim().is_empty() {
+            violations.push(
+                Violation::new(
+                    "input.multiSpeakerMarkup.turns.text",
+                    format!("turn {} has no text", index)
+                )
+            );
+        }
+    }
+}
+
+fn validate_multi_speaker_voice(voice: &VoiceSelectionParams, violations: &mut Vec<Violation>) {
+    let is_multi_speaker = voice.name
+        .as_ref()
+        .is_some_and(|name| name.to_string() == MULTI_SPEAKER_VOICE);
+    if !is_multi_speaker {
+        violations.push(
+            Violation::new(
+                "voice.name",
+                format!("multi-speaker markup requires the voice {}", MULTI_SPEAKER_VOICE)
+            )
+        );
+    }
+}
+
+fn check_input_size(input: &SynthesisInput, violations: &mut Vec<Violation>) {
+    if input.len() > MAX_INPUT_BYTES {
+        violations.push(
+            Violation::new(
+                input_field(input),
+                format!("input is {} bytes, the limit is {} bytes", input.len(), MAX_INPUT_BYTES)
+            )
+        );
+    }
+}
+
+fn validate_voice(voice: &VoiceSelectionParams, violations: &mut Vec<Violation>) {
+    if let Some(name) = &voice.name {
+        if !name.language().matches(&voice.languageCode.base()) {
+            violations.push(
+                Violation::new(
+                    "voice.languageCode",
+                    format!(
+                        "'{}' doesn't match the language of voice '{}'",
+                        voice.languageCode,
+                        name
+                    )
+                )
+            );
+        }
+    }
+    if let Some(custom_voice) = &voice.customVoice {
+        if custom_voice.model.trim().is_empty() {
+            violations.push(Violation::new("voice.customVoice.model", "model must be set"));
+        }
+        if custom_voice.reportedUsage == Some(ReportedUsage::ReportedUsageUnspecified) {
+            violations.push(
+                Violation::new(
+                    "voice.customVoice.reportedUsage",
+                    "requests with an unspecified reported usage are rejected"
+                )
+            );
+        }
+    }
+    if let Some(voice_clone) = &voice.voiceClone {
+        if voice_clone.voiceCloningKey.trim().is_empty() {
+            violations.push(
+                Violation::new("voice.voiceClone.voiceCloningKey", "voice cloning key must be set")
+            );
+        }
+        if voice.customVoice.is_some() {
+            violations.push(
+                Violation::new("voice", "customVoice and voiceClone are mutually exclusive")
+            );
+        }
+    }
+}
+
+fn validate_pronunciations(
+    pronunciations: &CustomPronunciations,
+    voice: &VoiceSelectionParams,
+    violations: &mut Vec<Violation>
+) {
+    if let Some(name) = &voice.name {
+        if *name.family() == VoiceFamily::Journey {
+            violations.push(
+                Violation::new(
+                    "input.customPronunciations",
+                    format!("voice '{}' doesn't support custom pronunciations", name)
+                )
+            );
+        }
+    }
+    for params in &pronunciations.pronunciations {
+        if params.phrase.trim().is_empty() {
+            violations.push(
+                Violation::new("input.customPronunciations.phrase", "phrase must not be empty")
+            );
+        }
+        if params.pronunciation.trim().is_empty() {
+            violations.push(
+                Violation::new(
+                    "input.customPronunciations.pronunciation",
+                    format!("the pronunciation of '{}' is empty", params.phrase)
+                )
+            );
+        }
+        if params.phoneticEncoding == PhoneticEncoding::PhoneticEncodingUnspecified {
+            violations.push(
+                Violation::new(
+                    "input.customPronunciations.phoneticEncoding",
+                    format!("the phonetic encoding of '{}' must be specified", params.phrase)
+                )
+            );
+        }
+    }
+}
+
+fn validate_audio_config(config: &AudioConfig, violations: &mut Vec<Violation>) {
+    if config.audioEncoding == AudioEncoding::AudioEncodingUnspecified {
+        violations.push(Violation::new("audioConfig.audioEncoding", "encoding must be specified"));
+    }
+
+    if let Some(rate) = config.speakingRate {
+        // 0.0 is treated by the API as "unset".
+        if rate != 0.0 && !SPEAKING_RATE_RANGE.contains(&rate) {
+            violations.push(out_of_range("audioConfig.speakingRate", rate, &SPEAKING_RATE_RANGE));
+        }
+    }
+
+    if let Some(pitch) = config.pitch {
+        if !PITCH_RANGE.contains(&pitch) {
+            violations.push(out_of_range("audioConfig.pitch", pitch, &PITCH_RANGE));
+        }
+    }
+
+    if let Some(gain) = config.volumeGainDb {
+        if !VOLUME_GAIN_DB_RANGE.contains(&gain) {
+            violations.push(out_of_range("audioConfig.volumeGainDb", gain, &VOLUME_GAIN_DB_RANGE));
+        }
+    }
+
+    if let Some(sample_rate) = config.sampleRateHertz {
+        if !SAMPLE_RATE_HERTZ_RANGE.contains(&sample_rate) {
+            violations.push(
+                out_of_range("audioConfig.sampleRateHertz", sample_rate, &SAMPLE_RATE_HERTZ_RANGE)
+            );
+        } else if let Some(supported) = config.audioEncoding.supported_sample_rates() {
+            if !supported.contains(&sample_rate) {
+                violations.push(
+                    Violation::new(
+                        "audioConfig.sampleRateHertz",
+                        format!(
+                            "{} Hz is not supported by {:?}, expected one of {:?}",
+                            sample_rate,
+                            config.audioEncoding,
+                            supported
+                        )
+                    )
+                );
+            }
+        }
+    }
+
+    if let Some(profiles) = &config.effectsProfileId {
+        for profile in profiles {
+            if !KNOWN_EFFECTS_PROFILES.contains(&profile.as_str()) {
+                violations.push(
+                    Violation::new(
+                        "audioConfig.effectsProfileId",
+                        format!("unknown effects profile '{}'", profile)
+                    )
+                );
+            }
+        }
+    }
+}
+
+fn out_of_range<T: fmt::Display>(
+    field: &'static str,
+    value: T,
+    range: &RangeInclusive<T>
+) -> Violation {
+    Violation::new(
+        field,
+        format!("{} is outside of the range [{}, {}]", value, range.start(), range.end())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        services::synthesize::SynthesizeSpeechBuilder,
+        types::{ synthesize::Turn, voice_name::VoiceName },
+    };
+
+    fn builder(input: SynthesisInput) -> SynthesizeSpeechBuilder {
+        SynthesizeSpeechBuilder::new("token".to_string(), input)
+    }
+
+    fn text(text: &str) -> SynthesizeSpeechBuilder {
+        builder(SynthesisInput::Text(text.to_string()))
+    }
+
+    fn fields(result: Result<(), ValidationError>) -> Vec<&'static str> {
+        result.err().map_or_else(Vec::new, |error| {
+            error.violations
+                .iter()
+                .map(|violation| violation.field)
+                .collect()
+        })
+    }
+
+    #[test]
+    fn accepts_a_default_request() {
+        assert_eq!(text("Hello").validate(), Ok(()));
+    }
+
+    #[test]
+    fn checks_the_speaking_rate_range() {
+        for rate in [0.0, 0.25, 1.0, 4.0] {
+            assert_eq!(text("Hello").speaking_rate(rate).validate(), Ok(()), "{}", rate);
+        }
+        for rate in [0.1, 4.5, -1.0] {
+            let result = text("Hello").speaking_rate(rate).validate();
+            assert_eq!(fields(result), ["audioConfig.speakingRate"], "{}", rate);
+        }
+    }
+
+    #[test]
+    fn checks_the_pitch_volume_and_sample_rate_ranges() {
+        assert_eq!(text("Hello").pitch(-20.0).volume_gain_db(16.0).validate(), Ok(()));
+        assert_eq!(fields(text("Hello").pitch(20.5).validate()), ["audioConfig.pitch"]);
+        let result = text("Hello").volume_gain_db(-96.5).validate();
+        assert_eq!(fields(result), ["audioConfig.volumeGainDb"]);
+        let result = text("Hello").sample_rate_hertz(7999).validate();
+        assert_eq!(fields(result), ["audioConfig.sampleRateHertz"]);
+    }
+
+    #[test]
+    fn checks_the_sample_rates_of_the_encoding() {
+        let ogg = text("Hello").audio_encoding(AudioEncoding::OggOpus);
+        assert_eq!(ogg.clone().sample_rate_hertz(48000).validate(), Ok(()));
+        let error = ogg.sample_rate_hertz(44100).validate().unwrap_err();
+        assert_eq!(error.violations[0].field, "audioConfig.sampleRateHertz");
+        assert!(error.violations[0].message.contains("44100 Hz is not supported by OggOpus"));
+    }
+
+    #[test]
+    fn limits_the_input_to_5000_bytes() {
+        assert_eq!(text(&"a".repeat(MAX_INPUT_BYTES)).validate(), Ok(()));
+        let error = text(&"a".repeat(MAX_INPUT_BYTES + 1)).validate().unwrap_err();
+        assert_eq!(error.violations, [
+            Violation::new("input.text", "input is 5001 bytes, the limit is 5000 bytes"),
+        ]);
+        // The limit is in bytes, not characters.
+        let result = text(&"é".repeat(2501)).validate();
+        assert_eq!(fields(result), ["input.text"]);
+        let ssml = format!("<speak>{}</speak>", "a".repeat(MAX_INPUT_BYTES));
+        assert_eq!(fields(builder(SynthesisInput::Ssml(ssml)).validate()), ["input.ssml"]);
+    }
+
+    #[test]
+    fn lists_every_violation() {
+        let error = text("")
+            .speaking_rate(10.0)
+            .pitch(-30.0)
+            .volume_gain_db(20.0)
+            .effects_profile_id("loud-speaker")
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            error.violations
+                .iter()
+                .map(|violation| violation.field)
+                .collect::<Vec<_>>(),
+            [
+                "input.text",
+                "audioConfig.speakingRate",
+                "audioConfig.pitch",
+                "audioConfig.volumeGainDb",
+                "audioConfig.effectsProfileId",
+            ]
+        );
+        let message = error.to_string();
+        assert!(message.starts_with("invalid synthesis request: input.text: "));
+        assert_eq!(message.matches("; ").count(), 4);
+    }
+
+    #[test]
+    fn checks_the_input_against_the_voice() {
+        let journey: VoiceName = "en-US-Journey-F".parse().unwrap();
+        let ssml = builder(SynthesisInput::Ssml("<speak>Hi</speak>".to_string()));
+        let result = ssml.voice_name(journey).validate();
+        assert_eq!(fields(result), ["input.ssml"]);
+
+        let neural: VoiceName = "en-US-Neural2-F".parse().unwrap();
+        let result = text("Hi")
+            .voice_name(neural)
+            .language_code("fr-FR".parse().unwrap())
+            .validate();
+        assert_eq!(fields(result), ["voice.languageCode"]);
+    }
+
+    #[test]
+    fn checks_the_speakers_of_a_dialogue() {
+        let dialogue = |turns: Vec<Turn>| text("").dialogue(turns);
+        let turns = vec![Turn::new("R", "Hi"), Turn::new("S", "Hello"), Turn::new("U", "Hey")];
+        assert_eq!(dialogue(turns).validate(), Ok(()));
+
+        let result = dialogue(vec![Turn::new("R", "Hi"), Turn::new("A", "Hello")]).validate();
+        assert_eq!(fields(result), ["input.multiSpeakerMarkup.turns.speaker"]);
+        let error = dialogue(vec![Turn::new("r", "Hi")]).validate().unwrap_err();
+        let message = "turn 0 has speaker 'r', expected one of R, S, T, U";
+        assert_eq!(error.violations[0].message, message);
+
+        let result = dialogue(vec![Turn::new("R", " ")]).validate();
+        assert_eq!(fields(result), ["input.multiSpeakerMarkup.turns.text"]);
+        let result = dialogue(Vec::new()).validate();
+        assert_eq!(fields(result), ["input.multiSpeakerMarkup", "input.multiSpeakerMarkup.turns"]);
+    }
+
+    #[test]
+    fn requires_the_multi_speaker_voice_for_a_dialogue() {
+        let neural: VoiceName = "en-US-Neural2-F".parse().unwrap();
+        let result = text("").dialogue(vec![Turn::new("R", "Hi")]).voice_name(neural).validate();
+        assert_eq!(fields(result), ["voice.name"]);
+    }
+}
@@ -0,0 +1,280 @@
+// src/services/voice_resolver.rs
+
+use std::collections::{ HashMap, HashSet };
+
+use crate::{
+    types::{
+        language::LanguageTag,
+        synthesize::{ SsmlVoiceGender, VoiceSelectionParams },
+        voice_name::VoiceFamily,
+        voices::Voice,
+    },
+    services::voices::{ language_matches, VoiceCatalog },
+};
+
+/// What the caller would like to hear. Every field is a preference, not a requirement:
+/// the resolver falls back to other genders, tiers and regions rather than failing.
+#[derive(Debug, Clone)]
+pub struct VoicePreferences {
+    /// Language of the voice, e.g. `en-AU`.
+    pub language_code: LanguageTag,
+    /// Preferred gender of the voice.
+    pub gender: Option<SsmlVoiceGender>,
+    /// Preferred voice families, best first. Voices of other families are ranked after these.
+    pub families: Vec<VoiceFamily>,
+}
+
+impl VoicePreferences {
+    /// Creates preferences for `language_code`, preferring Neural2 over WaveNet over Standard voices.
+    pub fn new(language_code: LanguageTag) -> Self {
+        VoicePreferences {
+            language_code,
+            gender: None,
+            families: vec![VoiceFamily::Neural2, VoiceFamily::Wavenet, VoiceFamily::Standard],
+        }
+    }
+
+    pub fn gender(mut self, gender: SsmlVoiceGender) -> Self {
+        self.gender = Some(gender);
+        self
+    }
+
+    pub fn families(mut self, families: Vec<VoiceFamily>) -> Self {
+        self.families = families;
+        self
+    }
+}
+
+/// Picks concrete voices from a [VoiceCatalog] according to [VoicePreferences].
+///
+/// Languages are tried in order: the requested language, its configured region fallbacks
+/// (e.g. `en-AU` → `en-GB` → `en-US`), then any region of the same base language.
+/// Within a language, voices of the preferred gender come first, then by family.
+#[derive(Debug, Clone)]
+pub struct VoiceResolver {
+    region_fallbacks: HashMap<String, Vec<String>>,
+}
+
+impl Default for VoiceResolver {
+    fn default() -> Self {
+        VoiceResolver::empty()
+            .with_fallback("en-AU", &["en-GB", "en-US"])
+            .with_fallback("en-IN", &["en-GB", "en-US"])
+            .with_fallback("en-GB", &["en-US"])
+            .with_fallback("en-CA", &["en-US"])
+            .with_fallback("fr-CA", &["fr-FR"])
+            .with_fallback("es-US", &["es-ES"])
+            .with_fallback("pt-PT", &["pt-BR"])
+            .with_fallback("de-AT", &["de-DE"])
+            .with_fallback("de-CH", &["de-DE"])
+            .with_fallback("nl-BE", &["nl-NL"])
+            .with_fallback("zh-CN", &["cmn-CN"])
+            .with_fallback("zh-TW", &["cmn-TW"])
+            .with_fallback("zh-HK", &["yue-HK"])
+    }
+}
+
+impl VoiceResolver {
+    /// Creates a resolver without any region fallback.
+    pub fn empty() -> Self {
+        VoiceResolver { region_fallbacks: HashMap::new() }
+    }
+
+    /// Sets the languages tried, in order, when no voice is available for `language_code`.
+    pub fn with_fallback(mut self, language_code: &str, fallbacks: &[&str]) -> Self {
+        self.region_fallbacks.insert(
+            language_code.to_ascii_lowercase(),
+            fallbacks
+                .iter()
+                .map(|code| code.to_string())
+                .collect()
+        );
+        self
+    }
+
+    /// Returns the languages tried for `language_code`, in order.
+    pub fn language_chain(&self, language_code: &LanguageTag) -> Vec<String> {
+        let mut chain = vec![language_code.to_string()];
+        let key = language_code.as_str().to_ascii_lowercase();
+        if let Some(fallbacks) = self.region_fallbacks.get(&key) {
+            chain.extend(fallbacks.iter().cloned());
+        }
+        if language_code.base() != *language_code {
+            chain.push(language_code.language().to_string());
+        }
+        chain
+    }
+
+    /// Resolves every candidate voice for `preferences`, best first.
+    ///
+    /// The result can be passed to
+    /// [SynthesizeSpeechBuilder::voice_chain](crate::services::synthesize::SynthesizeSpeechBuilder::voice_chain)
+    /// so that the next voice is tried if the API rejects one. It is empty if the catalog has no
+    /// voice for the language or any of its fallbacks.
+    pub fn resolve(
+        &self,
+        catalog: &VoiceCatalog,
+        preferences: &VoicePreferences
+    ) -> Vec<VoiceSelectionParams> {
+        let mut seen = HashSet::new();
+        let mut resolved = Vec::new();
+
+        for language_code in self.language_chain(&preferences.language_code) {
+            let mut candidates: Vec<&Voice> = catalog
+                .iter()
+                .filter(|voice| !seen.contains(&voice.name))
+                .filter(|voice| {
+                    voice.languageCodes
+                        .iter()
+                        .any(|code| language_matches(code.as_str(), &language_code))
+                })
+                .collect();
+            candidates.sort_by_key(|voice| self.rank(voice, preferences));
+
+            for voice in candidates {
+                seen.insert(voice.name.clone());
+                resolved.push(VoiceSelectionParams {
+                    languageCode: matching_language_code(voice, &language_code),
+                    ssmlGender: voice.ssmlGender,
+                    ..VoiceSelectionParams::from_name(voice.name.clone())
+                });
+            }
+        }
+
+        resolved
+    }
+
+    /// Resolves the best voice for `preferences`, if any.
+    pub fn resolve_best(
+        &self,
+        catalog: &VoiceCatalog,
+        preferences: &VoicePreferences
+    ) -> Option<VoiceSelectionParams> {
+        self.resolve(catalog, preferences).into_iter().next()
+    }
+
+    fn rank(&self, voice: &Voice, preferences: &VoicePreferences) -> (bool, usize, String) {
+        let gender_mismatch = preferences.gender.is_some_and(|gender| gender != voice.ssmlGender);
+        let family = preferences.families
+            .iter()
+            .position(|preferred| preferred == voice.name.family())
+            .unwrap_or(preferences.families.len());
+        (gender_mismatch, family, voice.name.to_string())
+    }
+}
+
+/// Returns the language code of `voice` matching `language_code`, or the language of its name.
+fn matching_language_code(voice: &Voice, language_code: &str) -> LanguageTag {
+    voice.languageCodes
+        .iter()
+        .find(|code| language_matches(code.as_str(), language_code))
+        .unwrap_or(voice.name.language())
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::voice_name::VoiceName;
+
+    fn voice(name: &str, gender: SsmlVoiceGender) -> Voice {
+        let name: VoiceName = name.parse().unwrap();
+        Voice {
+            languageCodes: vec![name.language().clone()],
+            name,
+            ssmlGender: gender,
+            naturalSampleRateHertz: 24000,
+        }
+    }
+
+    fn names(voices: &[VoiceSelectionParams]) -> Vec<String> {
+        voices
+            .iter()
+            .map(|voice| format!("{} {}", voice.languageCode, voice.name.as_ref().unwrap()))
+            .collect()
+    }
+
+    fn english() -> VoiceCatalog {
+        VoiceCatalog::new(
+            vec![
+                voice("en-US-Standard-A", SsmlVoiceGender::Female),
+                voice("en-US-Wavenet-B", SsmlVoiceGender::Male),
+                voice("en-US-Neural2-D", SsmlVoiceGender::Male),
+                voice("en-US-Neural2-C", SsmlVoiceGender::Female),
+                voice("en-US-Chirp3-HD-Achernar", SsmlVoiceGender::Female),
+                voice("en-GB-Standard-B", SsmlVoiceGender::Male),
+                voice("en-GB-Neural2-A", SsmlVoiceGender::Female),
+                voice("fr-FR-Neural2-A", SsmlVoiceGender::Female)
+            ]
+        )
+    }
+
+    #[test]
+    fn chains_languages_through_their_fallbacks() {
+        let resolver = VoiceResolver::default();
+        let chain = |language: &str| resolver.language_chain(&language.parse().unwrap());
+        assert_eq!(chain("en-AU"), ["en-AU", "en-GB", "en-US", "en"]);
+        assert_eq!(chain("en-us"), ["en-US", "en"]);
+        assert_eq!(chain("fr"), ["fr"]);
+
+        let resolver = VoiceResolver::empty().with_fallback("EN-au", &["en-NZ"]);
+        let chain = resolver.language_chain(&"en-AU".parse().unwrap());
+        assert_eq!(chain, ["en-AU", "en-NZ", "en"]);
+    }
+
+    #[test]
+    fn ranks_by_gender_then_family_then_name() {
+        let resolver = VoiceResolver::default();
+        let preferences = VoicePreferences::new("en-US".parse().unwrap());
+        assert_eq!(names(&resolver.resolve(&english(), &preferences))[..5], [
+            "en-US en-US-Neural2-C",
+            "en-US en-US-Neural2-D",
+            "en-US en-US-Wavenet-B",
+            "en-US en-US-Standard-A",
+            "en-US en-US-Chirp3-HD-Achernar",
+        ]);
+
+        let preferences = preferences.gender(SsmlVoiceGender::Male);
+        assert_eq!(names(&resolver.resolve(&english(), &preferences))[..5], [
+            "en-US en-US-Neural2-D",
+            "en-US en-US-Wavenet-B",
+            "en-US en-US-Neural2-C",
+            "en-US en-US-Standard-A",
+            "en-US en-US-Chirp3-HD-Achernar",
+        ]);
+
+        let preferences = VoicePreferences::new("en-US".parse().unwrap())
+            .families(vec![VoiceFamily::Chirp3Hd]);
+        let best = resolver.resolve_best(&english(), &preferences).unwrap();
+        assert_eq!(best.name.unwrap().to_string(), "en-US-Chirp3-HD-Achernar");
+        assert_eq!(best.ssmlGender, SsmlVoiceGender::Female);
+    }
+
+    #[test]
+    fn falls_back_from_en_au_to_en_gb_then_en_us() {
+        let resolver = VoiceResolver::default();
+        let preferences = VoicePreferences::new("en-AU".parse().unwrap());
+        assert_eq!(names(&resolver.resolve(&english(), &preferences)), [
+            "en-GB en-GB-Neural2-A",
+            "en-GB en-GB-Standard-B",
+            "en-US en-US-Neural2-C",
+            "en-US en-US-Neural2-D",
+            "en-US en-US-Wavenet-B",
+            "en-US en-US-Standard-A",
+            "en-US en-US-Chirp3-HD-Achernar",
+        ]);
+
+        // Without fallbacks, any region of the language is ranked as one.
+        let resolver = VoiceResolver::empty();
+        let resolved = resolver.resolve(&english(), &preferences);
+        assert_eq!(names(&resolved)[..3], [
+            "en-GB en-GB-Neural2-A",
+            "en-US en-US-Neural2-C",
+            "en-US en-US-Neural2-D",
+        ]);
+
+        let preferences = VoicePreferences::new("ko-KR".parse().unwrap());
+        assert!(resolver.resolve(&english(), &preferences).is_empty());
+        assert!(resolver.resolve_best(&english(), &preferences).is_none());
+    }
+}
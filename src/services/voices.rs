@@ -0,0 +1,322 @@
+// src/services/voices.rs
+
+use std::time::{ Duration, Instant };
+
+use reqwest::Client;
+
+use crate::{
+    types::{
+        language::LanguageTag,
+        synthesize::SsmlVoiceGender,
+        voice_name::{ VoiceFamily, VoiceName },
+        voices::{ ListVoicesResponse, Voice },
+    },
+    services::{ endpoint::ApiVersion, synthesize::SynthesisError },
+};
+
+/// Fetches the voices supported by the TTS service.
+///
+/// # Arguments
+///
+/// * `access_token` - The OAuth access token used to authenticate the request.
+/// * `endpoint` - Base URL of the API, usually [DEFAULT_ENDPOINT](crate::services::endpoint::DEFAULT_ENDPOINT).
+/// * `language_code` - Optional BCP-47 language tag. If set, only voices that can be used to
+///   synthesize this language are returned.
+///
+/// # Returns
+///
+/// Returns a [Result] which is either the list of [Voice]s or a [SynthesisError].
+pub async fn list_voices(
+    access_token: &str,
+    endpoint: &str,
+    language_code: Option<&str>
+) -> Result<Vec<Voice>, SynthesisError> {
+    let client = Client::new();
+    let mut request = client
+        .get(ApiVersion::V1.url(endpoint, "voices"))
+        .bearer_auth(access_token);
+    if let Some(language_code) = language_code {
+        request = request.query(&[("languageCode", language_code)]);
+    }
+
+    let response = request.send().await?;
+
+    if response.status() != reqwest::StatusCode::OK {
+        return Err(SynthesisError::from_response(response).await);
+    }
+
+    Ok(response.json::<ListVoicesResponse>().await?.voices)
+}
+
+/// A snapshot of the voices returned by `voices.list`.
+///
+/// The catalog remembers when it was fetched so it can be cached by
+/// [TextToSpeechClient](crate::client::TextToSpeechClient), and offers filtering and paging
+/// helpers that can be chained, e.g. `catalog.for_language("en").with_gender(Female).page(0, 25)`.
+#[derive(Debug, Clone)]
+pub struct VoiceCatalog {
+    voices: Vec<Voice>,
+    fetched_at: Instant,
+}
+
+impl VoiceCatalog {
+    /// Creates a catalog from a list of voices, sorted by name.
+    pub fn new(mut voices: Vec<Voice>) -> Self {
+        voices.sort_by_cached_key(|voice| voice.name.to_string());
+        VoiceCatalog {
+            voices,
+            fetched_at: Instant::now(),
+        }
+    }
+
+    /// Returns the voices in the catalog.
+    pub fn voices(&self) -> &[Voice] {
+        &self.voices
+    }
+
+    /// Consumes the catalog and returns its voices.
+    pub fn into_voices(self) -> Vec<Voice> {
+        self.voices
+    }
+
+    pub fn len(&self) -> usize {
+        self.voices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.voices.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Voice> {
+        self.voices.iter()
+    }
+
+    /// Returns the instant at which the voices were fetched.
+    pub fn fetched_at(&self) -> Instant {
+        self.fetched_at
+    }
+
+    /// Returns `true` if the catalog is older than `ttl`.
+    pub fn is_expired(&self, ttl: Duration) -> bool {
+        self.fetched_at.elapsed() >= ttl
+    }
+
+    /// Looks up a voice by its name.
+    pub fn find(&self, name: &VoiceName) -> Option<&Voice> {
+        self.voices.iter().find(|voice| voice.name == *name)
+    }
+
+    /// Returns the voices matching `predicate`.
+    pub fn filter<P: FnMut(&Voice) -> bool>(&self, mut predicate: P) -> VoiceCatalog {
+        VoiceCatalog {
+            voices: self.voices
+                .iter()
+                .filter(|voice| predicate(voice))
+                .cloned()
+                .collect(),
+            fetched_at: self.fetched_at,
+        }
+    }
+
+    /// Returns the voices supporting `language_code`.
+    ///
+    /// Matching is case-insensitive and done per subtag, so `en` matches `en-US` and `en-GB`,
+    /// while `en-US` only matches `en-US`.
+    pub fn for_language(&self, language_code: &str) -> VoiceCatalog {
+        self.filter(|voice| {
+            voice.languageCodes.iter().any(|code| language_matches(code.as_str(), language_code))
+        })
+    }
+
+    /// Returns the voices of the given gender.
+    pub fn with_gender(&self, gender: SsmlVoiceGender) -> VoiceCatalog {
+        self.filter(|voice| voice.ssmlGender == gender)
+    }
+
+    /// Returns the voices of the given family.
+    pub fn with_family(&self, family: &VoiceFamily) -> VoiceCatalog {
+        self.filter(|voice| voice.name.family() == family)
+    }
+
+    /// Returns the voices whose name contains `pattern`, ignoring case (e.g. `Neural2`).
+    pub fn name_contains(&self, pattern: &str) -> VoiceCatalog {
+        let pattern = pattern.to_lowercase();
+        self.filter(|voice| voice.name.to_string().to_lowercase().contains(&pattern))
+    }
+
+    /// Returns every language code supported by at least one voice, sorted and deduplicated.
+    pub fn language_codes(&self) -> Vec<&str> {
+        let mut codes: Vec<&str> = self.voices
+            .iter()
+            .flat_map(|voice| voice.languageCodes.iter().map(LanguageTag::as_str))
+            .collect();
+        codes.sort_unstable();
+        codes.dedup();
+        codes
+    }
+
+    /// Returns the number of pages of `page_size` voices.
+    pub fn page_count(&self, page_size: usize) -> usize {
+        if page_size == 0 {
+            return 0;
+        }
+        self.voices.len().div_ceil(page_size)
+    }
+
+    /// Returns the zero-based page `page` of `page_size` voices. Out of range pages are empty.
+    pub fn page(&self, page: usize, page_size: usize) -> &[Voice] {
+        let start = page.saturating_mul(page_size).min(self.voices.len());
+        let end = start.saturating_add(page_size).min(self.voices.len());
+        &self.voices[start..end]
+    }
+}
+
+impl IntoIterator for VoiceCatalog {
+    type Item = Voice;
+    type IntoIter = std::vec::IntoIter<Voice>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.voices.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a VoiceCatalog {
+    type Item = &'a Voice;
+    type IntoIter = std::slice::Iter<'a, Voice>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.voices.iter()
+    }
+}
+
+/// Returns `true` if `code` is `filter` or one of its more specific tags.
+pub(crate) fn language_matches(code: &str, filter: &str) -> bool {
+    let mut code = code.split('-');
+    filter.split('-').all(|subtag| {
+        code.next().is_some_and(|other| other.eq_ignore_ascii_case(subtag))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::TestServer;
+
+    fn voice(name: &str, language_codes: &[&str], gender: SsmlVoiceGender) -> Voice {
+        Voice {
+            languageCodes: language_codes
+                .iter()
+                .map(|code| code.parse().unwrap())
+                .collect(),
+            name: name.parse().unwrap(),
+            ssmlGender: gender,
+            naturalSampleRateHertz: 24000,
+        }
+    }
+
+    fn catalog() -> VoiceCatalog {
+        VoiceCatalog::new(
+            vec![
+                voice("fr-FR-Neural2-A", &["fr-FR"], SsmlVoiceGender::Female),
+                voice("en-US-Wavenet-B", &["en-US"], SsmlVoiceGender::Male),
+                voice("en-GB-Neural2-A", &["en-GB"], SsmlVoiceGender::Female),
+                voice("en-US-Neural2-C", &["en-US", "es-US"], SsmlVoiceGender::Female),
+                voice("en-US-Neural2-D", &["en-US"], SsmlVoiceGender::Male)
+            ]
+        )
+    }
+
+    fn names(catalog: &VoiceCatalog) -> Vec<String> {
+        catalog
+            .iter()
+            .map(|voice| voice.name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn sorts_voices_by_name() {
+        assert_eq!(names(&catalog()), [
+            "en-GB-Neural2-A",
+            "en-US-Neural2-C",
+            "en-US-Neural2-D",
+            "en-US-Wavenet-B",
+            "fr-FR-Neural2-A",
+        ]);
+        let name = "en-US-Wavenet-B".parse().unwrap();
+        assert_eq!(catalog().find(&name).unwrap().ssmlGender, SsmlVoiceGender::Male);
+        assert!(catalog().find(&"de-DE-Neural2-A".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    fn filters_by_language_gender_family_and_name() {
+        let catalog = catalog();
+        assert_eq!(names(&catalog.for_language("en")), [
+            "en-GB-Neural2-A",
+            "en-US-Neural2-C",
+            "en-US-Neural2-D",
+            "en-US-Wavenet-B",
+        ]);
+        assert_eq!(names(&catalog.for_language("EN-us")).len(), 3);
+        assert_eq!(names(&catalog.for_language("es")), ["en-US-Neural2-C"]);
+        assert!(catalog.for_language("e").is_empty());
+        assert!(catalog.for_language("en-US-x").is_empty());
+
+        let female = catalog.for_language("en").with_gender(SsmlVoiceGender::Female);
+        assert_eq!(names(&female), ["en-GB-Neural2-A", "en-US-Neural2-C"]);
+        let wavenet = catalog.with_family(&VoiceFamily::Wavenet);
+        assert_eq!(names(&wavenet), ["en-US-Wavenet-B"]);
+        assert_eq!(names(&catalog.name_contains("neural2-a")), [
+            "en-GB-Neural2-A",
+            "fr-FR-Neural2-A",
+        ]);
+
+        assert_eq!(catalog.language_codes(), ["en-GB", "en-US", "es-US", "fr-FR"]);
+        assert_eq!(female.fetched_at(), catalog.fetched_at());
+    }
+
+    #[test]
+    fn pages_voices() {
+        let catalog = catalog();
+        assert_eq!(catalog.page_count(2), 3);
+        assert_eq!(catalog.page_count(5), 1);
+        assert_eq!(catalog.page_count(0), 0);
+
+        let page = |page: usize, size: usize| -> Vec<String> {
+            catalog
+                .page(page, size)
+                .iter()
+                .map(|voice| voice.name.to_string())
+                .collect()
+        };
+        assert_eq!(page(0, 2), ["en-GB-Neural2-A", "en-US-Neural2-C"]);
+        assert_eq!(page(2, 2), ["fr-FR-Neural2-A"]);
+        assert!(page(3, 2).is_empty());
+        assert!(page(0, 0).is_empty());
+        assert!(page(usize::MAX, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn expires_after_the_ttl() {
+        let catalog = VoiceCatalog::new(Vec::new());
+        assert!(catalog.is_expired(Duration::ZERO));
+        assert!(!catalog.is_expired(Duration::from_secs(3600)));
+    }
+
+    #[tokio::test]
+    async fn lists_voices_of_a_language() {
+        let server = TestServer::start(|_| {
+            let voices = r#"{"voices": [{"languageCodes": ["en-US"], "name": "en-US-Neural2-F",
+                "ssmlGender": "FEMALE", "naturalSampleRateHertz": 24000}]}"#;
+            (200, voices.to_string())
+        }).await;
+
+        let voices = list_voices("token", &server.endpoint, Some("en-US")).await.unwrap();
+        assert_eq!(voices.len(), 1);
+        list_voices("token", &server.endpoint, None).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/voices?languageCode=en-US");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer token"));
+        assert_eq!(requests[1].path, "/v1/voices");
+    }
+}
@@ -0,0 +1,286 @@
+// src/ssml/attributes.rs
+
+//! Attribute values of the SSML elements, limited to the ones Google Text-to-Speech supports.
+//! See <https://cloud.google.com/text-to-speech/docs/ssml>.
+
+use std::{ fmt, time::Duration };
+
+/// Formats `duration` as an SSML time designation, e.g. `2s` or `250ms`.
+pub fn format_time(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis.is_multiple_of(1000) {
+        format!("{}s", millis / 1000)
+    } else {
+        format!("{}ms", millis)
+    }
+}
+
+/// `strength` of a `<break>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BreakStrength {
+    None,
+    XWeak,
+    Weak,
+    Medium,
+    Strong,
+    XStrong,
+}
+
+impl BreakStrength {
+    pub const ALL: [BreakStrength; 6] = [
+        BreakStrength::None,
+        BreakStrength::XWeak,
+        BreakStrength::Weak,
+        BreakStrength::Medium,
+        BreakStrength::Strong,
+        BreakStrength::XStrong,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BreakStrength::None => "none",
+            BreakStrength::XWeak => "x-weak",
+            BreakStrength::Weak => "weak",
+            BreakStrength::Medium => "medium",
+            BreakStrength::Strong => "strong",
+            BreakStrength::XStrong => "x-strong",
+        }
+    }
+}
+
+/// `rate` of a `<prosody>`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProsodyRate {
+    XSlow,
+    Slow,
+    Medium,
+    Fast,
+    XFast,
+    Default,
+    /// Rate relative to the default, in percent: `80.0` is 80% of the default speed.
+    Percent(f32),
+}
+
+impl fmt::Display for ProsodyRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProsodyRate::XSlow => f.write_str("x-slow"),
+            ProsodyRate::Slow => f.write_str("slow"),
+            ProsodyRate::Medium => f.write_str("medium"),
+            ProsodyRate::Fast => f.write_str("fast"),
+            ProsodyRate::XFast => f.write_str("x-fast"),
+            ProsodyRate::Default => f.write_str("default"),
+            ProsodyRate::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+/// `pitch` of a `<prosody>`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProsodyPitch {
+    XLow,
+    Low,
+    Medium,
+    High,
+    XHigh,
+    Default,
+    /// Relative change in semitones, e.g. `-2.0` for `-2st`.
+    Semitones(f32),
+    /// Relative change in percent, e.g. `10.0` for `+10%`.
+    Percent(f32),
+}
+
+impl fmt::Display for ProsodyPitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProsodyPitch::XLow => f.write_str("x-low"),
+            ProsodyPitch::Low => f.write_str("low"),
+            ProsodyPitch::Medium => f.write_str("medium"),
+            ProsodyPitch::High => f.write_str("high"),
+            ProsodyPitch::XHigh => f.write_str("x-high"),
+            ProsodyPitch::Default => f.write_str("default"),
+            ProsodyPitch::Semitones(semitones) => write!(f, "{:+}st", semitones),
+            ProsodyPitch::Percent(percent) => write!(f, "{:+}%", percent),
+        }
+    }
+}
+
+/// `volume` of a `<prosody>`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProsodyVolume {
+    Silent,
+    XSoft,
+    Soft,
+    Medium,
+    Loud,
+    XLoud,
+    Default,
+    /// Relative change in decibels, e.g. `-6.0` for `-6dB`.
+    Decibels(f32),
+}
+
+impl fmt::Display for ProsodyVolume {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProsodyVolume::Silent => f.write_str("silent"),
+            ProsodyVolume::XSoft => f.write_str("x-soft"),
+            ProsodyVolume::Soft => f.write_str("soft"),
+            ProsodyVolume::Medium => f.write_str("medium"),
+            ProsodyVolume::Loud => f.write_str("loud"),
+            ProsodyVolume::XLoud => f.write_str("x-loud"),
+            ProsodyVolume::Default => f.write_str("default"),
+            ProsodyVolume::Decibels(decibels) => write!(f, "{:+}dB", decibels),
+        }
+    }
+}
+
+/// `level` of an `<emphasis>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmphasisLevel {
+    Strong,
+    Moderate,
+    None,
+    Reduced,
+}
+
+impl EmphasisLevel {
+    pub const ALL: [EmphasisLevel; 4] = [
+        EmphasisLevel::Strong,
+        EmphasisLevel::Moderate,
+        EmphasisLevel::None,
+        EmphasisLevel::Reduced,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmphasisLevel::Strong => "strong",
+            EmphasisLevel::Moderate => "moderate",
+            EmphasisLevel::None => "none",
+            EmphasisLevel::Reduced => "reduced",
+        }
+    }
+}
+
+/// `interpret-as` of a `<say-as>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InterpretAs {
+    Currency,
+    Telephone,
+    Verbatim,
+    SpellOut,
+    Date,
+    Characters,
+    Cardinal,
+    Ordinal,
+    Fraction,
+    /// Bleeps the content out.
+    Expletive,
+    Unit,
+    Time,
+}
+
+impl InterpretAs {
+    pub const ALL: [InterpretAs; 12] = [
+        InterpretAs::Currency,
+        InterpretAs::Telephone,
+        InterpretAs::Verbatim,
+        InterpretAs::SpellOut,
+        InterpretAs::Date,
+        InterpretAs::Characters,
+        InterpretAs::Cardinal,
+        InterpretAs::Ordinal,
+        InterpretAs::Fraction,
+        InterpretAs::Expletive,
+        InterpretAs::Unit,
+        InterpretAs::Time,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InterpretAs::Currency => "currency",
+            InterpretAs::Telephone => "telephone",
+            InterpretAs::Verbatim => "verbatim",
+            InterpretAs::SpellOut => "spell-out",
+            InterpretAs::Date => "date",
+            InterpretAs::Characters => "characters",
+            InterpretAs::Cardinal => "cardinal",
+            InterpretAs::Ordinal => "ordinal",
+            InterpretAs::Fraction => "fraction",
+            InterpretAs::Expletive => "expletive",
+            InterpretAs::Unit => "unit",
+            InterpretAs::Time => "time",
+        }
+    }
+}
+
+/// `alphabet` of a `<phoneme>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhoneticAlphabet {
+    Ipa,
+    XSampa,
+}
+
+impl PhoneticAlphabet {
+    pub const ALL: [PhoneticAlphabet; 2] = [PhoneticAlphabet::Ipa, PhoneticAlphabet::XSampa];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PhoneticAlphabet::Ipa => "ipa",
+            PhoneticAlphabet::XSampa => "x-sampa",
+        }
+    }
+}
+
+/// `gender` of a `<voice>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VoiceGender {
+    Male,
+    Female,
+    Neutral,
+}
+
+impl VoiceGender {
+    pub const ALL: [VoiceGender; 3] = [
+        VoiceGender::Male,
+        VoiceGender::Female,
+        VoiceGender::Neutral,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VoiceGender::Male => "male",
+            VoiceGender::Female => "female",
+            VoiceGender::Neutral => "neutral",
+        }
+    }
+}
+
+impl fmt::Display for BreakStrength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for EmphasisLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for InterpretAs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for PhoneticAlphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for VoiceGender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,182 @@
+// src/ssml/builder.rs
+
+use std::time::Duration;
+
+use crate::{
+    ssml::{
+        attributes::{ BreakStrength, EmphasisLevel, InterpretAs, PhoneticAlphabet },
+        node::{ Audio, Break, Media, MediaContent, Node, Prosody, SayAs, Speak, Voice },
+    },
+    types::language::LanguageTag,
+};
+
+/// Fluent builder of SSML content.
+///
+/// Nested elements are built with a closure receiving a fresh builder. Text is escaped when
+/// the document is serialized, so it can be passed as is.
+///
+/// ```
+/// use std::time::Duration;
+/// use google_tts_api::ssml::{ attributes::ProsodyRate, builder::SsmlBuilder, node::Prosody };
+///
+/// let ssml = SsmlBuilder::new()
+///     .paragraph(|p| p.sentence(|s| s.text("Tom & Jerry")).pause(Duration::from_millis(300)))
+///     .prosody(Prosody::new().rate(ProsodyRate::Slow), |b| b.text("slowly"))
+///     .to_ssml();
+/// assert_eq!(
+///     ssml,
+///     "<speak><p><s>Tom &amp; Jerry</s><break time=\"300ms\"/></p>\
+///      <prosody rate=\"slow\">slowly</prosody></speak>"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SsmlBuilder {
+    nodes: Vec<Node>,
+}
+
+impl SsmlBuilder {
+    pub fn new() -> Self {
+        SsmlBuilder::default()
+    }
+
+    /// Appends a node built elsewhere.
+    pub fn node(mut self, node: Node) -> Self {
+        self.nodes.push(node);
+        self
+    }
+
+    pub fn text(self, text: &str) -> Self {
+        self.node(Node::Text(text.to_string()))
+    }
+
+    pub fn paragraph(self, content: impl FnOnce(SsmlBuilder) -> SsmlBuilder) -> Self {
+        self.node(Node::Paragraph(build(content)))
+    }
+
+    pub fn sentence(self, content: impl FnOnce(SsmlBuilder) -> SsmlBuilder) -> Self {
+        self.node(Node::Sentence(build(content)))
+    }
+
+    /// Appends a `<break>` of the given duration.
+    pub fn pause(self, time: Duration) -> Self {
+        self.node(Node::Break(Break { time: Some(time), strength: None }))
+    }
+
+    /// Appends a `<break>` of the given strength.
+    pub fn break_strength(self, strength: BreakStrength) -> Self {
+        self.node(Node::Break(Break { time: None, strength: Some(strength) }))
+    }
+
+    pub fn prosody(
+        self,
+        prosody: Prosody,
+        content: impl FnOnce(SsmlBuilder) -> SsmlBuilder
+    ) -> Self {
+        self.node(Node::Prosody(prosody, build(content)))
+    }
+
+    pub fn emphasis(
+        self,
+        level: EmphasisLevel,
+        content: impl FnOnce(SsmlBuilder) -> SsmlBuilder
+    ) -> Self {
+        self.node(Node::Emphasis(Some(level), build(content)))
+    }
+
+    /// Appends a `<say-as>` reading `text` as `interpret_as`. Use [SsmlBuilder::node] with
+    /// a [SayAs] to set a format or detail.
+    pub fn say_as(self, interpret_as: InterpretAs, text: &str) -> Self {
+        self.node(Node::SayAs(SayAs::new(interpret_as, text)))
+    }
+
+    /// Appends `text`, read as `alias`.
+    pub fn sub(self, alias: &str, text: &str) -> Self {
+        self.node(Node::Sub { alias: alias.to_string(), text: text.to_string() })
+    }
+
+    /// Appends `text`, read with the pronunciation `ph` written in `alphabet`.
+    pub fn phoneme(self, alphabet: PhoneticAlphabet, ph: &str, text: &str) -> Self {
+        self.node(Node::Phoneme { alphabet, ph: ph.to_string(), text: text.to_string() })
+    }
+
+    /// Appends a `<mark>`, returned as a timepoint when SSML marks are enabled.
+    pub fn mark(self, name: &str) -> Self {
+        self.node(Node::Mark(name.to_string()))
+    }
+
+    pub fn audio(self, audio: Audio) -> Self {
+        self.node(Node::Audio(audio))
+    }
+
+    /// Appends a `<par>`, whose media elements play at the same time.
+    pub fn par(self, media: impl FnOnce(MediaBuilder) -> MediaBuilder) -> Self {
+        self.node(Node::Par(media(MediaBuilder::default()).media))
+    }
+
+    /// Appends a `<seq>`, whose media elements play one after the other.
+    pub fn seq(self, media: impl FnOnce(MediaBuilder) -> MediaBuilder) -> Self {
+        self.node(Node::Seq(media(MediaBuilder::default()).media))
+    }
+
+    pub fn voice(self, voice: Voice, content: impl FnOnce(SsmlBuilder) -> SsmlBuilder) -> Self {
+        self.node(Node::Voice(voice, build(content)))
+    }
+
+    pub fn lang(
+        self,
+        language: LanguageTag,
+        content: impl FnOnce(SsmlBuilder) -> SsmlBuilder
+    ) -> Self {
+        self.node(Node::Lang(language, build(content)))
+    }
+
+    /// Returns the nodes built so far.
+    pub fn into_nodes(self) -> Vec<Node> {
+        self.nodes
+    }
+
+    /// Wraps the content in a `<speak>` document.
+    pub fn build(self) -> Speak {
+        Speak::new(self.nodes)
+    }
+
+    /// Serializes the content as a `<speak>` document.
+    pub fn to_ssml(self) -> String {
+        self.build().to_string()
+    }
+}
+
+fn build(content: impl FnOnce(SsmlBuilder) -> SsmlBuilder) -> Vec<Node> {
+    content(SsmlBuilder::new()).into_nodes()
+}
+
+/// Builder of the `<media>` elements of a `<par>` or `<seq>`.
+#[derive(Debug, Clone, Default)]
+pub struct MediaBuilder {
+    media: Vec<Media>,
+}
+
+impl MediaBuilder {
+    /// Appends a `<media>` element speaking `content`, with the timing of `media`, e.g.
+    /// `Media::default().id("question").begin("0.5s")`. The content of `media` is replaced.
+    pub fn speak(
+        mut self,
+        media: Media,
+        content: impl FnOnce(SsmlBuilder) -> SsmlBuilder
+    ) -> Self {
+        self.media.push(Media { content: MediaContent::Speak(build(content)), ..media });
+        self
+    }
+
+    /// Appends a `<media>` element playing `audio`, with the timing of `media`.
+    pub fn audio(mut self, media: Media, audio: Audio) -> Self {
+        self.media.push(Media { content: MediaContent::Audio(audio), ..media });
+        self
+    }
+
+    /// Appends a `<media>` element as is.
+    pub fn media(mut self, media: Media) -> Self {
+        self.media.push(media);
+        self
+    }
+}
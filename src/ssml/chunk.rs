@@ -0,0 +1,475 @@
+// src/ssml/chunk.rs
+
+//! Splitting of inputs too long for a single request.
+//!
+//! Text is split at paragraph boundaries first, then at sentence, clause and word boundaries
+//! for the parts still too long, and only as a last resort between characters. SSML documents
+//! are split between elements, and inside the text and the `<speak>`, `<p>`, `<s>`,
+//! `<prosody>`, `<emphasis>`, `<voice>` and `<lang>` elements too long to fit; each chunk is
+//! a complete document, with the elements it's inside of opened again.
+
+use crate::{
+    ssml::{ convert::split_sentences, parser::{ markup_end, tag_name } },
+    types::synthesize::SynthesisInput,
+};
+
+/// Elements whose content can be split across chunks, each chunk repeating the element.
+const SPLITTABLE_ELEMENTS: [&str; 7] = ["speak", "p", "s", "prosody", "emphasis", "voice", "lang"];
+
+/// Splits `input` into inputs of at most `max_bytes`, counted as the API does. Multi-speaker
+/// inputs are returned whole.
+pub fn split_input(input: &SynthesisInput, max_bytes: usize) -> Vec<SynthesisInput> {
+    match input {
+        SynthesisInput::Text(text) => {
+            split_text(text, max_bytes).into_iter().map(SynthesisInput::Text).collect()
+        }
+        SynthesisInput::Ssml(ssml) => {
+            split_ssml(ssml, max_bytes).into_iter().map(SynthesisInput::Ssml).collect()
+        }
+        SynthesisInput::Markup(markup) => {
+            split_text(markup, max_bytes).into_iter().map(SynthesisInput::Markup).collect()
+        }
+        SynthesisInput::MultiSpeaker(_) => vec![input.clone()],
+    }
+}
+
+/// Splits plain text into chunks of at most `max_bytes`.
+pub fn split_text(text: &str, max_bytes: usize) -> Vec<String> {
+    if text.len() <= max_bytes {
+        return vec![text.to_string()];
+    }
+    let mut packer = Packer::new(max_bytes, false);
+    packer.push_text(text, Boundary::Paragraph);
+    packer.finish()
+}
+
+/// Splits an SSML document into documents of at most `max_bytes`, without breaking elements
+/// apart. A chunk can still be larger when an element that can't be split is, or one whose
+/// tags take half of `max_bytes` or more; documents that aren't well-formed, or whose
+/// `<speak>` tags are that long, are returned whole.
+pub fn split_ssml(ssml: &str, max_bytes: usize) -> Vec<String> {
+    if ssml.len() <= max_bytes {
+        return vec![ssml.to_string()];
+    }
+    match root(ssml) {
+        Some(Piece::Element { open, close, children, .. }) if
+            leaves_room(open, close, max_bytes)
+        => {
+            split_element(open, &children, close, max_bytes)
+        }
+        _ => vec![ssml.to_string()],
+    }
+}
+
+/// Where text is split, from the most to the least preferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Boundary {
+    Paragraph,
+    Sentence,
+    Clause,
+    Word,
+    Character,
+}
+
+impl Boundary {
+    fn next(self) -> Option<Boundary> {
+        match self {
+            Boundary::Paragraph => Some(Boundary::Sentence),
+            Boundary::Sentence => Some(Boundary::Clause),
+            Boundary::Clause => Some(Boundary::Word),
+            Boundary::Word => Some(Boundary::Character),
+            Boundary::Character => None,
+        }
+    }
+
+    /// Returns what joins pieces split at this boundary back together.
+    fn separator(self) -> &'static str {
+        match self {
+            Boundary::Paragraph => "\n\n",
+            Boundary::Character => "",
+            _ => " ",
+        }
+    }
+
+    /// Splits `text` into trimmed pieces. Pieces split between characters hold at most
+    /// `max_bytes`, without breaking entity references if the text is `escaped`.
+    fn split(self, text: &str, max_bytes: usize, escaped: bool) -> Vec<String> {
+        let pieces = match self {
+            Boundary::Paragraph => split_paragraphs(text),
+            Boundary::Sentence => split_sentences(text),
+            Boundary::Clause => split_clauses(text),
+            Boundary::Word => text.split_whitespace().map(str::to_string).collect(),
+            Boundary::Character => split_characters(text, max_bytes, escaped),
+        };
+        pieces
+            .into_iter()
+            .map(|piece| piece.trim().to_string())
+            .filter(|piece| !piece.is_empty())
+            .collect()
+    }
+}
+
+fn split_paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            paragraphs.push(paragraph.join("\n"));
+            paragraph.clear();
+        } else {
+            paragraph.push(line);
+        }
+    }
+    paragraphs.push(paragraph.join("\n"));
+    paragraphs
+}
+
+/// Splits after commas, semicolons, colons and dashes followed by a space, and after their
+/// full-width forms.
+fn split_clauses(text: &str) -> Vec<String> {
+    let mut clauses = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+        let half_width = ",;:—–".contains(c) && next.is_some_and(char::is_whitespace);
+        if half_width || "、，；：".contains(c) {
+            let end = i + c.len_utf8();
+            clauses.push(text[start..end].to_string());
+            start = end;
+        }
+    }
+    clauses.push(text[start..].to_string());
+    clauses
+}
+
+fn split_characters(text: &str, max_bytes: usize, escaped: bool) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while rest.len() > max_bytes {
+        let mut end = max_bytes.max(1);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        if escaped {
+            // Cut before an entity reference rather than through it.
+            if let Some(ampersand) = rest[..end].rfind('&') {
+                if !rest[ampersand..end].contains(';') && ampersand > 0 {
+                    end = ampersand;
+                }
+            }
+        }
+        if end == 0 {
+            end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+        }
+        pieces.push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+    pieces.push(rest.to_string());
+    pieces
+}
+
+/// Gathers pieces of content into chunks of at most `max_bytes`.
+struct Packer {
+    max_bytes: usize,
+    /// Whether the content is XML, whose entity references must not be split.
+    escaped: bool,
+    current: String,
+    chunks: Vec<String>,
+}
+
+impl Packer {
+    fn new(max_bytes: usize, escaped: bool) -> Self {
+        Packer { max_bytes, escaped, current: String::new(), chunks: Vec::new() }
+    }
+
+    fn fits(&self, length: usize) -> bool {
+        self.current.len() + length <= self.max_bytes
+    }
+
+    /// Ends the current chunk.
+    fn flush(&mut self) {
+        let chunk = self.current.trim();
+        if !chunk.is_empty() {
+            self.chunks.push(chunk.to_string());
+        }
+        self.current.clear();
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        self.flush();
+        self.chunks
+    }
+
+    /// Appends content that can't be split, in a new chunk if it doesn't fit in this one.
+    fn push_whole(&mut self, content: &str) {
+        if !self.fits(content.len()) {
+            self.flush();
+        }
+        self.current.push_str(content);
+    }
+
+    /// Appends text, split at `boundary` if it doesn't fit in the current chunk.
+    fn push_text(&mut self, text: &str, boundary: Boundary) {
+        if self.fits(text.len()) {
+            self.current.push_str(text);
+            return;
+        }
+
+        let separator = boundary.separator();
+        let pieces = boundary.split(text, self.max_bytes, self.escaped);
+        let last = pieces.len().saturating_sub(1);
+        for (i, mut piece) in pieces.into_iter().enumerate() {
+            // Keep the text apart from the elements around it.
+            if i == 0 && text.starts_with(char::is_whitespace) {
+                piece.insert(0, ' ');
+            }
+            if i == last && text.ends_with(char::is_whitespace) {
+                piece.push(' ');
+            }
+            let separator = if i == 0 || self.current.is_empty() { "" } else { separator };
+
+            if self.fits(separator.len() + piece.len()) {
+                self.current.push_str(separator);
+                self.current.push_str(&piece);
+            } else if piece.trim().len() <= self.max_bytes {
+                self.flush();
+                self.current.push_str(piece.trim_start());
+            } else if let Some(next) = boundary.next() {
+                self.current.push_str(separator);
+                self.push_text(&piece, next);
+            } else {
+                self.flush();
+                self.chunks.push(piece.trim().to_string());
+            }
+        }
+    }
+
+    /// Appends SSML content.
+    fn push_pieces(&mut self, pieces: &[Piece]) {
+        for piece in pieces {
+            match piece {
+                Piece::Text(text) => self.push_text(text, Boundary::Paragraph),
+                Piece::Markup(markup) => self.push_whole(markup),
+                // Repeating tags that take most of a chunk would leave only a few characters
+                // of content in each, so such an element is kept whole in an oversized chunk
+                // rather than losing the voice, language or prosody it sets.
+                Piece::Element { name, raw, open, close, .. } if
+                    raw.len() <= self.max_bytes ||
+                    !SPLITTABLE_ELEMENTS.contains(name) ||
+                    !leaves_room(open, close, self.max_bytes)
+                => {
+                    self.push_whole(raw);
+                }
+                Piece::Element { open, close, children, .. } => {
+                    self.flush();
+                    let chunks = split_element(open, children, close, self.max_bytes);
+                    self.chunks.extend(chunks);
+                }
+            }
+        }
+    }
+}
+
+/// Returns `true` if the tags of an element leave at least half of `max_bytes` to its content.
+fn leaves_room(open: &str, close: &str, max_bytes: usize) -> bool {
+    (open.len() + close.len()) * 2 <= max_bytes
+}
+
+/// Splits the content of an element into chunks of at most `max_bytes`, each wrapped in the
+/// element's tags, which must [leave room](leaves_room) for it.
+fn split_element(open: &str, children: &[Piece], close: &str, max_bytes: usize) -> Vec<String> {
+    let mut packer = Packer::new(max_bytes - (open.len() + close.len()), true);
+    packer.push_pieces(children);
+    packer
+        .finish()
+        .into_iter()
+        .map(|content| format!("{}{}{}", open, content, close))
+        .collect()
+}
+
+/// Part of an SSML document, borrowed from its source.
+enum Piece<'a> {
+    Text(&'a str),
+    /// A comment, a CDATA section, a processing instruction or an empty element.
+    Markup(&'a str),
+    Element {
+        name: &'a str,
+        open: &'a str,
+        close: &'a str,
+        /// The whole element, tags included.
+        raw: &'a str,
+        children: Vec<Piece<'a>>,
+    },
+}
+
+/// Reads the root element of a document, skipping the XML declaration and comments before it.
+fn root(source: &str) -> Option<Piece<'_>> {
+    let mut rest = source.trim_start();
+    while rest.starts_with("<?") || rest.starts_with("<!") {
+        rest = rest[markup_end(rest)..].trim_start();
+    }
+    element(rest).map(|(piece, _)| piece)
+}
+
+/// Reads the element starting `source`, and returns it with its length.
+fn element(source: &str) -> Option<(Piece<'_>, usize)> {
+    let open_end = markup_end(source);
+    let open = &source[..open_end];
+    if !open.ends_with('>') {
+        return None;
+    }
+    if open.ends_with("/>") {
+        return Some((Piece::Markup(open), open_end));
+    }
+
+    let name = tag_name(open);
+    let mut children = Vec::new();
+    let mut offset = open_end;
+    loop {
+        let rest = &source[offset..];
+        if rest.is_empty() {
+            return None;
+        }
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            children.push(Piece::Text(&rest[..end]));
+            offset += end;
+        } else if rest.starts_with("</") {
+            let end = markup_end(rest);
+            let close = &rest[..end];
+            if tag_name(close) != name {
+                return None;
+            }
+            offset += end;
+            let raw = &source[..offset];
+            return Some((Piece::Element { name, open, close, raw, children }, offset));
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            let end = markup_end(rest);
+            children.push(Piece::Markup(&rest[..end]));
+            offset += end;
+        } else {
+            let (child, length) = element(rest)?;
+            children.push(child);
+            offset += length;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssml::lint;
+
+    /// Checks that every chunk holds at most `max_bytes`, is a valid document and has no
+    /// entity reference cut in two.
+    fn check_ssml(chunks: &[String], max_bytes: usize) {
+        for chunk in chunks {
+            assert!(chunk.len() <= max_bytes, "{:?} is longer than {}", chunk, max_bytes);
+            assert_eq!(lint::errors(chunk), [], "{:?}", chunk);
+            for (i, _) in chunk.match_indices('&') {
+                let reference = &chunk[i..chunk[i..].find(';').map_or(chunk.len(), |end| i + end)];
+                assert!(
+                    ["&amp", "&lt", "&gt", "&quot", "&apos"].contains(&reference),
+                    "{:?} cuts {:?}",
+                    chunk,
+                    reference
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn splits_text_at_the_best_boundary() {
+        let text = "First sentence here. Second one, with a clause; and more.\n\nA new paragraph.";
+        assert_eq!(split_text(text, 100), [text]);
+        assert_eq!(split_text(text, 30), [
+            "First sentence here.",
+            "Second one, with a clause;",
+            "and more.\n\nA new paragraph.",
+        ]);
+        assert_eq!(split_text("abcdéfgh", 3), ["abc", "dé", "fgh"]);
+    }
+
+    #[test]
+    fn text_chunks_hold_at_most_max_bytes() {
+        let text = "Xin chào các bạn, hôm nay trời đẹp quá! 日本語の文章も、ここにあります。\n\n"
+            .repeat(20);
+        // Down to the length of the longest character, which can't be split.
+        for max_bytes in [4, 5, 17, 64, 300] {
+            let chunks = split_text(&text, max_bytes);
+            assert!(chunks.iter().all(|chunk| chunk.len() <= max_bytes), "{:?}", chunks);
+            let joined: String = chunks.concat();
+            let expected: String = text.split_whitespace().collect();
+            assert_eq!(joined.split_whitespace().collect::<String>(), expected);
+        }
+    }
+
+    #[test]
+    fn splits_ssml_into_valid_documents() {
+        let ssml = concat!(
+            "<speak><p>Tom &amp; Jerry &amp; Spike &amp; Tyke.</p>",
+            r#"<break time="1s"/><p>Short.</p></speak>"#
+        );
+        let chunks = split_ssml(ssml, 40);
+        assert_eq!(chunks, [
+            "<speak><p>Tom &amp;</p></speak>",
+            "<speak><p>Jerry &amp;</p></speak>",
+            "<speak><p>Spike &amp; Tyke.</p></speak>",
+            r#"<speak><break time="1s"/></speak>"#,
+            "<speak><p>Short.</p></speak>",
+        ]);
+        check_ssml(&chunks, 40);
+
+        let ssml = format!(
+            concat!(
+                "<speak><p><s>{}</s></p>",
+                r#"<prosody rate="slow"><emphasis>{}</emphasis></prosody></speak>"#
+            ),
+            "Les élèves &lt;3 &quot;Ça va&quot;, dit-il. ".repeat(10),
+            "&amp;&amp;&amp;&amp;&amp;&amp;&amp;&amp;&amp;&amp;&amp;&amp;"
+        );
+        for max_bytes in [100, 120, 250, 1000] {
+            check_ssml(&split_ssml(&ssml, max_bytes), max_bytes);
+        }
+    }
+
+    #[test]
+    fn keeps_wrappers_leaving_no_room_for_content_whole() {
+        let prosody = concat!(
+            r#"<prosody rate="slow" pitch="+2st" volume="loud">"#,
+            "one two three four five six</prosody>"
+        );
+        let ssml = format!("<speak><p>Before it.</p>{}<p>After it.</p></speak>", prosody);
+        let chunks = split_ssml(&ssml, 50);
+        assert_eq!(chunks, [
+            "<speak><p>Before it.</p></speak>".to_string(),
+            format!("<speak>{}</speak>", prosody),
+            "<speak><p>After it.</p></speak>".to_string(),
+        ]);
+        check_ssml(&[chunks[0].clone(), chunks[2].clone()], 50);
+        assert_eq!(lint::errors(&chunks[1]), []);
+
+        // Nor is the root element dropped.
+        let ssml = concat!(
+            r#"<speak xml:lang="en-US" xmlns="http://www.w3.org/2001/10/synthesis">"#,
+            "Hi</speak>"
+        );
+        assert_eq!(split_ssml(ssml, 50), [ssml]);
+    }
+
+    #[test]
+    fn returns_documents_that_are_not_well_formed_whole() {
+        let ssml = "<speak><p>one two three four five</speak>";
+        assert_eq!(split_ssml(ssml, 20), [ssml]);
+    }
+
+    #[test]
+    fn splits_inputs_by_kind() {
+        let text = SynthesisInput::Text("one two three".to_string());
+        assert_eq!(split_input(&text, 8), [
+            SynthesisInput::Text("one two".to_string()),
+            SynthesisInput::Text("three".to_string()),
+        ]);
+    }
+}
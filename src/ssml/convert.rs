@@ -0,0 +1,482 @@
+// src/ssml/convert.rs
+
+//! Conversion of plain text into structured SSML.
+
+use std::time::Duration;
+
+use crate::ssml::node::{ Break, Node, Speak };
+
+/// Pause after a heading, by default.
+pub const DEFAULT_HEADING_PAUSE: Duration = Duration::from_millis(600);
+
+/// Pause after a list item, by default.
+pub const DEFAULT_LIST_ITEM_PAUSE: Duration = Duration::from_millis(300);
+
+/// Pause in place of an ellipsis, by default.
+pub const DEFAULT_ELLIPSIS_PAUSE: Duration = Duration::from_millis(400);
+
+/// Longest single line, in words, still taken for a heading.
+const MAX_HEADING_WORDS: usize = 12;
+
+/// Words followed by a period that don't end a sentence, compared in lowercase.
+const ABBREVIATIONS: &[&str] = &[
+    "mr",
+    "mrs",
+    "ms",
+    "dr",
+    "prof",
+    "sr",
+    "jr",
+    "st",
+    "vs",
+    "etc",
+    "e.g",
+    "i.e",
+    "no",
+    "fig",
+    "approx",
+    "inc",
+    "ltd",
+    "co",
+];
+
+/// Converts plain text into an SSML document, so that long texts are read with the pauses
+/// a reader would make.
+///
+/// Blocks separated by blank lines become `<p>` paragraphs and their sentences `<s>`
+/// elements. A block made of one short line without final punctuation, or a line underlined
+/// with `===` or `---`, is read as a heading. Lines starting with `-`, `*`, `+`, `•` or a
+/// number such as `1.` are read as list items, without their marker. Text is escaped when
+/// the document is serialized.
+///
+/// The result converts into a [SynthesisInput](crate::types::synthesize::SynthesisInput),
+/// e.g. `builder.input(TextConverter::new().convert(text).into())`.
+#[derive(Debug, Clone)]
+pub struct TextConverter {
+    heading_pause: Duration,
+    list_item_pause: Duration,
+    ellipsis_pause: Duration,
+    sentence_marks: Option<String>,
+}
+
+impl Default for TextConverter {
+    fn default() -> Self {
+        TextConverter {
+            heading_pause: DEFAULT_HEADING_PAUSE,
+            list_item_pause: DEFAULT_LIST_ITEM_PAUSE,
+            ellipsis_pause: DEFAULT_ELLIPSIS_PAUSE,
+            sentence_marks: None,
+        }
+    }
+}
+
+impl TextConverter {
+    pub fn new() -> Self {
+        TextConverter::default()
+    }
+
+    /// Sets the pause after headings; [Duration::ZERO] adds none.
+    pub fn heading_pause(mut self, pause: Duration) -> Self {
+        self.heading_pause = pause;
+        self
+    }
+
+    /// Sets the pause after list items; [Duration::ZERO] adds none.
+    pub fn list_item_pause(mut self, pause: Duration) -> Self {
+        self.list_item_pause = pause;
+        self
+    }
+
+    /// Sets the pause read in place of `...` or `…`; [Duration::ZERO] keeps the ellipsis as text.
+    pub fn ellipsis_pause(mut self, pause: Duration) -> Self {
+        self.ellipsis_pause = pause;
+        self
+    }
+
+    /// Starts each sentence with a `<mark>` named `prefix` followed by the index of the
+    /// sentence in the document, from 0, e.g. `s0`, `s1`. Combined with
+    /// [enable_ssml_marks](crate::services::synthesize::SynthesizeSpeechBuilder::enable_ssml_marks),
+    /// this gives the time at which each sentence starts.
+    pub fn sentence_marks(mut self, prefix: &str) -> Self {
+        self.sentence_marks = Some(prefix.to_string());
+        self
+    }
+
+    /// Converts `text` into a `<speak>` document.
+    pub fn convert(&self, text: &str) -> Speak {
+        let mut conversion = Conversion { converter: self, sentences: 0 };
+        let children = blocks(text)
+            .into_iter()
+            .map(|block| conversion.block(block))
+            .collect();
+        Speak::new(children)
+    }
+
+    /// Converts `text` and serializes the resulting document.
+    pub fn to_ssml(&self, text: &str) -> String {
+        self.convert(text).to_string()
+    }
+}
+
+/// State kept while converting one document.
+struct Conversion<'a> {
+    converter: &'a TextConverter,
+    sentences: usize,
+}
+
+impl Conversion<'_> {
+    fn block(&mut self, block: Block) -> Node {
+        let mut nodes = Vec::new();
+        match block {
+            Block::Heading(heading) => {
+                nodes.push(self.sentence(&heading));
+                push_pause(&mut nodes, self.converter.heading_pause);
+            }
+            Block::List(items) => {
+                for item in items {
+                    for sentence in split_sentences(&item) {
+                        nodes.push(self.sentence(&sentence));
+                    }
+                    push_pause(&mut nodes, self.converter.list_item_pause);
+                }
+            }
+            Block::Paragraph(paragraph) => {
+                for sentence in split_sentences(&paragraph) {
+                    nodes.push(self.sentence(&sentence));
+                }
+            }
+        }
+        Node::Paragraph(nodes)
+    }
+
+    fn sentence(&mut self, sentence: &str) -> Node {
+        let mut nodes = Vec::new();
+        if let Some(prefix) = &self.converter.sentence_marks {
+            nodes.push(Node::Mark(format!("{}{}", prefix, self.sentences)));
+        }
+        self.sentences += 1;
+
+        if self.converter.ellipsis_pause.is_zero() {
+            nodes.push(Node::Text(sentence.to_string()));
+            return Node::Sentence(nodes);
+        }
+        let mut rest = sentence;
+        while let Some((start, end)) = find_ellipsis(rest) {
+            let before = rest[..start].trim_end();
+            if !before.is_empty() {
+                nodes.push(Node::Text(before.to_string()));
+            }
+            push_pause(&mut nodes, self.converter.ellipsis_pause);
+            rest = rest[end..].trim_start();
+        }
+        if !rest.is_empty() {
+            nodes.push(Node::Text(rest.to_string()));
+        }
+        Node::Sentence(nodes)
+    }
+}
+
+fn push_pause(nodes: &mut Vec<Node>, pause: Duration) {
+    if !pause.is_zero() {
+        nodes.push(Node::Break(Break { time: Some(pause), strength: None }));
+    }
+}
+
+/// Returns the byte range of the first `...` (or longer run of periods) or `…` in `text`.
+/// Two periods, as in `1..10`, aren't an ellipsis.
+fn find_ellipsis(text: &str) -> Option<(usize, usize)> {
+    let dots = text.find("...").map(|start| {
+        let length = text[start..].chars().take_while(|c| *c == '.').count();
+        (start, start + length)
+    });
+    let character = text.find('…').map(|start| (start, start + '…'.len_utf8()));
+    [dots, character].into_iter().flatten().min_by_key(|(start, _)| *start)
+}
+
+#[derive(Debug)]
+enum Block {
+    Heading(String),
+    List(Vec<String>),
+    Paragraph(String),
+}
+
+/// Splits `text` into headings, lists and paragraphs.
+fn blocks(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut group = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            group_blocks(&group, &mut blocks);
+            group.clear();
+        } else {
+            group.push(line);
+        }
+    }
+    group_blocks(&group, &mut blocks);
+    blocks
+}
+
+/// Splits a group of consecutive non-blank lines into blocks.
+fn group_blocks(lines: &[&str], blocks: &mut Vec<Block>) {
+    if let [line] = lines {
+        let line = normalize(line);
+        if is_heading(&line) {
+            blocks.push(Block::Heading(line));
+            return;
+        }
+    }
+
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut items: Vec<String> = Vec::new();
+    for line in lines {
+        let indented = line.starts_with(char::is_whitespace);
+        let line = normalize(line);
+        if is_underline(&line) && paragraph.len() == 1 {
+            blocks.push(Block::Heading(paragraph.remove(0)));
+        } else if let Some(item) = list_item(&line) {
+            flush_paragraph(&mut paragraph, blocks);
+            items.push(item.to_string());
+        } else if let (true, Some(last)) = (indented, items.last_mut()) {
+            last.push(' ');
+            last.push_str(&line);
+        } else {
+            if !items.is_empty() {
+                blocks.push(Block::List(std::mem::take(&mut items)));
+            }
+            paragraph.push(line);
+        }
+    }
+    flush_paragraph(&mut paragraph, blocks);
+    if !items.is_empty() {
+        blocks.push(Block::List(items));
+    }
+}
+
+fn flush_paragraph(paragraph: &mut Vec<String>, blocks: &mut Vec<Block>) {
+    if !paragraph.is_empty() {
+        blocks.push(Block::Paragraph(paragraph.join(" ")));
+        paragraph.clear();
+    }
+}
+
+/// Collapses runs of whitespace into single spaces.
+fn normalize(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_heading(line: &str) -> bool {
+    let ends_with_punctuation = line.ends_with(|c: char| ".!?…:;,。！？".contains(c));
+    !ends_with_punctuation &&
+        list_item(line).is_none() &&
+        line.split_whitespace().count() <= MAX_HEADING_WORDS
+}
+
+fn is_underline(line: &str) -> bool {
+    line.len() >= 3 && (line.chars().all(|c| c == '=') || line.chars().all(|c| c == '-'))
+}
+
+/// Returns the content of `line` if it is a list item, without its marker.
+fn list_item(line: &str) -> Option<&str> {
+    for bullet in ["- ", "* ", "+ ", "• "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return Some(item.trim_start());
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 || digits > 3 {
+        return None;
+    }
+    let rest = &line[digits..];
+    rest.strip_prefix(". ")
+        .or_else(|| rest.strip_prefix(") "))
+        .map(str::trim_start)
+}
+
+/// Splits `text` into sentences, keeping their final punctuation.
+pub(crate) fn split_sentences(text: &str) -> Vec<String> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (_, c) = chars[i];
+        let full_width = "。！？".contains(c);
+        if !full_width && !".!?".contains(c) {
+            i += 1;
+            continue;
+        }
+
+        let first = i;
+        while i < chars.len() && ".!?。！？".contains(chars[i].1) {
+            i += 1;
+        }
+        let periods = chars[first..i].iter().filter(|(_, c)| *c == '.').count();
+        while i < chars.len() && ")]\"'”’»」』".contains(chars[i].1) {
+            i += 1;
+        }
+        let end = chars.get(i).map_or(text.len(), |(offset, _)| *offset);
+        let next = chars.get(i).map(|(_, c)| *c);
+
+        // An ellipsis trails off within the sentence.
+        if periods >= 2 && next.is_some() {
+            continue;
+        }
+        if !full_width && next.is_some_and(|c| !c.is_whitespace()) {
+            continue;
+        }
+        if periods == 1 && i - first == 1 && is_abbreviation(&text[start..chars[first].0]) {
+            continue;
+        }
+        let following = chars[i..].iter().map(|(_, c)| *c).find(|c| !c.is_whitespace());
+        if following.is_some_and(char::is_lowercase) {
+            continue;
+        }
+
+        let sentence = text[start..end].trim();
+        if !sentence.is_empty() {
+            sentences.push(sentence.to_string());
+        }
+        start = end;
+    }
+
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest.to_string());
+    }
+    sentences
+}
+
+/// Returns `true` if `text` ends with an abbreviation or an initial, before a period.
+fn is_abbreviation(text: &str) -> bool {
+    let word = text
+        .rsplit(|c: char| c.is_whitespace() || c == '(')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let is_initial = word.chars().count() == 1 && word.chars().all(char::is_alphabetic);
+    is_initial || ABBREVIATIONS.contains(&word.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns `text` converted by `converter`, as the content of the `<speak>` document.
+    fn convert(converter: &TextConverter, text: &str) -> String {
+        let ssml = converter.to_ssml(text);
+        ssml.trim_start_matches("<speak>").trim_end_matches("</speak>").to_string()
+    }
+
+    fn assert_converts(converter: &TextConverter, cases: &[(&str, &str)]) {
+        for (text, expected) in cases {
+            assert_eq!(convert(converter, text), *expected, "converting {:?}", text);
+        }
+    }
+
+    #[test]
+    fn splits_paragraphs_and_sentences() {
+        assert_converts(&TextConverter::new(), &[
+            (
+                "First paragraph. Second sentence!\n\nAnother one? Yes.",
+                concat!(
+                    "<p><s>First paragraph.</s><s>Second sentence!</s></p>",
+                    "<p><s>Another one?</s><s>Yes.</s></p>"
+                ),
+            ),
+            (
+                "He said \"stop.\" Then left.",
+                r#"<p><s>He said "stop."</s><s>Then left.</s></p>"#,
+            ),
+            ("Tom & <Jerry>.", "<p><s>Tom &amp; &lt;Jerry&gt;.</s></p>"),
+        ]);
+        assert_eq!(split_sentences("「こんにちは。」元気？ はい。"), ["「こんにちは。」", "元気？", "はい。"]);
+        assert_eq!(split_sentences("It costs 3.5 dollars. Ok."), ["It costs 3.5 dollars.", "Ok."]);
+        assert_eq!(split_sentences("see the docs. then leave."), ["see the docs. then leave."]);
+    }
+
+    #[test]
+    fn keeps_abbreviations_and_initials_in_the_sentence() {
+        let text = "Mr. Smith met Dr. Jones, e.g. at St. Mary's. Then J. R. R. Tolkien left.";
+        assert_eq!(split_sentences(text), [
+            "Mr. Smith met Dr. Jones, e.g. at St. Mary's.",
+            "Then J. R. R. Tolkien left.",
+        ]);
+        assert_eq!(split_sentences("See fig. 2 and no. 5 now. Done."), [
+            "See fig. 2 and no. 5 now.",
+            "Done.",
+        ]);
+    }
+
+    #[test]
+    fn reads_headings_with_a_pause() {
+        let heading = r#"<p><s>Results</s><break time="600ms"/></p><p><s>All good.</s></p>"#;
+        assert_converts(&TextConverter::new(), &[
+            ("Results\n\nAll good.", heading),
+            ("Results\n=======\nAll good.", heading),
+            ("Results\n---\nAll good.", heading),
+            // A line ending with punctuation is a sentence.
+            ("All good.\n\nResults:", "<p><s>All good.</s></p><p><s>Results:</s></p>"),
+        ]);
+        let converter = TextConverter::new().heading_pause(Duration::ZERO);
+        assert_converts(&converter, &[("Results", "<p><s>Results</s></p>")]);
+    }
+
+    #[test]
+    fn reads_list_items_without_their_marker() {
+        let text = "Shopping:\n- eggs\n- milk and\n  bread\n1. first. Second.\n2) next";
+        assert_converts(&TextConverter::new(), &[
+            (
+                text,
+                concat!(
+                    r#"<p><s>Shopping:</s></p><p><s>eggs</s><break time="300ms"/>"#,
+                    r#"<s>milk and bread</s><break time="300ms"/><s>first.</s><s>Second.</s>"#,
+                    r#"<break time="300ms"/><s>next</s><break time="300ms"/></p>"#
+                ),
+            ),
+        ]);
+        assert_eq!(list_item("• dot"), Some("dot"));
+        assert_eq!(list_item("1234. too long"), None);
+        assert_eq!(list_item("-not an item"), None);
+    }
+
+    #[test]
+    fn pauses_in_place_of_ellipses() {
+        assert_converts(&TextConverter::new(), &[
+            (
+                "Wait... what? Well… maybe.... Or not.",
+                concat!(
+                    r#"<p><s>Wait<break time="400ms"/>what?</s><s>Well<break time="400ms"/>"#,
+                    r#"maybe<break time="400ms"/>Or not.</s></p>"#
+                ),
+            ),
+            // Two periods aren't an ellipsis.
+            ("Count 1..10 and back.", "<p><s>Count 1..10 and back.</s></p>"),
+        ]);
+        assert_converts(&TextConverter::new().ellipsis_pause(Duration::ZERO), &[
+            ("Wait... what?", "<p><s>Wait... what?</s></p>"),
+        ]);
+
+        assert_eq!(find_ellipsis("a..b"), None);
+        assert_eq!(find_ellipsis("a..b...c"), Some((4, 7)));
+        assert_eq!(find_ellipsis("a…b...c"), Some((1, 4)));
+        assert_eq!(find_ellipsis("a.....b"), Some((1, 6)));
+    }
+
+    #[test]
+    fn numbers_sentence_marks_across_the_document() {
+        let converter = TextConverter::new()
+            .sentence_marks("s")
+            .heading_pause(Duration::ZERO)
+            .list_item_pause(Duration::ZERO);
+        assert_converts(&converter, &[
+            (
+                "Title\n\nOne. Two.\n\n- three",
+                concat!(
+                    r#"<p><s><mark name="s0"/>Title</s></p><p><s><mark name="s1"/>One.</s>"#,
+                    r#"<s><mark name="s2"/>Two.</s></p><p><s><mark name="s3"/>three</s></p>"#
+                ),
+            ),
+        ]);
+    }
+}
@@ -0,0 +1,825 @@
+// src/ssml/lexicon.rs
+
+//! Pronunciation lexicons.
+//!
+//! A [Lexicon] maps graphemes, e.g. product names or abbreviations, to an alias read instead
+//! of them (`<sub>`) or to their phonetic transcription (`<phoneme>`). Lexicons are built in
+//! code, or loaded from W3C [PLS](https://www.w3.org/TR/pronunciation-lexicon/) documents and
+//! from TOML files:
+//!
+//! ```toml
+//! case_sensitive = true
+//! whole_words = true
+//! # Alphabet of the [phonemes] table: "ipa" (default) or "x-sampa".
+//! alphabet = "ipa"
+//!
+//! [aliases]
+//! k8s = "Kubernetes"
+//! "AT&T" = "A T and T"
+//!
+//! [phonemes]
+//! tomato = "təˈmeɪtoʊ"
+//!
+//! [[lexeme]]
+//! graphemes = ["GIF", "gif"]
+//! phoneme = "dʒɪf"
+//! alphabet = "ipa"
+//! ```
+
+use std::{ collections::HashMap, fs, path::Path, sync::OnceLock };
+
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{
+    ssml::{
+        attributes::PhoneticAlphabet,
+        node::{ Node, Speak },
+        parser::{ self, markup_end, tag_name, Element, ParseError, Position },
+    },
+    types::synthesize::SynthesisInput,
+};
+
+/// Elements whose content is never rewritten, as it already tells how to read the text.
+const PROTECTED_ELEMENTS: [&str; 3] = ["sub", "phoneme", "say-as"];
+
+/// How the graphemes of a [Lexeme] are read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pronunciation {
+    /// Read as other text, with `<sub alias="...">`.
+    Alias(String),
+    /// Read with a phonetic transcription, with `<phoneme>`.
+    Phoneme {
+        alphabet: PhoneticAlphabet,
+        ph: String,
+    },
+}
+
+/// An entry of a [Lexicon]: graphemes sharing a pronunciation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexeme {
+    /// The spellings the entry applies to, e.g. `GIF` and `gif`.
+    pub graphemes: Vec<String>,
+    pub pronunciation: Pronunciation,
+}
+
+impl Lexeme {
+    /// Returns the node reading `text`, one of the graphemes as written in the input.
+    fn node(&self, text: &str) -> Node {
+        match &self.pronunciation {
+            Pronunciation::Alias(alias) => {
+                Node::Sub { alias: alias.clone(), text: text.to_string() }
+            }
+            Pronunciation::Phoneme { alphabet, ph } => {
+                Node::Phoneme { alphabet: *alphabet, ph: ph.clone(), text: text.to_string() }
+            }
+        }
+    }
+}
+
+/// Finds graphemes in text. Built when the lexicon is first applied.
+#[derive(Debug, Clone)]
+struct Matcher {
+    regex: Regex,
+    /// Index of the lexeme of every grapheme, lowercased if matching ignores case.
+    lexemes: HashMap<String, usize>,
+}
+
+/// A set of pronunciations applied to text and SSML inputs.
+///
+/// Graphemes match case-sensitively and as whole words by default. A lexeme added later
+/// replaces the pronunciation of the graphemes it shares with earlier ones, and longer
+/// graphemes are preferred over the shorter ones they start with.
+///
+/// Attach it to a [TextToSpeechClient](crate::client::TextToSpeechClient) with
+/// `set_lexicon` to apply it to every request, or to a single request with
+/// `SynthesizeSpeechBuilder::lexicon`.
+#[derive(Debug, Clone)]
+pub struct Lexicon {
+    lexemes: Vec<Lexeme>,
+    case_sensitive: bool,
+    whole_words: bool,
+    matcher: OnceLock<Option<Matcher>>,
+}
+
+impl Default for Lexicon {
+    fn default() -> Self {
+        Lexicon {
+            lexemes: Vec::new(),
+            case_sensitive: true,
+            whole_words: true,
+            matcher: OnceLock::new(),
+        }
+    }
+}
+
+impl Lexicon {
+    pub fn new() -> Self {
+        Lexicon::default()
+    }
+
+    /// Parses a W3C Pronunciation Lexicon Specification document. Of the pronunciations of a
+    /// lexeme, the first one marked with `prefer="true"` is used, or else the first one.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The PLS document, with a `<lexicon>` root element.
+    ///
+    /// # Returns
+    ///
+    /// Returns the lexicon, or the position of the first problem in the document.
+    pub fn from_pls(source: &str) -> Result<Self, ParseError> {
+        let root = parser::parse(source)?;
+        if root.name != "lexicon" {
+            return Err(error(root.position, format!("expected <lexicon>, found <{}>", root.name)));
+        }
+        let alphabet = match root.attribute("alphabet") {
+            Some(attribute) => parse_alphabet(&attribute.value, attribute.position)?,
+            None => PhoneticAlphabet::Ipa,
+        };
+
+        let mut lexicon = Lexicon::new();
+        for element in root.elements().filter(|element| element.name == "lexeme") {
+            lexicon.insert(pls_lexeme(element, alphabet)?);
+        }
+        Ok(lexicon)
+    }
+
+    /// Parses a TOML lexicon, in the format shown in the [module documentation](self).
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The TOML document.
+    ///
+    /// # Returns
+    ///
+    /// Returns the lexicon, or the position of the first problem in the document.
+    pub fn from_toml(source: &str) -> Result<Self, ParseError> {
+        let lexicon: TomlLexicon = toml::from_str(source).map_err(|e| {
+            let position = e.span().map_or(Position { line: 1, column: 1 }, |span| {
+                toml_position(source, span.start)
+            });
+            error(position, e.message().trim_end())
+        })?;
+        lexicon.into_lexicon(source)
+    }
+
+    /// Loads a lexicon from a file, read as TOML if its extension is `toml` and as PLS
+    /// otherwise.
+    pub fn load(
+        path: impl AsRef<Path>
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        let lexicon = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Lexicon::from_toml(&source)?,
+            _ => Lexicon::from_pls(&source)?,
+        };
+        Ok(lexicon)
+    }
+
+    /// Sets whether graphemes match only with the same case; `true` by default.
+    pub fn case_sensitive(mut self, enabled: bool) -> Self {
+        self.case_sensitive = enabled;
+        self.matcher = OnceLock::new();
+        self
+    }
+
+    /// Sets whether graphemes match only whole words, i.e. not next to a letter or digit;
+    /// `true` by default. Turn it off for languages written without spaces, such as Japanese.
+    pub fn whole_words(mut self, enabled: bool) -> Self {
+        self.whole_words = enabled;
+        self.matcher = OnceLock::new();
+        self
+    }
+
+    /// Reads `grapheme` as `alias`.
+    pub fn alias(self, grapheme: &str, alias: &str) -> Self {
+        self.lexeme(Lexeme {
+            graphemes: vec![grapheme.to_string()],
+            pronunciation: Pronunciation::Alias(alias.to_string()),
+        })
+    }
+
+    /// Reads `grapheme` with the pronunciation `ph`, written in `alphabet`.
+    pub fn phoneme(self, grapheme: &str, alphabet: PhoneticAlphabet, ph: &str) -> Self {
+        self.lexeme(Lexeme {
+            graphemes: vec![grapheme.to_string()],
+            pronunciation: Pronunciation::Phoneme { alphabet, ph: ph.to_string() },
+        })
+    }
+
+    /// Adds `lexeme`, replacing the pronunciation of its graphemes if they have one.
+    pub fn lexeme(mut self, lexeme: Lexeme) -> Self {
+        self.insert(lexeme);
+        self
+    }
+
+    /// Adds `lexeme`, replacing the pronunciation of its graphemes if they have one.
+    pub fn insert(&mut self, lexeme: Lexeme) {
+        self.lexemes.push(lexeme);
+        self.matcher = OnceLock::new();
+    }
+
+    /// Adds the lexemes of `other`, which take precedence over the ones of this lexicon.
+    pub fn extend(&mut self, other: &Lexicon) {
+        for lexeme in &other.lexemes {
+            self.insert(lexeme.clone());
+        }
+    }
+
+    pub fn lexemes(&self) -> &[Lexeme] {
+        &self.lexemes
+    }
+
+    pub fn len(&self) -> usize {
+        self.lexemes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lexemes.is_empty()
+    }
+
+    /// Applies the lexicon to plain text.
+    ///
+    /// # Returns
+    ///
+    /// Returns the text as SSML nodes, with every grapheme found wrapped in a `<sub>` or
+    /// `<phoneme>` element.
+    pub fn apply(&self, text: &str) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let mut end = 0;
+        for (start, length, lexeme) in self.find(text) {
+            if start > end {
+                nodes.push(Node::Text(text[end..start].to_string()));
+            }
+            end = start + length;
+            nodes.push(lexeme.node(&text[start..end]));
+        }
+        if end < text.len() {
+            nodes.push(Node::Text(text[end..].to_string()));
+        }
+        nodes
+    }
+
+    /// Applies the lexicon to the text content of an SSML document, leaving tags, comments,
+    /// CDATA sections and the content of `<sub>`, `<phoneme>` and `<say-as>` elements as
+    /// they are.
+    pub fn apply_ssml(&self, ssml: &str) -> String {
+        let mut output = String::with_capacity(ssml.len());
+        // Depth inside the elements whose content isn't rewritten.
+        let mut protected = 0usize;
+        let mut rest = ssml;
+
+        while !rest.is_empty() {
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                match protected {
+                    0 => self.apply_escaped(&rest[..end], &mut output),
+                    _ => output.push_str(&rest[..end]),
+                }
+                rest = &rest[end..];
+                continue;
+            }
+
+            let end = markup_end(rest);
+            let markup = &rest[..end];
+            let name = tag_name(markup);
+            let tag = !markup.starts_with("<!") && !markup.starts_with("<?");
+            if tag && markup.starts_with("</") {
+                protected = protected.saturating_sub(1);
+            } else if tag &&
+                !markup.ends_with("/>") &&
+                (protected > 0 || PROTECTED_ELEMENTS.contains(&name))
+            {
+                protected += 1;
+            }
+            output.push_str(markup);
+            rest = &rest[end..];
+        }
+        output
+    }
+
+    /// Applies the lexicon to a text or SSML input. Text input becomes SSML if a grapheme is
+    /// found in it; other inputs are returned unchanged.
+    pub fn apply_input(&self, input: SynthesisInput) -> SynthesisInput {
+        if self.is_empty() {
+            return input;
+        }
+        match input {
+            SynthesisInput::Text(text) => {
+                let nodes = self.apply(&text);
+                match nodes.as_slice() {
+                    [] | [Node::Text(_)] => SynthesisInput::Text(text),
+                    _ => Speak::new(nodes).into(),
+                }
+            }
+            SynthesisInput::Ssml(ssml) => SynthesisInput::Ssml(self.apply_ssml(&ssml)),
+            input => input,
+        }
+    }
+
+    /// Rewrites escaped text content into `output`.
+    fn apply_escaped(&self, escaped: &str, output: &mut String) {
+        let text = unescape(escaped);
+        let nodes = self.apply(&text);
+        if let [] | [Node::Text(_)] = nodes.as_slice() {
+            output.push_str(escaped);
+            return;
+        }
+        for node in nodes {
+            output.push_str(&node.to_string());
+        }
+    }
+
+    /// Returns the start, length and lexeme of every grapheme found in `text`.
+    fn find(&self, text: &str) -> Vec<(usize, usize, &Lexeme)> {
+        let Some(matcher) = self.matcher.get_or_init(|| self.matcher()) else {
+            return Vec::new();
+        };
+
+        let mut found = Vec::new();
+        let mut position = 0;
+        while let Some(found_match) = matcher.regex.find_at(text, position) {
+            let (start, end) = (found_match.start(), found_match.end());
+            let matched = found_match.as_str();
+            if self.whole_words && !is_whole_word(text, start, end) {
+                position = start + matched.chars().next().map_or(1, char::len_utf8);
+                continue;
+            }
+            let key = match self.case_sensitive {
+                true => matched.to_string(),
+                false => matched.to_lowercase(),
+            };
+            if let Some(index) = matcher.lexemes.get(&key) {
+                found.push((start, end - start, &self.lexemes[*index]));
+            }
+            position = end;
+        }
+        found
+    }
+
+    fn matcher(&self) -> Option<Matcher> {
+        let mut lexemes = HashMap::new();
+        for (index, lexeme) in self.lexemes.iter().enumerate() {
+            for grapheme in lexeme.graphemes.iter().filter(|grapheme| !grapheme.is_empty()) {
+                let key = match self.case_sensitive {
+                    true => grapheme.clone(),
+                    false => grapheme.to_lowercase(),
+                };
+                lexemes.insert(key, index);
+            }
+        }
+        if lexemes.is_empty() {
+            return None;
+        }
+
+        let mut graphemes: Vec<&String> = lexemes.keys().collect();
+        graphemes.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        let alternatives: Vec<String> = graphemes
+            .iter()
+            .map(|grapheme| regex::escape(grapheme))
+            .collect();
+        let flags = if self.case_sensitive { "" } else { "(?i)" };
+        let regex = Regex::new(&format!("{}(?:{})", flags, alternatives.join("|")))
+            .expect("valid pattern");
+        Some(Matcher { regex, lexemes })
+    }
+}
+
+/// Returns `true` if `text[start..end]` isn't preceded or followed by a letter or digit
+/// continuing it.
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let word = |c: char| c.is_alphanumeric() || c == '_';
+    let matched = &text[start..end];
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    let starts_word = matched.chars().next().is_some_and(word);
+    let ends_word = matched.chars().next_back().is_some_and(word);
+    let joined_before = starts_word && before.is_some_and(word);
+    let joined_after = ends_word && after.is_some_and(word);
+    !joined_before && !joined_after
+}
+
+/// Resolves the predefined entities and character references of XML text content. Unknown
+/// references are kept as they are.
+fn unescape(escaped: &str) -> String {
+    let mut text = String::with_capacity(escaped.len());
+    let mut rest = escaped;
+    while let Some(start) = rest.find('&') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest.find(';').map(|end| (&rest[1..end], end));
+        let replacement = reference.and_then(|(name, _)| {
+            match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => {
+                    let code = match name.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => name.strip_prefix('#').and_then(|decimal| decimal.parse().ok()),
+                    };
+                    code.and_then(char::from_u32)
+                }
+            }
+        });
+        match (replacement, reference) {
+            (Some(c), Some((_, end))) => {
+                text.push(c);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                text.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+fn error(position: Position, message: impl Into<String>) -> ParseError {
+    ParseError { position, message: message.into() }
+}
+
+fn parse_alphabet(value: &str, position: Position) -> Result<PhoneticAlphabet, ParseError> {
+    PhoneticAlphabet::ALL
+        .into_iter()
+        .find(|alphabet| alphabet.as_str().eq_ignore_ascii_case(value.trim()))
+        .ok_or_else(|| error(position, format!("unsupported alphabet '{}'", value)))
+}
+
+/// Reads a `<lexeme>` of a PLS document.
+fn pls_lexeme(element: &Element, alphabet: PhoneticAlphabet) -> Result<Lexeme, ParseError> {
+    let graphemes: Vec<String> = element
+        .elements()
+        .filter(|child| child.name == "grapheme")
+        .map(|child| child.text().trim().to_string())
+        .filter(|grapheme| !grapheme.is_empty())
+        .collect();
+    if graphemes.is_empty() {
+        return Err(error(element.position, "<lexeme> without <grapheme>"));
+    }
+
+    let pronunciations: Vec<&Element> = element
+        .elements()
+        .filter(|child| child.name == "phoneme" || child.name == "alias")
+        .collect();
+    let preferred = pronunciations
+        .iter()
+        .find(|child| child.attribute("prefer").is_some_and(|prefer| prefer.value == "true"))
+        .or(pronunciations.first());
+    let Some(preferred) = preferred else {
+        return Err(error(element.position, "<lexeme> without <phoneme> or <alias>"));
+    };
+
+    let text = preferred.text().trim().to_string();
+    let pronunciation = match preferred.name.as_str() {
+        "alias" => Pronunciation::Alias(text),
+        _ => {
+            let alphabet = match preferred.attribute("alphabet") {
+                Some(attribute) => parse_alphabet(&attribute.value, attribute.position)?,
+                None => alphabet,
+            };
+            Pronunciation::Phoneme { alphabet, ph: text }
+        }
+    };
+    Ok(Lexeme { graphemes, pronunciation })
+}
+
+/// A TOML lexicon, in the format shown in the [module documentation](self).
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlLexicon {
+    case_sensitive: Option<bool>,
+    whole_words: Option<bool>,
+    alphabet: Option<Spanned<String>>,
+    #[serde(default)]
+    aliases: IndexMap<String, String>,
+    #[serde(default)]
+    phonemes: IndexMap<String, String>,
+    #[serde(default)]
+    lexeme: Vec<Spanned<TomlLexeme>>,
+}
+
+/// A `[[lexeme]]` of a TOML lexicon.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlLexeme {
+    grapheme: Option<String>,
+    #[serde(default)]
+    graphemes: Vec<String>,
+    alias: Option<String>,
+    phoneme: Option<String>,
+    alphabet: Option<Spanned<String>>,
+}
+
+impl TomlLexicon {
+    fn into_lexicon(self, source: &str) -> Result<Lexicon, ParseError> {
+        let mut lexicon = Lexicon::new();
+        if let Some(enabled) = self.case_sensitive {
+            lexicon = lexicon.case_sensitive(enabled);
+        }
+        if let Some(enabled) = self.whole_words {
+            lexicon = lexicon.whole_words(enabled);
+        }
+        let alphabet = match &self.alphabet {
+            Some(value) => toml_alphabet(value, source)?,
+            None => PhoneticAlphabet::Ipa,
+        };
+
+        for (grapheme, alias) in self.aliases {
+            lexicon.insert(Lexeme {
+                graphemes: vec![grapheme],
+                pronunciation: Pronunciation::Alias(alias),
+            });
+        }
+        for (grapheme, ph) in self.phonemes {
+            lexicon.insert(Lexeme {
+                graphemes: vec![grapheme],
+                pronunciation: Pronunciation::Phoneme { alphabet, ph },
+            });
+        }
+        for entry in self.lexeme {
+            let position = toml_position(source, entry.span().start);
+            let entry = entry.into_inner();
+            let mut graphemes: Vec<String> = entry.grapheme.into_iter().collect();
+            graphemes.extend(entry.graphemes);
+            if graphemes.is_empty() {
+                return Err(error(position, "[[lexeme]] without 'grapheme' or 'graphemes'"));
+            }
+            let alphabet = match &entry.alphabet {
+                Some(value) => toml_alphabet(value, source)?,
+                None => alphabet,
+            };
+            let pronunciation = match (entry.alias, entry.phoneme) {
+                (Some(alias), None) => Pronunciation::Alias(alias),
+                (None, Some(ph)) => Pronunciation::Phoneme { alphabet, ph },
+                _ => {
+                    return Err(error(position, "[[lexeme]] needs either 'alias' or 'phoneme'"));
+                }
+            };
+            lexicon.insert(Lexeme { graphemes, pronunciation });
+        }
+        Ok(lexicon)
+    }
+}
+
+fn toml_alphabet(value: &Spanned<String>, source: &str) -> Result<PhoneticAlphabet, ParseError> {
+    parse_alphabet(value.get_ref(), toml_position(source, value.span().start))
+}
+
+/// Returns the line and column of the byte `offset` of `source`.
+fn toml_position(source: &str, offset: usize) -> Position {
+    let before = source.get(..offset).unwrap_or(source);
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Position {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias(grapheme: &str, alias: &str) -> Lexeme {
+        Lexeme {
+            graphemes: vec![grapheme.to_string()],
+            pronunciation: Pronunciation::Alias(alias.to_string()),
+        }
+    }
+
+    #[test]
+    fn reads_pls_documents() {
+        let lexicon = Lexicon::from_pls(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<lexicon version="1.0" xmlns="http://www.w3.org/2005/01/pronunciation-lexicon"
+    alphabet="ipa" xml:lang="en-US">
+  <lexeme>
+    <grapheme>GIF</grapheme>
+    <grapheme>gif</grapheme>
+    <phoneme>ɡɪf</phoneme>
+    <phoneme prefer="true">dʒɪf</phoneme>
+  </lexeme>
+  <lexeme>
+    <grapheme>W3C</grapheme>
+    <alias>World Wide Web Consortium</alias>
+  </lexeme>
+  <lexeme>
+    <grapheme>tomato</grapheme>
+    <phoneme alphabet="x-sampa">t@"meItoU</phoneme>
+  </lexeme>
+</lexicon>"#
+        ).unwrap();
+
+        assert_eq!(lexicon.lexemes(), [
+            Lexeme {
+                graphemes: vec!["GIF".to_string(), "gif".to_string()],
+                pronunciation: Pronunciation::Phoneme {
+                    alphabet: PhoneticAlphabet::Ipa,
+                    ph: "dʒɪf".to_string(),
+                },
+            },
+            alias("W3C", "World Wide Web Consortium"),
+            Lexeme {
+                graphemes: vec!["tomato".to_string()],
+                pronunciation: Pronunciation::Phoneme {
+                    alphabet: PhoneticAlphabet::XSampa,
+                    ph: "t@\"meItoU".to_string(),
+                },
+            },
+        ]);
+    }
+
+    #[test]
+    fn rejects_invalid_pls_documents() {
+        let error = Lexicon::from_pls("<speak>GIF</speak>").unwrap_err();
+        assert_eq!(error.message, "expected <lexicon>, found <speak>");
+
+        let error = Lexicon::from_pls(
+            "<lexicon>\n  <lexeme><alias>x</alias></lexeme>\n</lexicon>"
+        ).unwrap_err();
+        assert_eq!(error.position, Position { line: 2, column: 3 });
+        assert_eq!(error.message, "<lexeme> without <grapheme>");
+    }
+
+    #[test]
+    fn reads_toml_documents() {
+        let lexicon = Lexicon::from_toml(
+            r#"
+case_sensitive = false
+whole_words = false
+alphabet = "x-sampa"
+aliases.k8s = "Kubernetes"
+aliases."AT&T" = "A T and T"
+aliases.'C:\dir' = "the \"C\" drive\u0021"
+
+[phonemes]
+tomato = 't@"meItoU'
+
+[[lexeme]]
+graphemes = [
+    "GIF",
+    "gif", # trailing comma and comments
+]
+phoneme = "dʒɪf"
+alphabet = "IPA"
+
+[[lexeme]]
+grapheme = "W3C"
+alias = "World Wide Web Consortium"
+"#
+        ).unwrap();
+
+        assert!(!lexicon.case_sensitive);
+        assert!(!lexicon.whole_words);
+        assert_eq!(lexicon.lexemes(), [
+            alias("k8s", "Kubernetes"),
+            alias("AT&T", "A T and T"),
+            alias("C:\\dir", "the \"C\" drive!"),
+            Lexeme {
+                graphemes: vec!["tomato".to_string()],
+                pronunciation: Pronunciation::Phoneme {
+                    alphabet: PhoneticAlphabet::XSampa,
+                    ph: "t@\"meItoU".to_string(),
+                },
+            },
+            Lexeme {
+                graphemes: vec!["GIF".to_string(), "gif".to_string()],
+                pronunciation: Pronunciation::Phoneme {
+                    alphabet: PhoneticAlphabet::Ipa,
+                    ph: "dʒɪf".to_string(),
+                },
+            },
+            alias("W3C", "World Wide Web Consortium"),
+        ]);
+    }
+
+    #[test]
+    fn locates_errors_in_toml_documents() {
+        let error = |source: &str| {
+            let error = Lexicon::from_toml(source).unwrap_err();
+            (error.position.line, error.position.column, error.message)
+        };
+
+        assert_eq!(
+            error("case_sensitive = \"yes\"\n"),
+            (1, 18, "invalid type: string \"yes\", expected a boolean".to_string())
+        );
+        assert_eq!(
+            error("[aliases]\nk8s = 1\n"),
+            (2, 7, "invalid type: integer `1`, expected a string".to_string())
+        );
+        assert_eq!(error("alphabet = \"klingon\"\n"), (
+            1,
+            12,
+            "unsupported alphabet 'klingon'".to_string(),
+        ));
+        assert_eq!(error("\n[[lexeme]]\ngrapheme = \"GIF\"\n"), (
+            2,
+            1,
+            "[[lexeme]] needs either 'alias' or 'phoneme'".to_string(),
+        ));
+        assert_eq!(error("[[lexeme]]\nphoneme = \"dʒɪf\"\n"), (
+            1,
+            1,
+            "[[lexeme]] without 'grapheme' or 'graphemes'".to_string(),
+        ));
+        assert!(error("[lexemes]\n").2.starts_with("unknown field `lexemes`"));
+    }
+
+    #[test]
+    fn loads_files_by_extension() {
+        let directory = std::env::temp_dir().join(format!("lexicon-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let toml = directory.join("lexicon.toml");
+        fs::write(&toml, "[aliases]\nk8s = \"Kubernetes\"\n").unwrap();
+        let pls = directory.join("lexicon.pls");
+        fs::write(
+            &pls,
+            "<lexicon><lexeme><grapheme>k8s</grapheme><alias>Kubernetes</alias></lexeme></lexicon>"
+        ).unwrap();
+
+        let from_toml = Lexicon::load(&toml).unwrap();
+        let from_pls = Lexicon::load(&pls).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(from_toml.lexemes(), [alias("k8s", "Kubernetes")]);
+        assert_eq!(from_pls.lexemes(), from_toml.lexemes());
+        assert!(Lexicon::load(directory.join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn rewrites_text_content_only() {
+        let lexicon = Lexicon::new().alias("k8s", "Kubernetes").alias("AT&T", "A T and T");
+
+        assert_eq!(
+            lexicon.apply_ssml("<speak>k8s and AT&amp;T</speak>"),
+            concat!(
+                r#"<speak><sub alias="Kubernetes">k8s</sub> and "#,
+                r#"<sub alias="A T and T">AT&amp;T</sub></speak>"#
+            )
+        );
+        // Tag names and attribute values.
+        assert_eq!(
+            lexicon.apply_ssml(r#"<speak><mark name="k8s"/><k8s>x</k8s> k8s</speak>"#),
+            r#"<speak><mark name="k8s"/><k8s>x</k8s> <sub alias="Kubernetes">k8s</sub></speak>"#
+        );
+        // Content already telling how to read it.
+        assert_eq!(
+            lexicon.apply_ssml(
+                concat!(
+                    r#"<speak><sub alias="kates">k8s</sub><say-as interpret-as="characters">"#,
+                    "<emphasis>k8s</emphasis></say-as> k8s</speak>"
+                )
+            ),
+            concat!(
+                r#"<speak><sub alias="kates">k8s</sub><say-as interpret-as="characters">"#,
+                r#"<emphasis>k8s</emphasis></say-as> <sub alias="Kubernetes">k8s</sub></speak>"#
+            )
+        );
+        // Comments and CDATA sections.
+        assert_eq!(
+            lexicon.apply_ssml("<speak><!-- k8s --><![CDATA[k8s]]>k8s</speak>"),
+            r#"<speak><!-- k8s --><![CDATA[k8s]]><sub alias="Kubernetes">k8s</sub></speak>"#
+        );
+    }
+
+    #[test]
+    fn matches_whole_words_and_case() {
+        let lexicon = Lexicon::new().alias("k8s", "Kubernetes");
+        assert_eq!(lexicon.apply("k8sx K8S k8s."), [
+            Node::Text("k8sx K8S ".to_string()),
+            Node::Sub { alias: "Kubernetes".to_string(), text: "k8s".to_string() },
+            Node::Text(".".to_string()),
+        ]);
+
+        let lexicon = lexicon.case_sensitive(false).whole_words(false);
+        assert_eq!(lexicon.apply("k8sx K8S"), [
+            Node::Sub { alias: "Kubernetes".to_string(), text: "k8s".to_string() },
+            Node::Text("x ".to_string()),
+            Node::Sub { alias: "Kubernetes".to_string(), text: "K8S".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn turns_text_into_ssml_when_a_grapheme_is_found() {
+        let lexicon = Lexicon::new().phoneme("tomato", PhoneticAlphabet::Ipa, "təˈmeɪtoʊ");
+        assert_eq!(
+            lexicon.apply_input(SynthesisInput::Text("a tomato".to_string())),
+            SynthesisInput::Ssml(
+                r#"<speak>a <phoneme alphabet="ipa" ph="təˈmeɪtoʊ">tomato</phoneme></speak>"#
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            lexicon.apply_input(SynthesisInput::Text("a potato".to_string())),
+            SynthesisInput::Text("a potato".to_string())
+        );
+    }
+}
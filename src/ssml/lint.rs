@@ -0,0 +1,594 @@
+// src/ssml/lint.rs
+
+//! Offline checks of SSML documents against what Google Text-to-Speech accepts.
+//!
+//! The API answers a malformed or unsupported document with a bare `400 INVALID_ARGUMENT`;
+//! [lint] instead reports each problem with its line and column.
+
+use std::{ collections::HashMap, fmt, time::Duration };
+
+use crate::{
+    services::validation::{ PITCH_RANGE, SPEAKING_RATE_RANGE, VOLUME_GAIN_DB_RANGE },
+    ssml::{
+        attributes::{
+            BreakStrength,
+            EmphasisLevel,
+            InterpretAs,
+            PhoneticAlphabet,
+            VoiceGender,
+        },
+        parser::{ parse, Attribute, Element, Position, XmlNode },
+    },
+    types::language::LanguageTag,
+};
+
+/// Longest `<break>` the API accepts.
+pub const MAX_BREAK_TIME: Duration = Duration::from_secs(10);
+
+/// The elements Google supports, with their allowed and required attributes.
+const ELEMENTS: &[(&str, &[&str], &[&str])] = &[
+    ("speak", &["xmlns", "xmlns:xsi", "xsi:schemaLocation", "version", "xml:lang"], &[]),
+    ("p", &[], &[]),
+    ("s", &[], &[]),
+    ("break", &["time", "strength"], &[]),
+    ("say-as", &["interpret-as", "format", "detail", "language"], &["interpret-as"]),
+    (
+        "audio",
+        &["src", "clipBegin", "clipEnd", "speed", "repeatCount", "repeatDur", "soundLevel"],
+        &["src"],
+    ),
+    ("desc", &["xml:lang"], &[]),
+    ("sub", &["alias"], &["alias"]),
+    ("mark", &["name"], &["name"]),
+    ("prosody", &["rate", "pitch", "volume"], &[]),
+    ("emphasis", &["level"], &[]),
+    ("par", &[], &[]),
+    ("seq", &[], &[]),
+    (
+        "media",
+        &[
+            "xml:id",
+            "begin",
+            "end",
+            "repeatCount",
+            "repeatDur",
+            "soundLevel",
+            "fadeInDur",
+            "fadeOutDur",
+        ],
+        &[],
+    ),
+    ("phoneme", &["alphabet", "ph"], &["alphabet", "ph"]),
+    ("voice", &["name", "gender", "variant", "language", "languages", "required", "ordering"], &[]),
+    ("lang", &["xml:lang"], &["xml:lang"]),
+];
+
+/// Attributes holding a time designation such as `500ms` or `2.5s`.
+const TIME_ATTRIBUTES: &[&str] = &["clipBegin", "clipEnd", "repeatDur", "fadeInDur", "fadeOutDur"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The API rejects the document.
+    Error,
+    /// The document is accepted, but likely doesn't sound as intended.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// A problem found in an SSML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub position: Position,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.position, self.severity, self.message)
+    }
+}
+
+/// Checks an SSML document.
+///
+/// # Arguments
+///
+/// * `ssml` - The document, as sent in the `ssml` input field.
+///
+/// # Returns
+///
+/// The problems found, in document order. A document that isn't well-formed XML yields a
+/// single error at the point where parsing stopped.
+pub fn lint(ssml: &str) -> Vec<Diagnostic> {
+    let root = match parse(ssml) {
+        Ok(root) => root,
+        Err(error) => {
+            return vec![Diagnostic {
+                severity: Severity::Error,
+                position: error.position,
+                message: error.message,
+            }];
+        }
+    };
+
+    let mut linter = Linter { diagnostics: Vec::new(), marks: HashMap::new() };
+    if root.name != "speak" {
+        let message = format!("the root element must be <speak>, not <{}>", root.name);
+        linter.error(root.position, message);
+    }
+    linter.element(&root, None);
+    linter.diagnostics
+}
+
+/// Returns the errors found by [lint], leaving out warnings.
+pub fn errors(ssml: &str) -> Vec<Diagnostic> {
+    lint(ssml).into_iter().filter(Diagnostic::is_error).collect()
+}
+
+/// Parses an SSML time designation, e.g. `250ms` or `1.5s`.
+pub fn parse_time(value: &str) -> Option<Duration> {
+    let (number, unit) = match value.strip_suffix("ms") {
+        Some(number) => (number, 0.001),
+        None => (value.strip_suffix('s')?, 1.0),
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    // Negative, infinite and NaN values, and values too large for a duration, are invalid.
+    Duration::try_from_secs_f64(number * unit).ok()
+}
+
+struct Linter {
+    diagnostics: Vec<Diagnostic>,
+    marks: HashMap<String, Position>,
+}
+
+impl Linter {
+    fn error(&mut self, position: Position, message: String) {
+        self.diagnostics.push(Diagnostic { severity: Severity::Error, position, message });
+    }
+
+    fn warning(&mut self, position: Position, message: String) {
+        self.diagnostics.push(Diagnostic { severity: Severity::Warning, position, message });
+    }
+
+    fn element(&mut self, element: &Element, parent: Option<&str>) {
+        let name = element.name.as_str();
+        let Some((_, allowed, required)) = ELEMENTS.iter().find(|(known, ..)| *known == name)
+        else {
+            self.error(element.position, format!("<{}> is not supported", name));
+            return;
+        };
+
+        for attribute in &element.attributes {
+            if !allowed.contains(&attribute.name.as_str()) {
+                self.error(
+                    attribute.position,
+                    format!("<{}> doesn't support the '{}' attribute", name, attribute.name)
+                );
+            }
+        }
+        for attribute in required.iter() {
+            if element.attribute(attribute).is_none() {
+                self.error(element.position, format!("<{}> requires '{}'", name, attribute));
+            }
+        }
+
+        self.placement(element, parent);
+        self.attributes(element);
+
+        for child in &element.children {
+            match child {
+                XmlNode::Element(child) => self.element(child, Some(name)),
+                XmlNode::Text(text, position) => {
+                    if matches!(name, "par" | "seq") && !text.trim().is_empty() {
+                        self.error(*position, format!("<{}> may only contain <media>", name));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks where an element appears.
+    fn placement(&mut self, element: &Element, parent: Option<&str>) {
+        let name = element.name.as_str();
+        match (name, parent) {
+            ("speak", Some(parent)) if parent != "media" => {
+                self.error(element.position, "<speak> may only be nested in <media>".to_string());
+            }
+            ("media", parent) if !matches!(parent, Some("par" | "seq")) => {
+                self.error(element.position, "<media> must be inside <par> or <seq>".to_string());
+            }
+            ("desc", parent) if parent != Some("audio") => {
+                self.error(element.position, "<desc> must be inside <audio>".to_string());
+            }
+            (name, Some("par" | "seq")) if name != "media" => {
+                self.error(
+                    element.position,
+                    format!("<{}> must be wrapped in <media> inside <par> or <seq>", name)
+                );
+            }
+            _ => {}
+        }
+
+        let must_be_empty = matches!(name, "break" | "mark");
+        if must_be_empty && !element.children.is_empty() {
+            self.error(element.position, format!("<{}> must be empty", name));
+        }
+        let text_only = matches!(name, "say-as" | "sub" | "phoneme");
+        if text_only && element.elements().next().is_some() {
+            self.error(element.position, format!("<{}> may only contain text", name));
+        }
+    }
+
+    /// Checks the values of the attributes of an element.
+    fn attributes(&mut self, element: &Element) {
+        for attribute in &element.attributes {
+            match (element.name.as_str(), attribute.name.as_str()) {
+                ("break", "time") => self.break_time(attribute),
+                ("break", "strength") => {
+                    let known = BreakStrength::ALL.iter().map(BreakStrength::as_str);
+                    self.one_of(attribute, known);
+                }
+                ("say-as", "interpret-as") => {
+                    // `bleep` is accepted as an alias of `expletive`.
+                    let known = InterpretAs::ALL.iter().map(InterpretAs::as_str);
+                    self.one_of(attribute, known.chain(["bleep"]));
+                }
+                ("emphasis", "level") => {
+                    let known = EmphasisLevel::ALL.iter().map(EmphasisLevel::as_str);
+                    self.one_of(attribute, known);
+                }
+                ("phoneme", "alphabet") => {
+                    let known = PhoneticAlphabet::ALL.iter().map(PhoneticAlphabet::as_str);
+                    self.one_of(attribute, known);
+                }
+                ("voice", "gender") => {
+                    let known = VoiceGender::ALL.iter().map(VoiceGender::as_str);
+                    self.one_of(attribute, known);
+                }
+                ("prosody", "rate") => self.prosody_rate(attribute),
+                ("prosody", "pitch") => self.prosody_pitch(attribute),
+                ("prosody", "volume") => self.prosody_volume(attribute),
+                ("mark", "name") => self.mark(attribute),
+                (_, "xml:lang") | ("say-as" | "voice", "language")
+                    if attribute.value.parse::<LanguageTag>().is_err() =>
+                {
+                    self.error(
+                        attribute.position,
+                        format!("'{}' is not a valid language tag", attribute.value)
+                    );
+                }
+                (_, name)
+                    if TIME_ATTRIBUTES.contains(&name) && parse_time(&attribute.value).is_none() =>
+                {
+                    self.invalid_time(attribute);
+                }
+                _ => {}
+            }
+        }
+
+        if element.name == "break" && element.attribute("time").is_some() {
+            if let Some(strength) = element.attribute("strength") {
+                self.warning(
+                    strength.position,
+                    "'strength' is ignored when 'time' is set".to_string()
+                );
+            }
+        }
+    }
+
+    fn one_of<'a>(&mut self, attribute: &Attribute, known: impl Iterator<Item = &'a str>) {
+        let known: Vec<&str> = known.collect();
+        if !known.contains(&attribute.value.as_str()) {
+            self.error(
+                attribute.position,
+                format!(
+                    "'{}' is not a valid value of '{}', expected one of: {}",
+                    attribute.value,
+                    attribute.name,
+                    known.join(", ")
+                )
+            );
+        }
+    }
+
+    fn invalid_time(&mut self, attribute: &Attribute) {
+        self.error(
+            attribute.position,
+            format!(
+                "'{}' is not a valid time for '{}', expected e.g. '500ms' or '2s'",
+                attribute.value,
+                attribute.name
+            )
+        );
+    }
+
+    fn break_time(&mut self, attribute: &Attribute) {
+        match parse_time(&attribute.value) {
+            None => self.invalid_time(attribute),
+            Some(time) if time > MAX_BREAK_TIME => {
+                self.error(
+                    attribute.position,
+                    format!(
+                        "break time '{}' exceeds the maximum of {}s",
+                        attribute.value,
+                        MAX_BREAK_TIME.as_secs()
+                    )
+                );
+            }
+            Some(_) => {}
+        }
+    }
+
+    fn prosody_rate(&mut self, attribute: &Attribute) {
+        const KEYWORDS: &[&str] = &["x-slow", "slow", "medium", "fast", "x-fast", "default"];
+        if KEYWORDS.contains(&attribute.value.as_str()) {
+            return;
+        }
+        let min = SPEAKING_RATE_RANGE.start() * 100.0;
+        let max = SPEAKING_RATE_RANGE.end() * 100.0;
+        match number_with_unit(&attribute.value, "%") {
+            Some(percent) if (min..=max).contains(&percent) => {}
+            Some(_) => {
+                self.error(
+                    attribute.position,
+                    format!("rate '{}' is outside of {}% to {}%", attribute.value, min, max)
+                );
+            }
+            None => self.invalid_prosody(attribute, KEYWORDS, "a percentage such as '80%'"),
+        }
+    }
+
+    fn prosody_pitch(&mut self, attribute: &Attribute) {
+        const KEYWORDS: &[&str] = &["x-low", "low", "medium", "high", "x-high", "default"];
+        if KEYWORDS.contains(&attribute.value.as_str()) {
+            return;
+        }
+        if let Some(semitones) = number_with_unit(&attribute.value, "st") {
+            if !PITCH_RANGE.contains(&semitones) {
+                self.error(
+                    attribute.position,
+                    format!(
+                        "pitch '{}' is outside of {}st to {}st",
+                        attribute.value,
+                        PITCH_RANGE.start(),
+                        PITCH_RANGE.end()
+                    )
+                );
+            }
+        } else if let Some(percent) = number_with_unit(&attribute.value, "%") {
+            if percent <= -100.0 {
+                self.error(
+                    attribute.position,
+                    format!("pitch '{}' must be above -100%", attribute.value)
+                );
+            }
+        } else {
+            self.invalid_prosody(attribute, KEYWORDS, "a change such as '-2st' or '+10%'");
+        }
+    }
+
+    fn prosody_volume(&mut self, attribute: &Attribute) {
+        const KEYWORDS: &[&str] = &[
+            "silent",
+            "x-soft",
+            "soft",
+            "medium",
+            "loud",
+            "x-loud",
+            "default",
+        ];
+        if KEYWORDS.contains(&attribute.value.as_str()) {
+            return;
+        }
+        match number_with_unit(&attribute.value, "dB") {
+            Some(decibels) if VOLUME_GAIN_DB_RANGE.contains(&decibels) => {}
+            Some(_) => {
+                self.error(
+                    attribute.position,
+                    format!(
+                        "volume '{}' is outside of {}dB to {}dB",
+                        attribute.value,
+                        VOLUME_GAIN_DB_RANGE.start(),
+                        VOLUME_GAIN_DB_RANGE.end()
+                    )
+                );
+            }
+            None => self.invalid_prosody(attribute, KEYWORDS, "a change such as '-6dB'"),
+        }
+    }
+
+    fn invalid_prosody(&mut self, attribute: &Attribute, keywords: &[&str], expected: &str) {
+        self.error(
+            attribute.position,
+            format!(
+                "'{}' is not a valid {}, expected one of {} or {}",
+                attribute.value,
+                attribute.name,
+                keywords.join(", "),
+                expected
+            )
+        );
+    }
+
+    fn mark(&mut self, attribute: &Attribute) {
+        if attribute.value.is_empty() {
+            self.error(attribute.position, "mark names must not be empty".to_string());
+        } else if let Some(first) = self.marks.get(&attribute.value) {
+            let first = *first;
+            self.error(
+                attribute.position,
+                format!("duplicate mark '{}', first used at {}", attribute.value, first)
+            );
+        } else {
+            self.marks.insert(attribute.value.clone(), attribute.position);
+        }
+    }
+}
+
+/// Parses a number followed by `unit`, with an optional sign, e.g. `+2.5st`.
+fn number_with_unit(value: &str, unit: &str) -> Option<f32> {
+    let number: f32 = value.strip_suffix(unit)?.trim().parse().ok()?;
+    number.is_finite().then_some(number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the diagnostics of `ssml` as `line:column: severity: message`.
+    fn diagnostics(ssml: &str) -> Vec<String> {
+        lint(ssml).iter().map(Diagnostic::to_string).collect()
+    }
+
+    #[test]
+    fn accepts_a_valid_document() {
+        let ssml = r#"<speak xml:lang="en-US">
+  <p><s>Hello <break time="500ms"/> <mark name="a"/>world.</s></p>
+  <prosody rate="80%" pitch="-2st" volume="+3dB">Slowly.</prosody>
+  <say-as interpret-as="characters">SSML</say-as> <sub alias="World Wide Web">WWW</sub>
+  <audio src="https://example.com/a.mp3" clipBegin="1s"><desc>A sound</desc></audio>
+  <par><media><speak>Inside</speak></media></par>
+</speak>"#;
+        assert_eq!(diagnostics(ssml), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_a_malformed_document_as_a_single_error() {
+        assert_eq!(diagnostics("<speak>\n  <p>Hi</speak>"), [
+            "2:8: error: </speak> doesn't match <p> opened at 2:3",
+        ]);
+        assert_eq!(diagnostics(""), ["1:1: error: the document is empty"]);
+    }
+
+    #[test]
+    fn reports_unsupported_elements_and_attributes() {
+        assert_eq!(diagnostics("<p>Hi</p>"), [
+            "1:1: error: the root element must be <speak>, not <p>",
+        ]);
+        assert_eq!(diagnostics("<speak>\n  <foo>x</foo>\n</speak>"), [
+            "2:3: error: <foo> is not supported",
+        ]);
+        assert_eq!(diagnostics("<speak>\n  <break size=\"2\"/></speak>"), [
+            "2:10: error: <break> doesn't support the 'size' attribute",
+        ]);
+        assert_eq!(diagnostics("<speak><sub>WWW</sub> <phoneme ph=\"x\">y</phoneme></speak>"), [
+            "1:8: error: <sub> requires 'alias'",
+            "1:23: error: <phoneme> requires 'alphabet'",
+        ]);
+    }
+
+    #[test]
+    fn reports_misplaced_elements() {
+        assert_eq!(diagnostics("<speak><par>Hello<media/></par></speak>"), [
+            "1:13: error: <par> may only contain <media>",
+        ]);
+        let ssml = "<speak><p><speak>x</speak></p><media/><desc>d</desc><seq><p/></seq></speak>";
+        assert_eq!(diagnostics(ssml), [
+            "1:11: error: <speak> may only be nested in <media>",
+            "1:31: error: <media> must be inside <par> or <seq>",
+            "1:39: error: <desc> must be inside <audio>",
+            "1:58: error: <p> must be wrapped in <media> inside <par> or <seq>",
+        ]);
+        assert_eq!(diagnostics("<speak><break>x</break><sub alias=\"a\"><b/></sub></speak>"), [
+            "1:8: error: <break> must be empty",
+            "1:24: error: <sub> may only contain text",
+            "1:39: error: <b> is not supported",
+        ]);
+    }
+
+    #[test]
+    fn reports_invalid_attribute_values() {
+        let ssml = "<speak>\n <emphasis level=\"loudest\">x</emphasis>\
+            <say-as interpret-as=\"bleep\">x</say-as></speak>";
+        assert_eq!(diagnostics(ssml), [
+            "2:12: error: 'loudest' is not a valid value of 'level', \
+                expected one of: strong, moderate, none, reduced",
+        ]);
+        let ssml = "<speak><lang xml:lang=\"en-US\">x</lang>\
+            <voice language=\"en_US\">y</voice></speak>";
+        assert_eq!(diagnostics(ssml), [
+            "1:46: error: 'en_US' is not a valid language tag",
+        ]);
+    }
+
+    #[test]
+    fn reports_invalid_times() {
+        let ssml = "<speak><audio src=\"a\" clipBegin=\"soon\"/>\
+            <break time=\"11s\"/><break time=\"x\"/></speak>";
+        assert_eq!(diagnostics(ssml), [
+            "1:23: error: 'soon' is not a valid time for 'clipBegin', \
+                expected e.g. '500ms' or '2s'",
+            "1:48: error: break time '11s' exceeds the maximum of 10s",
+            "1:67: error: 'x' is not a valid time for 'time', expected e.g. '500ms' or '2s'",
+        ]);
+        assert_eq!(parse_time("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_time("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_time("-1s"), None);
+        assert_eq!(parse_time("NaNs"), None);
+        assert_eq!(parse_time("1e30s"), None);
+        assert_eq!(parse_time("1e300ms"), None);
+
+        let ssml = "<speak><break time=\"1e30s\"/><audio src=\"a\" clipBegin=\"1e300ms\"/></speak>";
+        assert_eq!(diagnostics(ssml), [
+            "1:15: error: '1e30s' is not a valid time for 'time', expected e.g. '500ms' or '2s'",
+            "1:44: error: '1e300ms' is not a valid time for 'clipBegin', \
+                expected e.g. '500ms' or '2s'",
+        ]);
+    }
+
+    #[test]
+    fn reports_prosody_out_of_range() {
+        let ssml = "<speak><prosody rate=\"500%\">a</prosody>\
+            <prosody rate=\"quick\">b</prosody></speak>";
+        assert_eq!(diagnostics(ssml), [
+            "1:17: error: rate '500%' is outside of 25% to 400%",
+            "1:49: error: 'quick' is not a valid rate, expected one of x-slow, slow, medium, fast, \
+                x-fast, default or a percentage such as '80%'",
+        ]);
+        let ssml = "<speak><prosody pitch=\"+30st\">a</prosody><prosody pitch=\"-100%\">b</prosody>\
+            <prosody pitch=\"up\">c</prosody></speak>";
+        assert_eq!(diagnostics(ssml), [
+            "1:17: error: pitch '+30st' is outside of -20st to 20st",
+            "1:51: error: pitch '-100%' must be above -100%",
+            "1:85: error: 'up' is not a valid pitch, expected one of x-low, low, medium, high, \
+                x-high, default or a change such as '-2st' or '+10%'",
+        ]);
+        let ssml = "<speak><prosody volume=\"+20dB\">a</prosody>\
+            <prosody volume=\"loud!\">b</prosody></speak>";
+        assert_eq!(diagnostics(ssml), [
+            "1:17: error: volume '+20dB' is outside of -96dB to 16dB",
+            "1:52: error: 'loud!' is not a valid volume, expected one of silent, x-soft, soft, \
+                medium, loud, x-loud, default or a change such as '-6dB'",
+        ]);
+    }
+
+    #[test]
+    fn reports_empty_and_duplicate_marks() {
+        let ssml = "<speak><mark name=\"\"/>\n<mark name=\"m\"/>\n  <mark name=\"m\"/></speak>";
+        assert_eq!(diagnostics(ssml), [
+            "1:14: error: mark names must not be empty",
+            "3:9: error: duplicate mark 'm', first used at 2:7",
+        ]);
+    }
+
+    #[test]
+    fn warns_without_failing() {
+        let ssml = "<speak>\n\t<break time=\"1s\" strength=\"weak\"/></speak>";
+        assert_eq!(diagnostics(ssml), ["2:19: warning: 'strength' is ignored when 'time' is set"]);
+        assert!(errors(ssml).is_empty());
+    }
+}
@@ -0,0 +1,935 @@
+// src/ssml/markdown.rs
+
+//! Rendering of Markdown into speech.
+//!
+//! Covers the parts of CommonMark used in chat messages, plus the Discord extensions:
+//! spoilers (`||text||`), multi-line block quotes (`>>> `) and subtext (`-# `). Reference
+//! links, tables and raw HTML are read as plain text.
+
+use std::time::Duration;
+
+use crate::ssml::{
+    attributes::{ BreakStrength, EmphasisLevel, InterpretAs },
+    convert::{ DEFAULT_HEADING_PAUSE, DEFAULT_LIST_ITEM_PAUSE },
+    node::{ Break, Node, SayAs, Speak },
+};
+
+/// How code blocks are read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeBlocks {
+    Skip,
+    /// Reads the given summary instead, e.g. `code block`.
+    Summary(String),
+    /// Reads the code as is.
+    Read,
+}
+
+/// How links written with a label, e.g. `[docs](https://example.com/docs)`, are read. Bare
+/// URLs are always read by their host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Links {
+    Label,
+    /// Reads the host of the URL, e.g. `example.com`.
+    Host,
+}
+
+/// How block quotes are read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockQuotes {
+    Skip,
+    Read,
+    /// Reads the given introduction, e.g. `quote`, before the quote.
+    Introduce(String),
+}
+
+/// How Discord spoilers are read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Spoilers {
+    Skip,
+    Read,
+    /// Bleeps the spoiler out with `<say-as interpret-as="expletive">`.
+    Bleep,
+    /// Reads the given word instead, e.g. `spoiler`.
+    Replace(String),
+}
+
+/// Renders Markdown into an SSML document.
+///
+/// Emphasis and bold text are read with `<emphasis>`, headings are followed by a pause and
+/// list items are read with their number. Markup characters are never read out.
+///
+/// The result converts into a [SynthesisInput](crate::types::synthesize::SynthesisInput),
+/// e.g. `builder.input(MarkdownRenderer::new().render(message).into())`.
+#[derive(Debug, Clone)]
+pub struct MarkdownRenderer {
+    heading_pause: Duration,
+    list_item_pause: Duration,
+    enumerate_lists: bool,
+    code_blocks: CodeBlocks,
+    links: Links,
+    block_quotes: BlockQuotes,
+    spoilers: Spoilers,
+}
+
+impl Default for MarkdownRenderer {
+    fn default() -> Self {
+        MarkdownRenderer {
+            heading_pause: DEFAULT_HEADING_PAUSE,
+            list_item_pause: DEFAULT_LIST_ITEM_PAUSE,
+            enumerate_lists: true,
+            code_blocks: CodeBlocks::Summary("code block".to_string()),
+            links: Links::Label,
+            block_quotes: BlockQuotes::Read,
+            spoilers: Spoilers::Replace("spoiler".to_string()),
+        }
+    }
+}
+
+impl MarkdownRenderer {
+    pub fn new() -> Self {
+        MarkdownRenderer::default()
+    }
+
+    /// Sets the pause after headings; [Duration::ZERO] adds none.
+    pub fn heading_pause(mut self, pause: Duration) -> Self {
+        self.heading_pause = pause;
+        self
+    }
+
+    /// Sets the pause after list items; [Duration::ZERO] adds none.
+    pub fn list_item_pause(mut self, pause: Duration) -> Self {
+        self.list_item_pause = pause;
+        self
+    }
+
+    /// Reads list items with their number, e.g. `1.`, which is the default. Bullet lists are
+    /// numbered from 1.
+    pub fn enumerate_lists(mut self, enumerate: bool) -> Self {
+        self.enumerate_lists = enumerate;
+        self
+    }
+
+    /// Sets how code blocks are read; summarized as `code block` by default.
+    pub fn code_blocks(mut self, code_blocks: CodeBlocks) -> Self {
+        self.code_blocks = code_blocks;
+        self
+    }
+
+    /// Sets how labeled links are read; by their label by default.
+    pub fn links(mut self, links: Links) -> Self {
+        self.links = links;
+        self
+    }
+
+    /// Sets how block quotes are read; like the rest of the text by default.
+    pub fn block_quotes(mut self, block_quotes: BlockQuotes) -> Self {
+        self.block_quotes = block_quotes;
+        self
+    }
+
+    /// Sets how spoilers are read; replaced with `spoiler` by default.
+    pub fn spoilers(mut self, spoilers: Spoilers) -> Self {
+        self.spoilers = spoilers;
+        self
+    }
+
+    /// Renders `markdown` into a `<speak>` document.
+    pub fn render(&self, markdown: &str) -> Speak {
+        let lines: Vec<&str> = markdown.lines().collect();
+        let children = parse_blocks(&lines)
+            .iter()
+            .map(|block| self.block(block))
+            .filter(|nodes| !nodes.is_empty())
+            .map(Node::Paragraph)
+            .collect();
+        Speak::new(children)
+    }
+
+    /// Renders `markdown` and serializes the resulting document.
+    pub fn to_ssml(&self, markdown: &str) -> String {
+        self.render(markdown).to_string()
+    }
+
+    /// Renders a block into sentences and breaks.
+    fn block(&self, block: &Block) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        match block {
+            Block::Heading(text) => {
+                push_sentence(&mut nodes, self.inline(text));
+                push_pause(&mut nodes, self.heading_pause);
+            }
+            Block::Paragraph(text) => push_sentence(&mut nodes, self.inline(text)),
+            Block::Code(code) => {
+                match &self.code_blocks {
+                    CodeBlocks::Skip => {}
+                    CodeBlocks::Summary(summary) => {
+                        push_sentence(&mut nodes, vec![Node::Text(summary.clone())]);
+                    }
+                    CodeBlocks::Read => push_sentence(&mut nodes, vec![Node::Text(code.clone())]),
+                }
+            }
+            Block::Quote(blocks) => {
+                match &self.block_quotes {
+                    BlockQuotes::Skip => {}
+                    BlockQuotes::Read => {
+                        nodes.extend(blocks.iter().flat_map(|block| self.block(block)));
+                    }
+                    BlockQuotes::Introduce(introduction) => {
+                        push_sentence(&mut nodes, vec![Node::Text(introduction.clone())]);
+                        nodes.extend(blocks.iter().flat_map(|block| self.block(block)));
+                    }
+                }
+            }
+            Block::List { start, items } => {
+                for (index, item) in items.iter().enumerate() {
+                    let mut item_nodes: Vec<Node> = item
+                        .iter()
+                        .flat_map(|block| self.block(block))
+                        .collect();
+                    if self.enumerate_lists {
+                        let number = format!("{}. ", start + index);
+                        match item_nodes.first_mut() {
+                            Some(Node::Sentence(children)) => {
+                                children.insert(0, Node::Text(number));
+                            }
+                            _ => item_nodes.insert(0, Node::Sentence(vec![Node::Text(number)])),
+                        }
+                    }
+                    nodes.extend(item_nodes);
+                    push_pause(&mut nodes, self.list_item_pause);
+                }
+            }
+            Block::Rule => {
+                let strength = Some(BreakStrength::Strong);
+                nodes.push(Node::Break(Break { time: None, strength }));
+            }
+        }
+        nodes
+    }
+
+    /// Renders inline Markdown.
+    fn inline(&self, text: &str) -> Vec<Node> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut nodes = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            match c {
+                '\\' if next.is_some_and(|next| next.is_ascii_punctuation()) => {
+                    push_text(&mut nodes, &chars[i + 1..i + 2]);
+                    i += 2;
+                }
+                '`' => {
+                    let length = run_length(&chars, i, '`');
+                    match find_run(&chars, i + length, '`', length) {
+                        Some(end) => {
+                            let code: String = chars[i + length..end].iter().collect();
+                            push_text(&mut nodes, &code.trim().chars().collect::<Vec<_>>());
+                            i = end + length;
+                        }
+                        None => {
+                            push_text(&mut nodes, &chars[i..i + length]);
+                            i += length;
+                        }
+                    }
+                }
+                '|' if next == Some('|') => {
+                    match find_pair(&chars, i + 2, '|') {
+                        Some(end) if end > i + 2 => {
+                            let inner: String = chars[i + 2..end].iter().collect();
+                            self.spoiler(&mut nodes, &inner);
+                            i = end + 2;
+                        }
+                        _ => {
+                            push_text(&mut nodes, &chars[i..i + 2]);
+                            i += 2;
+                        }
+                    }
+                }
+                '~' if next == Some('~') => {
+                    match find_pair(&chars, i + 2, '~') {
+                        Some(end) if end > i + 2 => {
+                            let inner: String = chars[i + 2..end].iter().collect();
+                            nodes.extend(self.inline(&inner));
+                            i = end + 2;
+                        }
+                        _ => {
+                            push_text(&mut nodes, &chars[i..i + 2]);
+                            i += 2;
+                        }
+                    }
+                }
+                '*' | '_' => {
+                    i = self.emphasis(&chars, i, &mut nodes);
+                }
+                '!' if next == Some('[') => {
+                    match parse_link(&chars, i + 1) {
+                        Some((alt, _, end)) => {
+                            nodes.extend(self.inline(&alt));
+                            i = end;
+                        }
+                        None => {
+                            push_text(&mut nodes, &chars[i..i + 1]);
+                            i += 1;
+                        }
+                    }
+                }
+                '[' => {
+                    match parse_link(&chars, i) {
+                        Some((label, url, end)) => {
+                            match (self.links, host(&url)) {
+                                (Links::Host, Some(host)) => push_str(&mut nodes, &host),
+                                _ => nodes.extend(self.inline(&label)),
+                            }
+                            i = end;
+                        }
+                        None => {
+                            push_text(&mut nodes, &chars[i..i + 1]);
+                            i += 1;
+                        }
+                    }
+                }
+                '<' => {
+                    let rest: String = chars[i + 1..].iter().collect();
+                    let autolink = rest
+                        .find('>')
+                        .map(|end| &rest[..end])
+                        .filter(|url| !url.contains(char::is_whitespace))
+                        .and_then(|url| Some((url.chars().count(), host(url)?)));
+                    match autolink {
+                        Some((length, host)) => {
+                            push_str(&mut nodes, &host);
+                            i += length + 2;
+                        }
+                        None => {
+                            push_text(&mut nodes, &chars[i..i + 1]);
+                            i += 1;
+                        }
+                    }
+                }
+                'h' if i == 0 || !chars[i - 1].is_alphanumeric() => {
+                    let rest: String = chars[i..].iter().collect();
+                    let url = bare_url(&rest);
+                    match url.and_then(|url| Some((url.chars().count(), host(url)?))) {
+                        Some((length, host)) => {
+                            push_str(&mut nodes, &host);
+                            i += length;
+                        }
+                        None => {
+                            push_text(&mut nodes, &chars[i..i + 1]);
+                            i += 1;
+                        }
+                    }
+                }
+                _ => {
+                    push_text(&mut nodes, &chars[i..i + 1]);
+                    i += 1;
+                }
+            }
+        }
+        nodes
+    }
+
+    /// Renders the emphasis opened by the delimiter run at `start`, and returns the index
+    /// following it. The run is read as text if it isn't closed.
+    fn emphasis(&self, chars: &[char], start: usize, nodes: &mut Vec<Node>) -> usize {
+        let delimiter = chars[start];
+        let length = run_length(chars, start, delimiter).min(3);
+        let opens =
+            chars.get(start + length).is_some_and(|c| !c.is_whitespace()) &&
+            (delimiter == '*' || start == 0 || !chars[start - 1].is_alphanumeric());
+
+        let close = find_closing(chars, start + length, delimiter, length).filter(|_| opens);
+        let Some(end) = close else {
+            push_text(nodes, &chars[start..start + length]);
+            return start + length;
+        };
+
+        let inner: String = chars[start + length..end].iter().collect();
+        let level = if length == 1 { EmphasisLevel::Moderate } else { EmphasisLevel::Strong };
+        nodes.push(Node::Emphasis(Some(level), self.inline(&inner)));
+        end + length
+    }
+
+    fn spoiler(&self, nodes: &mut Vec<Node>, spoiler: &str) {
+        match &self.spoilers {
+            Spoilers::Skip => {}
+            Spoilers::Read => nodes.extend(self.inline(spoiler)),
+            Spoilers::Bleep => {
+                let text = plain_text(&self.inline(spoiler));
+                nodes.push(Node::SayAs(SayAs::new(InterpretAs::Expletive, &text)));
+            }
+            Spoilers::Replace(word) => push_str(nodes, word),
+        }
+    }
+}
+
+/// A block of a Markdown document.
+#[derive(Debug)]
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    Code(String),
+    Quote(Vec<Block>),
+    List {
+        start: usize,
+        items: Vec<Vec<Block>>,
+    },
+    Rule,
+}
+
+fn parse_blocks(lines: &[&str]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            i += 1;
+            continue;
+        }
+
+        if let Some(fence) = code_fence(trimmed) {
+            let mut code = Vec::new();
+            i += 1;
+            while i < lines.len() && !lines[i].trim_start().starts_with(fence) {
+                code.push(lines[i]);
+                i += 1;
+            }
+            i += 1;
+            blocks.push(Block::Code(code.join("\n")));
+        } else if indentation(line) >= 4 {
+            let mut code = Vec::new();
+            while i < lines.len() && (indentation(lines[i]) >= 4 || lines[i].trim().is_empty()) {
+                code.push(lines[i].trim());
+                i += 1;
+            }
+            blocks.push(Block::Code(code.join("\n").trim_end().to_string()));
+        } else if let Some(heading) = atx_heading(trimmed) {
+            blocks.push(Block::Heading(heading.to_string()));
+            i += 1;
+        } else if is_rule(trimmed) {
+            blocks.push(Block::Rule);
+            i += 1;
+        } else if let Some(rest) = trimmed.strip_prefix(">>>") {
+            // Discord: the rest of the message is quoted.
+            let mut quoted = vec![rest.trim_start()];
+            quoted.extend_from_slice(&lines[i + 1..]);
+            blocks.push(Block::Quote(parse_blocks(&quoted)));
+            i = lines.len();
+        } else if trimmed.starts_with('>') {
+            let mut quoted = Vec::new();
+            while let Some(rest) = lines.get(i).and_then(|line| quoted_line(line)) {
+                quoted.push(rest.strip_prefix(' ').unwrap_or(rest));
+                i += 1;
+            }
+            blocks.push(Block::Quote(parse_blocks(&quoted)));
+        } else if let Some(marker) = list_marker(line) {
+            i = parse_list(lines, i, marker, &mut blocks);
+        } else {
+            let mut paragraph = vec![trimmed.strip_prefix("-# ").unwrap_or(trimmed)];
+            i += 1;
+            while i < lines.len() {
+                let next = lines[i].trim();
+                if is_setext_underline(next) {
+                    i += 1;
+                    blocks.push(Block::Heading(paragraph.join(" ")));
+                    paragraph.clear();
+                    break;
+                }
+                if interrupts_paragraph(lines[i]) {
+                    break;
+                }
+                paragraph.push(next);
+                i += 1;
+            }
+            if !paragraph.is_empty() {
+                blocks.push(Block::Paragraph(paragraph.join(" ")));
+            }
+        }
+    }
+    blocks
+}
+
+/// A list item marker.
+#[derive(Debug, Clone, Copy)]
+struct ListMarker {
+    /// Bullet character, or `.` or `)` after the number of an ordered item.
+    delimiter: char,
+    number: Option<usize>,
+    indentation: usize,
+    /// Width of the marker and the spaces after it; continuation lines are indented by it.
+    width: usize,
+}
+
+fn list_marker(line: &str) -> Option<ListMarker> {
+    let indentation = indentation(line);
+    if indentation >= 4 {
+        return None;
+    }
+    let trimmed = line.trim_start();
+    let spaces = |rest: &str| {
+        let count = rest.chars().take_while(|c| *c == ' ').count();
+        (count > 0 || rest.is_empty()).then_some(count.max(1))
+    };
+
+    if let Some(bullet) = trimmed.chars().next().filter(|c| matches!(c, '-' | '*' | '+')) {
+        if trimmed.starts_with("-# ") {
+            return None;
+        }
+        let width = 1 + spaces(&trimmed[1..])?;
+        return Some(ListMarker { delimiter: bullet, number: None, indentation, width });
+    }
+
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 || digits > 9 {
+        return None;
+    }
+    let delimiter = trimmed[digits..].chars().next().filter(|c| matches!(c, '.' | ')'))?;
+    let width = digits + 1 + spaces(&trimmed[digits + 1..])?;
+    let number = trimmed[..digits].parse().ok();
+    Some(ListMarker { delimiter, number, indentation, width })
+}
+
+/// Parses the list starting at `start`, and returns the index of the line following it.
+fn parse_list(lines: &[&str], start: usize, first: ListMarker, blocks: &mut Vec<Block>) -> usize {
+    let mut items: Vec<Vec<&str>> = Vec::new();
+    let mut i = start;
+
+    while i < lines.len() {
+        let line = lines[i];
+        match list_marker(line) {
+            Some(marker) if marker.delimiter == first.delimiter &&
+                marker.indentation < first.indentation + first.width => {
+                let content = &line.trim_start()[marker.width.min(line.trim_start().len())..];
+                items.push(vec![content]);
+                i += 1;
+            }
+            _ => {
+                let content_indentation = first.indentation + first.width;
+                if line.trim().is_empty() {
+                    let continues = lines
+                        .get(i + 1)
+                        .is_some_and(|next| {
+                            indentation(next) >= content_indentation ||
+                                list_marker(next).is_some_and(|marker| {
+                                    marker.delimiter == first.delimiter
+                                })
+                        });
+                    if !continues {
+                        break;
+                    }
+                    if let Some(item) = items.last_mut() {
+                        item.push("");
+                    }
+                    i += 1;
+                } else if indentation(line) >= content_indentation {
+                    if let Some(item) = items.last_mut() {
+                        item.push(strip_indentation(line, content_indentation));
+                    }
+                    i += 1;
+                } else if items.last().is_some_and(|item| !item.last().unwrap_or(&"").is_empty()) &&
+                    !interrupts_paragraph(line) {
+                    // A lazy continuation of the item's paragraph.
+                    if let Some(item) = items.last_mut() {
+                        item.push(line.trim());
+                    }
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+        }
+    }
+
+    blocks.push(Block::List {
+        start: first.number.unwrap_or(1),
+        items: items
+            .iter()
+            .map(|item| parse_blocks(item))
+            .collect(),
+    });
+    i
+}
+
+/// Returns `true` if `line` starts a block that ends a paragraph.
+fn interrupts_paragraph(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.is_empty() ||
+        code_fence(trimmed).is_some() ||
+        atx_heading(trimmed).is_some() ||
+        trimmed.starts_with('>') ||
+        trimmed.starts_with("-# ") ||
+        is_rule(trimmed) ||
+        list_marker(line).is_some_and(|marker| marker.number.is_none_or(|number| number == 1))
+}
+
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Removes up to `width` columns of indentation from `line`.
+fn strip_indentation(line: &str, width: usize) -> &str {
+    let mut columns = 0;
+    for (offset, c) in line.char_indices() {
+        if columns >= width || !c.is_whitespace() {
+            return &line[offset..];
+        }
+        columns += if c == '\t' { 4 } else { 1 };
+    }
+    ""
+}
+
+/// Returns the fence opening a fenced code block, e.g. ```` ``` ````.
+fn code_fence(line: &str) -> Option<&str> {
+    let fence_char = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let length = line.chars().take_while(|c| *c == fence_char).count();
+    (length >= 3).then(|| &line[..length])
+}
+
+/// Returns the text of an ATX heading, e.g. `## Title`.
+fn atx_heading(line: &str) -> Option<&str> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim_end())
+}
+
+/// Returns the content of a block quote line, e.g. `quoted` for `> quoted`.
+fn quoted_line(line: &str) -> Option<&str> {
+    line.trim_start().strip_prefix('>')
+}
+
+/// Returns `true` for a line underlining a heading, e.g. `===`.
+fn is_setext_underline(line: &str) -> bool {
+    !line.is_empty() &&
+        (line.chars().all(|c| c == '=') || (line.len() >= 2 && line.chars().all(|c| c == '-')))
+}
+
+/// Returns `true` for a thematic break, e.g. `---` or `* * *`.
+fn is_rule(line: &str) -> bool {
+    let marks: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3 &&
+        matches!(marks[0], '*' | '-' | '_') &&
+        marks.iter().all(|c| *c == marks[0])
+}
+
+/// Appends a sentence made of `children`, unless it is empty.
+fn push_sentence(nodes: &mut Vec<Node>, children: Vec<Node>) {
+    if !plain_text(&children).trim().is_empty() {
+        nodes.push(Node::Sentence(children));
+    }
+}
+
+/// Appends a pause, unless `nodes` already end with one, e.g. after a nested list.
+fn push_pause(nodes: &mut Vec<Node>, pause: Duration) {
+    if !pause.is_zero() && !matches!(nodes.last(), Some(Node::Break(_))) {
+        nodes.push(Node::Break(Break { time: Some(pause), strength: None }));
+    }
+}
+
+fn push_str(nodes: &mut Vec<Node>, text: &str) {
+    match nodes.last_mut() {
+        Some(Node::Text(last)) => last.push_str(text),
+        _ => nodes.push(Node::Text(text.to_string())),
+    }
+}
+
+fn push_text(nodes: &mut Vec<Node>, chars: &[char]) {
+    push_str(nodes, &chars.iter().collect::<String>());
+}
+
+/// Returns the text read from `nodes`, without markup.
+fn plain_text(nodes: &[Node]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(content) => text.push_str(content),
+            Node::Emphasis(_, children) | Node::Sentence(children) => {
+                text.push_str(&plain_text(children));
+            }
+            Node::SayAs(say_as) => text.push_str(&say_as.text),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn run_length(chars: &[char], start: usize, c: char) -> usize {
+    chars[start..].iter().take_while(|other| **other == c).count()
+}
+
+/// Finds a run of exactly `length` characters `c`, from `start`.
+fn find_run(chars: &[char], start: usize, c: char, length: usize) -> Option<usize> {
+    let mut i = start;
+    while i < chars.len() {
+        if chars[i] == c {
+            let run = run_length(chars, i, c);
+            if run == length {
+                return Some(i);
+            }
+            i += run;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Finds two consecutive characters `c`, from `start`.
+fn find_pair(chars: &[char], start: usize, c: char) -> Option<usize> {
+    (start..chars.len().saturating_sub(1)).find(|&i| chars[i] == c && chars[i + 1] == c)
+}
+
+/// Finds the delimiter run closing an emphasis opened with `length` characters `delimiter`.
+fn find_closing(chars: &[char], start: usize, delimiter: char, length: usize) -> Option<usize> {
+    let mut i = start;
+    while i < chars.len() {
+        if chars[i] == '\\' {
+            i += 2;
+            continue;
+        }
+        if chars[i] != delimiter {
+            i += 1;
+            continue;
+        }
+        let run = run_length(chars, i, delimiter);
+        let closes =
+            run >= length &&
+            i > start &&
+            !chars[i - 1].is_whitespace() &&
+            (delimiter == '*' || chars.get(i + length).is_none_or(|c| !c.is_alphanumeric()));
+        if closes && (run == length || length > 1) {
+            return Some(i);
+        }
+        i += run;
+    }
+    None
+}
+
+/// Parses `[label](url)` at `start`, and returns the label, the URL and the index following
+/// the link.
+fn parse_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let mut depth = 0;
+    let mut i = start;
+    let label_end = loop {
+        match chars.get(i)? {
+            '\\' => i += 1,
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    break i;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    };
+    if chars.get(label_end + 1) != Some(&'(') {
+        return None;
+    }
+    let url_end = (label_end + 2..chars.len()).find(|&i| chars[i] == ')')?;
+    let label = chars[start + 1..label_end].iter().collect();
+    let destination: String = chars[label_end + 2..url_end].iter().collect();
+    let url = destination
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .trim_matches(['<', '>'])
+        .to_string();
+    Some((label, url, url_end + 1))
+}
+
+/// Returns the URL at the start of `text`, without trailing punctuation.
+pub(crate) fn bare_url(text: &str) -> Option<&str> {
+    if !text.starts_with("http://") && !text.starts_with("https://") {
+        return None;
+    }
+    let end = text.find(|c: char| c.is_whitespace() || c == '<').unwrap_or(text.len());
+    Some(text[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'', '"']))
+}
+
+/// Returns the host of an `http` or `https` URL, without `www.`.
+pub(crate) fn host(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns `markdown` rendered by `renderer`, as the content of the `<speak>` document.
+    fn render(renderer: &MarkdownRenderer, markdown: &str) -> String {
+        let ssml = renderer.to_ssml(markdown);
+        ssml.trim_start_matches("<speak>").trim_end_matches("</speak>").to_string()
+    }
+
+    fn assert_renders(renderer: &MarkdownRenderer, cases: &[(&str, &str)]) {
+        for (markdown, expected) in cases {
+            assert_eq!(render(renderer, markdown), *expected, "rendering {:?}", markdown);
+        }
+    }
+
+    #[test]
+    fn renders_emphasis() {
+        assert_renders(&MarkdownRenderer::new(), &[
+            (
+                "**bold** and __bold__",
+                concat!(
+                    r#"<p><s><emphasis level="strong">bold</emphasis> and "#,
+                    r#"<emphasis level="strong">bold</emphasis></s></p>"#
+                ),
+            ),
+            (
+                "*em* and _em_",
+                concat!(
+                    r#"<p><s><emphasis level="moderate">em</emphasis> and "#,
+                    r#"<emphasis level="moderate">em</emphasis></s></p>"#
+                ),
+            ),
+            (
+                "***both*** or **unclosed",
+                r#"<p><s><emphasis level="strong">both</emphasis> or **unclosed</s></p>"#,
+            ),
+            // Underscores don't open emphasis inside words, asterisks do.
+            ("snake_case_name", "<p><s>snake_case_name</s></p>"),
+            ("2*3*4", r#"<p><s>2<emphasis level="moderate">3</emphasis>4</s></p>"#),
+            ("a * b * c", "<p><s>a * b * c</s></p>"),
+            (r"\*literal\*", "<p><s>*literal*</s></p>"),
+        ]);
+    }
+
+    #[test]
+    fn renders_lists() {
+        assert_renders(&MarkdownRenderer::new(), &[
+            (
+                "- one\n- two\n  - nested\n- three",
+                concat!(
+                    r#"<p><s>1. one</s><break time="300ms"/><s>2. two</s><s>1. nested</s>"#,
+                    r#"<break time="300ms"/><s>3. three</s><break time="300ms"/></p>"#
+                ),
+            ),
+            (
+                "3. three\n4. four",
+                r#"<p><s>3. three</s><break time="300ms"/><s>4. four</s><break time="300ms"/></p>"#,
+            ),
+        ]);
+
+        let renderer = MarkdownRenderer::new()
+            .enumerate_lists(false)
+            .list_item_pause(Duration::ZERO);
+        assert_renders(&renderer, &[("1) a\n2) b", "<p><s>a</s><s>b</s></p>")]);
+    }
+
+    #[test]
+    fn renders_code_blocks() {
+        let fenced = "Look:\n```rust\nlet x = 1;\n```\nDone.";
+        let indented = "Look:\n\n    let x = 1;\n    x + 1\n\nDone.";
+
+        let renderer = MarkdownRenderer::new();
+        let summarized = "<p><s>Look:</s></p><p><s>code block</s></p><p><s>Done.</s></p>";
+        assert_renders(&renderer, &[(fenced, summarized), (indented, summarized)]);
+
+        let renderer = MarkdownRenderer::new().code_blocks(CodeBlocks::Skip);
+        let skipped = "<p><s>Look:</s></p><p><s>Done.</s></p>";
+        assert_renders(&renderer, &[(fenced, skipped), (indented, skipped)]);
+
+        let renderer = MarkdownRenderer::new().code_blocks(CodeBlocks::Read);
+        assert_renders(&renderer, &[
+            (fenced, "<p><s>Look:</s></p><p><s>let x = 1;</s></p><p><s>Done.</s></p>"),
+            (indented, "<p><s>Look:</s></p><p><s>let x = 1;\nx + 1</s></p><p><s>Done.</s></p>"),
+        ]);
+    }
+
+    #[test]
+    fn renders_links() {
+        let cases = [
+            "See [the docs](https://www.example.com/docs).",
+            "See <https://docs.rs/regex>.",
+            "Go to https://github.com/rust-lang/rust, now.",
+            "![a cat](https://example.com/cat.png) and <not a link>",
+        ];
+        assert_renders(&MarkdownRenderer::new(), &[
+            (cases[0], "<p><s>See the docs.</s></p>"),
+            (cases[1], "<p><s>See docs.rs.</s></p>"),
+            (cases[2], "<p><s>Go to github.com, now.</s></p>"),
+            (cases[3], "<p><s>a cat and &lt;not a link&gt;</s></p>"),
+        ]);
+        assert_renders(&MarkdownRenderer::new().links(Links::Host), &[
+            (cases[0], "<p><s>See example.com.</s></p>"),
+            (cases[1], "<p><s>See docs.rs.</s></p>"),
+        ]);
+    }
+
+    #[test]
+    fn renders_block_quotes() {
+        let quote = "> quoted\n> more\n\nafter";
+        assert_renders(&MarkdownRenderer::new(), &[
+            (quote, "<p><s>quoted more</s></p><p><s>after</s></p>"),
+            // Discord: the rest of the message is quoted.
+            ("before\n>>> all\n\nof this", "<p><s>before</s></p><p><s>all</s><s>of this</s></p>"),
+        ]);
+        let renderer = MarkdownRenderer::new().block_quotes(BlockQuotes::Skip);
+        assert_renders(&renderer, &[(quote, "<p><s>after</s></p>")]);
+        let renderer = MarkdownRenderer::new()
+            .block_quotes(BlockQuotes::Introduce("quote".to_string()));
+        assert_renders(&renderer, &[
+            (quote, "<p><s>quote</s><s>quoted more</s></p><p><s>after</s></p>"),
+        ]);
+    }
+
+    #[test]
+    fn renders_spoilers() {
+        let spoiler = "It was ||the *butler*||!";
+        assert_renders(&MarkdownRenderer::new(), &[
+            (spoiler, "<p><s>It was spoiler!</s></p>"),
+            ("An empty |||| pair", "<p><s>An empty |||| pair</s></p>"),
+        ]);
+        assert_renders(&MarkdownRenderer::new().spoilers(Spoilers::Skip), &[
+            (spoiler, "<p><s>It was !</s></p>"),
+        ]);
+        assert_renders(&MarkdownRenderer::new().spoilers(Spoilers::Read), &[
+            (spoiler, r#"<p><s>It was the <emphasis level="moderate">butler</emphasis>!</s></p>"#),
+        ]);
+        assert_renders(&MarkdownRenderer::new().spoilers(Spoilers::Bleep), &[
+            (
+                spoiler,
+                r#"<p><s>It was <say-as interpret-as="expletive">the butler</say-as>!</s></p>"#,
+            ),
+        ]);
+    }
+
+    #[test]
+    fn renders_headings() {
+        assert_renders(&MarkdownRenderer::new(), &[
+            ("Title\n=====\nText", r#"<p><s>Title</s><break time="600ms"/></p><p><s>Text</s></p>"#),
+            ("Sub\n---\nText", r#"<p><s>Sub</s><break time="600ms"/></p><p><s>Text</s></p>"#),
+            (
+                "# Title\n-# small print",
+                r#"<p><s>Title</s><break time="600ms"/></p><p><s>small print</s></p>"#,
+            ),
+            (
+                "text\n\n---\n\nmore",
+                r#"<p><s>text</s></p><p><break strength="strong"/></p><p><s>more</s></p>"#,
+            ),
+        ]);
+        let renderer = MarkdownRenderer::new().heading_pause(Duration::ZERO);
+        assert_renders(&renderer, &[("## Title", "<p><s>Title</s></p>")]);
+    }
+}
@@ -0,0 +1,23 @@
+// src/ssml/mod.rs
+
+//! Typed SSML documents.
+//!
+//! [builder::SsmlBuilder] builds a [node::Speak] document that serializes to a string accepted
+//! by [SynthesizeSpeechBuilder::input_ssml](crate::services::synthesize::SynthesizeSpeechBuilder::input_ssml),
+//! escaping text and attribute values along the way. [lint::lint] checks documents written
+//! by hand, reporting problems with their line and column. [convert::TextConverter] structures
+//! plain text into paragraphs and sentences, and [markdown::MarkdownRenderer] reads Markdown
+//! without its markup. [lexicon::Lexicon] applies pronunciations loaded from PLS or TOML
+//! files to text and SSML, and [chunk::split_input] splits inputs too long for one request.
+//! [segment::Segmenter] splits text mixing languages into segments of a single language.
+
+pub mod attributes;
+pub mod builder;
+pub mod chunk;
+pub mod convert;
+pub mod lexicon;
+pub mod lint;
+pub mod markdown;
+pub mod node;
+pub mod parser;
+pub mod segment;
@@ -0,0 +1,673 @@
+// src/ssml/node.rs
+
+use std::{ fmt, time::Duration };
+
+use crate::{
+    ssml::attributes::{
+        format_time,
+        BreakStrength,
+        EmphasisLevel,
+        InterpretAs,
+        PhoneticAlphabet,
+        ProsodyPitch,
+        ProsodyRate,
+        ProsodyVolume,
+        VoiceGender,
+    },
+    types::{ language::LanguageTag, synthesize::SynthesisInput, voice_name::VoiceName },
+};
+
+/// Escapes `&`, `<` and `>` in text content.
+pub fn escape_text(text: &str) -> String {
+    escape(text, false)
+}
+
+/// Escapes `&`, `<`, `>`, `"` and `'` in an attribute value.
+pub fn escape_attribute(value: &str) -> String {
+    escape(value, true)
+}
+
+fn escape(value: &str, quotes: bool) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if quotes => escaped.push_str("&quot;"),
+            '\'' if quotes => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A `<speak>` document, the root of every SSML input.
+///
+/// Displays as the serialized document, ready for
+/// [SynthesizeSpeechBuilder::input_ssml](crate::services::synthesize::SynthesizeSpeechBuilder::input_ssml).
+/// Build one with [SsmlBuilder](crate::ssml::builder::SsmlBuilder).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Speak {
+    pub children: Vec<Node>,
+}
+
+impl Speak {
+    pub fn new(children: Vec<Node>) -> Self {
+        Speak { children }
+    }
+}
+
+impl fmt::Display for Speak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<speak>")?;
+        write_nodes(f, &self.children)?;
+        f.write_str("</speak>")
+    }
+}
+
+impl From<Speak> for SynthesisInput {
+    fn from(value: Speak) -> Self {
+        SynthesisInput::Ssml(value.to_string())
+    }
+}
+
+/// A node of an SSML document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    /// Text, escaped when serialized.
+    Text(String),
+    /// `<p>`: a paragraph.
+    Paragraph(Vec<Node>),
+    /// `<s>`: a sentence.
+    Sentence(Vec<Node>),
+    /// `<break>`: a pause.
+    Break(Break),
+    /// `<prosody>`: changes the rate, pitch or volume of the content.
+    Prosody(Prosody, Vec<Node>),
+    /// `<emphasis>`: stresses the content.
+    Emphasis(Option<EmphasisLevel>, Vec<Node>),
+    /// `<say-as>`: tells how the text should be read, e.g. as a date.
+    SayAs(SayAs),
+    /// `<sub>`: reads `alias` instead of `text`.
+    Sub {
+        alias: String,
+        text: String,
+    },
+    /// `<phoneme>`: reads `text` with the pronunciation `ph`.
+    Phoneme {
+        alphabet: PhoneticAlphabet,
+        ph: String,
+        text: String,
+    },
+    /// `<mark>`: a reference point returned as a timepoint.
+    Mark(String),
+    /// `<audio>`: plays an audio file, or speaks the fallback content if it can't be played.
+    Audio(Audio),
+    /// `<par>`: plays its media elements at the same time.
+    Par(Vec<Media>),
+    /// `<seq>`: plays its media elements one after the other.
+    Seq(Vec<Media>),
+    /// `<voice>`: speaks the content with another voice.
+    Voice(Voice, Vec<Node>),
+    /// `<lang>`: marks the content as being in another language.
+    Lang(LanguageTag, Vec<Node>),
+}
+
+/// Attributes of a `<break>`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Break {
+    pub time: Option<Duration>,
+    pub strength: Option<BreakStrength>,
+}
+
+/// Attributes of a `<prosody>`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Prosody {
+    pub rate: Option<ProsodyRate>,
+    pub pitch: Option<ProsodyPitch>,
+    pub volume: Option<ProsodyVolume>,
+}
+
+impl Prosody {
+    pub fn new() -> Self {
+        Prosody::default()
+    }
+
+    pub fn rate(mut self, rate: ProsodyRate) -> Self {
+        self.rate = Some(rate);
+        self
+    }
+
+    pub fn pitch(mut self, pitch: ProsodyPitch) -> Self {
+        self.pitch = Some(pitch);
+        self
+    }
+
+    pub fn volume(mut self, volume: ProsodyVolume) -> Self {
+        self.volume = Some(volume);
+        self
+    }
+}
+
+/// A `<say-as>` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SayAs {
+    pub interpret_as: InterpretAs,
+    /// E.g. `yyyymmdd` for a date, or `hms12` for a time.
+    pub format: Option<String>,
+    /// E.g. `1` to read an ordinal or cardinal with its full detail.
+    pub detail: Option<String>,
+    /// Language of the content, for `interpret-as="date"` and `"currency"`.
+    pub language: Option<LanguageTag>,
+    pub text: String,
+}
+
+impl SayAs {
+    pub fn new(interpret_as: InterpretAs, text: &str) -> Self {
+        SayAs {
+            interpret_as,
+            format: None,
+            detail: None,
+            language: None,
+            text: text.to_string(),
+        }
+    }
+
+    pub fn format(mut self, format: &str) -> Self {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    pub fn detail(mut self, detail: &str) -> Self {
+        self.detail = Some(detail.to_string());
+        self
+    }
+
+    pub fn language(mut self, language: LanguageTag) -> Self {
+        self.language = Some(language);
+        self
+    }
+}
+
+/// An `<audio>` element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Audio {
+    /// URL of the audio file. Must be `https`.
+    pub src: String,
+    pub clip_begin: Option<Duration>,
+    pub clip_end: Option<Duration>,
+    /// Playback speed, in percent of the normal speed.
+    pub speed: Option<u32>,
+    pub repeat_count: Option<u32>,
+    pub repeat_dur: Option<Duration>,
+    /// Volume change, in decibels.
+    pub sound_level: Option<f32>,
+    /// Content spoken if the audio can't be played.
+    pub fallback: Vec<Node>,
+}
+
+impl Audio {
+    pub fn new(src: &str) -> Self {
+        Audio {
+            src: src.to_string(),
+            clip_begin: None,
+            clip_end: None,
+            speed: None,
+            repeat_count: None,
+            repeat_dur: None,
+            sound_level: None,
+            fallback: Vec::new(),
+        }
+    }
+
+    pub fn clip(mut self, begin: Duration, end: Duration) -> Self {
+        self.clip_begin = Some(begin);
+        self.clip_end = Some(end);
+        self
+    }
+
+    pub fn speed(mut self, percent: u32) -> Self {
+        self.speed = Some(percent);
+        self
+    }
+
+    pub fn repeat_count(mut self, count: u32) -> Self {
+        self.repeat_count = Some(count);
+        self
+    }
+
+    pub fn repeat_dur(mut self, duration: Duration) -> Self {
+        self.repeat_dur = Some(duration);
+        self
+    }
+
+    pub fn sound_level(mut self, decibels: f32) -> Self {
+        self.sound_level = Some(decibels);
+        self
+    }
+
+    /// Speaks `text` if the audio can't be played.
+    pub fn fallback_text(mut self, text: &str) -> Self {
+        self.fallback.push(Node::Text(text.to_string()));
+        self
+    }
+}
+
+/// A `<media>` element, child of `<par>` and `<seq>`.
+///
+/// Timing attributes are kept as strings since they can refer to other media elements,
+/// e.g. `question.end+2.0s`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Media {
+    pub id: Option<String>,
+    pub begin: Option<String>,
+    pub end: Option<String>,
+    pub repeat_count: Option<u32>,
+    pub repeat_dur: Option<Duration>,
+    /// Volume change, in decibels.
+    pub sound_level: Option<f32>,
+    pub fade_in_dur: Option<Duration>,
+    pub fade_out_dur: Option<Duration>,
+    pub content: MediaContent,
+}
+
+/// What a `<media>` element plays.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaContent {
+    /// Spoken content, wrapped in a nested `<speak>`.
+    Speak(Vec<Node>),
+    Audio(Audio),
+}
+
+impl Default for Media {
+    fn default() -> Self {
+        Media::new(MediaContent::Speak(Vec::new()))
+    }
+}
+
+impl Media {
+    pub fn new(content: MediaContent) -> Self {
+        Media {
+            id: None,
+            begin: None,
+            end: None,
+            repeat_count: None,
+            repeat_dur: None,
+            sound_level: None,
+            fade_in_dur: None,
+            fade_out_dur: None,
+            content,
+        }
+    }
+
+    /// Sets `xml:id`, used to refer to this element in the timing of the others.
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn begin(mut self, begin: &str) -> Self {
+        self.begin = Some(begin.to_string());
+        self
+    }
+
+    pub fn end(mut self, end: &str) -> Self {
+        self.end = Some(end.to_string());
+        self
+    }
+
+    pub fn repeat_count(mut self, count: u32) -> Self {
+        self.repeat_count = Some(count);
+        self
+    }
+
+    pub fn repeat_dur(mut self, duration: Duration) -> Self {
+        self.repeat_dur = Some(duration);
+        self
+    }
+
+    pub fn sound_level(mut self, decibels: f32) -> Self {
+        self.sound_level = Some(decibels);
+        self
+    }
+
+    pub fn fade_in_dur(mut self, duration: Duration) -> Self {
+        self.fade_in_dur = Some(duration);
+        self
+    }
+
+    pub fn fade_out_dur(mut self, duration: Duration) -> Self {
+        self.fade_out_dur = Some(duration);
+        self
+    }
+}
+
+/// Attributes of a `<voice>`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Voice {
+    /// Name of the voice, e.g. `en-US-Neural2-F`.
+    pub name: Option<VoiceName>,
+    pub gender: Option<VoiceGender>,
+    pub language: Option<LanguageTag>,
+}
+
+impl Voice {
+    pub fn named(name: VoiceName) -> Self {
+        Voice {
+            name: Some(name),
+            ..Default::default()
+        }
+    }
+
+    pub fn gender(mut self, gender: VoiceGender) -> Self {
+        self.gender = Some(gender);
+        self
+    }
+
+    pub fn language(mut self, language: LanguageTag) -> Self {
+        self.language = Some(language);
+        self
+    }
+}
+
+fn write_nodes(f: &mut fmt::Formatter<'_>, nodes: &[Node]) -> fmt::Result {
+    nodes.iter().try_for_each(|node| write!(f, "{}", node))
+}
+
+fn write_attribute(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    value: Option<impl fmt::Display>
+) -> fmt::Result {
+    match value {
+        Some(value) => write!(f, " {}=\"{}\"", name, escape_attribute(&value.to_string())),
+        None => Ok(()),
+    }
+}
+
+fn write_element(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    attributes: impl FnOnce(&mut fmt::Formatter<'_>) -> fmt::Result,
+    children: &[Node]
+) -> fmt::Result {
+    write!(f, "<{}", name)?;
+    attributes(f)?;
+    if children.is_empty() {
+        return f.write_str("/>");
+    }
+    f.write_str(">")?;
+    write_nodes(f, children)?;
+    write!(f, "</{}>", name)
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Text(text) => f.write_str(&escape_text(text)),
+            Node::Paragraph(children) => write_element(f, "p", |_| Ok(()), children),
+            Node::Sentence(children) => write_element(f, "s", |_| Ok(()), children),
+            Node::Break(brk) => {
+                write_element(
+                    f,
+                    "break",
+                    |f| {
+                        write_attribute(f, "time", brk.time.map(format_time))?;
+                        write_attribute(f, "strength", brk.strength)
+                    },
+                    &[]
+                )
+            }
+            Node::Prosody(prosody, children) => {
+                write_element(
+                    f,
+                    "prosody",
+                    |f| {
+                        write_attribute(f, "rate", prosody.rate)?;
+                        write_attribute(f, "pitch", prosody.pitch)?;
+                        write_attribute(f, "volume", prosody.volume)
+                    },
+                    children
+                )
+            }
+            Node::Emphasis(level, children) => {
+                write_element(f, "emphasis", |f| write_attribute(f, "level", *level), children)
+            }
+            Node::SayAs(say_as) => {
+                f.write_str("<say-as")?;
+                write_attribute(f, "interpret-as", Some(say_as.interpret_as))?;
+                write_attribute(f, "format", say_as.format.as_ref())?;
+                write_attribute(f, "detail", say_as.detail.as_ref())?;
+                write_attribute(f, "language", say_as.language.as_ref())?;
+                write!(f, ">{}</say-as>", escape_text(&say_as.text))
+            }
+            Node::Sub { alias, text } => {
+                f.write_str("<sub")?;
+                write_attribute(f, "alias", Some(alias))?;
+                write!(f, ">{}</sub>", escape_text(text))
+            }
+            Node::Phoneme { alphabet, ph, text } => {
+                f.write_str("<phoneme")?;
+                write_attribute(f, "alphabet", Some(alphabet))?;
+                write_attribute(f, "ph", Some(ph))?;
+                write!(f, ">{}</phoneme>", escape_text(text))
+            }
+            Node::Mark(name) => {
+                write_element(f, "mark", |f| write_attribute(f, "name", Some(name)), &[])
+            }
+            Node::Audio(audio) => write!(f, "{}", audio),
+            Node::Par(media) => write_media_container(f, "par", media),
+            Node::Seq(media) => write_media_container(f, "seq", media),
+            Node::Voice(voice, children) => {
+                write_element(
+                    f,
+                    "voice",
+                    |f| {
+                        write_attribute(f, "name", voice.name.as_ref())?;
+                        write_attribute(f, "gender", voice.gender)?;
+                        write_attribute(f, "language", voice.language.as_ref())
+                    },
+                    children
+                )
+            }
+            Node::Lang(language, children) => {
+                let attributes = |f: &mut fmt::Formatter<'_>| {
+                    write_attribute(f, "xml:lang", Some(language))
+                };
+                write_element(f, "lang", attributes, children)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Audio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_element(
+            f,
+            "audio",
+            |f| {
+                write_attribute(f, "src", Some(&self.src))?;
+                write_attribute(f, "clipBegin", self.clip_begin.map(format_time))?;
+                write_attribute(f, "clipEnd", self.clip_end.map(format_time))?;
+                write_attribute(f, "speed", self.speed.map(|speed| format!("{}%", speed)))?;
+                write_attribute(f, "repeatCount", self.repeat_count)?;
+                write_attribute(f, "repeatDur", self.repeat_dur.map(format_time))?;
+                write_attribute(f, "soundLevel", self.sound_level.map(format_decibels))
+            },
+            &self.fallback
+        )
+    }
+}
+
+impl fmt::Display for Media {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<media")?;
+        write_attribute(f, "xml:id", self.id.as_ref())?;
+        write_attribute(f, "begin", self.begin.as_ref())?;
+        write_attribute(f, "end", self.end.as_ref())?;
+        write_attribute(f, "repeatCount", self.repeat_count)?;
+        write_attribute(f, "repeatDur", self.repeat_dur.map(format_time))?;
+        write_attribute(f, "soundLevel", self.sound_level.map(format_decibels))?;
+        write_attribute(f, "fadeInDur", self.fade_in_dur.map(format_time))?;
+        write_attribute(f, "fadeOutDur", self.fade_out_dur.map(format_time))?;
+        f.write_str(">")?;
+        match &self.content {
+            MediaContent::Speak(children) => {
+                f.write_str("<speak>")?;
+                write_nodes(f, children)?;
+                f.write_str("</speak>")?;
+            }
+            MediaContent::Audio(audio) => write!(f, "{}", audio)?,
+        }
+        f.write_str("</media>")
+    }
+}
+
+fn write_media_container(f: &mut fmt::Formatter<'_>, name: &str, media: &[Media]) -> fmt::Result {
+    write!(f, "<{}>", name)?;
+    media.iter().try_for_each(|media| write!(f, "{}", media))?;
+    write!(f, "</{}>", name)
+}
+
+fn format_decibels(decibels: f32) -> String {
+    format!("{:+}dB", decibels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssml::attributes::InterpretAs;
+
+    fn text(text: &str) -> Vec<Node> {
+        vec![Node::Text(text.to_string())]
+    }
+
+    #[test]
+    fn escapes_text_and_attributes() {
+        assert_eq!(escape_text(r#"Tom & "Jerry" <3 'em>"#), r#"Tom &amp; "Jerry" &lt;3 'em&gt;"#);
+        assert_eq!(
+            escape_attribute(r#"Tom & "Jerry" <3 'em>"#),
+            "Tom &amp; &quot;Jerry&quot; &lt;3 &apos;em&gt;"
+        );
+        assert_eq!(escape_text("Xin chào 👋"), "Xin chào 👋");
+
+        let node = Node::Sub {
+            alias: r#""quoted" & <more>"#.to_string(),
+            text: "a<b".to_string(),
+        };
+        let expected = r#"<sub alias="&quot;quoted&quot; &amp; &lt;more&gt;">a&lt;b</sub>"#;
+        assert_eq!(node.to_string(), expected);
+    }
+
+    #[test]
+    fn serializes_prosody() {
+        let prosody = Prosody::new()
+            .rate(ProsodyRate::Percent(80.0))
+            .pitch(ProsodyPitch::Semitones(-2.0))
+            .volume(ProsodyVolume::Decibels(6.0));
+        let cases = [
+            (
+                Node::Prosody(prosody, text("slow")),
+                r#"<prosody rate="80%" pitch="-2st" volume="+6dB">slow</prosody>"#,
+            ),
+            (
+                Node::Prosody(Prosody::new().rate(ProsodyRate::XFast), text("fast")),
+                r#"<prosody rate="x-fast">fast</prosody>"#,
+            ),
+            (
+                Node::Prosody(Prosody::new().pitch(ProsodyPitch::Percent(10.0)), text("up")),
+                r#"<prosody pitch="+10%">up</prosody>"#,
+            ),
+            (
+                Node::Prosody(Prosody::new().volume(ProsodyVolume::Silent), Vec::new()),
+                r#"<prosody volume="silent"/>"#,
+            ),
+        ];
+        for (node, expected) in cases {
+            assert_eq!(node.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn serializes_say_as() {
+        let date = SayAs::new(InterpretAs::Date, "2024-02-29")
+            .format("yyyymmdd")
+            .detail("1")
+            .language("en-GB".parse().unwrap());
+        let cases = [
+            (
+                Node::SayAs(date),
+                concat!(
+                    r#"<say-as interpret-as="date" format="yyyymmdd" detail="1" "#,
+                    r#"language="en-GB">2024-02-29</say-as>"#
+                ),
+            ),
+            (
+                Node::SayAs(SayAs::new(InterpretAs::Characters, "R&D")),
+                r#"<say-as interpret-as="characters">R&amp;D</say-as>"#,
+            ),
+        ];
+        for (node, expected) in cases {
+            assert_eq!(node.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn serializes_par_and_seq() {
+        let question = Media::new(MediaContent::Speak(text("Ready?")))
+            .id("question")
+            .fade_out_dur(Duration::from_millis(500));
+        let answer = Media::new(MediaContent::Audio(Audio::new("https://example.com/yes.mp3")))
+            .begin("question.end+2.0s")
+            .repeat_count(2)
+            .sound_level(-6.0);
+        let cases = [
+            (
+                Node::Par(vec![question, answer.clone()]),
+                concat!(
+                    r#"<par><media xml:id="question" fadeOutDur="500ms"><speak>Ready?</speak>"#,
+                    r#"</media><media begin="question.end+2.0s" repeatCount="2" "#,
+                    r#"soundLevel="-6dB"><audio src="https://example.com/yes.mp3"/></media></par>"#
+                ),
+            ),
+            (
+                Node::Seq(vec![answer]),
+                concat!(
+                    r#"<seq><media begin="question.end+2.0s" repeatCount="2" soundLevel="-6dB">"#,
+                    r#"<audio src="https://example.com/yes.mp3"/></media></seq>"#
+                ),
+            ),
+            (Node::Seq(Vec::new()), "<seq></seq>"),
+        ];
+        for (node, expected) in cases {
+            assert_eq!(node.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn serializes_voice_and_lang() {
+        let named = Voice::named("en-US-Neural2-F".parse().unwrap());
+        let by_gender = Voice::default()
+            .gender(VoiceGender::Female)
+            .language("fr-FR".parse().unwrap());
+        let cases = [
+            (Node::Voice(named, text("Hi")), r#"<voice name="en-US-Neural2-F">Hi</voice>"#),
+            (
+                Node::Voice(by_gender, text("Salut")),
+                r#"<voice gender="female" language="fr-FR">Salut</voice>"#,
+            ),
+            (
+                Node::Lang("ja-JP".parse().unwrap(), text("こんにちは")),
+                r#"<lang xml:lang="ja-JP">こんにちは</lang>"#,
+            ),
+            (Node::Lang("de-DE".parse().unwrap(), Vec::new()), r#"<lang xml:lang="de-DE"/>"#),
+        ];
+        for (node, expected) in cases {
+            assert_eq!(node.to_string(), expected);
+        }
+        let speak = Speak::new(vec![Node::Text("a".to_string()), Node::Mark("m".to_string())]);
+        let ssml = r#"<speak>a<mark name="m"/></speak>"#.to_string();
+        assert_eq!(SynthesisInput::from(speak), SynthesisInput::Ssml(ssml));
+    }
+}
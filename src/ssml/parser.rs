@@ -0,0 +1,501 @@
+// src/ssml/parser.rs
+
+//! A small XML parser keeping the position of every element, attribute and text node, so
+//! that problems can be reported where they are. It covers what SSML documents use:
+//! elements, attributes, character and predefined entity references, comments, CDATA
+//! sections and the XML declaration. Document type declarations are rejected.
+
+use std::fmt;
+
+/// A position in the source document. Lines and columns start at 1, and columns count
+/// characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Error returned when a document isn't well-formed XML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub position: Position,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An attribute of an [Element], with its value unescaped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    pub name: String,
+    pub value: String,
+    pub position: Position,
+}
+
+/// An element and its content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<Attribute>,
+    pub children: Vec<XmlNode>,
+    pub position: Position,
+}
+
+impl Element {
+    /// Returns the attribute `name`, if set.
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|attribute| attribute.name == name)
+    }
+
+    /// Returns the child elements, skipping text.
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            XmlNode::Element(element) => Some(element),
+            XmlNode::Text(..) => None,
+        })
+    }
+
+    /// Returns the text content of the element and its descendants.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for child in &self.children {
+            match child {
+                XmlNode::Element(element) => text.push_str(&element.text()),
+                XmlNode::Text(content, _) => text.push_str(content),
+            }
+        }
+        text
+    }
+}
+
+/// A node of a parsed document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlNode {
+    Element(Element),
+    /// Text, with entity references resolved.
+    Text(String, Position),
+}
+
+/// Parses `source` and returns its root element.
+pub fn parse(source: &str) -> Result<Element, ParseError> {
+    Parser::new(source).document()
+}
+
+/// Returns the length of the tag, comment, CDATA section or processing instruction at the
+/// start of `markup`.
+pub(crate) fn markup_end(markup: &str) -> usize {
+    let terminated = |start: &str, end: &str| {
+        markup
+            .strip_prefix(start)
+            .map(|rest| rest.find(end).map_or(markup.len(), |i| start.len() + i + end.len()))
+    };
+    if let Some(end) = terminated("<!--", "-->") {
+        return end;
+    }
+    if let Some(end) = terminated("<![CDATA[", "]]>") {
+        return end;
+    }
+    if let Some(end) = terminated("<?", "?>") {
+        return end;
+    }
+
+    let mut quote = None;
+    for (i, c) in markup.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => {
+                quote = Some(c);
+            }
+            (Some(open), _) if open == c => {
+                quote = None;
+            }
+            (None, '>') => {
+                return i + 1;
+            }
+            _ => {}
+        }
+    }
+    markup.len()
+}
+
+/// Returns the name of the element opened or closed by the tag `markup`.
+pub(crate) fn tag_name(markup: &str) -> &str {
+    let name = markup.trim_start_matches(['<', '/']);
+    let end = name
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(name.len());
+    &name[..end]
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Parser { source, offset: 0, line: 1, column: 1 }
+    }
+
+    fn position(&self) -> Position {
+        Position { line: self.line, column: self.column }
+    }
+
+    fn error<T>(&self, position: Position, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError { position, message: message.into() })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        self.rest().starts_with(prefix)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn advance(&mut self, count: usize) {
+        for _ in 0..count {
+            self.bump();
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// Consumes everything up to and including `terminator`, and returns what came before it.
+    fn until(&mut self, terminator: &str, what: &str) -> Result<&'a str, ParseError> {
+        let start = self.position();
+        match self.rest().find(terminator) {
+            Some(index) => {
+                let content = &self.rest()[..index];
+                self.advance(content.chars().count() + terminator.chars().count());
+                Ok(content)
+            }
+            None => self.error(start, format!("unterminated {}", what)),
+        }
+    }
+
+    fn document(&mut self) -> Result<Element, ParseError> {
+        if self.starts_with("\u{feff}") {
+            self.bump();
+        }
+        let mut root = None;
+        loop {
+            self.skip_whitespace();
+            let position = self.position();
+            if self.peek().is_none() {
+                break;
+            } else if self.starts_with("<?") {
+                self.until("?>", "processing instruction")?;
+            } else if self.starts_with("<!--") {
+                self.comment()?;
+            } else if self.starts_with("<!DOCTYPE") {
+                return self.error(position, "document type declarations are not supported");
+            } else if self.starts_with("<") {
+                if root.is_some() {
+                    return self.error(position, "content after the root element");
+                }
+                root = Some(self.element()?);
+            } else {
+                return self.error(position, "text outside of the root element");
+            }
+        }
+        match root {
+            Some(root) => Ok(root),
+            None => self.error(self.position(), "the document is empty"),
+        }
+    }
+
+    fn comment(&mut self) -> Result<(), ParseError> {
+        let position = self.position();
+        self.advance(4);
+        let content = self.until("-->", "comment")?;
+        if content.contains("--") {
+            return self.error(position, "'--' is not allowed inside a comment");
+        }
+        Ok(())
+    }
+
+    fn name(&mut self) -> Result<String, ParseError> {
+        let position = self.position();
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            let valid = if name.is_empty() {
+                c.is_alphabetic() || c == '_' || c == ':'
+            } else {
+                c.is_alphanumeric() || matches!(c, '_' | ':' | '-' | '.')
+            };
+            if !valid {
+                break;
+            }
+            name.push(c);
+            self.bump();
+        }
+        if name.is_empty() {
+            return self.error(position, "expected a name");
+        }
+        Ok(name)
+    }
+
+    fn element(&mut self) -> Result<Element, ParseError> {
+        let position = self.position();
+        self.bump();
+        let name = self.name()?;
+        let mut attributes: Vec<Attribute> = Vec::new();
+
+        loop {
+            let had_whitespace = self.peek().is_some_and(char::is_whitespace);
+            self.skip_whitespace();
+            if self.starts_with("/>") {
+                self.advance(2);
+                return Ok(Element { name, attributes, children: Vec::new(), position });
+            }
+            if self.starts_with(">") {
+                self.bump();
+                break;
+            }
+            if self.peek().is_none() {
+                return self.error(position, format!("unterminated start tag <{}>", name));
+            }
+            if !had_whitespace {
+                return self.error(self.position(), "expected whitespace between attributes");
+            }
+            let attribute = self.attribute()?;
+            if attributes.iter().any(|existing| existing.name == attribute.name) {
+                return self.error(
+                    attribute.position,
+                    format!("duplicate attribute '{}'", attribute.name)
+                );
+            }
+            attributes.push(attribute);
+        }
+
+        let children = self.content(&name, position)?;
+        Ok(Element { name, attributes, children, position })
+    }
+
+    fn attribute(&mut self) -> Result<Attribute, ParseError> {
+        let position = self.position();
+        let name = self.name()?;
+        self.skip_whitespace();
+        if self.bump() != Some('=') {
+            return self.error(position, format!("expected '=' after attribute '{}'", name));
+        }
+        self.skip_whitespace();
+        let quote = match self.peek() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => {
+                return self.error(self.position(), "expected a quoted attribute value");
+            }
+        };
+        self.bump();
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                None => {
+                    return self.error(position, format!("unterminated value of '{}'", name));
+                }
+                Some(c) if c == quote => {
+                    self.bump();
+                    break;
+                }
+                Some('<') => {
+                    return self.error(self.position(), "'<' is not allowed in attribute values");
+                }
+                Some('&') => value.push_str(&self.reference()?),
+                Some(c) => {
+                    value.push(c);
+                    self.bump();
+                }
+            }
+        }
+        Ok(Attribute { name, value, position })
+    }
+
+    fn content(&mut self, name: &str, start: Position) -> Result<Vec<XmlNode>, ParseError> {
+        let mut children = Vec::new();
+        let mut text = String::new();
+        let mut text_position = self.position();
+
+        loop {
+            if self.peek().is_none() {
+                return self.error(start, format!("<{}> is never closed", name));
+            }
+            if self.starts_with("<") && !text.is_empty() {
+                children.push(XmlNode::Text(std::mem::take(&mut text), text_position));
+            }
+            if self.starts_with("</") {
+                let position = self.position();
+                self.advance(2);
+                let end_name = self.name()?;
+                self.skip_whitespace();
+                if self.bump() != Some('>') {
+                    return self.error(position, format!("unterminated end tag </{}>", end_name));
+                }
+                if end_name != name {
+                    return self.error(
+                        position,
+                        format!(
+                            "</{}> doesn't match <{}> opened at {}",
+                            end_name,
+                            name,
+                            start
+                        )
+                    );
+                }
+                return Ok(children);
+            } else if self.starts_with("<!--") {
+                self.comment()?;
+            } else if self.starts_with("<![CDATA[") {
+                text_position = self.position();
+                self.advance(9);
+                children.push(
+                    XmlNode::Text(self.until("]]>", "CDATA section")?.to_string(), text_position)
+                );
+            } else if self.starts_with("<?") {
+                self.until("?>", "processing instruction")?;
+            } else if self.starts_with("<") {
+                children.push(XmlNode::Element(self.element()?));
+            } else {
+                if text.is_empty() {
+                    text_position = self.position();
+                }
+                match self.peek() {
+                    Some('&') => text.push_str(&self.reference()?),
+                    Some(c) => {
+                        text.push(c);
+                        self.bump();
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
+    /// Parses an entity or character reference and returns its replacement text.
+    fn reference(&mut self) -> Result<String, ParseError> {
+        let position = self.position();
+        let end = match self.rest().find(';') {
+            Some(end) if end <= 12 => end,
+            _ => {
+                return self.error(position, "'&' must be escaped as '&amp;'");
+            }
+        };
+        let reference = &self.rest()[1..end];
+        let replacement = match reference {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let code = if let Some(hex) = reference.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(decimal) = reference.strip_prefix('#') {
+                    decimal.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(char::from_u32)
+            }
+        };
+        match replacement {
+            Some(c) => {
+                self.advance(end + 1);
+                Ok(c.to_string())
+            }
+            None => self.error(position, format!("unknown entity '&{};'", reference)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the error of parsing `source` as `line:column: message`.
+    fn error(source: &str) -> String {
+        parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn positions_elements_attributes_and_text() {
+        let root = parse("<speak>\n  <break time=\"1s\"/>é <p>x</p>\n</speak>").unwrap();
+        assert_eq!(root.position, Position { line: 1, column: 1 });
+
+        let mut elements = root.elements();
+        let break_ = elements.next().unwrap();
+        assert_eq!(break_.position, Position { line: 2, column: 3 });
+        assert_eq!(break_.attribute("time").unwrap().position, Position { line: 2, column: 10 });
+        // Columns count characters, not bytes.
+        assert_eq!(elements.next().unwrap().position, Position { line: 2, column: 23 });
+
+        match &root.children[0] {
+            XmlNode::Text(text, position) => {
+                assert_eq!(text, "\n  ");
+                assert_eq!(*position, Position { line: 1, column: 8 });
+            }
+            node => panic!("unexpected node {:?}", node),
+        }
+    }
+
+    #[test]
+    fn resolves_references() {
+        let source = "<speak a=\"&quot;\">Tom &amp; Jerry &#233;&#x41;&lt;&gt;&apos;</speak>";
+        let root = parse(source).unwrap();
+        assert_eq!(root.text(), "Tom & Jerry éA<>'");
+        assert_eq!(root.attribute("a").unwrap().value, "\"");
+    }
+
+    #[test]
+    fn reports_where_parsing_stopped() {
+        let cases = [
+            ("", "1:1: the document is empty"),
+            ("<speak>\n  <p>Hi</speak>", "2:8: </speak> doesn't match <p> opened at 2:3"),
+            ("<speak>Tom &amp Jerry</speak>", "1:12: '&' must be escaped as '&amp;'"),
+            ("<speak>&nbsp;</speak>", "1:8: unknown entity '&nbsp;'"),
+            ("<speak>\n<p a=1>x</p></speak>", "2:6: expected a quoted attribute value"),
+            ("<speak a=\"1\"a=\"2\">", "1:13: expected whitespace between attributes"),
+            ("<speak a=\"1\" a=\"2\"/>", "1:14: duplicate attribute 'a'"),
+            ("<speak>héllo <b></speak>", "1:17: </speak> doesn't match <b> opened at 1:14"),
+            ("<speak>\n  <p>", "2:3: <p> is never closed"),
+            ("<speak></speak><p/>", "1:16: content after the root element"),
+            ("<speak><!-- a -- b --></speak>", "1:8: '--' is not allowed inside a comment"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(error(source), expected, "parsing {:?}", source);
+        }
+    }
+}
@@ -0,0 +1,565 @@
+// src/ssml/segment.rs
+
+//! Splitting of text mixing several languages.
+//!
+//! Text is first split by script: Latin, Han and kana, Hangul, Cyrillic and so on, each
+//! mapped to a language by the [Segmenter]. Han text is read as Japanese when the text has
+//! kana anywhere. Latin text is further split by word between Vietnamese, recognized by its
+//! letters and a few common words, and the language of Latin text. Callers can also fix the
+//! language of parts of the text with [LanguageHint]s.
+
+use std::{ collections::HashMap, ops::Range };
+
+use crate::types::language::LanguageTag;
+
+/// Common Vietnamese words written without diacritics, counted as Vietnamese in text that
+/// is already known to hold some.
+const VIETNAMESE_WORDS: [&str; 20] = [
+    "anh",
+    "em",
+    "con",
+    "cho",
+    "trong",
+    "khi",
+    "nhau",
+    "xin",
+    "ta",
+    "sao",
+    "mai",
+    "theo",
+    "vui",
+    "nha",
+    "nhanh",
+    "thanh",
+    "tin",
+    "hoa",
+    "qua",
+    "ra",
+];
+
+/// Latin letters only used by Vietnamese, besides the letters of the Latin Extended
+/// Additional block carrying both a vowel mark and a tone mark.
+const VIETNAMESE_LETTERS: &str = "ăĂđĐơƠưƯĩĨũŨ";
+
+/// Writing systems told apart by the [Segmenter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Thai,
+    Hangul,
+    /// Hiragana and katakana.
+    Kana,
+    /// Chinese characters, also used in Japanese.
+    Han,
+}
+
+impl Script {
+    /// Returns the script of `c`, or `None` for characters shared by every script: spaces,
+    /// digits, punctuation, symbols and emoji.
+    pub fn of(c: char) -> Option<Script> {
+        let script = match c {
+            'a'..='z' | 'A'..='Z' => Script::Latin,
+            '\u{d7}' | '\u{f7}' => {
+                return None;
+            }
+            '\u{c0}'..='\u{24f}' | '\u{1e00}'..='\u{1eff}' => Script::Latin,
+            '\u{370}'..='\u{3ff}' | '\u{1f00}'..='\u{1fff}' => Script::Greek,
+            '\u{400}'..='\u{52f}' => Script::Cyrillic,
+            '\u{590}'..='\u{5ff}' => Script::Hebrew,
+            '\u{600}'..='\u{6ff}' | '\u{750}'..='\u{77f}' => Script::Arabic,
+            '\u{900}'..='\u{97f}' => Script::Devanagari,
+            '\u{e00}'..='\u{e7f}' => Script::Thai,
+            '\u{1100}'..='\u{11ff}' | '\u{3130}'..='\u{318f}' | '\u{ac00}'..='\u{d7af}' => {
+                Script::Hangul
+            }
+            '\u{3040}'..='\u{30ff}' | '\u{31f0}'..='\u{31ff}' | '\u{ff66}'..='\u{ff9f}' => {
+                Script::Kana
+            }
+            '\u{3005}' |
+            '\u{3400}'..='\u{4dbf}' |
+            '\u{4e00}'..='\u{9fff}' |
+            '\u{f900}'..='\u{faff}' |
+            '\u{20000}'..='\u{2fa1f}' => Script::Han,
+            _ => {
+                return None;
+            }
+        };
+        Some(script)
+    }
+}
+
+/// A part of the text in a single language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageSegment {
+    pub text: String,
+    pub language: LanguageTag,
+    /// Byte range of the segment in the text it was split from.
+    pub range: Range<usize>,
+}
+
+/// Fixes the language of part of the text, whatever the script it's written in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageHint {
+    /// Byte range of the text in the language.
+    pub range: Range<usize>,
+    pub language: LanguageTag,
+}
+
+impl LanguageHint {
+    pub fn new(range: Range<usize>, language: LanguageTag) -> Self {
+        LanguageHint { range, language }
+    }
+}
+
+/// Splits text into [LanguageSegment]s.
+///
+/// The default languages are `en-US` for Latin, `vi-VN` for Vietnamese, `ja-JP` for kana,
+/// `cmn-CN` for Han, `ko-KR` for Hangul, `ru-RU` for Cyrillic, `el-GR` for Greek, `he-IL`
+/// for Hebrew, `ar-XA` for Arabic, `hi-IN` for Devanagari and `th-TH` for Thai.
+#[derive(Debug, Clone)]
+pub struct Segmenter {
+    scripts: HashMap<Script, LanguageTag>,
+    vietnamese: Option<LanguageTag>,
+    default_language: LanguageTag,
+    min_foreign_words: usize,
+}
+
+impl Default for Segmenter {
+    fn default() -> Self {
+        let scripts = [
+            (Script::Latin, "en-US"),
+            (Script::Kana, "ja-JP"),
+            (Script::Han, "cmn-CN"),
+            (Script::Hangul, "ko-KR"),
+            (Script::Cyrillic, "ru-RU"),
+            (Script::Greek, "el-GR"),
+            (Script::Hebrew, "he-IL"),
+            (Script::Arabic, "ar-XA"),
+            (Script::Devanagari, "hi-IN"),
+            (Script::Thai, "th-TH"),
+        ];
+        Segmenter {
+            scripts: scripts
+                .into_iter()
+                .map(|(script, language)| (script, tag(language)))
+                .collect(),
+            vietnamese: Some(tag("vi-VN")),
+            default_language: tag("en-US"),
+            min_foreign_words: 2,
+        }
+    }
+}
+
+fn tag(language: &str) -> LanguageTag {
+    language.parse().expect("built-in language tags are valid")
+}
+
+/// A word of Latin text, and whether it is Vietnamese.
+struct Word {
+    range: Range<usize>,
+    vietnamese: bool,
+}
+
+impl Segmenter {
+    pub fn new() -> Self {
+        Segmenter::default()
+    }
+
+    /// Sets the language of text written in `script`.
+    pub fn script(mut self, script: Script, language: LanguageTag) -> Self {
+        self.scripts.insert(script, language);
+        self
+    }
+
+    /// Sets the language of Latin words recognized as Vietnamese, or turns recognition off.
+    pub fn vietnamese(mut self, language: Option<LanguageTag>) -> Self {
+        self.vietnamese = language;
+        self
+    }
+
+    /// Sets the language of text without any letter, `en-US` by default.
+    pub fn default_language(mut self, language: LanguageTag) -> Self {
+        self.default_language = language;
+        self
+    }
+
+    /// Sets how many consecutive Latin words, without Vietnamese letters, make a segment of
+    /// their own inside Vietnamese text. Shorter runs, typically loanwords and names, are
+    /// read by the Vietnamese voice. 2 by default.
+    pub fn min_foreign_words(mut self, words: usize) -> Self {
+        self.min_foreign_words = words.max(1);
+        self
+    }
+
+    /// Splits `text` into segments of a single language.
+    pub fn segment(&self, text: &str) -> Vec<LanguageSegment> {
+        self.segment_with_hints(text, &[])
+    }
+
+    /// Splits `text` into segments of a single language, using the language of `hints` for
+    /// the text they cover.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to split.
+    /// * `hints` - Parts of the text in a known language. Ranges are moved back to the
+    ///   nearest character boundary, and hints overlapping an earlier one are ignored.
+    ///
+    /// # Returns
+    ///
+    /// Returns the segments in order. They cover the whole text: spaces and punctuation
+    /// belong to the segment before them.
+    pub fn segment_with_hints(&self, text: &str, hints: &[LanguageHint]) -> Vec<LanguageSegment> {
+        let has_kana = text.chars().any(|c| Script::of(c) == Some(Script::Kana));
+        let has_vietnamese = self.vietnamese.is_some() && is_vietnamese_text(text);
+
+        let mut spans: Vec<(Range<usize>, Option<LanguageTag>)> = Vec::new();
+        let mut offset = 0;
+        let mut hints: Vec<&LanguageHint> = hints.iter().collect();
+        hints.sort_by_key(|hint| hint.range.start);
+        for hint in hints {
+            let start = floor_char_boundary(text, hint.range.start);
+            let end = floor_char_boundary(text, hint.range.end);
+            if start < offset || start >= end {
+                continue;
+            }
+            self.detect(text, offset..start, has_kana, has_vietnamese, &mut spans);
+            spans.push((start..end, Some(hint.language.clone())));
+            offset = end;
+        }
+        self.detect(text, offset..text.len(), has_kana, has_vietnamese, &mut spans);
+
+        let mut segments: Vec<LanguageSegment> = Vec::new();
+        for (range, language) in spans {
+            // Text without letters joins the segment before it, or the next one.
+            let Some(language) = language else {
+                if let Some(last) = segments.last_mut() {
+                    last.range.end = range.end;
+                }
+                continue;
+            };
+            match segments.last_mut() {
+                Some(last) if last.language == language => last.range.end = range.end,
+                _ => {
+                    let start = if segments.is_empty() { 0 } else { range.start };
+                    segments.push(LanguageSegment {
+                        text: String::new(),
+                        language,
+                        range: start..range.end,
+                    });
+                }
+            }
+        }
+        if segments.is_empty() && !text.is_empty() {
+            segments.push(LanguageSegment {
+                text: String::new(),
+                language: self.default_language.clone(),
+                range: 0..text.len(),
+            });
+        }
+        for segment in &mut segments {
+            segment.text = text[segment.range.clone()].to_string();
+        }
+        segments
+    }
+
+    /// Appends the spans of `range` in `text`, with their language, or `None` for text
+    /// without letters.
+    fn detect(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        has_kana: bool,
+        has_vietnamese: bool,
+        spans: &mut Vec<(Range<usize>, Option<LanguageTag>)>
+    ) {
+        let mut runs: Vec<(Range<usize>, Option<Script>)> = Vec::new();
+        for (i, c) in text[range.clone()].char_indices() {
+            let start = range.start + i;
+            let end = start + c.len_utf8();
+            // Japanese mixes Han and kana: keep them in one run.
+            let script = Script::of(c).map(|script| {
+                match script {
+                    Script::Kana => Script::Han,
+                    script => script,
+                }
+            });
+            match runs.last_mut() {
+                Some((run, run_script)) if *run_script == script || script.is_none() => {
+                    run.end = end;
+                }
+                _ => runs.push((start..end, script)),
+            }
+        }
+
+        for (run, script) in runs {
+            match script {
+                None => spans.push((run, None)),
+                Some(Script::Latin) if has_vietnamese => {
+                    self.detect_vietnamese(text, run, spans);
+                }
+                Some(Script::Han) => {
+                    let kana = has_kana || text[run.clone()].chars().any(is_kana);
+                    let script = if kana { Script::Kana } else { Script::Han };
+                    spans.push((run, self.language_of(script)));
+                }
+                Some(script) => spans.push((run, self.language_of(script))),
+            }
+        }
+    }
+
+    /// Appends the spans of a run of Latin text, split between Vietnamese and other words.
+    fn detect_vietnamese(
+        &self,
+        text: &str,
+        run: Range<usize>,
+        spans: &mut Vec<(Range<usize>, Option<LanguageTag>)>
+    ) {
+        let mut words: Vec<Word> = Vec::new();
+        let mut start = None;
+        for (i, c) in text[run.clone()].char_indices().chain([(run.len(), ' ')]) {
+            let letter = Script::of(c) == Some(Script::Latin) || (c == '\'' && start.is_some());
+            match (letter, start) {
+                (true, None) => {
+                    start = Some(i);
+                }
+                (false, Some(word_start)) => {
+                    let word = &text[run.start + word_start..run.start + i];
+                    words.push(Word {
+                        range: run.start + word_start..run.start + i,
+                        vietnamese: is_vietnamese_word(word),
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        // Short runs of other words next to Vietnamese ones are read as Vietnamese.
+        let mut i = 0;
+        while i < words.len() {
+            if words[i].vietnamese {
+                i += 1;
+                continue;
+            }
+            let end = words[i..]
+                .iter()
+                .position(|word| word.vietnamese)
+                .map_or(words.len(), |position| i + position);
+            let next_to_vietnamese = i > 0 || end < words.len();
+            if next_to_vietnamese && end - i < self.min_foreign_words {
+                for word in &mut words[i..end] {
+                    word.vietnamese = true;
+                }
+            }
+            i = end;
+        }
+
+        let mut offset = run.start;
+        for word in words {
+            let language = match word.vietnamese {
+                true => self.vietnamese.clone(),
+                false => self.language_of(Script::Latin),
+            };
+            // Spaces and punctuation before the word stay with the word before them.
+            if offset < word.range.start {
+                spans.push((offset..word.range.start, None));
+            }
+            spans.push((word.range.clone(), language));
+            offset = word.range.end;
+        }
+        if offset < run.end {
+            spans.push((offset..run.end, None));
+        }
+    }
+
+    fn language_of(&self, script: Script) -> Option<LanguageTag> {
+        Some(self.scripts.get(&script).unwrap_or(&self.default_language).clone())
+    }
+}
+
+fn is_kana(c: char) -> bool {
+    Script::of(c) == Some(Script::Kana)
+}
+
+fn is_vietnamese_letter(c: char) -> bool {
+    ('\u{1ea0}'..='\u{1ef9}').contains(&c) || VIETNAMESE_LETTERS.contains(c)
+}
+
+/// Returns `true` if `text` has letters only used by Vietnamese, or both accented letters
+/// and common Vietnamese words, as in `Xin chào`.
+fn is_vietnamese_text(text: &str) -> bool {
+    if text.chars().any(is_vietnamese_letter) {
+        return true;
+    }
+    let accented = text.chars().any(|c| !c.is_ascii() && Script::of(c) == Some(Script::Latin));
+    accented &&
+        text
+            .split(|c: char| !c.is_alphabetic())
+            .any(|word| VIETNAMESE_WORDS.contains(&word.to_lowercase().as_str()))
+}
+
+/// Returns `true` if `word` has Vietnamese letters, any other accented letter, or is a
+/// common Vietnamese word. Only called on text recognized as Vietnamese.
+fn is_vietnamese_word(word: &str) -> bool {
+    word.chars().any(|c| !c.is_ascii() && c.is_alphabetic()) ||
+        VIETNAMESE_WORDS.contains(&word.to_lowercase().as_str())
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the language and text of every segment.
+    fn segments(segments: Vec<LanguageSegment>) -> Vec<(String, String)> {
+        segments
+            .into_iter()
+            .map(|segment| (segment.language.to_string(), segment.text))
+            .collect()
+    }
+
+    fn expected(segments: &[(&str, &str)]) -> Vec<(String, String)> {
+        segments
+            .iter()
+            .map(|(language, text)| (language.to_string(), text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn splits_vietnamese_from_english() {
+        let text = "Hôm nay mình đi xem Avengers Endgame nhé, vui lắm!";
+        let found = Segmenter::new().segment(text);
+        assert_eq!(
+            segments(found.clone()),
+            expected(&[
+                ("vi-VN", "Hôm nay mình đi "),
+                ("en-US", "xem Avengers Endgame "),
+                ("vi-VN", "nhé, vui lắm!"),
+            ])
+        );
+        // The segments cover the whole text.
+        assert_eq!(found[0].range.start, 0);
+        assert!(found.windows(2).all(|pair| pair[0].range.end == pair[1].range.start));
+        assert_eq!(found[2].range.end, text.len());
+    }
+
+    #[test]
+    fn splits_vietnamese_japanese_and_english() {
+        let found = Segmenter::new().segment("Xin chào 日本語の勉強 and some English here.");
+        assert_eq!(
+            segments(found),
+            expected(&[
+                ("vi-VN", "Xin chào "),
+                ("ja-JP", "日本語の勉強 "),
+                ("en-US", "and some English here."),
+            ])
+        );
+    }
+
+    #[test]
+    fn reads_han_as_japanese_only_with_kana() {
+        let found = Segmenter::new().segment("我爱北京。 Hello world");
+        assert_eq!(
+            segments(found),
+            expected(&[("cmn-CN", "我爱北京。 "), ("en-US", "Hello world")])
+        );
+
+        let found = Segmenter::new().segment("東京 is in Japan, 東京はどこ");
+        assert_eq!(found[0].language.to_string(), "ja-JP");
+    }
+
+    #[test]
+    fn splits_by_script() {
+        let found = Segmenter::new().segment("Привет, world! 안녕하세요");
+        assert_eq!(
+            segments(found),
+            expected(&[("ru-RU", "Привет, "), ("en-US", "world! "), ("ko-KR", "안녕하세요")])
+        );
+        let found = Segmenter::new().script(Script::Latin, tag("fr-FR")).segment("Bonjour");
+        assert_eq!(segments(found), expected(&[("fr-FR", "Bonjour")]));
+    }
+
+    #[test]
+    fn keeps_short_foreign_runs_in_vietnamese() {
+        let text = "Mình thích Discord lắm";
+        assert_eq!(
+            segments(Segmenter::new().segment(text)),
+            expected(&[("vi-VN", text)])
+        );
+        assert_eq!(
+            segments(Segmenter::new().min_foreign_words(1).segment(text)),
+            expected(&[("vi-VN", "Mình thích "), ("en-US", "Discord "), ("vi-VN", "lắm")])
+        );
+        assert_eq!(
+            segments(Segmenter::new().vietnamese(None).segment("Xin chào các bạn")),
+            expected(&[("en-US", "Xin chào các bạn")])
+        );
+    }
+
+    #[test]
+    fn reads_text_without_letters_in_the_default_language() {
+        let found = Segmenter::new().segment("123, 456!");
+        assert_eq!(segments(found), expected(&[("en-US", "123, 456!")]));
+        let segmenter = Segmenter::new().default_language(tag("vi-VN"));
+        assert_eq!(segments(segmenter.segment("...")), expected(&[("vi-VN", "...")]));
+        assert_eq!(Segmenter::new().segment(""), []);
+    }
+
+    #[test]
+    fn uses_the_language_of_hints() {
+        let hint = LanguageHint::new(0..7, tag("en-US"));
+        let found = Segmenter::new().segment_with_hints("Discord rất vui", &[hint]);
+        assert_eq!(segments(found), expected(&[("en-US", "Discord "), ("vi-VN", "rất vui")]));
+    }
+
+    #[test]
+    fn moves_hints_to_character_boundaries_and_skips_overlapping_ones() {
+        // 'ấ' takes the bytes 9..12: the first hint ends before it.
+        let hints = [
+            LanguageHint::new(5..12, tag("fr-FR")),
+            LanguageHint::new(0..10, tag("en-GB")),
+            LanguageHint::new(13..13, tag("de-DE")),
+            LanguageHint::new(15..100, tag("es-ES")),
+        ];
+        let found = Segmenter::new().segment_with_hints("Discord rất vui", &hints);
+        assert_eq!(
+            segments(found.clone()),
+            expected(&[("en-GB", "Discord r"), ("vi-VN", "ất v"), ("es-ES", "ui")])
+        );
+        assert_eq!(found[2].range, 15..17);
+    }
+
+    #[test]
+    fn finds_character_boundaries() {
+        let text = "aấb";
+        assert_eq!(floor_char_boundary(text, 0), 0);
+        assert_eq!(floor_char_boundary(text, 2), 1);
+        assert_eq!(floor_char_boundary(text, 3), 1);
+        assert_eq!(floor_char_boundary(text, 4), 4);
+        assert_eq!(floor_char_boundary(text, 10), 5);
+    }
+
+    #[test]
+    fn recognizes_vietnamese() {
+        assert!(is_vietnamese_text("đi"));
+        assert!(is_vietnamese_text("Xin chào"));
+        assert!(!is_vietnamese_text("Xin chao"));
+        assert!(!is_vietnamese_text("Café au lait"));
+        assert!(is_vietnamese_word("Anh"));
+        assert!(is_vietnamese_word("chào"));
+        assert!(!is_vietnamese_word("Discord"));
+    }
+}
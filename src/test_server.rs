@@ -0,0 +1,143 @@
+// src/test_server.rs
+
+//! A local stand-in for the REST API, answering each request with a canned response.
+
+use std::sync::{ Arc, Mutex };
+
+use tokio::{ io::{ AsyncReadExt, AsyncWriteExt }, net::{ TcpListener, TcpStream } };
+
+/// A request received by a [TestServer].
+#[derive(Debug, Clone)]
+pub(crate) struct ReceivedRequest {
+    pub method: String,
+    /// The path of the request, with its query string.
+    pub path: String,
+    /// The headers of the request, their names in lowercase.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl ReceivedRequest {
+    /// Returns the value of the header `name`, given in lowercase.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parses the body as JSON, `null` if it is empty or isn't JSON.
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).unwrap_or_default()
+    }
+}
+
+type Handler = dyn Fn(&ReceivedRequest) -> (u16, String) + Send + Sync;
+
+/// An HTTP/1.1 server on 127.0.0.1 answering with the status and JSON body returned by its
+/// handler. Stops when dropped.
+pub(crate) struct TestServer {
+    /// The base URL of the server, to be used as the endpoint of the API.
+    pub endpoint: String,
+    requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl TestServer {
+    /// Starts a server on a free port.
+    pub async fn start<F>(handler: F) -> TestServer
+        where F: Fn(&ReceivedRequest) -> (u16, String) + Send + Sync + 'static
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let received = requests.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, handler.clone(), received.clone()));
+            }
+        });
+
+        TestServer { endpoint, requests, task }
+    }
+
+    /// Returns the requests received so far, in order.
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Answers the requests of a connection until the client closes it.
+async fn serve(
+    mut stream: TcpStream,
+    handler: Arc<Handler>,
+    received: Arc<Mutex<Vec<ReceivedRequest>>>
+) {
+    let mut buffer = Vec::new();
+    while let Some(request) = read_request(&mut stream, &mut buffer).await {
+        let (status, body) = handler(&request);
+        received.lock().unwrap().push(request);
+        let response = format!(
+            "HTTP/1.1 {} Test\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        if stream.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Reads the next request of a connection, keeping in `buffer` what was read past it.
+async fn read_request(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> Option<ReceivedRequest> {
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        read_more(stream, buffer).await?;
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+    let length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let body_start = head_end + 4;
+    while buffer.len() < body_start + length {
+        read_more(stream, buffer).await?;
+    }
+    let body = String::from_utf8_lossy(&buffer[body_start..body_start + length]).into_owned();
+    buffer.drain(..body_start + length);
+
+    Some(ReceivedRequest { method, path, headers, body })
+}
+
+async fn read_more(stream: &mut TcpStream, buffer: &mut Vec<u8>) -> Option<()> {
+    let mut chunk = [0; 4096];
+    match stream.read(&mut chunk).await {
+        Ok(0) | Err(_) => None,
+        Ok(read) => {
+            buffer.extend_from_slice(&chunk[..read]);
+            Some(())
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::{ fmt, fs::File, io::{ self, Write }, path::{ Path, PathBuf }, sync::OnceLock };
+
+use base64::{ engine::general_purpose::STANDARD, Engine };
+use serde::{ Deserialize, Deserializer, Serialize, Serializer };
+
+use crate::types::synthesize::AudioEncoding;
+
+/// Decoded audio returned by a synthesis request.
+///
+/// Holds the raw audio bytes together with the encoding and sample rate that were requested,
+/// so the bytes can be written or played back without guessing their format.
+/// The base64 representation used on the wire is only computed when asked for.
+#[derive(Clone, Default)]
+pub struct AudioContent {
+    bytes: Vec<u8>,
+    encoding: AudioEncoding,
+    sample_rate_hertz: Option<i32>,
+    base64: OnceLock<String>,
+}
+
+impl AudioContent {
+    /// Creates audio content from raw bytes.
+    pub fn new(bytes: Vec<u8>, encoding: AudioEncoding, sample_rate_hertz: Option<i32>) -> Self {
+        AudioContent {
+            bytes,
+            encoding,
+            sample_rate_hertz,
+            base64: OnceLock::new(),
+        }
+    }
+
+    /// Decodes audio content from its base64 representation.
+    pub fn from_base64(
+        base64: &str,
+        encoding: AudioEncoding,
+        sample_rate_hertz: Option<i32>
+    ) -> Result<Self, base64::DecodeError> {
+        let bytes = STANDARD.decode(base64)?;
+        Ok(AudioContent::new(bytes, encoding, sample_rate_hertz))
+    }
+
+    /// Returns the decoded audio bytes.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Consumes the content and returns the decoded audio bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Returns the number of audio bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns `true` if there are no audio bytes.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns the encoding the audio was requested with.
+    pub fn encoding(&self) -> AudioEncoding {
+        self.encoding
+    }
+
+    /// Returns the sample rate the audio was requested with, falling back to the
+    /// encoding's default sample rate.
+    pub fn sample_rate_hertz(&self) -> Option<i32> {
+        self.sample_rate_hertz.or_else(|| self.encoding.default_sample_rate())
+    }
+
+    /// Returns the MIME type of the audio.
+    pub fn mime_type(&self) -> &'static str {
+        self.encoding.mime_type()
+    }
+
+    /// Returns the file extension (without the leading dot) of the audio.
+    pub fn file_extension(&self) -> &'static str {
+        self.encoding.file_extension()
+    }
+
+    /// Returns the base64 representation of the audio, as sent by the API.
+    pub fn base64(&self) -> &str {
+        self.base64.get_or_init(|| STANDARD.encode(&self.bytes))
+    }
+
+    /// Writes the audio bytes to `writer`.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.bytes)?;
+        writer.flush()
+    }
+
+    /// Saves the audio to `path`, replacing its extension with the one matching the encoding.
+    ///
+    /// # Returns
+    ///
+    /// Returns the path the audio was written to.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let path = path.as_ref().with_extension(self.file_extension());
+        self.write_to(File::create(&path)?)?;
+        Ok(path)
+    }
+
+    /// Records the format the audio was requested with.
+    pub(crate) fn set_format(&mut self, encoding: AudioEncoding, sample_rate_hertz: Option<i32>) {
+        self.encoding = encoding;
+        self.sample_rate_hertz = sample_rate_hertz;
+    }
+}
+
+impl fmt::Debug for AudioContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AudioContent")
+            .field("len", &self.bytes.len())
+            .field("encoding", &self.encoding)
+            .field("sample_rate_hertz", &self.sample_rate_hertz())
+            .finish()
+    }
+}
+
+impl AsRef<[u8]> for AudioContent {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl From<AudioContent> for Vec<u8> {
+    fn from(value: AudioContent) -> Self {
+        value.bytes
+    }
+}
+
+impl<'de> Deserialize<'de> for AudioContent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let base64 = String::deserialize(deserializer)?;
+        let bytes = STANDARD.decode(&base64).map_err(serde::de::Error::custom)?;
+        Ok(AudioContent::new(bytes, AudioEncoding::AudioEncodingUnspecified, None))
+    }
+}
+
+impl Serialize for AudioContent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.base64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_base64_only_when_asked() {
+        let content = AudioContent::new(b"audio".to_vec(), AudioEncoding::Mp3, None);
+        assert!(content.base64.get().is_none());
+        assert_eq!(content.base64(), "YXVkaW8=");
+        assert!(content.base64.get().is_some());
+        assert_eq!(serde_json::to_value(&content).unwrap(), serde_json::json!("YXVkaW8="));
+
+        let decoded = AudioContent::from_base64("YXVkaW8=", AudioEncoding::Mp3, None).unwrap();
+        assert_eq!(decoded.bytes(), b"audio");
+        assert!(decoded.base64.get().is_none());
+        assert!(AudioContent::from_base64("not base64!", AudioEncoding::Mp3, None).is_err());
+    }
+
+    #[test]
+    fn deserializes_without_a_format_until_one_is_set() {
+        let mut content: AudioContent = serde_json::from_str(r#""YXVkaW8=""#).unwrap();
+        assert_eq!(content.bytes(), b"audio");
+        assert_eq!(content.encoding(), AudioEncoding::AudioEncodingUnspecified);
+        assert_eq!(content.sample_rate_hertz(), None);
+        assert_eq!(content.mime_type(), "application/octet-stream");
+
+        content.set_format(AudioEncoding::Mulaw, None);
+        assert_eq!(content.sample_rate_hertz(), Some(8000));
+        content.set_format(AudioEncoding::Linear16, Some(16000));
+        assert_eq!(content.sample_rate_hertz(), Some(16000));
+        assert!(serde_json::from_str::<AudioContent>(r#""not base64!""#).is_err());
+    }
+
+    #[test]
+    fn describes_the_format_of_the_audio() {
+        for (encoding, mime_type, extension) in [
+            (AudioEncoding::Linear16, "audio/wav", "wav"),
+            (AudioEncoding::Alaw, "audio/wav", "wav"),
+            (AudioEncoding::Mp3, "audio/mpeg", "mp3"),
+            (AudioEncoding::OggOpus, "audio/ogg", "ogg"),
+            (AudioEncoding::Pcm, "audio/pcm", "pcm"),
+            (AudioEncoding::M4a, "audio/mp4", "m4a"),
+        ] {
+            let content = AudioContent::new(Vec::new(), encoding, None);
+            assert_eq!(content.mime_type(), mime_type);
+            assert_eq!(content.file_extension(), extension);
+        }
+    }
+
+    #[test]
+    fn saves_with_the_extension_of_the_encoding() {
+        let directory = std::env::temp_dir().join(format!("audio-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let content = AudioContent::new(b"audio".to_vec(), AudioEncoding::OggOpus, None);
+
+        let saved = content.save(directory.join("greeting.mp3")).unwrap();
+        let bytes = std::fs::read(&saved);
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(saved, directory.join("greeting.ogg"));
+        assert_eq!(bytes.unwrap(), b"audio");
+    }
+}
@@ -7,6 +7,10 @@ pub struct GoogleCredentials {
     pub private_key: String,
     pub client_email: String,
     pub token_uri: String,
+    /// The project the service account belongs to. Required for project scoped methods
+    /// such as long audio synthesis.
+    #[serde(default)]
+    pub project_id: Option<String>,
 }
 
 pub fn read_credentials(
@@ -0,0 +1,192 @@
+use std::{ fmt, str::FromStr };
+
+use serde::{ Deserialize, Deserializer, Serialize, Serializer };
+
+/// A BCP-47 language tag such as `en-US`, `cmn-CN` or `es-419`.
+///
+/// Tags are validated on parse and stored in their canonical casing: lowercase language,
+/// titlecase script and uppercase region (`EN-us` becomes `en-US`).
+/// Extension and private-use subtags are not supported by the API and are rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LanguageTag(String);
+
+/// Error returned when a [LanguageTag] cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLanguageTagError {
+    pub input: String,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for ParseLanguageTagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid language tag '{}': {}", self.input, self.reason)
+    }
+}
+
+impl std::error::Error for ParseLanguageTagError {}
+
+impl LanguageTag {
+    /// Returns the tag as a string, e.g. `en-US`.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the primary language subtag, e.g. `en` for `en-US`.
+    pub fn language(&self) -> &str {
+        self.0.split('-').next().unwrap_or_default()
+    }
+
+    /// Returns the script subtag, e.g. `Hant` for `cmn-Hant-TW`.
+    pub fn script(&self) -> Option<&str> {
+        self.0
+            .split('-')
+            .skip(1)
+            .find(|subtag| is_script(subtag))
+    }
+
+    /// Returns the region subtag, e.g. `US` for `en-US` or `419` for `es-419`.
+    pub fn region(&self) -> Option<&str> {
+        self.0
+            .split('-')
+            .skip(1)
+            .find(|subtag| is_region(subtag))
+    }
+
+    /// Returns a tag made only of the primary language subtag, e.g. `en` for `en-US`.
+    pub fn base(&self) -> LanguageTag {
+        LanguageTag(self.language().to_string())
+    }
+
+    /// Returns `true` if this tag is `other` or one of its more specific tags,
+    /// e.g. `en-US` matches `en` and `en-US`, but not `en-GB`.
+    pub fn matches(&self, other: &LanguageTag) -> bool {
+        let mut subtags = self.0.split('-');
+        other.0.split('-').all(|subtag| subtags.next() == Some(subtag))
+    }
+}
+
+impl FromStr for LanguageTag {
+    type Err = ParseLanguageTagError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = |reason| ParseLanguageTagError { input: input.to_string(), reason };
+
+        let mut subtags = input.split('-').peekable();
+        let language = subtags.next().unwrap_or_default();
+        if !(2..=8).contains(&language.len()) || language.len() == 4 {
+            return Err(error("language subtag must be 2-3 or 5-8 letters"));
+        }
+        if !language.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(error("language subtag must only contain letters"));
+        }
+        let mut canonical = language.to_ascii_lowercase();
+
+        if let Some(script) = subtags.next_if(|subtag| is_script(subtag)) {
+            canonical.push('-');
+            canonical.push_str(&script[..1].to_ascii_uppercase());
+            canonical.push_str(&script[1..].to_ascii_lowercase());
+        }
+
+        if let Some(region) = subtags.next_if(|subtag| is_region(subtag)) {
+            canonical.push('-');
+            canonical.push_str(&region.to_ascii_uppercase());
+        }
+
+        for variant in subtags {
+            let is_variant =
+                variant.chars().all(|c| c.is_ascii_alphanumeric()) &&
+                ((5..=8).contains(&variant.len()) ||
+                    (variant.len() == 4 && variant.starts_with(|c: char| c.is_ascii_digit())));
+            if !is_variant {
+                return Err(error("unexpected subtag after the region"));
+            }
+            canonical.push('-');
+            canonical.push_str(&variant.to_ascii_lowercase());
+        }
+
+        Ok(LanguageTag(canonical))
+    }
+}
+
+fn is_script(subtag: &str) -> bool {
+    subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+fn is_region(subtag: &str) -> bool {
+    (subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic())) ||
+        (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl fmt::Display for LanguageTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for LanguageTag {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Serialize for LanguageTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for LanguageTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_display() {
+        for tag in ["en", "en-US", "es-419", "cmn-CN", "cmn-Hant-TW", "de-CH-1996"] {
+            let parsed: LanguageTag = tag.parse().unwrap();
+            assert_eq!(parsed.to_string(), tag);
+            assert_eq!(parsed.to_string().parse::<LanguageTag>().unwrap(), parsed);
+        }
+    }
+
+    #[test]
+    fn canonicalizes_the_casing() {
+        let tag: LanguageTag = "CMN-hant-tw".parse().unwrap();
+        assert_eq!(tag.as_str(), "cmn-Hant-TW");
+        assert_eq!(tag.language(), "cmn");
+        assert_eq!(tag.script(), Some("Hant"));
+        assert_eq!(tag.region(), Some("TW"));
+        assert_eq!(tag.base().as_str(), "cmn");
+    }
+
+    #[test]
+    fn rejects_invalid_tags() {
+        for tag in ["", "e", "engl", "e1-US", "en-US-x", "en-US-x-private", "en_US", "en--US"] {
+            assert!(tag.parse::<LanguageTag>().is_err(), "{} was accepted", tag);
+        }
+        let error = "en-US-x".parse::<LanguageTag>().unwrap_err();
+        assert_eq!(error.reason, "unexpected subtag after the region");
+        assert_eq!(error.to_string(), format!("invalid language tag 'en-US-x': {}", error.reason));
+    }
+
+    #[test]
+    fn matches_more_specific_tags() {
+        let tag = |tag: &str| tag.parse::<LanguageTag>().unwrap();
+        assert!(tag("en-US").matches(&tag("en")));
+        assert!(tag("en-US").matches(&tag("en-US")));
+        assert!(!tag("en-US").matches(&tag("en-GB")));
+        assert!(!tag("en").matches(&tag("en-US")));
+    }
+
+    #[test]
+    fn deserializes_from_a_string() {
+        let tag: LanguageTag = serde_json::from_str(r#""en-gb""#).unwrap();
+        assert_eq!(serde_json::to_string(&tag).unwrap(), r#""en-GB""#);
+        assert!(serde_json::from_str::<LanguageTag>(r#""en_US""#).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{ Deserialize, Serialize };
+
+use crate::types::synthesize::{ AudioConfig, RequestInput, VoiceSelectionParams };
+
+/// The top-level message sent by the client for the `synthesizeLongAudio` method.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "input": {
+///     object (RequestInput)
+///   },
+///   "audioConfig": {
+///     object (AudioConfig)
+///   },
+///   "outputGcsUri": string,
+///   "voice": {
+///     object (VoiceSelectionParams)
+///   }
+/// }
+/// ```
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Serialize)]
+pub struct SynthesizeLongAudioRequest {
+    /// Required. The Synthesizer requires either plain text or SSML as input.
+    pub input: RequestInput,
+    /// Required. The configuration of the synthesized audio.
+    pub audioConfig: AudioConfig,
+    /// Required. Specifies a Cloud Storage URI for the synthesis results. Must be specified in the format: `gs://bucket_name/object_name`, and the bucket must already exist.
+    pub outputGcsUri: String,
+    /// Required. The desired voice of the synthesized audio.
+    pub voice: VoiceSelectionParams,
+}
+
+/// Metadata for response returned by the `synthesizeLongAudio` method.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "startTime": string,
+///   "lastUpdateTime": string,
+///   "progressPercentage": number
+/// }
+/// ```
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SynthesizeLongAudioMetadata {
+    /// Time when the request was received, as an RFC 3339 timestamp.
+    pub startTime: Option<String>,
+    /// Time of the most recent processing update, as an RFC 3339 timestamp.
+    pub lastUpdateTime: Option<String>,
+    /// The progress of the most recent processing update in percentage, ie. 70.0%.
+    #[serde(default)]
+    pub progressPercentage: f64,
+}
+
+/// The message returned to the client by the `synthesizeLongAudio` method.
+///
+/// The audio is written to `outputGcsUri`, so the response has no payload.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SynthesizeLongAudioResponse {}
@@ -1,2 +1,9 @@
+pub mod audio;
 pub mod credentials;
+pub mod language;
+pub mod long_audio;
+pub mod operation;
+pub mod pronunciation;
 pub mod synthesize;
+pub mod voice_name;
+pub mod voices;
@@ -0,0 +1,80 @@
+use serde::{ Deserialize, Serialize };
+
+/// A long-running operation, as returned by the `operations` API.
+///
+/// `M` is the type of the operation metadata and `R` the type of its response once done.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "name": string,
+///   "metadata": {
+///     "@type": string,
+///     field1: ...,
+///     ...
+///   },
+///   "done": boolean,
+///
+///   // Union field result can be only one of the following:
+///   "error": {
+///     object (Status)
+///   },
+///   "response": {
+///     "@type": string,
+///     field1: ...,
+///     ...
+///   }
+///   // End of list of possible types for union field result.
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct LongRunningOperation<M, R> {
+    /// The server-assigned name, e.g. `projects/123/locations/global/operations/456`.
+    pub name: String,
+    /// Service-specific metadata associated with the operation, typically progress information.
+    pub metadata: Option<M>,
+    /// If the value is `false`, it means the operation is still in progress.
+    /// If `true`, the operation is completed, and either `error` or `response` is available.
+    #[serde(default)]
+    pub done: bool,
+    /// The error result of the operation in case of failure or cancellation.
+    pub error: Option<Status>,
+    /// The normal response of the operation in case of success.
+    pub response: Option<R>,
+}
+
+/// The logical error model used by Google APIs.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "code": integer,
+///   "message": string,
+///   "details": [
+///     {
+///       "@type": string,
+///       field1: ...,
+///       ...
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Status {
+    /// The status code, which should be an enum value of `google.rpc.Code`.
+    #[serde(default)]
+    pub code: i32,
+    /// A developer-facing error message, which should be in English.
+    #[serde(default)]
+    pub message: String,
+    /// A list of messages that carry the error details.
+    #[serde(default)]
+    pub details: Vec<serde_json::Value>,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
@@ -0,0 +1,154 @@
+use serde::{ Deserialize, Serialize };
+
+/// The phonetic alphabet a [CustomPronunciationParams::pronunciation] is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum PhoneticEncoding {
+    /// Not specified. The request will be rejected.
+    #[default]
+    #[serde(rename = "PHONETIC_ENCODING_UNSPECIFIED")]
+    PhoneticEncodingUnspecified,
+    /// IPA, e.g. `ˈtoʊmeɪtoʊ`. See <https://en.wikipedia.org/wiki/International_Phonetic_Alphabet>.
+    #[serde(rename = "PHONETIC_ENCODING_IPA")]
+    Ipa,
+    /// X-SAMPA, e.g. `"t_hoUmeItoU`. See <https://en.wikipedia.org/wiki/X-SAMPA>.
+    #[serde(rename = "PHONETIC_ENCODING_X_SAMPA")]
+    XSampa,
+}
+
+/// Pronunciation customization for a phrase.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "phrase": string,
+///   "phoneticEncoding": enum (PhoneticEncoding),
+///   "pronunciation": string
+/// }
+/// ```
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomPronunciationParams {
+    /// The phrase to which the customization is applied. The phrase can be multiple words,
+    /// such as proper nouns, but shouldn't span the length of the sentence.
+    pub phrase: String,
+    /// The phonetic encoding of the phrase.
+    pub phoneticEncoding: PhoneticEncoding,
+    /// The pronunciation of the phrase. This must be in the phonetic encoding specified above.
+    pub pronunciation: String,
+}
+
+impl CustomPronunciationParams {
+    pub fn new(phrase: &str, encoding: PhoneticEncoding, pronunciation: &str) -> Self {
+        CustomPronunciationParams {
+            phrase: phrase.to_string(),
+            phoneticEncoding: encoding,
+            pronunciation: pronunciation.to_string(),
+        }
+    }
+}
+
+/// A collection of pronunciation customizations, sent as `SynthesisInput::customPronunciations`.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "pronunciations": [
+///     {
+///       object (CustomPronunciationParams)
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct CustomPronunciations {
+    /// The pronunciation customizations to be applied.
+    #[serde(default)]
+    pub pronunciations: Vec<CustomPronunciationParams>,
+}
+
+impl CustomPronunciations {
+    /// Adds `params`, replacing the customization of the same phrase if there is one.
+    pub fn insert(&mut self, params: CustomPronunciationParams) {
+        match self.pronunciations.iter_mut().find(|p| p.phrase == params.phrase) {
+            Some(existing) => {
+                *existing = params;
+            }
+            None => self.pronunciations.push(params),
+        }
+    }
+
+    /// Returns `true` if a customization is set for `phrase`.
+    pub fn contains(&self, phrase: &str) -> bool {
+        self.pronunciations.iter().any(|p| p.phrase == phrase)
+    }
+}
+
+/// A reusable set of pronunciations, e.g. product names or personal names that the voices
+/// get wrong.
+///
+/// Attach it to a [TextToSpeechClient](crate::client::TextToSpeechClient) with
+/// `set_pronunciations` to apply it to every request, or pass it to a single request with
+/// `SynthesizeSpeechBuilder::pronunciations`. Phrases must match the input exactly.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PronunciationSet {
+    pronunciations: CustomPronunciations,
+}
+
+impl PronunciationSet {
+    pub fn new() -> Self {
+        PronunciationSet::default()
+    }
+
+    /// Adds the IPA pronunciation of `phrase`.
+    pub fn ipa(self, phrase: &str, pronunciation: &str) -> Self {
+        self.with(CustomPronunciationParams::new(phrase, PhoneticEncoding::Ipa, pronunciation))
+    }
+
+    /// Adds the X-SAMPA pronunciation of `phrase`.
+    pub fn x_sampa(self, phrase: &str, pronunciation: &str) -> Self {
+        self.with(CustomPronunciationParams::new(phrase, PhoneticEncoding::XSampa, pronunciation))
+    }
+
+    /// Adds `params`, replacing the pronunciation of the same phrase if there is one.
+    pub fn with(mut self, params: CustomPronunciationParams) -> Self {
+        self.insert(params);
+        self
+    }
+
+    /// Adds `params`, replacing the pronunciation of the same phrase if there is one.
+    pub fn insert(&mut self, params: CustomPronunciationParams) {
+        self.pronunciations.insert(params);
+    }
+
+    /// Removes the pronunciation of `phrase`.
+    pub fn remove(&mut self, phrase: &str) {
+        self.pronunciations.pronunciations.retain(|p| p.phrase != phrase);
+    }
+
+    pub fn len(&self) -> usize {
+        self.pronunciations.pronunciations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pronunciations.pronunciations.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, CustomPronunciationParams> {
+        self.pronunciations.pronunciations.iter()
+    }
+}
+
+impl From<PronunciationSet> for CustomPronunciations {
+    fn from(value: PronunciationSet) -> Self {
+        value.pronunciations
+    }
+}
+
+impl<'a> IntoIterator for &'a PronunciationSet {
+    type Item = &'a CustomPronunciationParams;
+    type IntoIter = std::slice::Iter<'a, CustomPronunciationParams>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -1,40 +1,173 @@
 use serde::{ Deserialize, Serialize };
 
+use crate::types::{
+    audio::AudioContent,
+    language::LanguageTag,
+    pronunciation::CustomPronunciations,
+    voice_name::VoiceName,
+};
+
 /// Represents a request to synthesize speech.
 ///
 /// This struct holds the data necessary to make a request to a speech synthesis service.
 /// It includes the text to be synthesized, voice parameters, and audio configuration.
 #[allow(non_snake_case)]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SynthesizeRequest {
     /// Required. The Synthesizer requires either plain text or SSML as input.
-    pub input: SynthesisInput,
+    pub input: RequestInput,
     /// The desired voice of the synthesized audio.
     pub voice: VoiceSelectionParams,
     /// The configuration of the synthesized audio.
     pub audioConfig: AudioConfig,
+    /// Whether and what timepoints are returned in the response. Only supported by the `v1beta1` API.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enableTimePointing: Option<Vec<TimepointType>>,
+}
+
+/// The type of timepoint information returned in the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimepointType {
+    /// Not specified. No timepoint information will be returned.
+    #[serde(rename = "TIMEPOINT_TYPE_UNSPECIFIED")]
+    TimepointTypeUnspecified,
+    /// Timepoint information of `<mark>` tags in SSML input will be returned.
+    #[serde(rename = "SSML_MARK")]
+    SsmlMark,
 }
 
 /// Represents the input for speech synthesis.
 ///
-/// This struct can contain either plain text or SSML for synthesis.
+/// Exactly one input source is sent, so a request can't carry both text and SSML.
+/// Serializes to the `input_source` union field of the API.
 ///
 /// JSON representation
-/// ```
+/// ```json
 /// {
 ///   // Union field input_source can be only one of the following:
 ///   "text": string,
-///   "ssml": string
+///   "ssml": string,
+///   "markup": string,
+///   "multiSpeakerMarkup": {
+///     object (MultiSpeakerMarkup)
+///   }
 ///   // End of list of possible types for union field input_source.
 /// }
 /// ```
-#[derive(Debug, Serialize)]
-pub struct SynthesisInput {
-    // Define the structure of the request body according to Google's API
-    // The raw text to be synthesized.
-    pub text: Option<String>,
-    // The Speech Synthesis Markup Language (SSML) document to be synthesized. The SSML document must be valid and well-formed. Otherwise the RPC will fail and return [google.rpc.Code.INVALID_ARGUMENT](https://cloud.google.com/text-to-speech/docs/reference/rest/Shared.Types/Code#ENUM_VALUES.INVALID_ARGUMENT). For more information, see SSML.
-    pub ssml: Option<String>,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SynthesisInput {
+    /// The raw text to be synthesized.
+    #[serde(rename = "text")]
+    Text(String),
+    /// The Speech Synthesis Markup Language (SSML) document to be synthesized. The SSML document must be valid and well-formed. Otherwise the RPC will fail and return [google.rpc.Code.INVALID_ARGUMENT](https://cloud.google.com/text-to-speech/docs/reference/rest/Shared.Types/Code#ENUM_VALUES.INVALID_ARGUMENT). For more information, see SSML.
+    #[serde(rename = "ssml")]
+    Ssml(String),
+    /// Markup for Chirp 3 HD voices: text with `[pause]`-style tags. Not supported by other voices.
+    #[serde(rename = "markup")]
+    Markup(String),
+    /// The multi-speaker input to be synthesized. Only applicable for multi-speaker synthesis,
+    /// with the [MULTI_SPEAKER_VOICE] voice.
+    #[serde(rename = "multiSpeakerMarkup")]
+    MultiSpeaker(MultiSpeakerMarkup),
+}
+
+impl SynthesisInput {
+    /// Returns the size of the input in bytes, as counted against the API limit.
+    pub fn len(&self) -> usize {
+        match self {
+            SynthesisInput::Text(text) |
+            SynthesisInput::Ssml(text) |
+            SynthesisInput::Markup(text) => text.len(),
+            SynthesisInput::MultiSpeaker(markup) => {
+                markup.turns
+                    .iter()
+                    .map(|turn| turn.text.len())
+                    .sum()
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The `input` object of a request: a [SynthesisInput] and the pronunciations applied to it.
+///
+/// JSON representation
+/// ```json
+/// {
+///   // Fields of SynthesisInput
+///   "customPronunciations": {
+///     object (CustomPronunciations)
+///   }
+/// }
+/// ```
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestInput {
+    #[serde(flatten)]
+    pub source: SynthesisInput,
+    /// Optional. The pronunciation customizations applied to the input. Phrases must match the
+    /// input exactly, and must not be inside an SSML `<phoneme>` tag.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub customPronunciations: Option<CustomPronunciations>,
+}
+
+impl From<SynthesisInput> for RequestInput {
+    fn from(source: SynthesisInput) -> Self {
+        RequestInput {
+            source,
+            customPronunciations: None,
+        }
+    }
+}
+
+/// Name of the Studio voice that reads [MultiSpeakerMarkup].
+pub const MULTI_SPEAKER_VOICE: &str = "en-US-Studio-MultiSpeaker";
+
+/// A dialogue between several speakers, read by the [MULTI_SPEAKER_VOICE] voice.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "turns": [
+///     {
+///       object (Turn)
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiSpeakerMarkup {
+    /// Required. Speaker turns, in the order they are spoken.
+    pub turns: Vec<Turn>,
+}
+
+/// A single turn of a [MultiSpeakerMarkup] dialogue.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "speaker": string,
+///   "text": string
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Turn {
+    /// Required. The speaker of the turn, one of `R`, `S`, `T` or `U`.
+    pub speaker: String,
+    /// Required. The text to speak.
+    pub text: String,
+}
+
+impl Turn {
+    pub fn new(speaker: &str, text: &str) -> Self {
+        Turn {
+            speaker: speaker.to_string(),
+            text: text.to_string(),
+        }
+    }
 }
 
 /// Parameters for voice selection in speech synthesis.
@@ -42,38 +175,68 @@ pub struct SynthesisInput {
 /// This struct defines the language, name, gender, and custom voice parameters for synthesis.
 ///
 /// JSON representation
-/// ```
+/// ```json
 /// {
 ///     "languageCode": string,
 ///     "name": string,
 ///     "ssmlGender": enum (SsmlVoiceGender),
 ///     "customVoice": {
 ///       object (CustomVoiceParams)
+///     },
+///     "voiceClone": {
+///       object (VoiceCloneParams)
 ///     }
 /// }
 /// ```
 #[allow(non_snake_case)]
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceSelectionParams {
     /// Required. The language (and potentially also the region) of the voice expressed as a BCP-47 language tag, e.g. "en-US". This should not include a script tag (e.g. use "cmn-cn" rather than "cmn-Hant-cn"), because the script will be inferred from the input provided in the SynthesisInput. The TTS service will use this parameter to help choose an appropriate voice. Note that the TTS service may choose a voice with a slightly different language code than the one selected; it may substitute a different region (e.g. using en-US rather than en-CA if there isn't a Canadian voice available), or even a different language, e.g. using "nb" (Norwegian Bokmal) instead of "no" (Norwegian)".
     #[serde(default = "default_language_code")]
-    pub languageCode: String,
+    pub languageCode: LanguageTag,
     /// The name of the voice. If not set, the service will choose a voice based on the other parameters such as languageCode and gender.
-    pub name: Option<String>,
+    pub name: Option<VoiceName>,
     /// The preferred gender of the voice. If not set, the service will choose a voice based on the other parameters such as languageCode and name. Note that this is only a preference, not requirement; if a voice of the appropriate gender is not available, the synthesizer should substitute a voice with a different gender rather than failing the request.
     #[serde(default)]
     pub ssmlGender: SsmlVoiceGender,
     /// The configuration for a custom voice. If CustomVoiceParams.model is set, the service will choose the custom voice matching the specified configuration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub customVoice: Option<CustomVoiceParams>,
+    /// Optional. The configuration for a voice clone. If VoiceCloneParams.voiceCloningKey is set, the service will choose the voice clone matching the specified configuration. Only supported by the `v1beta1` API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voiceClone: Option<VoiceCloneParams>,
+}
+
+impl Default for VoiceSelectionParams {
+    fn default() -> Self {
+        VoiceSelectionParams {
+            languageCode: default_language_code(),
+            name: None,
+            ssmlGender: SsmlVoiceGender::default(),
+            customVoice: None,
+            voiceClone: None,
+        }
+    }
+}
+
+impl VoiceSelectionParams {
+    /// Selects the voice `name`, using its language as `languageCode`.
+    pub fn from_name(name: VoiceName) -> Self {
+        VoiceSelectionParams {
+            languageCode: name.language().clone(),
+            name: Some(name),
+            ..Default::default()
+        }
+    }
 }
 
 /// Default language code used in `VoiceSelectionParams`.
-pub fn default_language_code() -> String {
-    "en-US".to_string()
+pub fn default_language_code() -> LanguageTag {
+    "en-US".parse().expect("en-US is a valid language tag")
 }
 
 /// Enum representing the gender of the voice in speech synthesis.
-#[derive(Serialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SsmlVoiceGender {
     /// Gender unspecified.
     #[serde(rename = "SSML_VOICE_GENDER_UNSPECIFIED")]
@@ -95,38 +258,74 @@ pub enum SsmlVoiceGender {
 /// This struct defines the custom voice model and usage for the synthesized speech.
 ///
 /// JSON representation
-/// ```
+/// ```json
 /// {
 ///   "model": string,
 ///   "reportedUsage": enum (ReportedUsage)
 /// }
 /// ```
 #[allow(non_snake_case)]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CustomVoiceParams {
     /// Required. The name of the AutoML model that synthesizes the custom voice.
     pub model: String,
     /// Optional. The usage of the synthesized audio to be reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reportedUsage: Option<ReportedUsage>,
 }
 
+impl CustomVoiceParams {
+    pub fn new(model: &str, reported_usage: ReportedUsage) -> Self {
+        CustomVoiceParams {
+            model: model.to_string(),
+            reportedUsage: Some(reported_usage),
+        }
+    }
+}
+
 /// The usage of the synthesized audio. Usage does not affect billing.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ReportedUsage {
     /// Request with reported usage unspecified will be rejected.
+    #[serde(rename = "REPORTED_USAGE_UNSPECIFIED")]
     ReportedUsageUnspecified,
     /// For scenarios where the synthesized audio is not downloadable and can only be used once. For example, real-time request in IVR system.
+    #[serde(rename = "REALTIME")]
     Realtime,
     /// For scenarios where the synthesized audio is downloadable and can be reused. For example, the synthesized audio is downloaded, stored in customer service system and played repeatedly.
+    #[serde(rename = "OFFLINE")]
     Offline,
 }
 
+/// The configuration of a voice clone, an instant custom voice created from a short recording.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "voiceCloningKey": string
+/// }
+/// ```
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoiceCloneParams {
+    /// Required. Created by GenerateVoiceCloningKey.
+    pub voiceCloningKey: String,
+}
+
+impl VoiceCloneParams {
+    pub fn new(voice_cloning_key: &str) -> Self {
+        VoiceCloneParams {
+            voiceCloningKey: voice_cloning_key.to_string(),
+        }
+    }
+}
+
 /// Audio configuration for speech synthesis.
 ///
 /// This struct defines the encoding, rate, pitch, volume, and other audio settings for the synthesized speech.
 ///
 /// JSON Representation
-/// ```
+/// ```json
 /// {
 ///   "audioEncoding": enum (AudioEncoding),
 ///   "speakingRate": number,
@@ -139,7 +338,7 @@ pub enum ReportedUsage {
 /// }
 /// ```
 #[allow(non_snake_case)]
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AudioConfig {
     /// Required. The format of the audio byte stream.
     #[serde(default)]
@@ -157,37 +356,250 @@ pub struct AudioConfig {
 }
 
 /// Enum representing the audio encoding for speech synthesis.
-#[derive(Debug, Serialize, Default)]
+///
+/// Covers every encoding supported by the `v1` and `v1beta1` APIs. Use the metadata helpers
+/// ([AudioEncoding::mime_type], [AudioEncoding::file_extension], ...) to handle the returned bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum AudioEncoding {
     /// Not specified. Will return result [google.rpc.Code.INVALID_ARGUMENT](https://cloud.google.com/text-to-speech/docs/reference/rest/Shared.Types/Code#ENUM_VALUES.INVALID_ARGUMENT).
     #[serde(rename = "AUDIO_ENCODING_UNSPECIFIED")]
     AudioEncodingUnspecified,
+    /// Uncompressed 16-bit signed little-endian samples (Linear PCM). Audio content returned as LINEAR16 also contains a WAV header.
+    #[serde(rename = "LINEAR16")]
+    Linear16,
     #[default]
     /// MP3 audio at 32kbps.
     #[serde(rename = "MP3")]
     Mp3,
-    /// Uncompressed 16-bit signed little-endian samples (Linear PCM). Audio content returned as LINEAR16 also contains a WAV header.
-    /// Linear16,
+    /// MP3 at 64kbps. Only available through the `v1beta1` API.
+    #[serde(rename = "MP3_64_KBPS")]
+    Mp3_64Kbps,
     /// Opus encoded audio wrapped in an ogg container. The result will be a file which can be played natively on Android, and in browsers (at least Chrome and Firefox). The quality of the encoding is considerably higher than MP3 while using approximately the same bitrate.
-    #[serde(rename = "OGGOPUS")]
+    #[serde(rename = "OGG_OPUS")]
     OggOpus,
-    // 8-bit samples that compand 14-bit audio samples using G.711 PCMU/mu-law. Audio content returned as MULAW also contains a WAV header.
+    /// 8-bit samples that compand 14-bit audio samples using G.711 PCMU/mu-law. Audio content returned as MULAW also contains a WAV header.
     #[serde(rename = "MULAW")]
     Mulaw,
     /// 8-bit samples that compand 14-bit audio samples using G.711 PCMU/A-law. Audio content returned as ALAW also contains a WAV header.
     #[serde(rename = "ALAW")]
     Alaw,
+    /// Uncompressed 16-bit signed little-endian samples (Linear PCM). Note that as opposed to LINEAR16, audio won't be wrapped in a WAV (or any other) header.
+    #[serde(rename = "PCM")]
+    Pcm,
+    /// M4A audio.
+    #[serde(rename = "M4A")]
+    M4a,
+}
+
+impl AudioEncoding {
+    /// Every encoding that can be requested from the API.
+    pub const ALL: [AudioEncoding; 8] = [
+        AudioEncoding::Linear16,
+        AudioEncoding::Mp3,
+        AudioEncoding::Mp3_64Kbps,
+        AudioEncoding::OggOpus,
+        AudioEncoding::Mulaw,
+        AudioEncoding::Alaw,
+        AudioEncoding::Pcm,
+        AudioEncoding::M4a,
+    ];
+
+    /// Returns the MIME type of audio content returned with this encoding.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioEncoding::AudioEncodingUnspecified => "application/octet-stream",
+            AudioEncoding::Linear16 | AudioEncoding::Mulaw | AudioEncoding::Alaw => "audio/wav",
+            AudioEncoding::Mp3 | AudioEncoding::Mp3_64Kbps => "audio/mpeg",
+            AudioEncoding::OggOpus => "audio/ogg",
+            AudioEncoding::Pcm => "audio/pcm",
+            AudioEncoding::M4a => "audio/mp4",
+        }
+    }
+
+    /// Returns the file extension (without the leading dot) for audio content returned with this encoding.
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            AudioEncoding::AudioEncodingUnspecified => "bin",
+            AudioEncoding::Linear16 | AudioEncoding::Mulaw | AudioEncoding::Alaw => "wav",
+            AudioEncoding::Mp3 | AudioEncoding::Mp3_64Kbps => "mp3",
+            AudioEncoding::OggOpus => "ogg",
+            AudioEncoding::Pcm => "pcm",
+            AudioEncoding::M4a => "m4a",
+        }
+    }
+
+    /// Returns `true` if the audio content is wrapped in a container or carries a header
+    /// (WAV, MP3, Ogg or MP4). Only raw `PCM` is returned as bare samples.
+    pub fn has_container(&self) -> bool {
+        !matches!(self, AudioEncoding::Pcm | AudioEncoding::AudioEncodingUnspecified)
+    }
+
+    /// Returns `true` if the audio content starts with a WAV header.
+    pub fn has_wav_header(&self) -> bool {
+        matches!(self, AudioEncoding::Linear16 | AudioEncoding::Mulaw | AudioEncoding::Alaw)
+    }
+
+    /// Returns the sample rate used by the API when `sampleRateHertz` is not set.
+    ///
+    /// This is the natural sample rate of most voices; telephony encodings default to 8 kHz.
+    pub fn default_sample_rate(&self) -> Option<i32> {
+        match self {
+            AudioEncoding::AudioEncodingUnspecified => None,
+            AudioEncoding::Mulaw | AudioEncoding::Alaw => Some(8000),
+            _ => Some(24000),
+        }
+    }
+
+    /// Returns the number of bits per sample for uncompressed encodings.
+    pub fn bits_per_sample(&self) -> Option<u16> {
+        match self {
+            AudioEncoding::Linear16 | AudioEncoding::Pcm => Some(16),
+            AudioEncoding::Mulaw | AudioEncoding::Alaw => Some(8),
+            _ => None,
+        }
+    }
+
+    /// Returns the sample rates accepted for this encoding, or `None` if any rate
+    /// between 8 kHz and 48 kHz is accepted.
+    pub fn supported_sample_rates(&self) -> Option<&'static [i32]> {
+        match self {
+            AudioEncoding::OggOpus => Some(&[8000, 12000, 16000, 24000, 48000]),
+            AudioEncoding::Mp3 | AudioEncoding::Mp3_64Kbps =>
+                Some(&[8000, 11025, 12000, 16000, 22050, 24000, 32000, 44100, 48000]),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this encoding is only available through the `v1beta1` API.
+    pub fn requires_v1beta1(&self) -> bool {
+        matches!(self, AudioEncoding::Mp3_64Kbps)
+    }
 }
 
 /// Represents a response from a speech synthesis request.
 #[allow(non_snake_case)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct SynthesizeResponse {
     /// Contains the synthesized audio content.
     ///
     /// This field stores the audio data bytes encoded as specified in the request.
     /// For encodings that are wrapped in containers (e.g., MP3, OGG_OPUS), it includes
     /// the appropriate header. For LINEAR16 audio, it contains a WAV header.
-    /// In JSON representations, the audio content is base64-encoded.
-    pub audioContent: String,
+    /// In JSON representations, the audio content is base64-encoded; it is decoded on
+    /// deserialization and tagged with the encoding and sample rate of the request.
+    pub audioContent: AudioContent,
+    /// A link between a position in the original request input and a corresponding time in the output audio.
+    /// It's only supported via `<mark>` of SSML input, and only returned by the `v1beta1` API.
+    #[serde(default)]
+    pub timepoints: Vec<Timepoint>,
+    /// The audio metadata of `audioContent`. Only returned by the `v1beta1` API.
+    pub audioConfig: Option<AudioConfig>,
+}
+
+/// This contains a mapping between a certain point in the input text and a corresponding time in the output audio.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "markName": string,
+///   "timeSeconds": number
+/// }
+/// ```
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Timepoint {
+    /// Timepoint name as received from the client within `<mark>` tag.
+    pub markName: String,
+    /// Time offset in seconds from the start of the synthesized audio.
+    pub timeSeconds: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deserializes_timepoints_and_audio_config() {
+        let response: SynthesizeResponse = serde_json::from_value(
+            json!({
+                "audioContent": "YXVkaW8=",
+                "timepoints": [
+                    { "markName": "start", "timeSeconds": 0.0 },
+                    { "markName": "end", "timeSeconds": 1.25 },
+                ],
+                "audioConfig": { "audioEncoding": "LINEAR16", "sampleRateHertz": 16000 },
+            })
+        ).unwrap();
+
+        assert_eq!(response.audioContent.bytes(), b"audio");
+        assert_eq!(response.timepoints, [
+            Timepoint { markName: "start".to_string(), timeSeconds: 0.0 },
+            Timepoint { markName: "end".to_string(), timeSeconds: 1.25 },
+        ]);
+        let config = response.audioConfig.unwrap();
+        assert_eq!(config.audioEncoding, AudioEncoding::Linear16);
+        assert_eq!(config.sampleRateHertz, Some(16000));
+        assert_eq!(config.speakingRate, None);
+    }
+
+    #[test]
+    fn deserializes_v1_responses_without_timepoints() {
+        let response: SynthesizeResponse = serde_json::from_value(
+            json!({ "audioContent": "YXVkaW8=" })
+        ).unwrap();
+        assert!(response.timepoints.is_empty());
+        assert!(response.audioConfig.is_none());
+
+        let types = vec![TimepointType::SsmlMark];
+        assert_eq!(serde_json::to_value(types).unwrap(), json!(["SSML_MARK"]));
+    }
+
+    #[test]
+    fn serializes_a_dialogue_as_multi_speaker_markup() {
+        let turns = vec![Turn::new("R", "Hi"), Turn::new("S", "Hello")];
+        let input = RequestInput::from(SynthesisInput::MultiSpeaker(MultiSpeakerMarkup { turns }));
+        let value = json!({
+            "multiSpeakerMarkup": {
+                "turns": [{ "speaker": "R", "text": "Hi" }, { "speaker": "S", "text": "Hello" }],
+            },
+        });
+        assert_eq!(serde_json::to_value(&input).unwrap(), value);
+        assert_eq!(serde_json::from_value::<SynthesisInput>(value).unwrap(), input.source);
+        assert_eq!(input.source.len(), 7);
+    }
+
+    #[test]
+    fn serializes_reported_usage_by_wire_name() {
+        for (usage, name) in [
+            (ReportedUsage::ReportedUsageUnspecified, "REPORTED_USAGE_UNSPECIFIED"),
+            (ReportedUsage::Realtime, "REALTIME"),
+            (ReportedUsage::Offline, "OFFLINE"),
+        ] {
+            assert_eq!(serde_json::to_value(usage).unwrap(), json!(name));
+            assert_eq!(serde_json::from_value::<ReportedUsage>(json!(name)).unwrap(), usage);
+        }
+
+        let custom = CustomVoiceParams::new("projects/p/models/m", ReportedUsage::Offline);
+        let value = json!({ "model": "projects/p/models/m", "reportedUsage": "OFFLINE" });
+        assert_eq!(serde_json::to_value(&custom).unwrap(), value);
+        assert_eq!(serde_json::from_value::<CustomVoiceParams>(value).unwrap(), custom);
+    }
+
+    #[test]
+    fn serializes_voice_clones() {
+        let voice = VoiceSelectionParams {
+            voiceClone: Some(VoiceCloneParams::new("key")),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&voice).unwrap();
+        assert_eq!(value["voiceClone"], json!({ "voiceCloningKey": "key" }));
+        assert!(value.get("customVoice").is_none());
+
+        let voice: VoiceSelectionParams = serde_json::from_value(value).unwrap();
+        assert_eq!(voice.voiceClone, Some(VoiceCloneParams::new("key")));
+
+        let voice = serde_json::to_value(VoiceSelectionParams::default()).unwrap();
+        assert!(voice.get("voiceClone").is_none());
+    }
 }
@@ -0,0 +1,237 @@
+use std::{ fmt, str::FromStr };
+
+use serde::{ Deserialize, Deserializer, Serialize, Serializer };
+
+use crate::types::language::{ LanguageTag, ParseLanguageTagError };
+
+/// Family of a voice, the segment of its name between the language and the variant
+/// (`Neural2` in `en-US-Neural2-F`, `Chirp3-HD` in `en-US-Chirp3-HD-Achernar`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum VoiceFamily {
+    Standard,
+    Wavenet,
+    Neural2,
+    News,
+    Studio,
+    Polyglot,
+    Journey,
+    Casual,
+    ChirpHd,
+    Chirp3Hd,
+    /// A family this crate doesn't know about yet.
+    Other(String),
+}
+
+impl VoiceFamily {
+    /// Returns `true` if voices of this family accept SSML input.
+    pub fn supports_ssml(&self) -> bool {
+        !matches!(self, VoiceFamily::Journey | VoiceFamily::ChirpHd | VoiceFamily::Chirp3Hd)
+    }
+
+    /// Returns `true` if voices of this family honor `AudioConfig::pitch`.
+    pub fn supports_pitch(&self) -> bool {
+        !matches!(self, VoiceFamily::Journey | VoiceFamily::ChirpHd | VoiceFamily::Chirp3Hd)
+    }
+}
+
+impl FromStr for VoiceFamily {
+    type Err = std::convert::Infallible;
+
+    fn from_str(family: &str) -> Result<Self, Self::Err> {
+        Ok(match family.to_ascii_lowercase().as_str() {
+            "standard" => VoiceFamily::Standard,
+            "wavenet" => VoiceFamily::Wavenet,
+            "neural2" => VoiceFamily::Neural2,
+            "news" => VoiceFamily::News,
+            "studio" => VoiceFamily::Studio,
+            "polyglot" => VoiceFamily::Polyglot,
+            "journey" => VoiceFamily::Journey,
+            "casual" => VoiceFamily::Casual,
+            "chirp-hd" => VoiceFamily::ChirpHd,
+            "chirp3-hd" => VoiceFamily::Chirp3Hd,
+            _ => VoiceFamily::Other(family.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for VoiceFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VoiceFamily::Standard => "Standard",
+            VoiceFamily::Wavenet => "Wavenet",
+            VoiceFamily::Neural2 => "Neural2",
+            VoiceFamily::News => "News",
+            VoiceFamily::Studio => "Studio",
+            VoiceFamily::Polyglot => "Polyglot",
+            VoiceFamily::Journey => "Journey",
+            VoiceFamily::Casual => "Casual",
+            VoiceFamily::ChirpHd => "Chirp-HD",
+            VoiceFamily::Chirp3Hd => "Chirp3-HD",
+            VoiceFamily::Other(family) => family,
+        })
+    }
+}
+
+/// A parsed voice name such as `en-US-Neural2-F` or `cmn-CN-Wavenet-A`.
+///
+/// A name is made of a language tag (language and region), a [VoiceFamily] and a variant.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VoiceName {
+    language: LanguageTag,
+    family: VoiceFamily,
+    variant: String,
+}
+
+/// Error returned when a [VoiceName] cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseVoiceNameError {
+    pub input: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseVoiceNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid voice name '{}': {}", self.input, self.reason)
+    }
+}
+
+impl std::error::Error for ParseVoiceNameError {}
+
+impl VoiceName {
+    /// Creates a voice name from its parts.
+    pub fn new(language: LanguageTag, family: VoiceFamily, variant: &str) -> Self {
+        VoiceName {
+            language,
+            family,
+            variant: variant.to_string(),
+        }
+    }
+
+    /// Returns the language of the voice, e.g. `en-US` for `en-US-Neural2-F`.
+    pub fn language(&self) -> &LanguageTag {
+        &self.language
+    }
+
+    /// Returns the family of the voice, e.g. [VoiceFamily::Neural2] for `en-US-Neural2-F`.
+    pub fn family(&self) -> &VoiceFamily {
+        &self.family
+    }
+
+    /// Returns the variant of the voice, e.g. `F` for `en-US-Neural2-F`.
+    pub fn variant(&self) -> &str {
+        &self.variant
+    }
+}
+
+impl FromStr for VoiceName {
+    type Err = ParseVoiceNameError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = |reason: String| ParseVoiceNameError { input: input.to_string(), reason };
+
+        let parts: Vec<&str> = input.split('-').collect();
+        if parts.len() < 4 {
+            return Err(error("expected <language>-<region>-<family>-<variant>".to_string()));
+        }
+        let is_valid_part = |part: &&str| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric())
+        };
+        if !parts.iter().all(is_valid_part) {
+            return Err(error("parts must be non-empty and alphanumeric".to_string()));
+        }
+
+        let language: LanguageTag = parts[..2]
+            .join("-")
+            .parse()
+            .map_err(|e: ParseLanguageTagError| error(e.reason.to_string()))?;
+        if language.region().is_none() {
+            return Err(error("missing region".to_string()));
+        }
+
+        let family = parts[2..parts.len() - 1].join("-").parse().unwrap_or_else(|e| match e {});
+        let variant = parts[parts.len() - 1];
+
+        Ok(VoiceName::new(language, family, variant))
+    }
+}
+
+impl fmt::Display for VoiceName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}-{}", self.language, self.family, self.variant)
+    }
+}
+
+impl Serialize for VoiceName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for VoiceName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_display() {
+        let names = [
+            "en-US-Neural2-F",
+            "cmn-CN-Wavenet-A",
+            "en-US-Chirp3-HD-Achernar",
+            "en-US-Chirp-HD-D",
+            "es-US-Polyglot-1",
+            "en-GB-Standard-B",
+        ];
+        for name in names {
+            let parsed: VoiceName = name.parse().unwrap();
+            assert_eq!(parsed.to_string(), name);
+            assert_eq!(parsed.to_string().parse::<VoiceName>().unwrap(), parsed);
+        }
+    }
+
+    #[test]
+    fn splits_the_name_into_its_parts() {
+        let name: VoiceName = "en-US-Chirp3-HD-Achernar".parse().unwrap();
+        assert_eq!(name.language().as_str(), "en-US");
+        assert_eq!(*name.family(), VoiceFamily::Chirp3Hd);
+        assert_eq!(name.variant(), "Achernar");
+
+        let name: VoiceName = "cmn-cn-wavenet-a".parse().unwrap();
+        assert_eq!(name.to_string(), "cmn-CN-Wavenet-a");
+        assert_eq!(*name.family(), VoiceFamily::Wavenet);
+    }
+
+    #[test]
+    fn keeps_unknown_families() {
+        let name: VoiceName = "en-US-Future-X".parse().unwrap();
+        assert_eq!(*name.family(), VoiceFamily::Other("Future".to_string()));
+        assert!(name.family().supports_ssml());
+        assert_eq!(name.to_string(), "en-US-Future-X");
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        let names = ["Achernar", "en-US-F", "en-Neural2-F-G", "en-US--F", "en-US-Neural2-F!", ""];
+        for name in names {
+            assert!(name.parse::<VoiceName>().is_err(), "{} was accepted", name);
+        }
+        let error = "Achernar".parse::<VoiceName>().unwrap_err();
+        assert_eq!(error.input, "Achernar");
+        assert_eq!(
+            error.to_string(),
+            "invalid voice name 'Achernar': expected <language>-<region>-<family>-<variant>"
+        );
+    }
+
+    #[test]
+    fn deserializes_from_a_string() {
+        let name: VoiceName = serde_json::from_str(r#""en-US-Neural2-F""#).unwrap();
+        assert_eq!(serde_json::to_string(&name).unwrap(), r#""en-US-Neural2-F""#);
+        assert!(serde_json::from_str::<VoiceName>(r#""Achernar""#).is_err());
+    }
+}
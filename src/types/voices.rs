@@ -0,0 +1,100 @@
+use serde::{ Deserialize, Deserializer, Serialize };
+
+use crate::types::{ language::LanguageTag, synthesize::SsmlVoiceGender, voice_name::VoiceName };
+
+/// Description of a voice supported by the TTS service.
+///
+/// JSON representation
+/// ```json
+/// {
+///   "languageCodes": [
+///     string
+///   ],
+///   "name": string,
+///   "ssmlGender": enum (SsmlVoiceGender),
+///   "naturalSampleRateHertz": integer
+/// }
+/// ```
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Voice {
+    /// The languages that this voice supports, expressed as BCP-47 language tags (e.g. "en-US", "es-419", "cmn-tw").
+    /// Tags that can't be parsed are skipped.
+    #[serde(default, deserialize_with = "deserialize_language_codes")]
+    pub languageCodes: Vec<LanguageTag>,
+    /// The name of this voice. Each distinct voice has a unique name.
+    pub name: VoiceName,
+    /// The gender of this voice.
+    #[serde(default)]
+    pub ssmlGender: SsmlVoiceGender,
+    /// The natural sample rate (in hertz) for this voice.
+    #[serde(default)]
+    pub naturalSampleRateHertz: i32,
+}
+
+/// The message returned to the client by the `voices.list` method.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListVoicesResponse {
+    /// The list of voices. Voices whose name can't be parsed are skipped, so that a voice
+    /// this crate doesn't understand doesn't fail the whole list.
+    #[serde(default, deserialize_with = "deserialize_voices")]
+    pub voices: Vec<Voice>,
+}
+
+fn deserialize_voices<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Voice>, D::Error> {
+    let voices = Vec::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(
+        voices
+            .into_iter()
+            .filter_map(|voice| {
+                Voice::deserialize(&voice)
+                    .map_err(|e| log::warn!("skipping voice {}: {}", voice, e))
+                    .ok()
+            })
+            .collect()
+    )
+}
+
+fn deserialize_language_codes<'de, D: Deserializer<'de>>(
+    deserializer: D
+) -> Result<Vec<LanguageTag>, D::Error> {
+    let codes = Vec::<String>::deserialize(deserializer)?;
+    Ok(
+        codes
+            .iter()
+            .filter_map(|code| {
+                code.parse().map_err(|e| log::warn!("skipping language code: {}", e)).ok()
+            })
+            .collect()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_voices_and_language_codes_that_cannot_be_parsed() {
+        let response: ListVoicesResponse = serde_json::from_str(
+            r#"{"voices": [
+                {"name": "Achernar"},
+                {"name": "en-US-Neural2-F", "languageCodes": ["en-US", "not a tag"],
+                 "ssmlGender": "FEMALE", "naturalSampleRateHertz": 24000},
+                {"languageCodes": ["en-US"]}
+            ]}"#
+        ).unwrap();
+
+        assert_eq!(response.voices.len(), 1);
+        let voice = &response.voices[0];
+        assert_eq!(voice.name.to_string(), "en-US-Neural2-F");
+        assert_eq!(voice.languageCodes, ["en-US".parse::<LanguageTag>().unwrap()]);
+        assert_eq!(voice.ssmlGender, SsmlVoiceGender::Female);
+        assert_eq!(voice.naturalSampleRateHertz, 24000);
+    }
+
+    #[test]
+    fn accepts_a_response_without_voices() {
+        let response: ListVoicesResponse = serde_json::from_str("{}").unwrap();
+        assert!(response.voices.is_empty());
+    }
+}